@@ -2,7 +2,10 @@
 
 use futures::{StreamExt, TryStreamExt};
 
-use crate::{ctx, util::StreamOnce};
+use crate::{
+    ctx,
+    util::{CancelOnDrop, StreamOnce},
+};
 use std::{sync::Arc, time::Duration};
 
 /// Generates a unique response ID for a chat completion.
@@ -11,6 +14,14 @@ pub fn response_id(created: u64) -> String {
     format!("chtcpl-{}-{}", uuid.simple(), created)
 }
 
+/// The error a completion ends with once its context is cancelled.
+fn cancelled_error<CTXEXT>(ctx: &ctx::Context<CTXEXT>) -> super::Error {
+    match ctx.max_cost_exceeded() {
+        Some(max_cost) => super::Error::MaxCostExceeded(max_cost),
+        None => super::Error::Cancelled,
+    }
+}
+
 /// Client for creating chat completions.
 ///
 /// Handles Ensemble LLM fetching, upstream provider selection with fallbacks,
@@ -121,38 +132,62 @@ where
         + 'static,
         super::Error,
     >{
+        // cancel the completion if the response is dropped before it ends,
+        // or once it reaches its maximum cost
+        let ctx = ctx.child_with_max_cost(request.max_cost);
+        let cancel = ctx.cancel.clone();
         let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
         let _ = tokio::spawn(async move {
             let mut aggregate: Option<
                 objectiveai::chat::completions::response::streaming::ChatCompletionChunk,
             > = None;
             let mut error = false;
-            let stream = match self
-                .clone()
-                .create_streaming_for_chat(ctx.clone(), request.clone())
-                .await
-            {
+            let stream =
+                self.create_streaming_for_chat(ctx.clone(), request.clone());
+            let stream = match tokio::select! {
+                result = stream => result,
+                _ = ctx.cancel.cancelled() => Err(cancelled_error(&ctx)),
+            } {
                 Ok(stream) => stream,
                 Err(e) => {
                     let _ = tx.send(Err(e));
                     return;
                 }
             };
-            futures::pin_mut!(stream);
+            // end the upstream stream as soon as the request is cancelled
+            let mut stream = stream
+                .take_until(Box::pin(ctx.cancel.clone().cancelled_owned()));
             while let Some(result) = stream.next().await {
                 match &result {
-                    Ok(chunk) => match &mut aggregate {
-                        Some(aggregate) => aggregate.push(chunk),
-                        None => {
-                            aggregate = Some(chunk.clone());
+                    Ok(chunk) => {
+                        if let Some(usage) = &chunk.usage {
+                            ctx.spend(usage.total_cost);
                         }
-                    },
+                        match &mut aggregate {
+                            Some(aggregate) => aggregate.push(chunk),
+                            None => {
+                                aggregate = Some(chunk.clone());
+                            }
+                        }
+                    }
                     Err(_) => {
                         error = true;
                     }
                 }
                 let _ = tx.send(result);
             }
+            // the stopping future only resolves if the request is cancelled,
+            // an upstream stream that ends on its own is not stopped, and
+            // neither is one that already reported its usage, which comes
+            // last, as the completion whose cost reached the budget does
+            if stream.take_result().is_some()
+                && aggregate
+                    .as_ref()
+                    .is_none_or(|aggregate| aggregate.usage.is_none())
+            {
+                error = true;
+                let _ = tx.send(Err(cancelled_error(&ctx)));
+            }
             drop(stream);
             drop(tx);
             if !error {
@@ -164,7 +199,10 @@ where
         let mut stream =
             tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
         match stream.next().await {
-            Some(Ok(chunk)) => Ok(StreamOnce::new(Ok(chunk)).chain(stream)),
+            Some(Ok(chunk)) => Ok(CancelOnDrop::new(
+                StreamOnce::new(Ok(chunk)).chain(stream),
+                cancel,
+            )),
             Some(Err(e)) => Err(e),
             None => unreachable!(),
         }
@@ -198,37 +236,56 @@ where
                 objectiveai::chat::completions::response::streaming::ChatCompletionChunk,
             > = None;
             let mut error = false;
-            let stream = match self
-                .clone()
-                .create_streaming_for_vector(
-                    ctx.clone(),
-                    request,
-                    vector_pfx_indices,
-                    ensemble_llm,
-                )
-                .await
-            {
+            let stream = self.create_streaming_for_vector(
+                ctx.clone(),
+                request,
+                vector_pfx_indices,
+                ensemble_llm,
+            );
+            let stream = match tokio::select! {
+                result = stream => result,
+                _ = ctx.cancel.cancelled() => Err(cancelled_error(&ctx)),
+            } {
                 Ok(stream) => stream,
                 Err(e) => {
                     let _ = tx.send(Err(e));
                     return;
                 }
             };
-            futures::pin_mut!(stream);
+            // end the upstream stream as soon as the request is cancelled
+            let mut stream = stream
+                .take_until(Box::pin(ctx.cancel.clone().cancelled_owned()));
             while let Some(result) = stream.next().await {
                 match &result {
-                    Ok(chunk) => match &mut aggregate {
-                        Some(aggregate) => aggregate.push(chunk),
-                        None => {
-                            aggregate = Some(chunk.clone());
+                    Ok(chunk) => {
+                        if let Some(usage) = &chunk.usage {
+                            ctx.spend(usage.total_cost);
                         }
-                    },
+                        match &mut aggregate {
+                            Some(aggregate) => aggregate.push(chunk),
+                            None => {
+                                aggregate = Some(chunk.clone());
+                            }
+                        }
+                    }
                     Err(_) => {
                         error = true;
                     }
                 }
                 let _ = tx.send(result);
             }
+            // the stopping future only resolves if the request is cancelled,
+            // an upstream stream that ends on its own is not stopped, and
+            // neither is one that already reported its usage, which comes
+            // last, as the completion whose cost reached the budget does
+            if stream.take_result().is_some()
+                && aggregate
+                    .as_ref()
+                    .is_none_or(|aggregate| aggregate.usage.is_none())
+            {
+                error = true;
+                let _ = tx.send(Err(cancelled_error(&ctx)));
+            }
             drop(stream);
             drop(tx);
             if !error {
@@ -21,6 +21,12 @@ pub enum Error {
     /// Multiple errors occurred during fallback attempts.
     #[error("multiple errors: {0:?}")]
     MultipleErrors(Vec<Error>),
+    /// The request was cancelled before the completion finished.
+    #[error("cancelled")]
+    Cancelled,
+    /// The request was cancelled for reaching its maximum cost.
+    #[error("max cost exceeded: {0}")]
+    MaxCostExceeded(rust_decimal::Decimal),
 }
 
 impl objectiveai::error::StatusError for Error {
@@ -32,6 +38,8 @@ impl objectiveai::error::StatusError for Error {
             Error::EnsembleLlmNotFound => 404,
             Error::InvalidEnsembleLlm(_) => 400,
             Error::MultipleErrors(_) => 500,
+            Error::Cancelled => 499,
+            Error::MaxCostExceeded(_) => 402,
         }
     }
 
@@ -68,6 +76,17 @@ impl objectiveai::error::StatusError for Error {
                         })
                     }).collect::<Vec<_>>(),
                 }),
+                Error::Cancelled => serde_json::json!({
+                    "kind": "cancelled",
+                    "error": "request was cancelled",
+                }),
+                Error::MaxCostExceeded(max_cost) => serde_json::json!({
+                    "kind": "max_cost_exceeded",
+                    "error": format!(
+                        "request was cancelled for reaching its maximum cost of {}",
+                        max_cost,
+                    ),
+                }),
             }
         }))
     }
@@ -0,0 +1,102 @@
+//! Spend limits of requests.
+
+use rust_decimal::Decimal;
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+};
+
+/// Bounds the total cost of the upstream calls made for a request.
+///
+/// Costs are recorded as usage arrives. Once the total reaches `max_cost`,
+/// the request's cancellation token is cancelled, ending its outstanding
+/// upstream calls and failing any started afterwards. Costs also count
+/// towards the budget of the enclosing request, if any.
+#[derive(Debug)]
+pub struct Budget {
+    /// The maximum total cost.
+    pub max_cost: Decimal,
+    /// The total cost so far.
+    spent: Mutex<Decimal>,
+    /// Whether the total cost has reached `max_cost`.
+    exceeded: AtomicBool,
+    /// Cancelled once the total cost reaches `max_cost`.
+    cancel: tokio_util::sync::CancellationToken,
+    /// The budget of the enclosing request.
+    parent: Option<Arc<Budget>>,
+}
+
+impl Budget {
+    pub fn new(
+        max_cost: Decimal,
+        cancel: tokio_util::sync::CancellationToken,
+        parent: Option<Arc<Budget>>,
+    ) -> Self {
+        Self {
+            max_cost,
+            spent: Mutex::new(Decimal::ZERO),
+            exceeded: AtomicBool::new(false),
+            cancel,
+            parent,
+        }
+    }
+
+    /// Records a cost, cancelling the request once the total reaches
+    /// `max_cost`.
+    pub fn spend(&self, cost: Decimal) {
+        let reached = {
+            let mut spent = self.spent.lock().unwrap();
+            *spent += cost;
+            *spent >= self.max_cost
+        };
+        if reached && !self.exceeded.swap(true, Ordering::SeqCst) {
+            self.cancel.cancel();
+        }
+        if let Some(parent) = &self.parent {
+            parent.spend(cost);
+        }
+    }
+
+    /// The total cost so far.
+    pub fn spent(&self) -> Decimal {
+        *self.spent.lock().unwrap()
+    }
+
+    /// Returns the first budget, from this one outwards, whose total cost
+    /// has reached its `max_cost`.
+    pub fn exceeded(&self) -> Option<&Budget> {
+        if self.exceeded.load(Ordering::SeqCst) {
+            Some(self)
+        } else {
+            self.parent.as_deref().and_then(Budget::exceeded)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    #[test]
+    fn test_spend() {
+        let parent_cancel = tokio_util::sync::CancellationToken::new();
+        let parent = Arc::new(Budget::new(dec!(1), parent_cancel.clone(), None));
+        let cancel = parent_cancel.child_token();
+        let budget = Budget::new(dec!(0.5), cancel.clone(), Some(parent.clone()));
+
+        budget.spend(dec!(0.25));
+        assert!(budget.exceeded().is_none());
+        assert!(!cancel.is_cancelled());
+
+        budget.spend(dec!(0.25));
+        assert_eq!(budget.exceeded().unwrap().max_cost, dec!(0.5));
+        assert!(cancel.is_cancelled());
+        assert!(!parent_cancel.is_cancelled());
+        assert_eq!(parent.spent(), dec!(0.5));
+
+        parent.spend(dec!(0.5));
+        assert!(parent_cancel.is_cancelled());
+        assert_eq!(budget.spent(), dec!(0.5));
+    }
+}
@@ -12,8 +12,29 @@ use std::sync::Arc;
 /// # Caches
 ///
 /// The caches deduplicate concurrent fetches for the same resource within a request.
-/// When multiple parts of a request need the same ensemble or ensemble LLM,
-/// only one fetch is performed and the result is shared.
+/// When multiple parts of a request need the same Function, Profile, ensemble
+/// or ensemble LLM, only one fetch is performed and the result is shared.
+/// Likewise, the compatibility check of a root Function and Profile runs
+/// once, however many executions of the request flatten them.
+///
+/// # Cancellation
+///
+/// Cancelling [`Context::cancel`] ends every outstanding upstream chat
+/// completion made with the context, and fails any started afterwards, so
+/// that the work depending on them drains and reports what completed.
+///
+/// # Shared roots
+///
+/// A context created with [`Context::with_root`] carries an already
+/// resolved root Function and Profile, which every execution made with it
+/// flattens against instead of resolving its own, as the items of a batch
+/// do.
+///
+/// # Spend limits
+///
+/// A context created with [`Context::child_with_max_cost`] carries a
+/// [`Budget`](super::Budget), which cancels it once the upstream calls made
+/// with it reach the maximum cost.
 #[derive(Debug)]
 pub struct Context<CTXEXT> {
     /// Custom context extension (e.g., for BYOK keys).
@@ -48,6 +69,58 @@ pub struct Context<CTXEXT> {
             >,
         >,
     >,
+    /// Cache for Function fetches, keyed by owner/repository/commit.
+    pub function_cache: Arc<
+        DashMap<
+            (String, String, Option<String>),
+            Shared<
+                tokio::sync::oneshot::Receiver<
+                    Result<
+                        Option<objectiveai::functions::response::GetFunction>,
+                        objectiveai::error::ResponseError,
+                    >,
+                >,
+            >,
+        >,
+    >,
+    /// Cache for Profile fetches, keyed by owner/repository/commit.
+    pub profile_cache: Arc<
+        DashMap<
+            (String, String, Option<String>),
+            Shared<
+                tokio::sync::oneshot::Receiver<
+                    Result<
+                        Option<
+                            objectiveai::functions::profiles::response::GetProfile,
+                        >,
+                        objectiveai::error::ResponseError,
+                    >,
+                >,
+            >,
+        >,
+    >,
+    /// Issues found by the compatibility check of root Functions and
+    /// Profiles, keyed by their JSON, so that each is checked once.
+    pub compatibility_cache: Arc<
+        DashMap<
+            String,
+            Arc<
+                tokio::sync::OnceCell<
+                    Vec<objectiveai::functions::CompatibilityIssue>,
+                >,
+            >,
+        >,
+    >,
+    /// Cancelled when the request is abandoned, ending every upstream call
+    /// made on its behalf.
+    pub cancel: tokio_util::sync::CancellationToken,
+    /// Bounds the cost of the upstream calls made with the context.
+    pub budget: Option<Arc<super::Budget>>,
+    /// Root Function and Profile shared by every execution made with the
+    /// context, resolved once.
+    pub root: Option<
+        Arc<(crate::functions::FunctionParam, crate::functions::ProfileParam)>,
+    >,
 }
 
 impl<CTXEXT> Clone for Context<CTXEXT> {
@@ -57,6 +130,12 @@ impl<CTXEXT> Clone for Context<CTXEXT> {
             cost_multiplier: self.cost_multiplier,
             ensemble_cache: self.ensemble_cache.clone(),
             ensemble_llm_cache: self.ensemble_llm_cache.clone(),
+            function_cache: self.function_cache.clone(),
+            profile_cache: self.profile_cache.clone(),
+            compatibility_cache: self.compatibility_cache.clone(),
+            cancel: self.cancel.clone(),
+            budget: self.budget.clone(),
+            root: self.root.clone(),
         }
     }
 }
@@ -69,6 +148,72 @@ impl<CTXEXT> Context<CTXEXT> {
             cost_multiplier,
             ensemble_cache: Arc::new(DashMap::new()),
             ensemble_llm_cache: Arc::new(DashMap::new()),
+            function_cache: Arc::new(DashMap::new()),
+            profile_cache: Arc::new(DashMap::new()),
+            compatibility_cache: Arc::new(DashMap::new()),
+            cancel: tokio_util::sync::CancellationToken::new(),
+            budget: None,
+            root: None,
+        }
+    }
+
+    /// Creates a context sharing this context's caches, with a cancellation
+    /// token that is cancelled along with this one but can also be
+    /// cancelled on its own.
+    pub fn child(&self) -> Self {
+        Self {
+            cancel: self.cancel.child_token(),
+            ..self.clone()
         }
     }
+
+    /// Creates a child context (see [`Context::child`]), bounded by
+    /// `max_cost` if given, along with this context's own bound.
+    pub fn child_with_max_cost(
+        &self,
+        max_cost: Option<rust_decimal::Decimal>,
+    ) -> Self {
+        let child = self.child();
+        match max_cost {
+            Some(max_cost) => Self {
+                budget: Some(Arc::new(super::Budget::new(
+                    max_cost,
+                    child.cancel.clone(),
+                    self.budget.clone(),
+                ))),
+                ..child
+            },
+            None => child,
+        }
+    }
+
+    /// Creates a context sharing this context's caches and cancellation,
+    /// whose executions flatten against the already resolved `function`
+    /// and `profile` rather than the ones their requests reference.
+    pub fn with_root(
+        &self,
+        function: crate::functions::FunctionParam,
+        profile: crate::functions::ProfileParam,
+    ) -> Self {
+        Self {
+            root: Some(Arc::new((function, profile))),
+            ..self.clone()
+        }
+    }
+
+    /// Records the cost of an upstream call against the context's budget.
+    pub fn spend(&self, cost: rust_decimal::Decimal) {
+        if let Some(budget) = &self.budget {
+            budget.spend(cost);
+        }
+    }
+
+    /// Returns the maximum cost the context was cancelled for reaching, if
+    /// any.
+    pub fn max_cost_exceeded(&self) -> Option<rust_decimal::Decimal> {
+        self.budget
+            .as_deref()
+            .and_then(super::Budget::exceeded)
+            .map(|budget| budget.max_cost)
+    }
 }
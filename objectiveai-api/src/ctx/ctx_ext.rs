@@ -17,4 +17,14 @@ pub trait ContextExt {
         &self,
         upstream: chat::completions::upstream::Upstream,
     ) -> Result<Option<String>, objectiveai::error::ResponseError>;
+
+    /// Returns an opaque identity for the caller, if its credentials
+    /// identify it.
+    ///
+    /// Resources that outlive a request, such as jobs, are only visible to
+    /// requests with the same identity. Returns `None` by default, so that
+    /// they are shared by every caller without credentials.
+    fn caller(&self) -> Option<String> {
+        None
+    }
 }
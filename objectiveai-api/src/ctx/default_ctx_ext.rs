@@ -42,4 +42,13 @@ impl super::ContextExt for DefaultContextExt {
             }
         }
     }
+
+    /// Identifies BYOK callers by a hash of their key, never the key itself.
+    fn caller(&self) -> Option<String> {
+        self.openrouter_byok.as_ref().map(|byok| {
+            let mut hasher = twox_hash::XxHash3_128::with_seed(0);
+            hasher.write(byok.as_bytes());
+            format!("openrouter-{:032x}", hasher.finish_128())
+        })
+    }
 }
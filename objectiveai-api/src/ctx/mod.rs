@@ -4,10 +4,12 @@
 //! the `ContextExt` trait. This enables features like BYOK (Bring Your Own Key)
 //! support where users can provide their own upstream API keys.
 
+mod budget;
 mod ctx;
 mod ctx_ext;
 mod default_ctx_ext;
 
+pub use budget::*;
 pub use ctx::*;
 pub use ctx_ext::*;
 pub use default_ctx_ext::*;
@@ -0,0 +1,152 @@
+//! Compatibility checks between Functions and Profiles.
+//!
+//! Fetches the nested Functions, remote Profiles and Ensembles referenced by
+//! a Function and Profile, so that the whole task structure is checked
+//! before anything runs.
+
+use crate::ctx;
+use objectiveai::functions::{
+    CompatibilityDefinitions, CompatibilityIssue, Function, Profile,
+    ScalarFunctionTaskExpression, TaskExpression, TaskProfile,
+    VectorFunctionTaskExpression,
+};
+use objectiveai::vector::completions::request::Ensemble;
+use std::collections::hash_map::Entry;
+use std::sync::Arc;
+
+/// Checks that `profile` fits the task structure of `function`, fetching
+/// every definition they reference.
+///
+/// Returns the mismatches found. Like the SDK's resolver, references that
+/// cannot be fetched are left unchecked, so that a task which is skipped
+/// never fails the check; the execution reports them if the task runs.
+pub async fn check_compatibility<CTXEXT>(
+    ctx: ctx::Context<CTXEXT>,
+    function: &Function,
+    profile: &Profile,
+    function_fetcher: Arc<
+        impl super::function_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    >,
+    profile_fetcher: Arc<
+        impl super::profile_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    >,
+    ensemble_fetcher: Arc<
+        crate::ensemble::fetcher::CachingFetcher<
+            CTXEXT,
+            impl crate::ensemble::fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+        >,
+    >,
+) -> Vec<CompatibilityIssue>
+where
+    CTXEXT: ctx::ContextExt + Send + Sync + 'static,
+{
+    let mut definitions = CompatibilityDefinitions::default();
+
+    // walk the task structure, fetching what is referenced
+    let profile_tasks = match profile {
+        Profile::Remote(profile) => profile.tasks.clone(),
+        Profile::Inline(profile) => profile.tasks.clone(),
+    };
+    let mut pending = vec![(function.tasks().to_vec(), profile_tasks)];
+    while let Some((tasks, task_profiles)) = pending.pop() {
+        for (task, task_profile) in tasks.iter().zip(task_profiles) {
+            match (task, task_profile) {
+                (
+                    TaskExpression::ScalarFunction(
+                        ScalarFunctionTaskExpression {
+                            owner,
+                            repository,
+                            commit,
+                            ..
+                        },
+                    )
+                    | TaskExpression::VectorFunction(
+                        VectorFunctionTaskExpression {
+                            owner,
+                            repository,
+                            commit,
+                            ..
+                        },
+                    ),
+                    task_profile @ (TaskProfile::RemoteFunction { .. }
+                    | TaskProfile::InlineFunction(_)),
+                ) => {
+                    // fetch the nested Function
+                    let key = CompatibilityDefinitions::key(
+                        owner,
+                        repository,
+                        Some(commit),
+                    );
+                    if !definitions.functions.contains_key(&key) {
+                        let Ok(Some(function)) = function_fetcher
+                            .fetch(ctx.clone(), owner, repository, Some(commit))
+                            .await
+                        else {
+                            continue;
+                        };
+                        definitions
+                            .functions
+                            .insert(key.clone(), function.inner);
+                    }
+
+                    // fetch the nested Profile
+                    let nested_task_profiles = match task_profile {
+                        TaskProfile::RemoteFunction {
+                            owner,
+                            repository,
+                            commit,
+                        } => {
+                            let key = CompatibilityDefinitions::key(
+                                &owner,
+                                &repository,
+                                commit.as_deref(),
+                            );
+                            if !definitions.profiles.contains_key(&key) {
+                                let Ok(Some(profile)) = profile_fetcher
+                                    .fetch(
+                                        ctx.clone(),
+                                        &owner,
+                                        &repository,
+                                        commit.as_deref(),
+                                    )
+                                    .await
+                                else {
+                                    continue;
+                                };
+                                definitions
+                                    .profiles
+                                    .insert(key.clone(), profile.inner);
+                            }
+                            definitions.profiles[&key].tasks.clone()
+                        }
+                        TaskProfile::InlineFunction(profile) => profile.tasks,
+                        _ => unreachable!(),
+                    };
+                    pending.push((
+                        definitions.functions[&key].tasks().to_vec(),
+                        nested_task_profiles,
+                    ));
+                }
+                (
+                    TaskExpression::VectorCompletion(_),
+                    TaskProfile::VectorCompletion {
+                        ensemble: Ensemble::Id(id),
+                        ..
+                    },
+                ) => {
+                    // fetch the Ensemble
+                    if let Entry::Vacant(entry) = definitions.ensembles.entry(id)
+                        && let Ok(Some((ensemble, _))) = ensemble_fetcher
+                            .fetch(ctx.clone(), entry.key())
+                            .await
+                    {
+                        entry.insert(ensemble);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    function.check_compatibility(profile, &definitions)
+}
@@ -0,0 +1,184 @@
+//! Attributions of Function outputs to their tasks and Ensemble LLMs.
+//!
+//! Replays the weighted average of a Function's task outputs, recording the
+//! normalized weight and weighted output of each task, and attributes the
+//! scores of vector completion tasks to the votes of each Ensemble LLM.
+
+use indexmap::IndexMap;
+use objectiveai::functions::executions::response::{
+    Attribution, LlmAttribution, TaskAttribution,
+};
+use objectiveai::functions::expression::FunctionOutput;
+use rust_decimal::Decimal;
+
+/// Accumulates the votes of a Function's vector completion tasks.
+#[derive(Debug, Clone)]
+pub struct Attributor {
+    /// The votes of each task, by the path of its vector completions.
+    votes: Vec<IndexMap<Vec<u64>, Vec<objectiveai::vector::completions::response::Vote>>>,
+}
+
+impl Attributor {
+    pub fn new(tasks_len: usize) -> Self {
+        Self {
+            votes: vec![IndexMap::new(); tasks_len],
+        }
+    }
+
+    /// Adds votes of a vector completion of the task at `index`.
+    pub fn push_votes(
+        &mut self,
+        index: usize,
+        task_path: &[u64],
+        votes: &[objectiveai::vector::completions::response::Vote],
+    ) {
+        if votes.is_empty() {
+            return;
+        }
+        if let Some(task_votes) = self.votes.get_mut(index) {
+            task_votes
+                .entry(task_path.to_vec())
+                .or_default()
+                .extend(votes.iter().cloned());
+        }
+    }
+
+    /// Attributes the Function's output to its tasks.
+    ///
+    /// `task_outputs` are the outputs of the tasks after their output
+    /// expressions, and `inverted` whether each was inverted. With an
+    /// `output_expression`, the weighted outputs are not attributed.
+    pub fn finish(
+        self,
+        profile_weights: &[Decimal],
+        task_outputs: &[Option<FunctionOutput>],
+        inverted: &[bool],
+        output_expression: bool,
+    ) -> Attribution {
+        // the weights are L1-normalized as in the weighted average
+        let total_weight: Decimal = task_outputs
+            .iter()
+            .enumerate()
+            .filter(|(_, output)| {
+                output
+                    .as_ref()
+                    .is_some_and(|output| !matches!(output, FunctionOutput::Err(_)))
+            })
+            .map(|(i, _)| profile_weights.get(i).copied().unwrap_or(Decimal::ZERO))
+            .sum();
+        let tasks = task_outputs
+            .iter()
+            .zip(self.votes)
+            .enumerate()
+            .map(|(i, (output, votes))| {
+                let output = match output {
+                    Some(FunctionOutput::Err(_)) | None => return None,
+                    Some(output) => output.clone(),
+                };
+                let weight = if total_weight > Decimal::ZERO {
+                    profile_weights.get(i).copied().unwrap_or(Decimal::ZERO) / total_weight
+                } else {
+                    Decimal::ZERO
+                };
+                Some(TaskAttribution {
+                    weight,
+                    inverted: inverted.get(i).copied().unwrap_or(false),
+                    contribution: if output_expression {
+                        None
+                    } else {
+                        Some(scale(&output, weight))
+                    },
+                    output,
+                    llms: votes
+                        .iter()
+                        .flat_map(|(task_path, votes)| {
+                            LlmAttribution::from_votes(task_path, votes)
+                        })
+                        .collect(),
+                })
+            })
+            .collect();
+        Attribution { tasks }
+    }
+}
+
+/// Scales each score of an output by `weight`.
+fn scale(output: &FunctionOutput, weight: Decimal) -> FunctionOutput {
+    match output {
+        FunctionOutput::Scalar(s) => FunctionOutput::Scalar(*s * weight),
+        FunctionOutput::Vector(v) => {
+            FunctionOutput::Vector(v.iter().map(|s| *s * weight).collect())
+        }
+        FunctionOutput::MultiLabel(scores) => FunctionOutput::MultiLabel(
+            scores.iter().map(|(label, s)| (label.clone(), *s * weight)).collect(),
+        ),
+        FunctionOutput::Categorical(scores) => FunctionOutput::Categorical(
+            scores.iter().map(|(label, s)| (label.clone(), *s * weight)).collect(),
+        ),
+        FunctionOutput::Err(e) => FunctionOutput::Err(e.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    #[test]
+    fn test_contributions_sum_to_output() {
+        let profile_weights = [dec!(1), dec!(3), dec!(2)];
+        let task_outputs = [
+            Some(FunctionOutput::Scalar(dec!(0.2))),
+            Some(FunctionOutput::Scalar(dec!(0.6))),
+            None,
+        ];
+        let attribution = Attributor::new(3).finish(
+            &profile_weights,
+            &task_outputs,
+            &[false, true, false],
+            false,
+        );
+        let (output, _) = super::super::compute_function_output(
+            &objectiveai::functions::expression::Input::Object(IndexMap::new()),
+            None,
+            &crate::functions::FunctionType::Scalar,
+            &profile_weights,
+            &task_outputs,
+        );
+
+        let first = attribution.tasks[0].as_ref().unwrap();
+        assert_eq!(first.weight, dec!(0.25));
+        assert!(!first.inverted);
+        let second = attribution.tasks[1].as_ref().unwrap();
+        assert_eq!(second.weight, dec!(0.75));
+        assert!(second.inverted);
+        assert!(attribution.tasks[2].is_none());
+
+        let sum: Decimal = attribution
+            .tasks
+            .iter()
+            .flatten()
+            .map(|task| match task.contribution {
+                Some(FunctionOutput::Scalar(s)) => s,
+                ref other => panic!("expected scalar contribution, got {:?}", other),
+            })
+            .sum();
+        match output {
+            FunctionOutput::Scalar(s) => assert_eq!(s, sum),
+            other => panic!("expected scalar output, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_output_expression_not_attributed() {
+        let attribution = Attributor::new(1).finish(
+            &[dec!(1)],
+            &[Some(FunctionOutput::Scalar(dec!(0.5)))],
+            &[false],
+            true,
+        );
+        let task = attribution.tasks[0].as_ref().unwrap();
+        assert_eq!(task.weight, dec!(1));
+        assert!(task.contribution.is_none());
+    }
+}
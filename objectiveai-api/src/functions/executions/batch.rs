@@ -0,0 +1,234 @@
+//! Batch Function execution.
+
+use crate::{chat, ctx, functions, vector};
+use futures::{Stream, StreamExt};
+use std::{sync::Arc, time};
+
+/// Generates a unique response ID for batch Function executions.
+pub fn batch_response_id(created: u64) -> String {
+    let uuid = uuid::Uuid::new_v4();
+    format!("fncbat-{}-{}", uuid.simple(), created)
+}
+
+impl<CTXEXT, FENSLLM, CUSG, FENS, FVVOTE, FCVOTE, VUSG, FFN, FPFL, FUSG>
+    super::Client<CTXEXT, FENSLLM, CUSG, FENS, FVVOTE, FCVOTE, VUSG, FFN, FPFL, FUSG>
+where
+    CTXEXT: ctx::ContextExt + Send + Sync + 'static,
+    FENSLLM:
+        crate::ensemble_llm::fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    CUSG: chat::completions::usage_handler::UsageHandler<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FENS: crate::ensemble::fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    FVVOTE: vector::completions::completion_votes_fetcher::Fetcher<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FCVOTE: vector::completions::cache_vote_fetcher::Fetcher<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    VUSG: vector::completions::usage_handler::UsageHandler<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FFN: functions::function_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    FPFL: functions::profile_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    FUSG: super::usage_handler::UsageHandler<CTXEXT> + Send + Sync + 'static,
+{
+    /// Executes a batch and returns every item with the aggregated usage.
+    pub async fn create_batch_unary(
+        self: Arc<Self>,
+        ctx: ctx::Context<CTXEXT>,
+        params: Arc<
+            objectiveai::functions::executions::batch::request::FunctionExecutionBatchCreateParams,
+        >,
+        max_concurrency: usize,
+    ) -> Result<
+        objectiveai::functions::executions::batch::response::unary::FunctionExecutionBatch,
+        super::Error,
+    > {
+        let mut aggregate: Option<
+            objectiveai::functions::executions::batch::response::streaming::FunctionExecutionBatchChunk,
+        > = None;
+        let stream = self
+            .create_batch_streaming(ctx, params, max_concurrency)
+            .await?;
+        futures::pin_mut!(stream);
+        while let Some(chunk) = stream.next().await {
+            match &mut aggregate {
+                Some(aggregate) => aggregate.push(&chunk),
+                None => aggregate = Some(chunk),
+            }
+        }
+        Ok(aggregate.unwrap().into())
+    }
+
+    /// Executes a batch, streaming each item as soon as it completes.
+    ///
+    /// At most `max_concurrency` inputs are executed at once, or fewer if the
+    /// request asks for fewer. The Function and Profile are resolved once
+    /// and every item flattens against them, sharing the request context's
+    /// Ensemble fetches; only the tasks compiled from each item's input
+    /// differ. Usage is recorded per item and aggregated into the final
+    /// chunk.
+    pub async fn create_batch_streaming(
+        self: Arc<Self>,
+        ctx: ctx::Context<CTXEXT>,
+        params: Arc<
+            objectiveai::functions::executions::batch::request::FunctionExecutionBatchCreateParams,
+        >,
+        max_concurrency: usize,
+    ) -> Result<
+        impl Stream<Item = objectiveai::functions::executions::batch::response::streaming::FunctionExecutionBatchChunk>
+        + Send
+        + 'static,
+        super::Error,
+    > {
+        // timestamp the batch
+        let created = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let id = batch_response_id(created);
+
+        // validate the batch
+        if params.inputs.is_empty() {
+            return Err(super::Error::InvalidBatch(
+                "inputs must not be empty".to_string(),
+            ));
+        }
+        if params
+            .retry_tokens
+            .as_ref()
+            .is_some_and(|tokens| tokens.len() != params.inputs.len())
+        {
+            return Err(super::Error::InvalidBatch(
+                "retry_tokens must have one entry per input".to_string(),
+            ));
+        }
+
+        // resolve the Function and Profile once, so that a missing one fails
+        // the whole batch and every item flattens against the same root
+        let function = match &params.function {
+            objectiveai::functions::executions::batch::request::BatchFunction::Remote {
+                owner,
+                repository,
+                commit,
+            } => {
+                let function = self
+                    .function_fetcher
+                    .fetch(ctx.clone(), owner, repository, commit.as_deref())
+                    .await
+                    .map_err(super::Error::FetchFunction)?
+                    .ok_or(super::Error::FunctionNotFound)?;
+                functions::FunctionParam::FetchedOrInline {
+                    full_id: Some((
+                        function.owner,
+                        function.repository,
+                        function.commit,
+                    )),
+                    function: objectiveai::functions::Function::Remote(
+                        function.inner,
+                    ),
+                }
+            }
+            objectiveai::functions::executions::batch::request::BatchFunction::Inline(
+                function,
+            ) => functions::FunctionParam::FetchedOrInline {
+                full_id: None,
+                function: objectiveai::functions::Function::Inline(
+                    function.clone(),
+                ),
+            },
+        };
+        let profile = match &params.profile {
+            objectiveai::functions::executions::batch::request::BatchProfile::Remote {
+                owner,
+                repository,
+                commit,
+            } => {
+                let profile = self
+                    .profile_fetcher
+                    .fetch(ctx.clone(), owner, repository, commit.as_deref())
+                    .await
+                    .map_err(super::Error::FetchProfile)?
+                    .ok_or(super::Error::ProfileNotFound)?;
+                functions::ProfileParam::FetchedOrInline {
+                    full_id: Some((
+                        profile.owner,
+                        profile.repository,
+                        profile.commit,
+                    )),
+                    profile: objectiveai::functions::Profile::Remote(
+                        profile.inner,
+                    ),
+                }
+            }
+            objectiveai::functions::executions::batch::request::BatchProfile::Inline(
+                profile,
+            ) => functions::ProfileParam::FetchedOrInline {
+                full_id: None,
+                profile: objectiveai::functions::Profile::Inline(
+                    profile.clone(),
+                ),
+            },
+        };
+        let ctx = ctx.with_root(function, profile);
+
+        // execute every input with bounded concurrency
+        let max_concurrency = max_concurrency.max(1);
+        let concurrency = params
+            .max_concurrency
+            .map_or(max_concurrency, |n| n.clamp(1, max_concurrency));
+        let items = futures::stream::iter(0..params.inputs.len())
+            .map(move |index| {
+                let client = self.clone();
+                let ctx = ctx.clone();
+                let request = params.request(index).unwrap();
+                async move {
+                    match client
+                        .create_unary_handle_usage(ctx, Arc::new(request))
+                        .await
+                    {
+                        Ok(execution) => objectiveai::functions::executions::batch::response::unary::FunctionExecutionBatchItem {
+                            index,
+                            execution: Some(execution),
+                            error: None,
+                        },
+                        Err(e) => objectiveai::functions::executions::batch::response::unary::FunctionExecutionBatchItem {
+                            index,
+                            execution: None,
+                            error: Some(objectiveai::error::ResponseError::from(&e)),
+                        },
+                    }
+                }
+            })
+            .buffer_unordered(concurrency);
+
+        Ok(async_stream::stream! {
+            let mut usage = objectiveai::vector::completions::response::Usage::default();
+            futures::pin_mut!(items);
+            while let Some(item) = items.next().await {
+                if let Some(execution) = &item.execution {
+                    usage.push(&execution.usage);
+                }
+                yield objectiveai::functions::executions::batch::response::streaming::FunctionExecutionBatchChunk {
+                    id: id.clone(),
+                    items: vec![item],
+                    created,
+                    object: objectiveai::functions::executions::batch::response::streaming::Object::FunctionExecutionBatchChunk,
+                    usage: None,
+                };
+            }
+            yield objectiveai::functions::executions::batch::response::streaming::FunctionExecutionBatchChunk {
+                id,
+                items: Vec::new(),
+                created,
+                object: objectiveai::functions::executions::batch::response::streaming::Object::FunctionExecutionBatchChunk,
+                usage: Some(usage),
+            };
+        })
+    }
+}
@@ -0,0 +1,338 @@
+//! Cache of whole Function executions.
+//!
+//! Executions that opt in with `cache` are stored once they finish without
+//! errors, keyed by the caller, the Function and Profile, the canonical ID
+//! of the input and the options that affect the result. Remote Functions
+//! and Profiles are identified by their resolved commit, so a new commit
+//! never reuses a stale execution. Inline ones are identified by their
+//! canonical JSON definition.
+//!
+//! The cache holds a bounded number of executions, evicting the least
+//! recently used once full, and executions expire after a default TTL
+//! unless the request sets its own.
+
+use indexmap::IndexMap;
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Identifies the Function or Profile of a cached execution.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExecutionCacheId {
+    /// A remote Function or Profile, as `owner/repository/commit`.
+    Remote(String),
+    /// The canonical JSON of an inline Function or Profile definition.
+    Inline(String),
+}
+
+impl ExecutionCacheId {
+    /// Identifies an inline Function or Profile by its definition.
+    pub fn inline(definition: &impl serde::Serialize) -> Self {
+        Self::Inline(canonical_json(definition))
+    }
+
+    /// Whether this is a remote Function or Profile matching `filter`,
+    /// either `owner/repository` or `owner/repository/commit`.
+    fn matches(&self, filter: &str) -> bool {
+        match self {
+            Self::Remote(full_id) => {
+                full_id == filter
+                    || full_id
+                        .strip_prefix(filter)
+                        .is_some_and(|commit| commit.starts_with('/'))
+            }
+            Self::Inline(_) => false,
+        }
+    }
+}
+
+/// Key of a cached execution.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExecutionCacheKey {
+    /// The identity of the caller, from [`ContextExt::caller`], so that
+    /// executions are never shared between callers.
+    ///
+    /// [`ContextExt::caller`]: crate::ctx::ContextExt::caller
+    pub caller: Option<String>,
+    /// The Function executed.
+    pub function: ExecutionCacheId,
+    /// The Profile executed.
+    pub profile: ExecutionCacheId,
+    /// The canonical ID of the input.
+    pub input: String,
+    /// The canonical JSON of the options that affect the result.
+    pub options: String,
+}
+
+impl ExecutionCacheKey {
+    /// Creates the key of an execution of `request` by `caller` with the
+    /// given Function and Profile.
+    pub fn new(
+        caller: Option<String>,
+        function: ExecutionCacheId,
+        profile: ExecutionCacheId,
+        request: &objectiveai::functions::executions::request::Request,
+    ) -> Self {
+        let base = request.base();
+        let options = serde_json::json!({
+            "from_rng": base.from_rng,
+            "reasoning": base.reasoning,
+            "strategy": base.strategy,
+            "top_k": base.top_k,
+            "seed": base.seed,
+            "provider": base.provider,
+        });
+        Self {
+            caller,
+            function,
+            profile,
+            input: base.input.id(),
+            options: canonical_json(&options),
+        }
+    }
+}
+
+/// Serializes `value` to JSON with the keys of every object sorted, so that
+/// values differing only in key order serialize alike.
+fn canonical_json(value: &impl serde::Serialize) -> String {
+    fn canonical(value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(object) => {
+                let mut entries = object
+                    .into_iter()
+                    .map(|(key, value)| (key, canonical(value)))
+                    .collect::<Vec<_>>();
+                entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+                serde_json::Value::Object(entries.into_iter().collect())
+            }
+            serde_json::Value::Array(array) => serde_json::Value::Array(
+                array.into_iter().map(canonical).collect(),
+            ),
+            value => value,
+        }
+    }
+    canonical(serde_json::to_value(value).unwrap()).to_string()
+}
+
+#[derive(Debug)]
+struct ExecutionCacheEntry {
+    execution:
+        objectiveai::functions::executions::response::streaming::FunctionExecutionChunk,
+    expires: Option<Instant>,
+}
+
+impl ExecutionCacheEntry {
+    fn expired(&self, now: Instant) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+}
+
+/// In-memory cache of finished Function executions.
+///
+/// `capacity` is set by `FUNCTION_EXECUTIONS_CACHE_CAPACITY` and
+/// `default_ttl` by `FUNCTION_EXECUTIONS_CACHE_TTL`.
+#[derive(Debug)]
+pub struct ExecutionCache {
+    /// Stored executions, least recently used first.
+    entries: Mutex<IndexMap<ExecutionCacheKey, ExecutionCacheEntry>>,
+    /// Maximum number of executions stored at once.
+    pub capacity: usize,
+    /// How long executions are reused for when the request sets no TTL.
+    /// Executions do not expire if `None`.
+    pub default_ttl: Option<Duration>,
+}
+
+impl ExecutionCache {
+    /// Creates an empty cache holding up to `capacity` executions.
+    pub fn new(capacity: usize, default_ttl: Option<Duration>) -> Self {
+        Self {
+            entries: Mutex::new(IndexMap::new()),
+            capacity,
+            default_ttl,
+        }
+    }
+
+    /// Returns the stored execution for `key`, if it has not expired.
+    pub fn get(
+        &self,
+        key: &ExecutionCacheKey,
+    ) -> Option<
+        objectiveai::functions::executions::response::streaming::FunctionExecutionChunk,
+    > {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        let index = entries.get_index_of(key)?;
+        if entries[index].expired(now) {
+            entries.shift_remove_index(index);
+            return None;
+        }
+        let last = entries.len() - 1;
+        entries.move_index(index, last);
+        Some(entries[last].execution.clone())
+    }
+
+    /// Stores an execution for `key`, reusable for `ttl`, or the default
+    /// TTL if not given.
+    ///
+    /// Expired executions are evicted, then the least recently used ones
+    /// until the cache has room.
+    pub fn insert(
+        &self,
+        key: ExecutionCacheKey,
+        execution: objectiveai::functions::executions::response::streaming::FunctionExecutionChunk,
+        ttl: Option<Duration>,
+    ) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, entry| !entry.expired(now));
+        entries.shift_remove(&key);
+        while entries.len() >= self.capacity.max(1) {
+            entries.shift_remove_index(0);
+        }
+        entries.insert(
+            key,
+            ExecutionCacheEntry {
+                execution,
+                expires: ttl.or(self.default_ttl).map(|ttl| now + ttl),
+            },
+        );
+    }
+
+    /// Removes the stored executions of `caller` for remote Functions and
+    /// Profiles matching the filters, returning how many were removed.
+    ///
+    /// Each filter is either `owner/repository` or
+    /// `owner/repository/commit`. Without filters, every stored execution
+    /// of `caller` is removed.
+    pub fn invalidate(
+        &self,
+        caller: Option<&str>,
+        function: Option<&str>,
+        profile: Option<&str>,
+    ) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let len = entries.len();
+        entries.retain(|key, _| {
+            !(key.caller.as_deref() == caller
+                && function.is_none_or(|function| key.function.matches(function))
+                && profile.is_none_or(|profile| key.profile.matches(profile)))
+        });
+        len - entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(function: &str, profile: &str, input: &str) -> ExecutionCacheKey {
+        ExecutionCacheKey {
+            caller: None,
+            function: ExecutionCacheId::Remote(function.to_string()),
+            profile: ExecutionCacheId::Remote(profile.to_string()),
+            input: input.to_string(),
+            options: String::new(),
+        }
+    }
+
+    fn execution(
+        id: &str,
+    ) -> objectiveai::functions::executions::response::streaming::FunctionExecutionChunk
+    {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "tasks": [],
+            "output": 0.5,
+            "created": 0,
+            "function": null,
+            "profile": null,
+            "object": "scalar.function.execution.chunk",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_get_and_expire() {
+        let cache = ExecutionCache::new(16, None);
+        cache.insert(key("a/f/1", "a/p/1", "x"), execution("1"), None);
+        cache.insert(
+            key("a/f/1", "a/p/1", "y"),
+            execution("2"),
+            Some(Duration::ZERO),
+        );
+        assert_eq!(cache.get(&key("a/f/1", "a/p/1", "x")).unwrap().id, "1");
+        assert!(cache.get(&key("a/f/2", "a/p/1", "x")).is_none());
+        assert!(cache.get(&key("a/f/1", "a/p/1", "y")).is_none());
+    }
+
+    #[test]
+    fn test_invalidate() {
+        let cache = ExecutionCache::new(16, None);
+        cache.insert(key("a/f/1", "a/p/1", "x"), execution("1"), None);
+        cache.insert(key("a/f/2", "a/p/1", "x"), execution("2"), None);
+        cache.insert(key("a/fg/1", "a/p/2", "x"), execution("3"), None);
+        cache.insert(
+            ExecutionCacheKey {
+                function: ExecutionCacheId::Inline(String::new()),
+                ..key("", "a/p/1", "x")
+            },
+            execution("4"),
+            None,
+        );
+        cache.insert(
+            ExecutionCacheKey {
+                caller: Some("c".to_string()),
+                ..key("a/f/1", "a/p/1", "x")
+            },
+            execution("5"),
+            None,
+        );
+        assert_eq!(cache.invalidate(None, Some("a/f/2"), None), 1);
+        assert_eq!(cache.invalidate(None, Some("a/f"), Some("a/p/2")), 0);
+        assert_eq!(cache.invalidate(None, None, Some("a/p")), 3);
+        assert_eq!(cache.invalidate(None, None, None), 0);
+        assert_eq!(cache.invalidate(Some("c"), None, None), 1);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = ExecutionCache::new(2, None);
+        cache.insert(key("a/f/1", "a/p/1", "x"), execution("1"), None);
+        cache.insert(key("a/f/1", "a/p/1", "y"), execution("2"), None);
+        assert!(cache.get(&key("a/f/1", "a/p/1", "x")).is_some());
+        cache.insert(key("a/f/1", "a/p/1", "z"), execution("3"), None);
+        assert!(cache.get(&key("a/f/1", "a/p/1", "x")).is_some());
+        assert!(cache.get(&key("a/f/1", "a/p/1", "y")).is_none());
+        assert!(cache.get(&key("a/f/1", "a/p/1", "z")).is_some());
+    }
+
+    #[test]
+    fn test_default_ttl_and_caller() {
+        let cache = ExecutionCache::new(16, Some(Duration::ZERO));
+        cache.insert(key("a/f/1", "a/p/1", "x"), execution("1"), None);
+        cache.insert(
+            key("a/f/1", "a/p/1", "y"),
+            execution("2"),
+            Some(Duration::from_secs(60)),
+        );
+        assert!(cache.get(&key("a/f/1", "a/p/1", "x")).is_none());
+        assert!(cache.get(&key("a/f/1", "a/p/1", "y")).is_some());
+        assert!(
+            cache
+                .get(&ExecutionCacheKey {
+                    caller: Some("c".to_string()),
+                    ..key("a/f/1", "a/p/1", "y")
+                })
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_inline_canonical() {
+        assert_eq!(
+            ExecutionCacheId::inline(&serde_json::json!({"a": 1, "b": {"c": 2, "d": 3}})),
+            ExecutionCacheId::inline(&serde_json::json!({"b": {"d": 3, "c": 2}, "a": 1})),
+        );
+    }
+}
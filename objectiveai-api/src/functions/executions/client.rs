@@ -2,7 +2,7 @@
 
 use crate::{
     chat, ctx, functions,
-    util::{ChoiceIndexer, StreamOnce},
+    util::{CancelOnDrop, ChoiceIndexer, StreamOnce},
     vector,
 };
 use futures::{Stream, StreamExt, TryStreamExt};
@@ -10,7 +10,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     hash::Hasher,
-    sync::Arc,
+    sync::{Arc, Mutex},
     time,
 };
 
@@ -26,14 +26,42 @@ pub fn vector_response_id(created: u64) -> String {
     format!("vctfnc-{}-{}", uuid.simple(), created)
 }
 
+/// Generates a unique response ID for multi-label Function executions.
+pub fn multi_label_response_id(created: u64) -> String {
+    let uuid = uuid::Uuid::new_v4();
+    format!("mlbfnc-{}-{}", uuid.simple(), created)
+}
+
+/// Generates a unique response ID for categorical Function executions.
+pub fn categorical_response_id(created: u64) -> String {
+    let uuid = uuid::Uuid::new_v4();
+    format!("ctgfnc-{}-{}", uuid.simple(), created)
+}
+
+/// Returns the tournament strategy of a request, if any, where `top_k`
+/// implies the Swiss system.
+fn tournament_strategy(
+    base: &objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody,
+) -> Option<objectiveai::functions::executions::request::Strategy> {
+    match &base.strategy {
+        Some(strategy) if strategy.is_tournament() => Some(strategy.clone()),
+        _ => base.top_k.map(|_| {
+            objectiveai::functions::executions::request::Strategy::SwissSystem {
+                pool: None,
+                rounds: None,
+            }
+        }),
+    }
+}
+
 /// Computes the final function output as a weighted average of task outputs.
 ///
-/// All task outputs are already validated `FunctionOutput` (scalar or vector)
-/// by their respective output expressions. This function is deterministically
+/// All task outputs are already validated `FunctionOutput` (scalar, vector,
+/// multi-label or categorical) by their respective output expressions. This function is deterministically
 /// infallible - all inputs are assumed valid.
 ///
 /// The weights are L1-normalized for the indices that are present (non-None, non-error).
-fn compute_weighted_function_output(
+pub(crate) fn compute_weighted_function_output(
     function_type: &functions::FunctionType,
     profile_weights: &[rust_decimal::Decimal],
     task_outputs: &[Option<objectiveai::functions::expression::FunctionOutput>],
@@ -117,6 +145,34 @@ fn compute_weighted_function_output(
             }
             FunctionOutput::Vector(result)
         }
+        functions::FunctionType::MultiLabel { labels }
+        | functions::FunctionType::Categorical { categories: labels } => {
+            // Compute weighted average for each label with L1-normalized weights
+            let mut result: indexmap::IndexMap<String, Decimal> = labels
+                .iter()
+                .map(|label| (label.clone(), Decimal::ZERO))
+                .collect();
+            for (weight, fn_output) in &weighted_outputs {
+                match fn_output {
+                    FunctionOutput::MultiLabel(scores) | FunctionOutput::Categorical(scores) => {
+                        let normalized_weight = *weight / total_weight;
+                        for (label, val) in scores {
+                            match result.get_mut(label) {
+                                Some(sum) => *sum += normalized_weight * val,
+                                None => panic!("unexpected label: {}", label),
+                            }
+                        }
+                    }
+                    _ => {
+                        panic!("expected labelled output in labelled function, got {:?}", fn_output);
+                    }
+                }
+            }
+            match function_type {
+                functions::FunctionType::MultiLabel { .. } => FunctionOutput::MultiLabel(result),
+                _ => FunctionOutput::Categorical(result),
+            }
+        }
     }
 }
 /// Applies a task's output expression to transform a raw task output into a FunctionOutput.
@@ -131,7 +187,7 @@ fn compute_weighted_function_output(
 /// the function type (scalar vs vector) and optional output length.
 ///
 /// Returns the output (possibly as `FunctionOutput::Err` if invalid) and an optional error.
-fn apply_task_output_expression(
+pub(crate) fn apply_task_output_expression(
     input: &objectiveai::functions::expression::Input,
     task_output: objectiveai::functions::expression::TaskOutputOwned,
     output_expression: &objectiveai::functions::expression::Expression,
@@ -144,28 +200,44 @@ fn apply_task_output_expression(
     use objectiveai::functions::expression::{FunctionOutput, TaskOutput, Params, ParamsRef};
     use rust_decimal::Decimal;
 
+    fn invert_distribution<'a>(v: impl ExactSizeIterator<Item = &'a mut Decimal>) {
+        let mut v = v.collect::<Vec<_>>();
+        if v.is_empty() {
+            return;
+        }
+        for x in v.iter_mut() {
+            **x = Decimal::ONE - **x;
+        }
+        let sum: Decimal = v.iter().map(|x| x.abs()).sum();
+        if sum == Decimal::ZERO {
+            let uniform = Decimal::ONE / Decimal::from(v.len());
+            for x in v.iter_mut() {
+                **x = uniform;
+            }
+        } else {
+            for x in v.iter_mut() {
+                **x /= sum;
+            }
+        }
+    }
+
     fn invert_function_output(output: FunctionOutput) -> FunctionOutput {
         match output {
             FunctionOutput::Scalar(s) => FunctionOutput::Scalar(Decimal::ONE - s),
             FunctionOutput::Vector(mut v) => {
-                if v.is_empty() {
-                    return FunctionOutput::Vector(v);
-                }
-                for x in &mut v {
+                invert_distribution(v.iter_mut());
+                FunctionOutput::Vector(v)
+            }
+            // labels are independent, each score is inverted on its own
+            FunctionOutput::MultiLabel(mut scores) => {
+                for x in scores.values_mut() {
                     *x = Decimal::ONE - *x;
                 }
-                let sum: Decimal = v.iter().map(|x| x.abs()).sum();
-                if sum == Decimal::ZERO {
-                    let uniform = Decimal::ONE / Decimal::from(v.len());
-                    for x in &mut v {
-                        *x = uniform;
-                    }
-                } else {
-                    for x in &mut v {
-                        *x /= sum;
-                    }
-                }
-                FunctionOutput::Vector(v)
+                FunctionOutput::MultiLabel(scores)
+            }
+            FunctionOutput::Categorical(mut scores) => {
+                invert_distribution(scores.values_mut());
+                FunctionOutput::Categorical(scores)
             }
             FunctionOutput::Err(e) => FunctionOutput::Err(e),
         }
@@ -176,6 +248,7 @@ fn apply_task_output_expression(
         input,
         output: Some(TaskOutput::Owned(task_output)),
         map: None,
+        tasks: None,
     });
 
     // Evaluate the expression - it transforms the raw output into FunctionOutput
@@ -185,14 +258,49 @@ fn apply_task_output_expression(
             return (
                 FunctionOutput::Err(serde_json::Value::Null),
                 Some(objectiveai::error::ResponseError::from(
-                    &super::Error::InvalidAppExpression(e),
+                    &super::Error::InvalidAppExpression(e.with_field(
+                        objectiveai::functions::expression::ExpressionField::Output,
+                    )),
                 )),
             );
         }
     };
 
     // Validate the output against the function type
-    let (validated, err) = match (function_type, result) {
+    let (validated, err) = validate_function_output(
+        function_type,
+        result,
+        "task_output_expression_error",
+    );
+
+    if err.is_none() && invert_output {
+        (invert_function_output(validated), None)
+    } else {
+        (validated, err)
+    }
+}
+
+/// Validates an output against the function type (scalar, vector,
+/// multi-label or categorical) and optional output length.
+///
+/// Labelled outputs may also be returned as a vector in label order, and are
+/// converted to scores keyed by label.
+///
+/// Returns the output (as `FunctionOutput::Err` if invalid) and an optional
+/// error. Error values produced by the expression itself are reported with
+/// `error_kind`.
+fn validate_function_output(
+    function_type: &functions::FunctionType,
+    result: objectiveai::functions::expression::FunctionOutput,
+    error_kind: &'static str,
+) -> (
+    objectiveai::functions::expression::FunctionOutput,
+    Option<objectiveai::error::ResponseError>,
+) {
+    use objectiveai::functions::expression::FunctionOutput;
+    use rust_decimal::Decimal;
+
+    match (function_type, result) {
         // Scalar function must return scalar output (allow -0.01 to 1.01 for floating point tolerance)
         (functions::FunctionType::Scalar, FunctionOutput::Scalar(s)) => {
             if s >= rust_decimal::dec!(-0.01) && s <= rust_decimal::dec!(1.01) {
@@ -206,8 +314,13 @@ fn apply_task_output_expression(
                 )
             }
         }
-        // Scalar function got vector output - error
-        (functions::FunctionType::Scalar, result @ FunctionOutput::Vector(_)) => (
+        // Scalar function got vector or labelled output - error
+        (
+            functions::FunctionType::Scalar,
+            result @ (FunctionOutput::Vector(_)
+            | FunctionOutput::MultiLabel(_)
+            | FunctionOutput::Categorical(_)),
+        ) => (
             result.into_err(),
             Some(objectiveai::error::ResponseError::from(
                 &super::Error::InvalidScalarOutput,
@@ -230,8 +343,13 @@ fn apply_task_output_expression(
                 )
             }
         }
-        // Vector function got scalar output - error
-        (functions::FunctionType::Vector { output_length, .. }, result @ FunctionOutput::Scalar(_)) => (
+        // Vector function got scalar or labelled output - error
+        (
+            functions::FunctionType::Vector { output_length, .. },
+            result @ (FunctionOutput::Scalar(_)
+            | FunctionOutput::MultiLabel(_)
+            | FunctionOutput::Categorical(_)),
+        ) => (
             result.into_err(),
             Some(objectiveai::error::ResponseError::from(
                 &super::Error::InvalidVectorOutput(output_length.unwrap_or_default() as usize),
@@ -243,18 +361,132 @@ fn apply_task_output_expression(
             Some(objectiveai::error::ResponseError {
                 code: 400,
                 message: serde_json::json!({
-                    "kind": "task_output_expression_error",
+                    "kind": error_kind,
                     "error": err_val,
                 }),
             }),
         ),
+        // Multi-label function must return a score in [0, 1] for each label,
+        // keyed by label or as a vector in label order
+        (functions::FunctionType::MultiLabel { labels }, result) => {
+            match result.into_labelled(labels) {
+                Ok(scores)
+                    if scores.values().all(|s| {
+                        *s >= rust_decimal::dec!(-0.01) && *s <= rust_decimal::dec!(1.01)
+                    }) =>
+                {
+                    (FunctionOutput::MultiLabel(scores), None)
+                }
+                Ok(scores) => (
+                    FunctionOutput::MultiLabel(scores).into_err(),
+                    Some(objectiveai::error::ResponseError::from(
+                        &super::Error::InvalidMultiLabelOutput(labels.clone()),
+                    )),
+                ),
+                Err(result) => (
+                    result.into_err(),
+                    Some(objectiveai::error::ResponseError::from(
+                        &super::Error::InvalidMultiLabelOutput(labels.clone()),
+                    )),
+                ),
+            }
+        }
+        // Categorical function must return scores summing to 1 over its
+        // categories, keyed by category or as a vector in category order
+        (functions::FunctionType::Categorical { categories }, result) => {
+            match result.into_labelled(categories) {
+                Ok(scores)
+                    if {
+                        let sum: Decimal = scores.values().cloned().sum();
+                        sum >= rust_decimal::dec!(0.99) && sum <= rust_decimal::dec!(1.01)
+                    } =>
+                {
+                    (FunctionOutput::Categorical(scores), None)
+                }
+                Ok(scores) => (
+                    FunctionOutput::Categorical(scores).into_err(),
+                    Some(objectiveai::error::ResponseError::from(
+                        &super::Error::InvalidCategoricalOutput(categories.clone()),
+                    )),
+                ),
+                Err(result) => (
+                    result.into_err(),
+                    Some(objectiveai::error::ResponseError::from(
+                        &super::Error::InvalidCategoricalOutput(categories.clone()),
+                    )),
+                ),
+            }
+        }
+    }
+}
+
+/// Computes the final function output from its task outputs.
+///
+/// Without an output expression, this is the weighted average of the task
+/// outputs (see [`compute_weighted_function_output`]). With one, the
+/// expression receives `input` and `tasks`, where `tasks[i]` is the task's
+/// output and profile weight (`null` for tasks without an output), and its
+/// result is validated against the function type.
+///
+/// Returns the output (possibly as `FunctionOutput::Err` if invalid) and an optional error.
+pub(crate) fn compute_function_output(
+    input: &objectiveai::functions::expression::Input,
+    output_expression: Option<&objectiveai::functions::expression::Expression>,
+    function_type: &functions::FunctionType,
+    profile_weights: &[rust_decimal::Decimal],
+    task_outputs: &[Option<objectiveai::functions::expression::FunctionOutput>],
+) -> (
+    objectiveai::functions::expression::FunctionOutput,
+    Option<objectiveai::error::ResponseError>,
+) {
+    use objectiveai::functions::expression::{
+        FunctionOutput, Params, ParamsRef, TaskOutputOwned, TaskResult,
     };
 
-    if err.is_none() && invert_output {
-        (invert_function_output(validated), None)
-    } else {
-        (validated, err)
-    }
+    let Some(output_expression) = output_expression else {
+        return (
+            compute_weighted_function_output(function_type, profile_weights, task_outputs),
+            None,
+        );
+    };
+
+    // expose each valid task output alongside its profile weight
+    let tasks = task_outputs
+        .iter()
+        .enumerate()
+        .map(|(i, task_output)| match task_output {
+            Some(output) if !matches!(output, FunctionOutput::Err(_)) => Some(TaskResult {
+                output: TaskOutputOwned::Function(output.clone()),
+                weight: Some(
+                    profile_weights.get(i).copied().unwrap_or(rust_decimal::Decimal::ZERO),
+                ),
+            }),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let params = Params::Ref(ParamsRef {
+        input,
+        output: None,
+        map: None,
+        tasks: Some(&tasks),
+    });
+
+    // Evaluate the expression - it combines the task outputs into a FunctionOutput
+    let result = match output_expression.compile_one::<FunctionOutput>(&params) {
+        Ok(result) => result,
+        Err(e) => {
+            return (
+                FunctionOutput::Err(serde_json::Value::Null),
+                Some(objectiveai::error::ResponseError::from(
+                    &super::Error::InvalidAppExpression(e.with_field(
+                        objectiveai::functions::expression::ExpressionField::Output,
+                    )),
+                )),
+            );
+        }
+    };
+
+    validate_function_output(function_type, result, "function_output_expression_error")
 }
 
 /// Client for executing Functions.
@@ -297,6 +529,14 @@ pub struct Client<
     pub profile_fetcher: Arc<FPFL>,
     /// Handler for recording usage after execution.
     pub usage_handler: Arc<FUSG>,
+    /// Callers and cancellation tokens of running executions, by response
+    /// ID.
+    pub cancellations: dashmap::DashMap<
+        String,
+        (Option<String>, tokio_util::sync::CancellationToken),
+    >,
+    /// Finished executions stored for requests with `cache`.
+    pub execution_cache: super::ExecutionCache,
 }
 
 impl<CTXEXT, FENSLLM, CUSG, FENS, FVVOTE, FCVOTE, VUSG, FFN, FPFL, FUSG>
@@ -322,6 +562,7 @@ impl<CTXEXT, FENSLLM, CUSG, FENS, FVVOTE, FCVOTE, VUSG, FFN, FPFL, FUSG>
         function_fetcher: Arc<FFN>,
         profile_fetcher: Arc<FPFL>,
         usage_handler: Arc<FUSG>,
+        execution_cache: super::ExecutionCache,
     ) -> Self {
         Self {
             chat_client,
@@ -330,6 +571,55 @@ impl<CTXEXT, FENSLLM, CUSG, FENS, FVVOTE, FCVOTE, VUSG, FFN, FPFL, FUSG>
             function_fetcher,
             profile_fetcher,
             usage_handler,
+            cancellations: dashmap::DashMap::new(),
+            execution_cache,
+        }
+    }
+
+    /// Cancels a running execution by its response ID.
+    ///
+    /// Outstanding upstream calls are aborted, and the execution finishes
+    /// with the tasks that completed, a retry token for them, and a
+    /// cancellation error. Only the caller that started an execution can
+    /// cancel it.
+    pub fn cancel(
+        &self,
+        ctx: &ctx::Context<CTXEXT>,
+        id: &str,
+    ) -> Result<(), super::Error>
+    where
+        CTXEXT: ctx::ContextExt,
+    {
+        match self.cancellations.get(id) {
+            Some(entry) if entry.0 == ctx.ext.caller() => {
+                entry.1.cancel();
+                Ok(())
+            }
+            _ => Err(super::Error::ExecutionNotFound),
+        }
+    }
+
+    /// Removes the caller's stored executions from the execution cache.
+    ///
+    /// Each filter is either `owner/repository` or `owner/repository/commit`
+    /// of a remote Function or Profile. Without filters, every stored
+    /// execution of the caller is removed. Executions stored for other
+    /// callers are never removed.
+    pub fn invalidate_cache(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        request: &objectiveai::functions::executions::cache::request::InvalidateCacheRequest,
+    ) -> objectiveai::functions::executions::cache::response::InvalidateCacheResponse
+    where
+        CTXEXT: ctx::ContextExt,
+    {
+        let invalidated = self.execution_cache.invalidate(
+            ctx.ext.caller().as_deref(),
+            request.function.as_deref(),
+            request.profile.as_deref(),
+        );
+        objectiveai::functions::executions::cache::response::InvalidateCacheResponse {
+            invalidated: invalidated as u64,
         }
     }
 }
@@ -389,6 +679,21 @@ where
     /// Executes a Function with streaming output and records usage.
     ///
     /// Streams chunks as they become available and records usage after completion.
+    ///
+    /// If the returned stream is dropped before it ends, or the execution is
+    /// cancelled with [`Client::cancel`], the execution is cancelled: it
+    /// finishes with whatever completed, followed by a cancellation error,
+    /// and the partial usage is recorded.
+    ///
+    /// With `max_cost`, the execution is cancelled in the same way once the
+    /// cost of its upstream calls reaches it, finishing with a max cost
+    /// error instead.
+    ///
+    /// With `cache`, a stored execution with the same key is returned as a
+    /// single chunk marked as cached, with no usage, and an execution that
+    /// finishes without errors is stored.
+    ///
+    /// Dry runs are rejected, they are estimated with [`Client::estimate`].
     pub async fn create_streaming_handle_usage(
         self: Arc<Self>,
         ctx: ctx::Context<CTXEXT>,
@@ -400,12 +705,46 @@ where
         + 'static,
         super::Error,
     >{
+        if request.base().dry_run.unwrap_or(false) {
+            return Err(super::Error::UnsupportedDryRun);
+        }
+
+        // cancel the execution if the response is dropped before it ends,
+        // or once it reaches its maximum cost
+        let ctx = ctx.child_with_max_cost(request.base().max_cost);
+        let cancel = ctx.cancel.clone();
         let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
         tokio::spawn(async move {
             let mut aggregate: Option<
                 objectiveai::functions::executions::response::streaming::FunctionExecutionChunk,
             > = None;
             let mut any_usage = false;
+            // reuse a stored execution, unless refreshing it
+            let cache = request.base().cache.as_ref();
+            let cache_key = match cache {
+                Some(_) => {
+                    match self.execution_cache_key(ctx.clone(), &request).await
+                    {
+                        Ok(key) => Some(key),
+                        Err(e) => {
+                            let _ = tx.send(Err(e));
+                            return;
+                        }
+                    }
+                }
+                None => None,
+            };
+            if let Some(key) = &cache_key
+                && !cache.and_then(|cache| cache.refresh).unwrap_or(false)
+                && let Some(mut execution) = self.execution_cache.get(key)
+            {
+                execution.cached = Some(true);
+                execution.usage = Some(
+                    objectiveai::vector::completions::response::Usage::default(),
+                );
+                let _ = tx.send(Ok(execution));
+                return;
+            }
             let stream = match self
                 .clone()
                 .create_streaming(ctx.clone(), request.clone())
@@ -417,8 +756,16 @@ where
                     return;
                 }
             };
+            // once cancelled, upstream calls end early and the execution
+            // drains with whatever completed
             futures::pin_mut!(stream);
             while let Some(chunk) = stream.next().await {
+                if aggregate.is_none() {
+                    self.cancellations.insert(
+                        chunk.id.clone(),
+                        (ctx.ext.caller(), ctx.cancel.clone()),
+                    );
+                }
                 any_usage |= chunk.any_usage();
                 match &mut aggregate {
                     Some(aggregate) => aggregate.push(&chunk),
@@ -427,19 +774,64 @@ where
                 let _ = tx.send(Ok(chunk));
             }
             drop(stream);
+            let Some(mut aggregate) = aggregate else {
+                return;
+            };
+            self.cancellations.remove(&aggregate.id);
+            if ctx.cancel.is_cancelled() {
+                let chunk = objectiveai::functions::executions::response::streaming::FunctionExecutionChunk {
+                    id: aggregate.id.clone(),
+                    tasks: Vec::new(),
+                    tasks_errors: None,
+                    reasoning: None,
+                    output: None,
+                    ranking: None,
+                    attribution: None,
+                    error: Some(objectiveai::error::ResponseError::from(
+                        &match ctx.max_cost_exceeded() {
+                            Some(max_cost) => {
+                                super::Error::MaxCostExceeded(max_cost)
+                            }
+                            None => super::Error::Cancelled,
+                        },
+                    )),
+                    // the tasks that completed are reused by a retry
+                    retry_token: aggregate.retry_token.clone(),
+                    created: aggregate.created,
+                    function: aggregate.function.clone(),
+                    profile: aggregate.profile.clone(),
+                    object: aggregate.object,
+                    usage: None,
+                    cached: None,
+                };
+                aggregate.push(&chunk);
+                let _ = tx.send(Ok(chunk));
+            } else if let Some(key) = cache_key
+                && aggregate.error.is_none()
+                && aggregate.tasks_errors != Some(true)
+            {
+                self.execution_cache.insert(
+                    key,
+                    aggregate.clone(),
+                    cache
+                        .and_then(|cache| cache.ttl)
+                        .map(time::Duration::from_millis),
+                );
+            }
             drop(tx);
             if any_usage {
                 self.usage_handler
-                    .handle_usage(ctx, request, aggregate.unwrap().into())
+                    .handle_usage(ctx, request, aggregate.into())
                     .await;
             }
         });
         let mut stream =
             tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
         match stream.next().await {
-            Some(Ok(chunk)) => {
-                Ok(StreamOnce::new(chunk).chain(stream.map(Result::unwrap)))
-            }
+            Some(Ok(chunk)) => Ok(CancelOnDrop::new(
+                StreamOnce::new(chunk).chain(stream.map(Result::unwrap)),
+                cancel,
+            )),
             Some(Err(e)) => Err(e),
             None => unreachable!(),
         }
@@ -478,6 +870,8 @@ where
     /// Fetches the Function and Profile, flattens them into tasks, and
     /// executes all tasks with streaming output. Handles reasoning summaries
     /// if requested.
+    ///
+    /// Dry runs are rejected, they are estimated with [`Client::estimate`].
     pub async fn create_streaming(
         self: Arc<Self>,
         ctx: ctx::Context<CTXEXT>,
@@ -488,6 +882,10 @@ where
         + 'static,
         super::Error,
     >{
+        if request.base().dry_run.unwrap_or(false) {
+            return Err(super::Error::UnsupportedDryRun);
+        }
+
         // timestamp the completion
         let created = time::SystemTime::now()
             .duration_since(time::UNIX_EPOCH)
@@ -508,31 +906,27 @@ where
             .transpose()?
             .map(Arc::new);
 
-        // validate that input_split and input_merge are present if strategy is Swiss
-        match (&request.base().strategy, request.inline_function()) {
+        // the tournament strategy, if any
+        let top_k = request.base().top_k;
+        let strategy = tournament_strategy(request.base());
+        let strategy = strategy.as_ref();
+
+        // validate that input_split and input_merge are present if strategy is a tournament
+        match (strategy, request.inline_function()) {
             (
-                Some(
-                    objectiveai::functions::executions::request::Strategy::SwissSystem {
-                        ..
-                    },
-                ),
+                Some(_),
                 Some(objectiveai::functions::InlineFunction::Vector {
                     input_split: Some(_),
                     input_merge: Some(_),
                     ..
                 })
             )=> { }
-            (
-                Some(
-                    objectiveai::functions::executions::request::Strategy::SwissSystem {
-                        ..
-                    },
-                ),
-                Some(_)
-            ) => {
+            (Some(strategy), Some(_)) => {
                 return Err(super::Error::InvalidFunctionForStrategy(
-                    "With 'swiss_system' strategy, Inline Function must be vector with both `input_split` and `input_merge` present."
-                        .to_string(),
+                    format!(
+                        "With '{}' strategy, Inline Function must be vector with both `input_split` and `input_merge` present.",
+                        strategy.name(),
+                    ),
                 ));
             }
             _ => { }
@@ -547,19 +941,15 @@ where
             )
             .await?;
 
-        // validate that ftp type is Vector if strategy is Swiss
-        match (&request.base().strategy, &ftp.r#type) {
-            (
-                Some(
-                    objectiveai::functions::executions::request::Strategy::SwissSystem {
-                        ..
-                    },
-                ),
-                functions::FunctionType::Scalar,
-            ) => {
+        // validate that ftp type is Vector if strategy is a tournament
+        match (strategy, &ftp.r#type) {
+            (Some(_), functions::FunctionType::Vector { .. }) => { }
+            (Some(strategy), _) => {
                 return Err(super::Error::InvalidFunctionForStrategy(
-                    "With 'swiss_system' strategy, Function must be of type 'vector'."
-                        .to_string(),
+                    format!(
+                        "With '{}' strategy, Function must be of type 'vector'.",
+                        strategy.name(),
+                    ),
                 ));
             }
             _ => { }
@@ -568,6 +958,9 @@ where
         // take description from ftp
         let description = ftp.description.take();
 
+        // take reasoning summary prompt from ftp
+        let reasoning_summary = ftp.reasoning_summary.take();
+
         // reasonong data
         let reasoning = request.base().reasoning.is_some();
         let mut reasoning_data = if reasoning {
@@ -650,27 +1043,21 @@ where
             None
         };
 
-        // Swiss System Strategy
+        // Tournament Strategies
         //
-        // A tournament-style ranking algorithm for vector functions:
+        // Tournament-style ranking algorithms for vector functions:
         //
-        // 1. Splits input into pools of `pool` size (or pool+1 when len % pool == 1
-        //    to avoid single-item trailing chunks)
-        // 2. Each pool must have at least 2 items, except when the original input
-        //    itself has only 1 item (user's choice)
-        // 3. Runs each round, accumulating scores for each item
-        // 4. After each round, re-sorts items by cumulative scores and re-pools
-        // 5. Final output is the average of scores from all rounds, mapped back
+        // 1. Splits input into items with `input_split`
+        // 2. Each round, the strategy groups items into pools, and each pool is
+        //    merged with `input_merge` and executed
+        // 3. The strategy records the scores of each pool, and decides the
+        //    pools of the next round from them (see `Tournament`)
+        // 4. Final output and ranking are computed by the strategy, mapped back
         //    to original input order
         //
         // Only the first round uses retry tokens; subsequent rounds do not.
         // Errors from subsequent rounds are included in the final output chunk.
-        if let Some(
-            objectiveai::functions::executions::request::Strategy::SwissSystem {
-                pool,
-                rounds,
-            }
-        ) = &request.base().strategy {
+        if let Some(strategy) = strategy {
             // take and unwrap input_split and input_merge
             let (input_split, input_merge) = match &ftp.r#type {
                 functions::FunctionType::Vector {
@@ -684,16 +1071,6 @@ where
                 _ => unreachable!(),
             };
 
-            // validate pool and rounds
-            let pool = pool.unwrap_or(10);
-            let rounds = rounds.unwrap_or(3);
-            if pool <= 1 || rounds == 0 {
-                return Err(super::Error::InvalidStrategy(
-                    "For 'swiss_system' strategy, 'pool' must be > 1 and 'rounds' must be > 0."
-                        .to_string(),
-                ));
-            }
-
             // split input
             let split_input = input_split.compile_one(
                 &objectiveai::functions::expression::Params::Ref(
@@ -701,30 +1078,31 @@ where
                         input: &request.base().input,
                         output: None,
                         map: None,
+                        tasks: None,
                     }
                 ),
             )?;
 
+            // validate strategy and pool the first round
+            let mut tournament =
+                super::Tournament::new(strategy, split_input.len(), top_k)?
+                    .expect("tournament strategy");
+            let pools = tournament.next_round().unwrap_or_default();
+
             // fetch initial FTPs
-            let mut ftp_futs = Vec::with_capacity(split_input.len() / pool + 1);
-            let mut pool_chunk_sizes: Vec<usize> = Vec::with_capacity(split_input.len() / pool + 1);
-            let chunks = split_input.chunks(
-                if split_input.len() % pool == 1 {
-                    pool + 1
-                } else {
-                    pool
-                }
-            );
-            for chunk in chunks {
-                pool_chunk_sizes.push(chunk.len());
+            let mut ftp_futs = Vec::with_capacity(pools.len());
+            for pool in pools {
                 let joined_input = input_merge.clone().compile_one(
                     &objectiveai::functions::expression::Params::Owned(
                         objectiveai::functions::expression::ParamsOwned {
                             input: objectiveai::functions::expression::Input::Array(
-                                chunk.to_vec(),
+                                pool.iter()
+                                    .map(|&i| split_input[i].clone())
+                                    .collect(),
                             ),
                             output: None,
                             map: None,
+                            tasks: None,
                         }
                     )
                 )?;
@@ -736,7 +1114,7 @@ where
             }
             let mut ftps = futures::future::try_join_all(ftp_futs).await?;
 
-            // setup reasoning data for Swiss system
+            // setup reasoning data for tournament
             let (mut swiss_vector_completions, mut swiss_index_maps, swiss_confidence_responses) = if reasoning {
                 // extract confidence_responses from reasoning_data (built from original ftp)
                 let (_, (_, confidence_responses), _) = reasoning_data.take().unwrap();
@@ -819,17 +1197,6 @@ where
                 first_round_retry_token.0.push(None);
             }
 
-            // track original indices: current_position -> original_index
-            let num_items = split_input.len();
-            let mut current_to_original: Vec<usize> = (0..num_items).collect();
-
-            // track cumulative scores per original index (for sorting)
-            let mut cumulative_scores: Vec<rust_decimal::Decimal> =
-                vec![rust_decimal::Decimal::ZERO; num_items];
-
-            // track outputs per round: round -> (original_index -> score)
-            let mut round_outputs: Vec<Vec<rust_decimal::Decimal>> = Vec::with_capacity(rounds as usize);
-
             // identifiers
             let function =
                 ftp.full_function_id.map(|(owner, repository, commit)| {
@@ -846,9 +1213,9 @@ where
                 // track errors from subsequent rounds to include in final output
                 let mut subsequent_round_error: Option<objectiveai::error::ResponseError> = None;
 
-                'rounds: for current_round in 1..=rounds {
+                let mut current_round: usize = 1;
+                'rounds: while !ftps.is_empty() {
                     let is_first_round = current_round == 1;
-                    let is_last_round = current_round == rounds;
 
                     // run all pools for this round
                     let mut streams = Vec::with_capacity(ftps.len());
@@ -925,6 +1292,8 @@ where
                                     },
                                     reasoning: None,
                                     output: None,
+                                    ranking: None,
+                                    attribution: None,
                                     error: None,
                                     retry_token: None,
                                     created,
@@ -932,6 +1301,7 @@ where
                                     profile: profile.clone(),
                                     object,
                                     usage: None,
+                                    cached: None,
                                 };
                             }
                             FtpStreamChunk::OutputChunk { retry_token: chunk_retry_token, .. } => {
@@ -964,71 +1334,38 @@ where
                                     }
                                 }
                             }
-                        }
-                    }
-
-                    // map pool outputs back to original indices and update cumulative scores
-                    let mut this_round_scores: Vec<rust_decimal::Decimal> =
-                        vec![rust_decimal::Decimal::ZERO; num_items];
-
-                    let mut position = 0usize;
-                    for (pool_idx, &chunk_size) in pool_chunk_sizes.iter().enumerate() {
-                        if let Some(scores) = pool_outputs.get(&pool_idx) {
-                            for (local_idx, &score) in scores.iter().enumerate() {
-                                let current_pos = position + local_idx;
-                                if current_pos < current_to_original.len() {
-                                    let original_idx = current_to_original[current_pos];
-                                    this_round_scores[original_idx] = score;
-                                    cumulative_scores[original_idx] += score;
+                            FtpStreamChunk::ChatCompletionTaskChunk { chunk, .. } => {
+                                // track usage and errors
+                                tasks_errors |= chunk.error.is_some();
+                                if let Some(chunk_usage) = &chunk.inner.usage {
+                                    usage.push_chat_completion_usage(chunk_usage);
                                 }
                             }
                         }
-                        // always advance by expected chunk size, even if pool had no output
-                        position += chunk_size;
                     }
-                    round_outputs.push(this_round_scores);
-
-                    // if not last round, re-sort and prepare next round
-                    if !is_last_round {
-                        // create sorted indices by cumulative score (descending), with original index as tie-breaker
-                        let mut sorted_indices: Vec<usize> = (0..num_items).collect();
-                        sorted_indices.sort_by(|&a, &b| {
-                            cumulative_scores[b].cmp(&cumulative_scores[a])
-                                .then_with(|| a.cmp(&b))
-                        });
-
-                        // update current_to_original mapping
-                        // sorted_indices[new_pos] = original_idx
-                        current_to_original = sorted_indices.clone();
-
-                        // rebuild split_input in new sorted order
-                        let sorted_split_input: Vec<objectiveai::functions::expression::Input> =
-                            sorted_indices.iter()
-                                .map(|&orig_idx| split_input[orig_idx].clone())
-                                .collect();
-
-                        // re-chunk and fetch new FTPs
-                        let chunks = sorted_split_input.chunks(
-                            if sorted_split_input.len() % pool == 1 {
-                                pool + 1
-                            } else {
-                                pool
-                            }
-                        );
 
-                        // update pool_chunk_sizes for this round
-                        pool_chunk_sizes.clear();
-                        let mut ftp_futs = Vec::with_capacity(chunks.len());
-                        for chunk in chunks {
-                            pool_chunk_sizes.push(chunk.len());
+                    // record pool outputs with the strategy
+                    for (pool_idx, scores) in pool_outputs {
+                        tournament.record(pool_idx, scores);
+                    }
+                    tournament.end_round();
+
+                    // pool the next round, if any
+                    if let Some(pools) = tournament.next_round() {
+                        // merge and fetch new FTPs
+                        let mut ftp_futs = Vec::with_capacity(pools.len());
+                        for pool in pools {
                             let joined_input = match input_merge.clone().compile_one(
                                 &objectiveai::functions::expression::Params::Owned(
                                     objectiveai::functions::expression::ParamsOwned {
                                         input: objectiveai::functions::expression::Input::Array(
-                                            chunk.to_vec(),
+                                            pool.iter()
+                                                .map(|&i| split_input[i].clone())
+                                                .collect(),
                                         ),
                                         output: None,
                                         map: None,
+                                        tasks: None,
                                     }
                                 )
                             ) {
@@ -1104,33 +1441,16 @@ where
                         // reset retry token tracking for next round
                         retry_token_indices.clear();
                         retry_token_index = 0;
+                        current_round += 1;
+                    } else {
+                        break;
                     }
                 }
 
-                // compute final output: average scores across rounds, in original order
-                let num_rounds = round_outputs.len();
-                let mut final_output: Vec<rust_decimal::Decimal> = vec![rust_decimal::Decimal::ZERO; num_items];
-
-                if num_rounds > 0 {
-                    let num_rounds_dec = rust_decimal::Decimal::from(num_rounds as u64);
-                    for original_idx in 0..num_items {
-                        let mut sum = rust_decimal::Decimal::ZERO;
-                        for round in &round_outputs {
-                            sum += round[original_idx];
-                        }
-                        final_output[original_idx] = sum / num_rounds_dec;
-                    }
-
-                    // normalize to sum to 1
-                    let total: rust_decimal::Decimal = final_output.iter().copied().sum();
-                    if total > rust_decimal::Decimal::ZERO {
-                        for score in &mut final_output {
-                            *score /= total;
-                        }
-                    }
-                }
+                // compute final output and ranking, in original order
+                let (final_output, ranking) = tournament.finish();
 
-                // handle reasoning for Swiss system
+                // handle reasoning for tournament
                 if let (Some(vector_completions), Some(index_maps), Some(mut confidence_responses)) =
                     (swiss_vector_completions, swiss_index_maps, swiss_confidence_responses)
                 {
@@ -1138,6 +1458,7 @@ where
                     let objectiveai::functions::executions::request::Reasoning {
                         model,
                         models,
+                        prompt,
                     } = request.base().reasoning.as_ref().unwrap();
 
                     // iterate over vector completion chunks
@@ -1226,6 +1547,7 @@ where
                         model.clone(),
                         models.clone(),
                         description,
+                        prompt.clone().or(reasoning_summary),
                         objectiveai::functions::expression::FunctionOutput::Vector(final_output.clone()),
                         confidence_responses,
                     ).await;
@@ -1249,6 +1571,8 @@ where
                             },
                             reasoning: Some(chunk),
                             output: None,
+                            ranking: None,
+                            attribution: None,
                             error: None,
                             retry_token: None,
                             created,
@@ -1256,6 +1580,7 @@ where
                             profile: profile.clone(),
                             object,
                             usage: None,
+                            cached: None,
                         };
                     }
                 }
@@ -1271,6 +1596,8 @@ where
                     },
                     reasoning: None,
                     output: Some(objectiveai::functions::expression::FunctionOutput::Vector(final_output)),
+                    ranking: Some(ranking),
+                    attribution: None,
                     error: subsequent_round_error,
                     retry_token: Some(first_round_retry_token.to_string()),
                     created,
@@ -1278,6 +1605,7 @@ where
                     profile,
                     object,
                     usage: Some(usage),
+                    cached: None,
                 };
             }))
         } else {
@@ -1348,6 +1676,7 @@ where
                     let objectiveai::functions::executions::request::Reasoning {
                         model,
                         models,
+                        prompt,
                     } = request.base().reasoning.as_ref().unwrap();
                     let (
                         vector_completions,
@@ -1452,6 +1781,7 @@ where
                         model.clone(),
                         models.clone(),
                         description,
+                        prompt.clone().or(reasoning_summary),
                         final_chunk.output.clone().expect("missing output"),
                         confidence_responses,
                     ).await;
@@ -1477,6 +1807,8 @@ where
                             tasks_errors: final_chunk.tasks_errors,
                             reasoning: Some(chunk),
                             output: None,
+                            ranking: None,
+                            attribution: None,
                             error: None,
                             retry_token: None,
                             created: final_chunk.created,
@@ -1484,6 +1816,7 @@ where
                             profile: final_chunk.profile.clone(),
                             object: final_chunk.object.clone(),
                             usage: None,
+                            cached: None,
                         };
                     }
 
@@ -1494,46 +1827,389 @@ where
         }
     }
 
-    async fn fetch_function_flat_task_profile(
+    /// Checks that a Profile fits the task structure of a Function,
+    /// fetching remote definitions as needed.
+    pub async fn check_compatibility(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        request: objectiveai::functions::CheckCompatibilityRequest,
+    ) -> Result<objectiveai::functions::CheckCompatibilityResponse, super::Error>
+    {
+        let function = match request.function {
+            objectiveai::functions::executions::batch::request::BatchFunction::Remote {
+                owner,
+                repository,
+                commit,
+            } => objectiveai::functions::Function::Remote(
+                self.function_fetcher
+                    .fetch(ctx.clone(), &owner, &repository, commit.as_deref())
+                    .await
+                    .map_err(super::Error::FetchFunction)?
+                    .ok_or(super::Error::FunctionNotFound)?
+                    .inner,
+            ),
+            objectiveai::functions::executions::batch::request::BatchFunction::Inline(
+                function,
+            ) => objectiveai::functions::Function::Inline(function),
+        };
+        let profile = match request.profile {
+            objectiveai::functions::executions::batch::request::BatchProfile::Remote {
+                owner,
+                repository,
+                commit,
+            } => objectiveai::functions::Profile::Remote(
+                self.profile_fetcher
+                    .fetch(ctx.clone(), &owner, &repository, commit.as_deref())
+                    .await
+                    .map_err(super::Error::FetchProfile)?
+                    .ok_or(super::Error::ProfileNotFound)?
+                    .inner,
+            ),
+            objectiveai::functions::executions::batch::request::BatchProfile::Inline(
+                profile,
+            ) => objectiveai::functions::Profile::Inline(profile),
+        };
+        Ok(functions::check_compatibility(
+            ctx,
+            &function,
+            &profile,
+            self.function_fetcher.clone(),
+            self.profile_fetcher.clone(),
+            self.ensemble_fetcher.clone(),
+        )
+        .await
+        .into())
+    }
+
+    /// Estimates the upstream calls of an execution without making them.
+    ///
+    /// The Function is flattened as for a dry run, so chat completion tasks
+    /// are not run. Tournament rounds are simulated as if every pool were a
+    /// tie. Dependent tasks are compiled once their dependencies have run,
+    /// and so are tasks that cannot be compiled without the outputs of chat
+    /// completion tasks, so their calls are not included.
+    ///
+    /// Fails if an Ensemble LLM cannot be fetched, rather than leave its
+    /// calls out.
+    pub async fn estimate(
         &self,
         ctx: ctx::Context<CTXEXT>,
         request: Arc<objectiveai::functions::executions::request::Request>,
-        input: Option<objectiveai::functions::expression::Input>,
-    ) -> Result<functions::FunctionFlatTaskProfile, super::Error> {
-        match &*request {
-            objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
-                body,
-            } => {
-                functions::get_flat_task_profile(
-                    ctx,
-                    Vec::new(),
-                    functions::FunctionParam::FetchedOrInline {
-                        full_id: None,
-                        function: objectiveai::functions::Function::Inline(
-                            body.function.clone(),
-                        ),
-                    },
-                    functions::ProfileParam::FetchedOrInline {
-                        full_id: None,
-                        profile: objectiveai::functions::Profile::Inline(
-                            body.profile.clone(),
-                        ),
-                    },
-                    input.unwrap_or_else(|| body.base.input.clone()),
-                    None, // Root-level function has no parent task output expression
-                    false, // Root-level function has no invert flag
-                    self.function_fetcher.clone(),
-                    self.profile_fetcher.clone(),
-                    self.ensemble_fetcher.clone(),
-                )
-                .await
+    ) -> Result<
+        objectiveai::functions::executions::response::FunctionExecutionEstimate,
+        super::Error,
+    > {
+        let request = if request.base().dry_run == Some(true) {
+            request
+        } else {
+            let mut request = (*request).clone();
+            request.base_mut().dry_run = Some(true);
+            Arc::new(request)
+        };
+        let mut estimator = super::Estimator::new(
+            request
+                .base()
+                .provider
+                .and_then(|provider| provider.max_price),
+        );
+        let mut llms = HashMap::new();
+
+        // flatten the Function
+        let ftp = self
+            .fetch_function_flat_task_profile(
+                ctx.clone(),
+                request.clone(),
+                None,
+            )
+            .await?;
+        self.fetch_estimate_llms(
+            ctx.clone(),
+            super::chat_completion_model_ids(&ftp),
+            &mut llms,
+        )
+        .await?;
+
+        // simulate the tournament, if any, otherwise execute the Function
+        let rounds = if let Some(strategy) = tournament_strategy(request.base())
+        {
+            let (input_split, input_merge) = match &ftp.r#type {
+                functions::FunctionType::Vector {
+                    input_split: Some(input_split),
+                    input_merge: Some(input_merge),
+                    ..
+                } => (input_split.clone(), input_merge.clone()),
+                _ => {
+                    return Err(super::Error::InvalidFunctionForStrategy(
+                        format!(
+                            "With '{}' strategy, Function must be of type 'vector' with both `input_split` and `input_merge` present.",
+                            strategy.name(),
+                        ),
+                    ));
+                }
+            };
+            estimator.push_function(&ftp, None, &llms, false);
+            let split_input = input_split.compile_one(
+                &objectiveai::functions::expression::Params::Ref(
+                    objectiveai::functions::expression::ParamsRef {
+                        input: &request.base().input,
+                        output: None,
+                        map: None,
+                        tasks: None,
+                    },
+                ),
+            )?;
+            let mut tournament = super::Tournament::new(
+                &strategy,
+                split_input.len(),
+                request.base().top_k,
+            )?
+            .expect("tournament strategy");
+            let mut round = 0;
+            while let Some(pools) = tournament.next_round() {
+                round += 1;
+                let mut ftp_futs = Vec::with_capacity(pools.len());
+                for pool in pools {
+                    let joined_input = input_merge.clone().compile_one(
+                        &objectiveai::functions::expression::Params::Owned(
+                            objectiveai::functions::expression::ParamsOwned {
+                                input: objectiveai::functions::expression::Input::Array(
+                                    pool.iter()
+                                        .map(|&i| split_input[i].clone())
+                                        .collect(),
+                                ),
+                                output: None,
+                                map: None,
+                                tasks: None,
+                            },
+                        ),
+                    )?;
+                    ftp_futs.push(self.fetch_function_flat_task_profile(
+                        ctx.clone(),
+                        request.clone(),
+                        Some(joined_input),
+                    ));
+                }
+                for ftp in futures::future::try_join_all(ftp_futs).await? {
+                    self.fetch_estimate_llms(
+                        ctx.clone(),
+                        super::chat_completion_model_ids(&ftp),
+                        &mut llms,
+                    )
+                    .await?;
+                    estimator.push_function(&ftp, Some(round), &llms, true);
+                }
+                tournament.end_round();
+            }
+            Some(round)
+        } else {
+            estimator.push_function(&ftp, None, &llms, true);
+            None
+        };
+
+        // the reasoning summary, prompted with the input and responses
+        if let Some(reasoning) = &request.base().reasoning {
+            if let objectiveai::chat::completions::request::Model::Id(id) =
+                &reasoning.model
+            {
+                self.fetch_estimate_llms(ctx, vec![id.clone()], &mut llms)
+                    .await?;
+            }
+            let llm = match &reasoning.model {
+                objectiveai::chat::completions::request::Model::Id(id) => {
+                    llms.get(id)
+                }
+                objectiveai::chat::completions::request::Model::Provided(
+                    llm,
+                ) => Some(llm),
+            };
+            if let Some(llm) = llm {
+                let responses = ftp
+                    .tasks
+                    .iter()
+                    .flatten()
+                    .flat_map(|task| task.vector_completion_ftps())
+                    .flat_map(|vector| vector.responses.iter())
+                    .collect::<Vec<_>>();
+                let template = reasoning
+                    .prompt
+                    .as_ref()
+                    .or(ftp.reasoning_summary.as_ref())
+                    .and_then(|prompt| prompt.template.as_ref());
+                let prompt_tokens = super::estimate_tokens(&[
+                    &request.base().input,
+                ]) + super::estimate_tokens(&[&ftp.description])
+                    + super::estimate_tokens(&responses)
+                    + template.map_or(0, |template| {
+                        super::estimate_tokens(&[template])
+                    });
+                estimator.push_call(
+                    objectiveai::functions::executions::response::EstimatedCallKind::ReasoningSummary,
+                    Vec::new(),
+                    None,
+                    llm,
+                    prompt_tokens,
+                );
+            }
+        }
+
+        let full_id = |id: &Option<(String, String, String)>| {
+            id.as_ref().map(|(owner, repository, commit)| {
+                format!("{}/{}/{}", owner, repository, commit)
+            })
+        };
+        Ok(estimator.finish(
+            full_id(&ftp.full_function_id),
+            full_id(&ftp.full_profile_id),
+            rounds,
+        ))
+    }
+
+    /// Fetches the Ensemble LLMs of `ids` missing from `llms`.
+    async fn fetch_estimate_llms(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        ids: Vec<String>,
+        llms: &mut HashMap<String, objectiveai::ensemble_llm::EnsembleLlmBase>,
+    ) -> Result<(), super::Error> {
+        for id in ids {
+            if llms.contains_key(&id) {
+                continue;
+            }
+            match self
+                .chat_client
+                .ensemble_llm_fetcher
+                .fetch(ctx.clone(), &id)
+                .await
+            {
+                Ok(Some((llm, _))) => {
+                    llms.insert(id, llm.base);
+                }
+                Ok(None) => return Err(super::Error::EnsembleLlmNotFound(id)),
+                Err(e) => return Err(super::Error::FetchEnsembleLlm(e)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolves the execution cache key of a request, fetching remote
+    /// Functions and Profiles for their commits.
+    async fn execution_cache_key(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        request: &objectiveai::functions::executions::request::Request,
+    ) -> Result<super::ExecutionCacheKey, super::Error> {
+        let function = match (request.remote_function(), request.inline_function()) {
+            (Some((owner, repository, commit)), _) => {
+                match self
+                    .function_fetcher
+                    .fetch(ctx.clone(), owner, repository, commit)
+                    .await
+                {
+                    Ok(Some(function)) => super::ExecutionCacheId::Remote(format!(
+                        "{}/{}/{}",
+                        function.owner, function.repository, function.commit
+                    )),
+                    Ok(None) => return Err(super::Error::FunctionNotFound),
+                    Err(e) => return Err(super::Error::FetchFunction(e)),
+                }
+            }
+            (None, Some(function)) => super::ExecutionCacheId::inline(function),
+            (None, None) => unreachable!(),
+        };
+        let profile = match (request.remote_profile(), request.inline_profile()) {
+            (Some((owner, repository, commit)), _) => {
+                match self
+                    .profile_fetcher
+                    .fetch(ctx.clone(), owner, repository, commit)
+                    .await
+                {
+                    Ok(Some(profile)) => super::ExecutionCacheId::Remote(format!(
+                        "{}/{}/{}",
+                        profile.owner, profile.repository, profile.commit
+                    )),
+                    Ok(None) => return Err(super::Error::ProfileNotFound),
+                    Err(e) => return Err(super::Error::FetchProfile(e)),
+                }
+            }
+            (None, Some(profile)) => super::ExecutionCacheId::inline(profile),
+            (None, None) => unreachable!(),
+        };
+        Ok(super::ExecutionCacheKey::new(
+            ctx.ext.caller(),
+            function,
+            profile,
+            request,
+        ))
+    }
+
+    /// Returns what the tasks of `request` are flattened with.
+    fn flat_task_profile_params(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        request: Arc<objectiveai::functions::executions::request::Request>,
+    ) -> functions::FlatTaskProfileParams<CTXEXT, FFN, FPFL, FENS, FENSLLM, CUSG>
+    {
+        functions::FlatTaskProfileParams {
+            ctx,
+            function_fetcher: self.function_fetcher.clone(),
+            profile_fetcher: self.profile_fetcher.clone(),
+            ensemble_fetcher: self.ensemble_fetcher.clone(),
+            chat_client: self.chat_client.clone(),
+            request: Some(request),
+        }
+    }
+
+    async fn fetch_function_flat_task_profile(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        request: Arc<objectiveai::functions::executions::request::Request>,
+        input: Option<objectiveai::functions::expression::Input>,
+    ) -> Result<functions::FunctionFlatTaskProfile, super::Error> {
+        // reuse a root resolved once for every execution of the context
+        if let Some(root) = ctx.root.clone() {
+            let (function, profile) = (*root).clone();
+            return functions::get_flat_task_profile(
+                self.flat_task_profile_params(ctx, request.clone()),
+                Vec::new(),
+                function,
+                profile,
+                input.unwrap_or_else(|| request.base().input.clone()),
+                None, // Root-level function has no parent task output expression
+                false, // Root-level function has no invert flag
+            )
+            .await;
+        }
+        match &*request {
+            objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+                body,
+            } => {
+                functions::get_flat_task_profile(
+                    self.flat_task_profile_params(ctx, request.clone()),
+                    Vec::new(),
+                    functions::FunctionParam::FetchedOrInline {
+                        full_id: None,
+                        function: objectiveai::functions::Function::Inline(
+                            body.function.clone(),
+                        ),
+                    },
+                    functions::ProfileParam::FetchedOrInline {
+                        full_id: None,
+                        profile: objectiveai::functions::Profile::Inline(
+                            body.profile.clone(),
+                        ),
+                    },
+                    input.unwrap_or_else(|| body.base.input.clone()),
+                    None, // Root-level function has no parent task output expression
+                    false, // Root-level function has no invert flag
+                )
+                .await
             }
             objectiveai::functions::executions::request::Request::FunctionInlineProfileRemote {
                 path,
                 body,
             } => {
                 functions::get_flat_task_profile(
-                    ctx,
+                    self.flat_task_profile_params(ctx, request.clone()),
                     Vec::new(),
                     functions::FunctionParam::FetchedOrInline {
                         full_id: None,
@@ -1549,9 +2225,6 @@ where
                     input.unwrap_or_else(|| body.base.input.clone()),
                     None, // Root-level function has no parent task output expression
                     false, // Root-level function has no invert flag
-                    self.function_fetcher.clone(),
-                    self.profile_fetcher.clone(),
-                    self.ensemble_fetcher.clone(),
                 )
                 .await
             }
@@ -1560,7 +2233,7 @@ where
                 body,
             } => {
                 functions::get_flat_task_profile(
-                    ctx,
+                    self.flat_task_profile_params(ctx, request.clone()),
                     Vec::new(),
                     functions::FunctionParam::Remote {
                         owner: path.fowner.clone(),
@@ -1576,9 +2249,6 @@ where
                     input.unwrap_or_else(|| body.base.input.clone()),
                     None, // Root-level function has no parent task output expression
                     false, // Root-level function has no invert flag
-                    self.function_fetcher.clone(),
-                    self.profile_fetcher.clone(),
-                    self.ensemble_fetcher.clone(),
                 )
                 .await
             }
@@ -1587,7 +2257,7 @@ where
                 body
             } => {
                 functions::get_flat_task_profile(
-                    ctx,
+                    self.flat_task_profile_params(ctx, request.clone()),
                     Vec::new(),
                     functions::FunctionParam::Remote {
                         owner: path.fowner.clone(),
@@ -1602,9 +2272,6 @@ where
                     input.unwrap_or_else(|| body.input.clone()),
                     None, // Root-level function has no parent task output expression
                     false, // Root-level function has no invert flag
-                    self.function_fetcher.clone(),
-                    self.profile_fetcher.clone(),
-                    self.ensemble_fetcher.clone(),
                 )
                 .await
             }
@@ -1682,6 +2349,159 @@ where
                 .flatten()
                 .boxed()
             }
+            // dependent tasks are executed by their Function
+            functions::FlatTaskProfile::Dependent(_) => unreachable!(),
+            // chat completion tasks already ran while flattening
+            functions::FlatTaskProfile::ChatCompletion(chat_ftp) => {
+                futures::stream::once(async move {
+                    FtpStreamChunk::ChatCompletionTaskChunk {
+                        retry_token:
+                            objectiveai::functions::executions::RetryToken(
+                                vec![chat_ftp.retry_token()],
+                            ),
+                        chunk: objectiveai::functions::executions::response::streaming::ChatCompletionTaskChunk {
+                            index: choice_indexer.get(task_index as usize),
+                            task_index,
+                            task_path: chat_ftp.path,
+                            inner: chat_ftp.completion,
+                            output: chat_ftp.output,
+                            error: chat_ftp.error,
+                        },
+                    }
+                })
+                .boxed()
+            }
+        }
+    }
+
+    /// Flattens a dependent task with the results of the tasks before it,
+    /// then executes it.
+    async fn execute_dependent_ftp_streaming(
+        self: Arc<Self>,
+        ctx: ctx::Context<CTXEXT>,
+        request: Arc<objectiveai::functions::executions::request::Request>,
+        root_retry_token: Option<
+            Arc<objectiveai::functions::executions::RetryToken>,
+        >,
+        ftp: functions::DependentFlatTaskProfile,
+        task_results: Arc<Mutex<TaskResults>>,
+        created: u64,
+        task_index: u64,
+        choice_indexer: Arc<ChoiceIndexer>,
+        swiss_round: Option<u64>,
+        swiss_pool_index: Option<u64>,
+    ) -> futures::stream::BoxStream<'static, FtpStreamChunk> {
+        let index = ftp.index;
+        let task_path = ftp.path.clone();
+        let is_function = ftp.is_function();
+        let is_vector_function = ftp.is_vector_function();
+        let results = task_results.lock().unwrap().results.clone();
+        match functions::get_dependent_flat_task_profile(
+            self.flat_task_profile_params(ctx.clone(), request.clone()),
+            ftp,
+            &results,
+        )
+        .await
+        {
+            // skipped
+            Ok(None) => futures::stream::empty().boxed(),
+            Ok(Some(inner_ftp)) => {
+                task_results.lock().unwrap().output_expressions[index] =
+                    match &inner_ftp {
+                        functions::FlatTaskProfile::Function(f) => f
+                            .task_output
+                            .clone()
+                            .map(|expr| (expr, f.invert_output)),
+                        functions::FlatTaskProfile::VectorCompletion(vc) => {
+                            Some((vc.output.clone(), vc.invert_output))
+                        }
+                        _ => unreachable!(),
+                    };
+                // the nested tasks of a dependent Function are not part of
+                // the retry token, their task indices are not reserved
+                let root_retry_token =
+                    if is_function { None } else { root_retry_token };
+                self.execute_ftp_streaming(
+                    ctx,
+                    request,
+                    root_retry_token,
+                    inner_ftp,
+                    created,
+                    task_index,
+                    choice_indexer,
+                    swiss_round,
+                    swiss_pool_index,
+                )
+                .map(move |chunk| match chunk {
+                    FtpStreamChunk::OutputChunk {
+                        task_index, output, ..
+                    } if is_function => FtpStreamChunk::OutputChunk {
+                        task_index,
+                        output,
+                        retry_token:
+                            objectiveai::functions::executions::RetryToken(
+                                vec![None],
+                            ),
+                    },
+                    chunk => chunk,
+                })
+                .boxed()
+            }
+            // report the error on the task, it produces no output
+            Err(e) => {
+                let error = Some(objectiveai::error::ResponseError::from(&e));
+                let index = choice_indexer.get(task_index as usize);
+                StreamOnce::new(if is_function {
+                    let (id, object) = if is_vector_function {
+                        (
+                            vector_response_id(created),
+                            objectiveai::functions::executions::response::streaming::Object::VectorFunctionExecutionChunk,
+                        )
+                    } else {
+                        (
+                            scalar_response_id(created),
+                            objectiveai::functions::executions::response::streaming::Object::ScalarFunctionExecutionChunk,
+                        )
+                    };
+                    FtpStreamChunk::FunctionExecutionChunk(
+                        objectiveai::functions::executions::response::streaming::FunctionExecutionTaskChunk {
+                            index,
+                            task_index,
+                            task_path,
+                            swiss_round,
+                            swiss_pool_index,
+                            inner: objectiveai::functions::executions::response::streaming::FunctionExecutionChunk {
+                                id,
+                                tasks: Vec::new(),
+                                tasks_errors: None,
+                                reasoning: None,
+                                output: None,
+                                ranking: None,
+                                attribution: None,
+                                error,
+                                retry_token: None,
+                                created,
+                                function: None,
+                                profile: None,
+                                object,
+                                usage: None,
+                                cached: None,
+                            },
+                        },
+                    )
+                } else {
+                    FtpStreamChunk::VectorCompletionTaskChunk(
+                        objectiveai::functions::executions::response::streaming::VectorCompletionTaskChunk {
+                            index,
+                            task_index,
+                            task_path,
+                            inner: Default::default(),
+                            error,
+                        },
+                    )
+                })
+                .boxed()
+            }
         }
     }
 
@@ -1763,15 +2583,19 @@ where
                                 ti == (chunk_task_index - task_index)
                             })
                             .unwrap();
-                        // insert retry token into correct position
-                        retry_token.insert(local_index, chunk_retry_token);
+                        // insert retry token at the task's first index
+                        retry_token.insert(
+                            task_indices[local_index] as usize,
+                            chunk_retry_token,
+                        );
                         // insert output into correct position
                         output[local_index] = match chunk_output {
                             objectiveai::functions::expression::TaskOutputOwned::Function(output) => output,
                             _ => unreachable!(),
                         };
                     }
-                    FtpStreamChunk::VectorCompletionTaskChunk(_) => {
+                    FtpStreamChunk::VectorCompletionTaskChunk(_)
+                    | FtpStreamChunk::ChatCompletionTaskChunk { .. } => {
                         unreachable!()
                     }
                 }
@@ -1810,6 +2634,14 @@ where
                 vector_response_id(created),
                 objectiveai::functions::executions::response::streaming::Object::VectorFunctionExecutionChunk,
             ),
+            functions::FunctionType::MultiLabel { .. } => (
+                multi_label_response_id(created),
+                objectiveai::functions::executions::response::streaming::Object::MultiLabelFunctionExecutionChunk,
+            ),
+            functions::FunctionType::Categorical { .. } => (
+                categorical_response_id(created),
+                objectiveai::functions::executions::response::streaming::Object::CategoricalFunctionExecutionChunk,
+            ),
         };
 
         // initialize task indices
@@ -1827,6 +2659,9 @@ where
                         functions::FlatTaskProfile::MapFunction(mf) => Some((mf.task_output.clone(), mf.invert_output)),
                         functions::FlatTaskProfile::VectorCompletion(vc) => Some((vc.output.clone(), vc.invert_output)),
                         functions::FlatTaskProfile::MapVectorCompletion(mvc) => Some((mvc.task_output.clone(), mvc.invert_output)),
+                        // set once the dependent task is flattened
+                        functions::FlatTaskProfile::ChatCompletion(_)
+                        | functions::FlatTaskProfile::Dependent(_) => None,
                     })
                 })
                 .collect();
@@ -1893,14 +2728,52 @@ where
         // create new choice indexer for children
         let child_choice_indexer = Arc::new(ChoiceIndexer::new(0));
 
-        // combine all streams into one
+        // collect votes for the attribution, if requested
+        let mut attributor = request
+            .base()
+            .attribution
+            .unwrap_or(false)
+            .then(|| super::Attributor::new(tasks_len));
+
+        // share task results and output expressions with dependent tasks
+        let task_results = Arc::new(Mutex::new(TaskResults {
+            results: vec![None; tasks_len],
+            output_expressions: task_output_expressions,
+        }));
+        let outer_task_results = task_results.clone();
+
+        // combine all streams into one, tasks run in order so that dependent
+        // tasks start once the tasks before them have finished
         let outer_task_indices = task_indices.clone();
         let stream = futures::stream::iter(
             ftp.tasks.into_iter().enumerate().filter_map(
                 move |(i, inner_ftp)| {
                     inner_ftp
                         .map(|inner_ftp| {
-                            if inner_ftp.len() > 0 {
+                            if let functions::FlatTaskProfile::Dependent(
+                                dependent_ftp,
+                            ) = inner_ftp
+                            {
+                                Some(
+                                    futures::stream::once(
+                                        self.clone()
+                                            .execute_dependent_ftp_streaming(
+                                                ctx.clone(),
+                                                request.clone(),
+                                                root_retry_token.clone(),
+                                                dependent_ftp,
+                                                task_results.clone(),
+                                                created,
+                                                task_index + task_indices[i],
+                                                child_choice_indexer.clone(),
+                                                swiss_round,
+                                                swiss_pool_index,
+                                            ),
+                                    )
+                                    .flatten()
+                                    .boxed(),
+                                )
+                            } else if inner_ftp.len() > 0 {
                                 Some(self.clone().execute_ftp_streaming(
                                     ctx.clone(),
                                     request.clone(),
@@ -1922,6 +2795,7 @@ where
         )
         .flatten();
         let task_indices = outer_task_indices;
+        let task_results = outer_task_results;
 
         // track whether child errors occurred
         let mut tasks_errors = false;
@@ -1953,6 +2827,21 @@ where
                         if let Some(completion_usage) = &chunk.inner.usage {
                             usage.push(completion_usage);
                         }
+                        if let Some(attributor) = &mut attributor {
+                            // mapped vector completions share their task's
+                            // local index
+                            let local_index = task_indices
+                                .iter()
+                                .rposition(|&ti| {
+                                    ti <= (chunk.task_index - task_index)
+                                })
+                                .unwrap();
+                            attributor.push_votes(
+                                local_index,
+                                &chunk.task_path,
+                                &chunk.inner.votes,
+                            );
+                        }
                         yield FtpStreamChunk::FunctionExecutionChunk(
                             objectiveai::functions::executions::response::streaming::FunctionExecutionTaskChunk {
                                 index: choice_indexer.get(
@@ -1976,6 +2865,74 @@ where
                                     },
                                     reasoning: None,
                                     output: None,
+                                    ranking: None,
+                                    attribution: None,
+                                    error: None,
+                                    retry_token: None,
+                                    created,
+                                    function: function.clone(),
+                                    profile: profile.clone(),
+                                    object,
+                                    usage: None,
+                                    cached: None,
+                                },
+                            },
+                        );
+                    }
+                    FtpStreamChunk::ChatCompletionTaskChunk {
+                        chunk,
+                        retry_token: chunk_retry_token,
+                    } => {
+                        tasks_errors |= chunk.error.is_some();
+                        if let Some(completion_usage) = &chunk.inner.usage {
+                            usage.push_chat_completion_usage(completion_usage);
+                        }
+                        let local_index = task_indices
+                            .iter()
+                            .position(|&ti| {
+                                ti == (chunk.task_index - task_index)
+                            })
+                            .unwrap();
+                        // keep the output in the retry token
+                        retry_token.insert(
+                            task_indices[local_index] as usize,
+                            chunk_retry_token,
+                        );
+                        // expose the output to dependent tasks
+                        if let Some(output) = &chunk.output {
+                            task_results.lock().unwrap().results[local_index] =
+                                Some(objectiveai::functions::expression::TaskResult {
+                                    output: objectiveai::functions::expression::TaskOutputOwned::ChatCompletion(
+                                        output.clone(),
+                                    ),
+                                    weight: None,
+                                });
+                        }
+                        yield FtpStreamChunk::FunctionExecutionChunk(
+                            objectiveai::functions::executions::response::streaming::FunctionExecutionTaskChunk {
+                                index: choice_indexer.get(
+                                    task_index as usize,
+                                ),
+                                task_index,
+                                task_path: ftp.path.clone(),
+                                swiss_round,
+                                swiss_pool_index,
+                                inner: objectiveai::functions::executions::response::streaming::FunctionExecutionChunk {
+                                    id: response_id.clone(),
+                                    tasks: vec![
+                                        objectiveai::functions::executions::response::streaming::TaskChunk::ChatCompletion(
+                                            chunk,
+                                        ),
+                                    ],
+                                    tasks_errors: if tasks_errors {
+                                        Some(true)
+                                    } else {
+                                        None
+                                    },
+                                    reasoning: None,
+                                    output: None,
+                                    ranking: None,
+                                    attribution: None,
                                     error: None,
                                     retry_token: None,
                                     created,
@@ -1983,6 +2940,7 @@ where
                                     profile: profile.clone(),
                                     object,
                                     usage: None,
+                                    cached: None,
                                 },
                             },
                         );
@@ -2016,6 +2974,8 @@ where
                                     },
                                     reasoning: None,
                                     output: None,
+                                    ranking: None,
+                                    attribution: None,
                                     error: None,
                                     retry_token: None,
                                     created,
@@ -2023,6 +2983,7 @@ where
                                     profile: profile.clone(),
                                     object,
                                     usage: None,
+                                    cached: None,
                                 },
                             },
                         );
@@ -2039,18 +3000,30 @@ where
                                 ti == (chunk_task_index - task_index)
                             })
                             .unwrap();
-                        // insert retry token into correct position
-                        retry_token.insert(local_index, chunk_retry_token);
+                        // insert retry token at the task's first index
+                        retry_token.insert(
+                            task_indices[local_index] as usize,
+                            chunk_retry_token,
+                        );
+                        // expose the raw output to dependent tasks
+                        let (expr, invert_output) = {
+                            let mut task_results = task_results.lock().unwrap();
+                            task_results.results[local_index] =
+                                Some(objectiveai::functions::expression::TaskResult {
+                                    output: chunk_output.clone(),
+                                    weight: None,
+                                });
+                            // All non-skipped tasks have required output expressions
+                            task_results.output_expressions[local_index]
+                                .clone()
+                                .expect("non-skipped task must have output expression")
+                        };
                         // apply task output expression to transform raw output into FunctionOutput
-                        // All non-skipped tasks have required output expressions
-                        let (expr, invert_output) = task_output_expressions[local_index]
-                            .as_ref()
-                            .expect("non-skipped task must have output expression");
                         let (transformed_output, transform_error) = apply_task_output_expression(
                             &ftp_input,
                             chunk_output,
-                            expr,
-                            *invert_output,
+                            &expr,
+                            invert_output,
                             &ftp_type,
                         );
                         // collect error if any
@@ -2068,15 +3041,38 @@ where
                 }
             }
 
-            // compute final output as weighted average of task outputs
-            let output = compute_weighted_function_output(
+            // compute final output from the function's output expression,
+            // or as weighted average of task outputs
+            let (output, function_output_error) = compute_function_output(
+                &ftp_input,
+                ftp.output.as_ref(),
                 &ftp.r#type,
                 &ftp.profile,
                 &output_input,
             );
 
-            // build error from task output expression errors if any
-            let output_error = if !task_output_errors.is_empty() {
+            // attribute the output to the tasks, if requested
+            let attribution = attributor.map(|attributor| {
+                let inverted = task_results
+                    .lock()
+                    .unwrap()
+                    .output_expressions
+                    .iter()
+                    .map(|expr| expr.as_ref().is_some_and(|(_, invert)| *invert))
+                    .collect::<Vec<_>>();
+                attributor.finish(
+                    &ftp.profile,
+                    &output_input,
+                    &inverted,
+                    ftp.output.is_some(),
+                )
+            });
+
+            // build error from the output expression error, or from task
+            // output expression errors if any
+            let output_error = if function_output_error.is_some() {
+                function_output_error
+            } else if !task_output_errors.is_empty() {
                 Some(objectiveai::error::ResponseError::from(
                     &super::Error::TaskOutputExpressionErrors(task_output_errors),
                 ))
@@ -2104,6 +3100,8 @@ where
                         },
                         reasoning: None,
                         output: Some(output.clone()),
+                        ranking: None,
+                        attribution,
                         error: output_error,
                         retry_token: Some(retry_token.to_string()),
                         created,
@@ -2111,6 +3109,7 @@ where
                         profile,
                         object,
                         usage: Some(usage),
+                        cached: None,
                     },
                 },
             );
@@ -2202,7 +3201,8 @@ where
                             _ => unreachable!(),
                         };
                     }
-                    FtpStreamChunk::FunctionExecutionChunk(_) => {
+                    FtpStreamChunk::FunctionExecutionChunk(_)
+                    | FtpStreamChunk::ChatCompletionTaskChunk { .. } => {
                         unreachable!();
                     }
                 }
@@ -2259,6 +3259,7 @@ where
                         first_chunk_timeout: request_base.first_chunk_timeout,
                         other_chunk_timeout: request_base.other_chunk_timeout,
                         responses: ftp.responses,
+                        max_cost: None,
                     },
                 ),
             )
@@ -2356,84 +3357,61 @@ where
         model: objectiveai::chat::completions::request::Model,
         models: Option<Vec<objectiveai::chat::completions::request::Model>>,
         description: Option<String>,
+        prompt: Option<objectiveai::functions::ReasoningSummaryPrompt>,
         output: objectiveai::functions::expression::FunctionOutput,
         confidence_responses: Vec<ConfidenceResponse>,
     ) -> impl Stream<Item = objectiveai::functions::executions::response::streaming::ReasoningSummaryChunk>
     + Send
     + 'static{
         // construct the prompt
-        let mut parts = Vec::new();
-        parts.push(objectiveai::chat::completions::request::RichContentPart::Text {
-            text: match description {
-                Some(description) => format!(
-                    "The ObjectiveAI Function has the following description: \"{}\"\n\nThe user provided the following input to the ObjectiveAI Function:\n",
-                    description,
-                ),
-                None => "The user provided the following input to an ObjectiveAI Function\n".to_string(),
-            },
-        });
-        parts.extend(request.base().input.clone().to_rich_content_parts(0));
-        parts.push(objectiveai::chat::completions::request::RichContentPart::Text {
-            text: match output {
-                objectiveai::functions::expression::FunctionOutput::Scalar(scalar) => {
-                    format!(
-                        "\n\nThe ObjectiveAI Function produced the following score: {}%\n\n",
-                        (scalar * rust_decimal::dec!(100)).round_dp(2),
-                    )
-                },
-                objectiveai::functions::expression::FunctionOutput::Vector(vector) => {
-                    format!(
-                        "\n\nThe ObjectiveAI Function produced the following vector of scores: [{}]\n\n",
-                        vector.iter()
-                            .map(|v| {
-                                format!(
-                                    "{}%",
-                                    (v * rust_decimal::dec!(100)).round_dp(2),
-                                )
-                            })
-                            .collect::<Vec<String>>()
-                            .join(", ")
-                    )
-                },
-                objectiveai::functions::expression::FunctionOutput::Err(serde_json::Value::Number(n)) if {
-                    n.as_f64().is_some()
-                        && n.as_f64().unwrap() >= 0.0
-                        && n.as_f64().unwrap() <= 1.0
-                } => format!(
-                    "\n\nThe ObjectiveAI Function erroneously produced the following score: {:.2}%\n\n",
-                    n.as_f64().unwrap() * 100.0,
-                ),
-                objectiveai::functions::expression::FunctionOutput::Err(serde_json::Value::Array(arr)) if {
-                    arr
-                        .iter()
-                        .all(|v| v.as_f64().is_some())
-                    && {
-                        let sum: f64 = arr
-                            .iter()
-                            .map(|v| v.as_f64().unwrap())
-                            .sum();
-                        sum >= 0.99 && sum <= 1.01
+        let (template, response_format) = match prompt {
+            Some(objectiveai::functions::ReasoningSummaryPrompt {
+                template,
+                schema,
+            }) => (
+                template,
+                schema.map(|json_schema| {
+                    objectiveai::chat::completions::request::ResponseFormat::JsonSchema {
+                        json_schema,
                     }
-                } => format!(
-                    "\n\nThe ObjectiveAI Function erroneously produced the following vector of scores: [{}]\n\n",
-                    arr.iter()
-                        .map(|v| format!("{:.2}%", v.as_f64().unwrap() * 100.0))
-                        .collect::<Vec<String>>()
-                        .join(", ")
-                ),
-                objectiveai::functions::expression::FunctionOutput::Err(err) => format!(
-                    "\n\nThe ObjectiveAI Function erroneously produced the following output:\n{}\n\n",
-                    serde_json::to_string_pretty(&err).unwrap(),
-                ),
+                }),
+            ),
+            None => (None, None),
+        };
+        let structured = response_format.is_some();
+        let parts = match template {
+            Some(template) => reasoning_summary_template_parts(
+                &template,
+                description,
+                &request.base().input,
+                &output,
+                confidence_responses,
+            ),
+            None => {
+                let mut parts = Vec::new();
+                parts.push(objectiveai::chat::completions::request::RichContentPart::Text {
+                    text: match description {
+                        Some(description) => format!(
+                            "The ObjectiveAI Function has the following description: \"{}\"\n\nThe user provided the following input to the ObjectiveAI Function:\n",
+                            description,
+                        ),
+                        None => "The user provided the following input to an ObjectiveAI Function\n".to_string(),
+                    },
+                });
+                parts.extend(request.base().input.clone().to_rich_content_parts(0));
+                parts.push(objectiveai::chat::completions::request::RichContentPart::Text {
+                    text: format!("\n\n{}\n\n", format_reasoning_summary_output(&output)),
+                });
+                parts.push(objectiveai::chat::completions::request::RichContentPart::Text {
+                    text: "The ObjectiveAI Function used LLM Ensembles to arrive at this output by making assertions with associated confidence scores:\n\n".to_string(),
+                });
+                parts.extend(ConfidenceResponse::assertions(confidence_responses));
+                parts.push(objectiveai::chat::completions::request::RichContentPart::Text {
+                    text: "\n\nYou are to present the output and summarize the reasoning process used by the ObjectiveAI Function to arrive at the output based on the assertions made above. Focus on the most confident assertions and explain how they contributed to the final output. If there were any low-confidence assertions, mention them with the caveat of low confidence. Provide a clear summary of the overall reasoning process.".to_string(),
+                });
+                parts
             }
-        });
-        parts.push(objectiveai::chat::completions::request::RichContentPart::Text {
-            text: "The ObjectiveAI Function used LLM Ensembles to arrive at this output by making assertions with associated confidence scores:\n\n".to_string(),
-        });
-        parts.extend(ConfidenceResponse::assertions(confidence_responses));
-        parts.push(objectiveai::chat::completions::request::RichContentPart::Text {
-            text: "\n\nYou are to present the output and summarize the reasoning process used by the ObjectiveAI Function to arrive at the output based on the assertions made above. Focus on the most confident assertions and explain how they contributed to the final output. If there were any low-confidence assertions, mention them with the caveat of low confidence. Provide a clear summary of the overall reasoning process.".to_string(),
-        });
+        };
 
         // create the streaming chat completion
         let mut stream = match self
@@ -2456,13 +3434,14 @@ where
                         model,
                         models,
                         top_logprobs: None,
-                        response_format: None,
+                        response_format,
                         seed: request.base().seed,
                         stream: Some(true),
                         tool_choice: None,
                         tools: None,
                         parallel_tool_calls: None,
                         prediction: None,
+                        max_cost: None,
                         backoff_max_elapsed_time: request
                             .base()
                             .backoff_max_elapsed_time,
@@ -2478,6 +3457,7 @@ where
                 return futures::future::Either::Left(StreamOnce::new(
                     objectiveai::functions::executions::response::streaming::ReasoningSummaryChunk {
                         inner: objectiveai::chat::completions::response::streaming::ChatCompletionChunk::default(),
+                        data: None,
                         error: Some(objectiveai::error::ResponseError::from(&e)),
                     }
                 ));
@@ -2491,6 +3471,7 @@ where
                 return futures::future::Either::Left(StreamOnce::new(
                     objectiveai::functions::executions::response::streaming::ReasoningSummaryChunk {
                         inner: objectiveai::chat::completions::response::streaming::ChatCompletionChunk::default(),
+                        data: None,
                         error: Some(objectiveai::error::ResponseError::from(&e)),
                     }
                 ));
@@ -2501,11 +3482,21 @@ where
             }
         };
 
-        // stream, buffered by 1 so as to attach errors
+        // stream, buffered by 1 so as to attach errors and structured data
         futures::future::Either::Right(async_stream::stream! {
+            let mut content = String::new();
             while let Some(chat_chunk) = next_chat_chunk.take() {
+                // aggregate the content of the summary
+                if structured {
+                    for choice in &chat_chunk.choices {
+                        if let Some(delta) = &choice.delta.content {
+                            content.push_str(delta);
+                        }
+                    }
+                }
+
                 // fetch the next chat chunk or error
-                let error = match stream.next().await {
+                let mut error = match stream.next().await {
                     Some(Ok(ncc)) => {
                         // set next chat chunk
                         next_chat_chunk = Some(ncc);
@@ -2522,9 +3513,29 @@ where
                     }
                 };
 
+                // parse the structured summary with the last chunk, unless
+                // the completion failed
+                let data = if structured
+                    && next_chat_chunk.is_none()
+                    && error.is_none()
+                {
+                    match serde_json::from_str(&content) {
+                        Ok(data) => Some(data),
+                        Err(e) => {
+                            error = Some(objectiveai::error::ResponseError::from(
+                                &super::Error::InvalidReasoningSummary(e),
+                            ));
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+
                 // yield the reasoning summary chunk
                 yield objectiveai::functions::executions::response::streaming::ReasoningSummaryChunk {
                     inner: chat_chunk,
+                    data,
                     error,
                 };
             }
@@ -2532,6 +3543,133 @@ where
     }
 }
 
+/// Formats the output of a Function for its reasoning summary prompt.
+fn format_reasoning_summary_output(
+    output: &objectiveai::functions::expression::FunctionOutput,
+) -> String {
+    match output {
+        objectiveai::functions::expression::FunctionOutput::Scalar(scalar) => {
+            format!(
+                "The ObjectiveAI Function produced the following score: {}%",
+                (scalar * rust_decimal::dec!(100)).round_dp(2),
+            )
+        },
+        objectiveai::functions::expression::FunctionOutput::Vector(vector) => {
+            format!(
+                "The ObjectiveAI Function produced the following vector of scores: [{}]",
+                vector.iter()
+                    .map(|v| {
+                        format!(
+                            "{}%",
+                            (v * rust_decimal::dec!(100)).round_dp(2),
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )
+        },
+        objectiveai::functions::expression::FunctionOutput::MultiLabel(scores) => {
+            format!(
+                "The ObjectiveAI Function produced the following independent score for each label: {{{}}}",
+                scores.iter()
+                    .map(|(label, v)| {
+                        format!(
+                            "\"{}\": {}%",
+                            json_escape::escape_str(label),
+                            (v * rust_decimal::dec!(100)).round_dp(2),
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )
+        },
+        objectiveai::functions::expression::FunctionOutput::Categorical(scores) => {
+            format!(
+                "The ObjectiveAI Function produced the following scores over its categories: {{{}}}",
+                scores.iter()
+                    .map(|(category, v)| {
+                        format!(
+                            "\"{}\": {}%",
+                            json_escape::escape_str(category),
+                            (v * rust_decimal::dec!(100)).round_dp(2),
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )
+        },
+        objectiveai::functions::expression::FunctionOutput::Err(serde_json::Value::Number(n)) if {
+            n.as_f64().is_some()
+                && n.as_f64().unwrap() >= 0.0
+                && n.as_f64().unwrap() <= 1.0
+        } => format!(
+            "The ObjectiveAI Function erroneously produced the following score: {:.2}%",
+            n.as_f64().unwrap() * 100.0,
+        ),
+        objectiveai::functions::expression::FunctionOutput::Err(serde_json::Value::Array(arr)) if {
+            arr
+                .iter()
+                .all(|v| v.as_f64().is_some())
+            && {
+                let sum: f64 = arr
+                    .iter()
+                    .map(|v| v.as_f64().unwrap())
+                    .sum();
+                sum >= 0.99 && sum <= 1.01
+            }
+        } => format!(
+            "The ObjectiveAI Function erroneously produced the following vector of scores: [{}]",
+            arr.iter()
+                .map(|v| format!("{:.2}%", v.as_f64().unwrap() * 100.0))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        objectiveai::functions::expression::FunctionOutput::Err(err) => format!(
+            "The ObjectiveAI Function erroneously produced the following output:\n{}",
+            serde_json::to_string_pretty(&err).unwrap(),
+        ),
+    }
+}
+
+/// Renders a reasoning summary prompt template into content parts.
+fn reasoning_summary_template_parts(
+    template: &str,
+    description: Option<String>,
+    input: &objectiveai::functions::expression::Input,
+    output: &objectiveai::functions::expression::FunctionOutput,
+    confidence_responses: Vec<ConfidenceResponse>,
+) -> Vec<objectiveai::chat::completions::request::RichContentPart> {
+    let mut parts = Vec::new();
+    for segment in objectiveai::functions::ReasoningSummaryPrompt::segments(template) {
+        match segment {
+            objectiveai::functions::ReasoningSummarySegment::Text(text) => {
+                parts.push(objectiveai::chat::completions::request::RichContentPart::Text {
+                    text: text.to_string(),
+                });
+            }
+            objectiveai::functions::ReasoningSummarySegment::Description => {
+                parts.push(objectiveai::chat::completions::request::RichContentPart::Text {
+                    text: description.clone().unwrap_or_default(),
+                });
+            }
+            objectiveai::functions::ReasoningSummarySegment::Input => {
+                parts.extend(input.clone().to_rich_content_parts(0));
+            }
+            objectiveai::functions::ReasoningSummarySegment::Output => {
+                parts.push(objectiveai::chat::completions::request::RichContentPart::Text {
+                    text: format_reasoning_summary_output(output),
+                });
+            }
+            objectiveai::functions::ReasoningSummarySegment::Assertions => {
+                parts.extend(ConfidenceResponse::assertions(
+                    confidence_responses.clone(),
+                ));
+            }
+        }
+    }
+    parts
+}
+
 /// Internal chunk type for streaming execution.
 ///
 /// Represents different kinds of chunks produced during flattened task
@@ -2542,6 +3680,13 @@ enum FtpStreamChunk {
     VectorCompletionTaskChunk(
         objectiveai::functions::executions::response::streaming::VectorCompletionTaskChunk,
     ),
+    /// The chunk of a Chat Completion task with its retry token.
+    ChatCompletionTaskChunk {
+        /// The replayed chat completion.
+        chunk: objectiveai::functions::executions::response::streaming::ChatCompletionTaskChunk,
+        /// Token for reusing the task's output.
+        retry_token: objectiveai::functions::executions::RetryToken,
+    },
     /// A chunk from a nested Function execution.
     FunctionExecutionChunk(
         objectiveai::functions::executions::response::streaming::FunctionExecutionTaskChunk,
@@ -2557,6 +3702,16 @@ enum FtpStreamChunk {
     },
 }
 
+/// Outputs of a Function's tasks, shared with its dependent tasks.
+struct TaskResults {
+    /// The raw output of each task that has finished.
+    results: Vec<Option<objectiveai::functions::expression::TaskResult>>,
+    /// The output expression of each task, set for a dependent task once it
+    /// has been flattened.
+    output_expressions:
+        Vec<Option<(objectiveai::functions::expression::Expression, bool)>>,
+}
+
 /// A response option with its aggregated confidence for reasoning summaries.
 ///
 /// Tracks confidence scores and reasoning across multiple Vector Completion
@@ -2770,3 +3925,173 @@ mod invert_output_tests {
         }
     }
 }
+
+#[cfg(test)]
+mod function_output_tests {
+    use super::*;
+    use objectiveai::functions::expression::{Expression, FunctionOutput};
+    use rust_decimal::dec;
+
+    fn empty_input() -> objectiveai::functions::expression::Input {
+        objectiveai::functions::expression::Input::Object(indexmap::IndexMap::new())
+    }
+
+    #[test]
+    fn function_output_defaults_to_weighted_average() {
+        let (out, err) = compute_function_output(
+            &empty_input(),
+            None,
+            &functions::FunctionType::Scalar,
+            &[dec!(1), dec!(3)],
+            &[
+                Some(FunctionOutput::Scalar(dec!(0.2))),
+                Some(FunctionOutput::Scalar(dec!(0.6))),
+            ],
+        );
+        assert!(err.is_none());
+        match out {
+            FunctionOutput::Scalar(v) => assert_eq!(v, dec!(0.5)),
+            other => panic!("expected scalar output, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn function_output_expression_min() {
+        let expr = Expression::Starlark(
+            "min([t['output'] for t in tasks if t != None and t['weight'] > 0])"
+                .to_string(),
+        );
+        let (out, err) = compute_function_output(
+            &empty_input(),
+            Some(&expr),
+            &functions::FunctionType::Scalar,
+            &[dec!(1), dec!(0), dec!(1), dec!(1)],
+            &[
+                Some(FunctionOutput::Scalar(dec!(0.7))),
+                Some(FunctionOutput::Scalar(dec!(0.1))),
+                None,
+                Some(FunctionOutput::Scalar(dec!(0.4))),
+            ],
+        );
+        assert!(err.is_none());
+        match out {
+            FunctionOutput::Scalar(v) => assert_eq!(v, dec!(0.4)),
+            other => panic!("expected scalar output, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn function_output_expression_invalid() {
+        let expr = Expression::Starlark(
+            "[t['output'] for t in tasks]".to_string(),
+        );
+        let (out, err) = compute_function_output(
+            &empty_input(),
+            Some(&expr),
+            &functions::FunctionType::Scalar,
+            &[dec!(1)],
+            &[Some(FunctionOutput::Scalar(dec!(0.7)))],
+        );
+        assert!(matches!(out, FunctionOutput::Err(_)));
+        assert!(err.is_some());
+    }
+
+    fn labels() -> Vec<String> {
+        vec!["a".to_string(), "b".to_string()]
+    }
+
+    #[test]
+    fn function_output_multi_label_weighted_average() {
+        let (out, err) = compute_function_output(
+            &empty_input(),
+            None,
+            &functions::FunctionType::MultiLabel { labels: labels() },
+            &[dec!(1), dec!(1)],
+            &[
+                Some(FunctionOutput::MultiLabel(indexmap::IndexMap::from([
+                    ("a".to_string(), dec!(0.2)),
+                    ("b".to_string(), dec!(1)),
+                ]))),
+                Some(FunctionOutput::MultiLabel(indexmap::IndexMap::from([
+                    ("a".to_string(), dec!(0.6)),
+                    ("b".to_string(), dec!(0.8)),
+                ]))),
+            ],
+        );
+        assert!(err.is_none());
+        match out {
+            FunctionOutput::MultiLabel(scores) => {
+                assert_eq!(scores["a"], dec!(0.4));
+                assert_eq!(scores["b"], dec!(0.9));
+            }
+            other => panic!("expected multi-label output, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn function_output_categorical_from_vector() {
+        let expr = Expression::Starlark("[0.25, 0.75]".to_string());
+        let (out, err) = compute_function_output(
+            &empty_input(),
+            Some(&expr),
+            &functions::FunctionType::Categorical {
+                categories: labels(),
+            },
+            &[dec!(1)],
+            &[None],
+        );
+        assert!(err.is_none());
+        match out {
+            FunctionOutput::Categorical(scores) => {
+                assert_eq!(scores["a"], dec!(0.25));
+                assert_eq!(scores["b"], dec!(0.75));
+            }
+            other => panic!("expected categorical output, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn function_output_categorical_invalid_sum() {
+        let expr = Expression::Starlark("{'a': 0.5, 'b': 0.75}".to_string());
+        let (out, err) = compute_function_output(
+            &empty_input(),
+            Some(&expr),
+            &functions::FunctionType::Categorical {
+                categories: labels(),
+            },
+            &[dec!(1)],
+            &[None],
+        );
+        assert!(matches!(out, FunctionOutput::Err(_)));
+        assert!(err.is_some());
+    }
+}
+
+#[cfg(test)]
+mod reasoning_summary_tests {
+    use super::*;
+    use objectiveai::functions::expression::FunctionOutput;
+    use rust_decimal::dec;
+
+    #[test]
+    fn reasoning_summary_template_renders_placeholders() {
+        let parts = reasoning_summary_template_parts(
+            "{{description}}\n{{output}}\n{{other}}",
+            Some("Rates jokes.".to_string()),
+            &objectiveai::functions::expression::Input::Object(indexmap::IndexMap::new()),
+            &FunctionOutput::Scalar(dec!(0.5)),
+            Vec::new(),
+        );
+        let text = parts
+            .into_iter()
+            .map(|part| match part {
+                objectiveai::chat::completions::request::RichContentPart::Text { text } => text,
+                other => panic!("expected text part, got {:?}", other),
+            })
+            .collect::<String>();
+        assert_eq!(
+            text,
+            "Rates jokes.\nThe ObjectiveAI Function produced the following score: 50.0%\n{{other}}",
+        );
+    }
+}
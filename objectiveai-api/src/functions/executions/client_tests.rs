@@ -1,7 +1,8 @@
 //! Tests for the function execution client.
 //!
 //! These tests use mock implementations of all fetcher traits and set
-//! `from_rng: true` on all requests to avoid network traffic.
+//! `from_rng: true` on requests to avoid network traffic. Tests of upstream
+//! calls serve a mock upstream locally instead.
 
 use crate::{chat, ctx, ensemble, ensemble_llm, functions, vector};
 use futures::StreamExt;
@@ -13,9 +14,12 @@ use std::sync::Arc;
 // Mock Types
 // ============================================================================
 
-/// Mock context extension that provides no BYOK keys.
-#[derive(Debug, Clone)]
-struct MockContextExt;
+/// Mock context extension that provides an optional BYOK key, which also
+/// identifies the caller.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MockContextExt {
+    pub(crate) byok: Option<String>,
+}
 
 #[async_trait::async_trait]
 impl ctx::ContextExt for MockContextExt {
@@ -23,13 +27,17 @@ impl ctx::ContextExt for MockContextExt {
         &self,
         _upstream: chat::completions::upstream::Upstream,
     ) -> Result<Option<String>, objectiveai::error::ResponseError> {
-        Ok(None)
+        Ok(self.byok.clone())
+    }
+
+    fn caller(&self) -> Option<String> {
+        self.byok.clone()
     }
 }
 
 /// Mock ensemble LLM fetcher that always returns None.
 #[derive(Debug, Clone)]
-struct MockEnsembleLlmFetcher;
+pub(crate) struct MockEnsembleLlmFetcher;
 
 #[async_trait::async_trait]
 impl ensemble_llm::fetcher::Fetcher<MockContextExt> for MockEnsembleLlmFetcher {
@@ -47,7 +55,7 @@ impl ensemble_llm::fetcher::Fetcher<MockContextExt> for MockEnsembleLlmFetcher {
 
 /// Mock ensemble fetcher that always returns None.
 #[derive(Debug, Clone)]
-struct MockEnsembleFetcher;
+pub(crate) struct MockEnsembleFetcher;
 
 #[async_trait::async_trait]
 impl ensemble::fetcher::Fetcher<MockContextExt> for MockEnsembleFetcher {
@@ -65,7 +73,7 @@ impl ensemble::fetcher::Fetcher<MockContextExt> for MockEnsembleFetcher {
 
 /// Mock completion votes fetcher that returns None.
 #[derive(Debug, Clone)]
-struct MockCompletionVotesFetcher;
+pub(crate) struct MockCompletionVotesFetcher;
 
 #[async_trait::async_trait]
 impl vector::completions::completion_votes_fetcher::Fetcher<MockContextExt>
@@ -85,7 +93,7 @@ impl vector::completions::completion_votes_fetcher::Fetcher<MockContextExt>
 
 /// Mock cache vote fetcher that returns None.
 #[derive(Debug, Clone)]
-struct MockCacheVoteFetcher;
+pub(crate) struct MockCacheVoteFetcher;
 
 #[async_trait::async_trait]
 impl vector::completions::cache_vote_fetcher::Fetcher<MockContextExt>
@@ -109,7 +117,7 @@ impl vector::completions::cache_vote_fetcher::Fetcher<MockContextExt>
 
 /// Mock function fetcher that always returns None.
 #[derive(Debug, Clone)]
-struct MockFunctionFetcher;
+pub(crate) struct MockFunctionFetcher;
 
 #[async_trait::async_trait]
 impl functions::function_fetcher::Fetcher<MockContextExt> for MockFunctionFetcher {
@@ -129,7 +137,7 @@ impl functions::function_fetcher::Fetcher<MockContextExt> for MockFunctionFetche
 
 /// Mock profile fetcher that always returns None.
 #[derive(Debug, Clone)]
-struct MockProfileFetcher;
+pub(crate) struct MockProfileFetcher;
 
 #[async_trait::async_trait]
 impl functions::profile_fetcher::Fetcher<MockContextExt> for MockProfileFetcher {
@@ -149,7 +157,7 @@ impl functions::profile_fetcher::Fetcher<MockContextExt> for MockProfileFetcher
 
 /// Mock chat completions usage handler that does nothing.
 #[derive(Debug, Clone)]
-struct MockChatUsageHandler;
+pub(crate) struct MockChatUsageHandler;
 
 #[async_trait::async_trait]
 impl chat::completions::usage_handler::UsageHandler<MockContextExt>
@@ -169,7 +177,7 @@ impl chat::completions::usage_handler::UsageHandler<MockContextExt>
 
 /// Mock vector completions usage handler that does nothing.
 #[derive(Debug, Clone)]
-struct MockVectorUsageHandler;
+pub(crate) struct MockVectorUsageHandler;
 
 #[async_trait::async_trait]
 impl vector::completions::usage_handler::UsageHandler<MockContextExt>
@@ -189,7 +197,7 @@ impl vector::completions::usage_handler::UsageHandler<MockContextExt>
 
 /// Mock function execution usage handler that does nothing.
 #[derive(Debug, Clone)]
-struct MockFunctionUsageHandler;
+pub(crate) struct MockFunctionUsageHandler;
 
 #[async_trait::async_trait]
 impl super::usage_handler::UsageHandler<MockContextExt> for MockFunctionUsageHandler {
@@ -207,13 +215,13 @@ impl super::usage_handler::UsageHandler<MockContextExt> for MockFunctionUsageHan
 // Type Aliases
 // ============================================================================
 
-type TestChatClient = chat::completions::Client<
+pub(crate) type TestChatClient = chat::completions::Client<
     MockContextExt,
     MockEnsembleLlmFetcher,
     MockChatUsageHandler,
 >;
 
-type TestVectorClient = vector::completions::Client<
+pub(crate) type TestVectorClient = vector::completions::Client<
     MockContextExt,
     MockEnsembleLlmFetcher,
     MockChatUsageHandler,
@@ -223,7 +231,7 @@ type TestVectorClient = vector::completions::Client<
     MockVectorUsageHandler,
 >;
 
-type TestFunctionClient = super::Client<
+pub(crate) type TestFunctionClient = super::Client<
     MockContextExt,
     MockEnsembleLlmFetcher,
     MockChatUsageHandler,
@@ -242,20 +250,30 @@ type TestFunctionClient = super::Client<
 
 /// Creates a test context with mock extension.
 fn create_test_context() -> ctx::Context<MockContextExt> {
-    ctx::Context::new(Arc::new(MockContextExt), Decimal::ONE)
+    ctx::Context::new(Arc::new(MockContextExt::default()), Decimal::ONE)
 }
 
 /// Creates a test chat completions client with mock dependencies.
-fn create_test_chat_client() -> Arc<TestChatClient> {
+pub(crate) fn create_test_chat_client() -> Arc<TestChatClient> {
+    // dummy upstream, never reached since from_rng=true
+    create_test_chat_client_with_upstream(
+        "https://openrouter.ai/api/v1".to_string(),
+    )
+}
+
+/// Creates a test chat completions client sending upstream calls to
+/// `api_base`.
+fn create_test_chat_client_with_upstream(
+    api_base: String,
+) -> Arc<TestChatClient> {
     let ensemble_llm_fetcher = Arc::new(
         ensemble_llm::fetcher::CachingFetcher::new(Arc::new(MockEnsembleLlmFetcher)),
     );
     let usage_handler = Arc::new(MockChatUsageHandler);
 
-    // Create OpenRouter client with dummy values (won't be used since from_rng=true)
     let openrouter_client = chat::completions::upstream::openrouter::Client::new(
         reqwest::Client::new(),
-        "https://openrouter.ai/api/v1".to_string(),
+        api_base,
         "dummy-api-key".to_string(),
         None, // user_agent
         None, // x_title
@@ -277,7 +295,7 @@ fn create_test_chat_client() -> Arc<TestChatClient> {
 }
 
 /// Creates a test vector completions client with mock dependencies.
-fn create_test_vector_client(
+pub(crate) fn create_test_vector_client(
     chat_client: Arc<TestChatClient>,
 ) -> Arc<TestVectorClient> {
     let ensemble_fetcher = Arc::new(ensemble::fetcher::CachingFetcher::new(Arc::new(
@@ -297,7 +315,7 @@ fn create_test_vector_client(
 }
 
 /// Creates a test function execution client with mock dependencies.
-fn create_test_function_client(
+pub(crate) fn create_test_function_client(
     chat_client: Arc<TestChatClient>,
     vector_client: Arc<TestVectorClient>,
 ) -> Arc<TestFunctionClient> {
@@ -315,6 +333,7 @@ fn create_test_function_client(
         function_fetcher,
         profile_fetcher,
         usage_handler,
+        super::ExecutionCache::new(16, None),
     ))
 }
 
@@ -343,10 +362,12 @@ fn empty_input() -> objectiveai::functions::expression::Input {
 fn create_simple_vector_function() -> objectiveai::functions::InlineFunction {
     objectiveai::functions::InlineFunction::Vector {
         input_maps: None,
+        output: None,
         tasks: vec![objectiveai::functions::TaskExpression::VectorCompletion(
             objectiveai::functions::VectorCompletionTaskExpression {
                 skip: None,
                 map: None,
+                dependencies: None,
                 messages: objectiveai::functions::expression::WithExpression::Value(vec![
                     objectiveai::functions::expression::WithExpression::Value(
                         objectiveai::chat::completions::request::MessageExpression::User(
@@ -382,6 +403,7 @@ fn create_simple_vector_function() -> objectiveai::functions::InlineFunction {
         )],
         input_split: None,
         input_merge: None,
+        reasoning_summary: None,
     }
 }
 
@@ -405,10 +427,12 @@ fn create_simple_profile() -> objectiveai::functions::InlineProfile {
 fn create_simple_scalar_function() -> objectiveai::functions::InlineFunction {
     objectiveai::functions::InlineFunction::Scalar {
         input_maps: None,
+        output: None,
         tasks: vec![objectiveai::functions::TaskExpression::VectorCompletion(
             objectiveai::functions::VectorCompletionTaskExpression {
                 skip: None,
                 map: None,
+                dependencies: None,
                 messages: objectiveai::functions::expression::WithExpression::Value(vec![
                     objectiveai::functions::expression::WithExpression::Value(
                         objectiveai::chat::completions::request::MessageExpression::User(
@@ -443,6 +467,7 @@ fn create_simple_scalar_function() -> objectiveai::functions::InlineFunction {
                 invert_output: None,
             },
         )],
+        reasoning_summary: None,
     }
 }
 
@@ -462,6 +487,228 @@ fn create_simple_scalar_profile() -> objectiveai::functions::InlineProfile {
     }
 }
 
+/// Creates an inline scalar profile whose ensemble has two LLMs, so that
+/// its executions succeed without task errors.
+fn create_two_llm_scalar_profile() -> objectiveai::functions::InlineProfile {
+    objectiveai::functions::InlineProfile {
+        tasks: vec![objectiveai::functions::TaskProfile::VectorCompletion {
+            ensemble: objectiveai::vector::completions::request::Ensemble::Provided(
+                objectiveai::ensemble::EnsembleBase {
+                    llms: ["openai/gpt-4o", "anthropic/claude-3-5-sonnet"]
+                        .into_iter()
+                        .map(|model| {
+                            objectiveai::ensemble_llm::EnsembleLlmBaseWithFallbacksAndCount {
+                                count: 1,
+                                inner: objectiveai::ensemble_llm::EnsembleLlmBase {
+                                    model: model.to_string(),
+                                    ..Default::default()
+                                },
+                                fallbacks: None,
+                            }
+                        })
+                        .collect(),
+                },
+            ),
+            profile:
+                objectiveai::vector::completions::request::Profile::Weights(
+                    vec![Decimal::ONE, Decimal::ONE],
+                ),
+        }],
+        profile: objectiveai::vector::completions::request::Profile::Weights(
+            vec![Decimal::ONE],
+        ),
+    }
+}
+
+/// Creates an inline scalar function whose first task is a chat completion.
+fn create_chat_scalar_function() -> objectiveai::functions::InlineFunction {
+    let objectiveai::functions::InlineFunction::Scalar { mut tasks, .. } =
+        create_simple_scalar_function()
+    else {
+        unreachable!()
+    };
+    tasks.insert(
+        0,
+        objectiveai::functions::TaskExpression::ChatCompletion(
+            objectiveai::functions::ChatCompletionTaskExpression {
+                skip: None,
+                messages: objectiveai::functions::expression::WithExpression::Value(vec![
+                    objectiveai::functions::expression::WithExpression::Value(
+                        objectiveai::chat::completions::request::MessageExpression::User(
+                            objectiveai::chat::completions::request::UserMessageExpression {
+                                content: objectiveai::functions::expression::WithExpression::Value(
+                                    objectiveai::chat::completions::request::RichContentExpression::Text(
+                                        "Summarize this".to_string(),
+                                    ),
+                                ),
+                                name: None,
+                            },
+                        ),
+                    ),
+                ]),
+                response_format: None,
+            },
+        ),
+    );
+    objectiveai::functions::InlineFunction::Scalar {
+        input_maps: None,
+        output: None,
+        tasks,
+        reasoning_summary: None,
+    }
+}
+
+/// Creates an inline scalar profile for the chat completion scalar function.
+fn create_chat_scalar_profile() -> objectiveai::functions::InlineProfile {
+    let mut profile = create_two_llm_scalar_profile();
+    profile.tasks.insert(
+        0,
+        objectiveai::functions::TaskProfile::ChatCompletion {
+            model: objectiveai::chat::completions::request::Model::Provided(
+                objectiveai::ensemble_llm::EnsembleLlmBase {
+                    model: "openai/gpt-4o".to_string(),
+                    ..Default::default()
+                },
+            ),
+            models: None,
+        },
+    );
+    profile.profile =
+        objectiveai::vector::completions::request::Profile::Weights(vec![
+            Decimal::ONE,
+            Decimal::ONE,
+        ]);
+    profile
+}
+
+/// Serves a local upstream that generates `content` for every chat
+/// completion, costing `cost` if given, recording its requests, and
+/// returns its base URL.
+async fn serve_upstream(
+    content: &'static str,
+    cost: Option<Decimal>,
+    requests: Arc<std::sync::Mutex<Vec<serde_json::Value>>>,
+) -> String {
+    let upstream = axum::Router::new().route(
+        "/chat/completions",
+        axum::routing::post(move |axum::Json(body): axum::Json<serde_json::Value>| {
+            requests.lock().unwrap().push(body);
+            let chunk = serde_json::to_string(
+                &chat::completions::upstream::openrouter::response::ChatCompletionChunk {
+                    id: "upstream".to_string(),
+                    choices: vec![objectiveai::chat::completions::response::streaming::Choice {
+                        delta: objectiveai::chat::completions::response::streaming::Delta {
+                            content: Some(content.to_string()),
+                            ..Default::default()
+                        },
+                        finish_reason: Some(
+                            objectiveai::chat::completions::response::FinishReason::Stop,
+                        ),
+                        ..Default::default()
+                    }],
+                    model: "openai/gpt-4o".to_string(),
+                    usage: cost.map(|cost| {
+                        chat::completions::upstream::openrouter::response::Usage {
+                            cost: Some(cost),
+                            ..Default::default()
+                        }
+                    }),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            async move {
+                axum::response::sse::Sse::new(futures::stream::iter(
+                    [chunk, "[DONE]".to_string()].map(|data| {
+                        Ok::<_, std::convert::Infallible>(
+                            axum::response::sse::Event::default().data(data),
+                        )
+                    }),
+                ))
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let api_base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(listener, upstream).await.unwrap();
+    });
+    api_base
+}
+
+/// Creates an inline scalar function whose second task depends on the first
+/// and is skipped if `skip` evaluates to true.
+fn create_gated_scalar_function(
+    dependencies: Vec<u64>,
+    skip: &str,
+) -> objectiveai::functions::InlineFunction {
+    let objectiveai::functions::InlineFunction::Scalar { mut tasks, .. } =
+        create_simple_scalar_function()
+    else {
+        unreachable!()
+    };
+    let mut gated = tasks[0].clone();
+    if let objectiveai::functions::TaskExpression::VectorCompletion(task) =
+        &mut gated
+    {
+        task.dependencies = Some(dependencies);
+        task.skip = Some(objectiveai::functions::expression::Expression::JMESPath(
+            skip.to_string(),
+        ));
+    }
+    tasks.push(gated);
+    objectiveai::functions::InlineFunction::Scalar {
+        input_maps: None,
+        output: None,
+        tasks,
+        reasoning_summary: None,
+    }
+}
+
+/// Creates an inline scalar profile for the gated scalar function.
+fn create_gated_scalar_profile() -> objectiveai::functions::InlineProfile {
+    let mut profile = create_simple_scalar_profile();
+    profile.tasks.push(profile.tasks[0].clone());
+    profile.profile =
+        objectiveai::vector::completions::request::Profile::Weights(vec![
+            Decimal::ONE,
+            Decimal::ONE,
+        ]);
+    profile
+}
+
+/// Creates a unary request for the gated scalar function.
+fn create_gated_scalar_request(
+    dependencies: Vec<u64>,
+    skip: &str,
+) -> Arc<objectiveai::functions::executions::request::Request> {
+    Arc::new(objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+        body: objectiveai::functions::executions::request::FunctionInlineProfileInlineRequestBody {
+            function: create_gated_scalar_function(dependencies, skip),
+            profile: create_gated_scalar_profile(),
+            base: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+                retry_token: None,
+                from_cache: None,
+                from_rng: Some(true),
+                cache: None,
+                reasoning: None,
+                strategy: None,
+                top_k: None,
+                input: empty_input(),
+                provider: None,
+                seed: None,
+                stream: None,
+                dry_run: None,
+                max_cost: None,
+                attribution: None,
+                backoff_max_elapsed_time: None,
+                first_chunk_timeout: None,
+                other_chunk_timeout: None,
+            },
+        },
+    })
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -488,12 +735,17 @@ mod tests {
                     retry_token: None,
                     from_cache: None,
                     from_rng: Some(true), // Use RNG instead of network calls
+                    cache: None,
                     reasoning: None,
                     strategy: None,
+                    top_k: None,
                     input: empty_input(),
                     provider: None,
                     seed: None,
                     stream: None,
+                    dry_run: None,
+                    max_cost: None,
+                    attribution: None,
                     backoff_max_elapsed_time: None,
                     first_chunk_timeout: None,
                     other_chunk_timeout: None,
@@ -542,12 +794,17 @@ mod tests {
                     retry_token: None,
                     from_cache: None,
                     from_rng: Some(true),
+                    cache: None,
                     reasoning: None,
                     strategy: None,
+                    top_k: None,
                     input: empty_input(),
                     provider: None,
                     seed: None,
                     stream: None,
+                    dry_run: None,
+                    max_cost: None,
+                    attribution: None,
                     backoff_max_elapsed_time: None,
                     first_chunk_timeout: None,
                     other_chunk_timeout: None,
@@ -594,12 +851,17 @@ mod tests {
                     retry_token: None,
                     from_cache: None,
                     from_rng: Some(true),
+                    cache: None,
                     reasoning: None,
                     strategy: None,
+                    top_k: None,
                     input: empty_input(),
                     provider: None,
                     seed: None,
                     stream: Some(true),
+                    dry_run: None,
+                    max_cost: None,
+                    attribution: None,
                     backoff_max_elapsed_time: None,
                     first_chunk_timeout: None,
                     other_chunk_timeout: None,
@@ -654,11 +916,13 @@ mod tests {
         // Create a function with two tasks
         let function = objectiveai::functions::InlineFunction::Vector {
             input_maps: None,
+            output: None,
             tasks: vec![
                 objectiveai::functions::TaskExpression::VectorCompletion(
                     objectiveai::functions::VectorCompletionTaskExpression {
                         skip: None,
                         map: None,
+                        dependencies: None,
                         messages: objectiveai::functions::expression::WithExpression::Value(vec![
                             objectiveai::functions::expression::WithExpression::Value(
                                 objectiveai::chat::completions::request::MessageExpression::User(
@@ -696,6 +960,7 @@ mod tests {
                     objectiveai::functions::VectorCompletionTaskExpression {
                         skip: None,
                         map: None,
+                        dependencies: None,
                         messages: objectiveai::functions::expression::WithExpression::Value(vec![
                             objectiveai::functions::expression::WithExpression::Value(
                                 objectiveai::chat::completions::request::MessageExpression::User(
@@ -732,6 +997,7 @@ mod tests {
             ],
             input_split: None,
             input_merge: None,
+            reasoning_summary: None,
         };
 
         // Create a profile with equal weights for both tasks
@@ -763,12 +1029,17 @@ mod tests {
                     retry_token: None,
                     from_cache: None,
                     from_rng: Some(true),
+                    cache: None,
                     reasoning: None,
                     strategy: None,
+                    top_k: None,
                     input: empty_input(),
                     provider: None,
                     seed: None,
                     stream: None,
+                    dry_run: None,
+                    max_cost: None,
+                    attribution: None,
                     backoff_max_elapsed_time: None,
                     first_chunk_timeout: None,
                     other_chunk_timeout: None,
@@ -858,12 +1129,17 @@ mod tests {
                     retry_token: None,
                     from_cache: None,
                     from_rng: Some(true),
+                    cache: None,
                     reasoning: None,
                     strategy: None,
+                    top_k: None,
                     input: empty_input(),
                     provider: None,
                     seed: None,
                     stream: None,
+                    dry_run: None,
+                    max_cost: None,
+                    attribution: None,
                     backoff_max_elapsed_time: None,
                     first_chunk_timeout: None,
                     other_chunk_timeout: None,
@@ -894,4 +1170,1331 @@ mod tests {
             "Should have 1 task"
         );
     }
+
+    /// Tests that the attribution breaks the output down by task and LLM.
+    #[tokio::test]
+    async fn test_attribution_with_rng() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let ctx = create_test_context();
+
+        // Create an ensemble with multiple LLMs
+        let ensemble = objectiveai::vector::completions::request::Ensemble::Provided(
+            objectiveai::ensemble::EnsembleBase {
+                llms: vec![
+                    objectiveai::ensemble_llm::EnsembleLlmBaseWithFallbacksAndCount {
+                        count: 2,
+                        inner: objectiveai::ensemble_llm::EnsembleLlmBase {
+                            model: "openai/gpt-4o".to_string(),
+                            ..Default::default()
+                        },
+                        fallbacks: None,
+                    },
+                    objectiveai::ensemble_llm::EnsembleLlmBaseWithFallbacksAndCount {
+                        count: 1,
+                        inner: objectiveai::ensemble_llm::EnsembleLlmBase {
+                            model: "anthropic/claude-3-5-sonnet".to_string(),
+                            ..Default::default()
+                        },
+                        fallbacks: None,
+                    },
+                ],
+            },
+        );
+
+        let profile = objectiveai::functions::InlineProfile {
+            tasks: vec![objectiveai::functions::TaskProfile::VectorCompletion {
+                ensemble,
+                // Profile weights are per-LLM-config, not per-instance
+                // We have 2 distinct LLM configs (gpt-4o and claude)
+                profile: objectiveai::vector::completions::request::Profile::Weights(
+                    vec![
+                        Decimal::new(6, 1), // 0.6 for gpt-4o (covers both instances)
+                        Decimal::new(4, 1), // 0.4 for claude
+                    ],
+                ),
+            }],
+            profile: objectiveai::vector::completions::request::Profile::Weights(
+                vec![Decimal::ONE],
+            ),
+        };
+
+        let request = Arc::new(objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+            body: objectiveai::functions::executions::request::FunctionInlineProfileInlineRequestBody {
+                function: create_simple_vector_function(),
+                profile,
+                base: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+                    retry_token: None,
+                    from_cache: None,
+                    from_rng: Some(true),
+                    cache: None,
+                    reasoning: None,
+                    strategy: None,
+                    top_k: None,
+                    input: empty_input(),
+                    provider: None,
+                    seed: None,
+                    stream: None,
+                    dry_run: None,
+                    max_cost: None,
+                    attribution: Some(true),
+                    backoff_max_elapsed_time: None,
+                    first_chunk_timeout: None,
+                    other_chunk_timeout: None,
+                },
+            },
+        });
+
+        let response = function_client
+            .create_unary_handle_usage(ctx, request)
+            .await
+            .unwrap();
+        let output = match &response.output {
+            objectiveai::functions::expression::FunctionOutput::Vector(scores) => scores.clone(),
+            other => panic!("Expected vector output, got {:?}", other),
+        };
+
+        let attribution = response.attribution.expect("Should include attribution");
+        assert_eq!(attribution.tasks.len(), 1);
+        let task = attribution.tasks[0].as_ref().expect("Task should be attributed");
+        assert_eq!(task.weight, Decimal::ONE);
+        assert!(!task.inverted);
+
+        // both LLM configs voted, and their contributions make up the scores
+        let mut votes = task.llms.iter().map(|llm| llm.votes).collect::<Vec<_>>();
+        votes.sort();
+        assert_eq!(votes, vec![1, 2]);
+        for (i, score) in output.iter().enumerate() {
+            let sum: Decimal = task.llms.iter().map(|llm| llm.contribution[i]).sum();
+            assert!((sum - score).abs() < Decimal::new(1, 6));
+        }
+    }
+
+    /// Tests that a batch executes every input and aggregates usage.
+    #[tokio::test]
+    async fn test_batch_execution_with_rng() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let ctx = create_test_context();
+
+        let params = Arc::new(objectiveai::functions::executions::batch::request::FunctionExecutionBatchCreateParams {
+            function: objectiveai::functions::executions::batch::request::BatchFunction::Inline(
+                create_simple_vector_function(),
+            ),
+            profile: objectiveai::functions::executions::batch::request::BatchProfile::Inline(
+                create_simple_profile(),
+            ),
+            inputs: vec![empty_input(), empty_input(), empty_input()],
+            retry_tokens: None,
+            max_concurrency: Some(2),
+            from_cache: None,
+            from_rng: Some(true),
+            cache: None,
+            reasoning: None,
+            strategy: None,
+            top_k: None,
+            provider: None,
+            seed: None,
+            stream: None,
+            backoff_max_elapsed_time: None,
+            first_chunk_timeout: None,
+            other_chunk_timeout: None,
+        });
+
+        let result = function_client
+            .create_batch_unary(ctx.clone(), params, 16)
+            .await;
+
+        assert!(result.is_ok(), "Batch execution should succeed: {:?}", result.err());
+
+        let response = result.unwrap();
+
+        // Verify the compatibility check ran once for every item
+        assert_eq!(ctx.compatibility_cache.len(), 1);
+
+        // Verify every item succeeded and items are in input order
+        assert_eq!(response.items.len(), 3, "Should have 3 items");
+        assert_eq!(response.retry_tokens.len(), 3, "Should have 3 retry tokens");
+        for (index, item) in response.items.iter().enumerate() {
+            assert_eq!(item.index, index);
+            assert!(item.error.is_none(), "Item should succeed: {:?}", item.error);
+            assert!(item.execution.is_some(), "Item should have an execution");
+        }
+
+        // Verify the usage is the sum of the item usages
+        let total_tokens: u64 = response
+            .items
+            .iter()
+            .map(|item| item.execution.as_ref().unwrap().usage.total_tokens)
+            .sum();
+        assert_eq!(response.usage.total_tokens, total_tokens);
+    }
+
+    /// Tests that an execution with a shared root flattens against it,
+    /// without resolving the Function and Profile its request references.
+    #[tokio::test]
+    async fn test_execution_with_shared_root() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        // the mock fetchers find nothing, so only the root can be used
+        let ctx = create_test_context().with_root(
+            functions::FunctionParam::FetchedOrInline {
+                full_id: Some((
+                    "owner".to_string(),
+                    "function".to_string(),
+                    "commit".to_string(),
+                )),
+                function: objectiveai::functions::Function::Inline(
+                    create_simple_vector_function(),
+                ),
+            },
+            functions::ProfileParam::FetchedOrInline {
+                full_id: None,
+                profile: objectiveai::functions::Profile::Inline(
+                    create_simple_profile(),
+                ),
+            },
+        );
+        let request = Arc::new(objectiveai::functions::executions::request::Request::FunctionRemoteProfileRemote {
+            path: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestPath {
+                fowner: "owner".to_string(),
+                frepository: "function".to_string(),
+                fcommit: None,
+                powner: "owner".to_string(),
+                prepository: "profile".to_string(),
+                pcommit: None,
+            },
+            body: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+                retry_token: None,
+                from_cache: None,
+                from_rng: Some(true),
+                cache: None,
+                reasoning: None,
+                strategy: None,
+                top_k: None,
+                input: empty_input(),
+                provider: None,
+                seed: None,
+                stream: None,
+                dry_run: None,
+                max_cost: None,
+                attribution: None,
+                backoff_max_elapsed_time: None,
+                first_chunk_timeout: None,
+                other_chunk_timeout: None,
+            },
+        });
+
+        let result = function_client
+            .create_unary_handle_usage(ctx, request)
+            .await;
+
+        assert!(result.is_ok(), "Execution should succeed: {:?}", result.err());
+        assert!(result.unwrap().error.is_none());
+    }
+
+    /// Tests that an empty batch is rejected.
+    #[tokio::test]
+    async fn test_batch_execution_empty_inputs() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let ctx = create_test_context();
+
+        let params = Arc::new(objectiveai::functions::executions::batch::request::FunctionExecutionBatchCreateParams {
+            function: objectiveai::functions::executions::batch::request::BatchFunction::Inline(
+                create_simple_vector_function(),
+            ),
+            profile: objectiveai::functions::executions::batch::request::BatchProfile::Inline(
+                create_simple_profile(),
+            ),
+            inputs: Vec::new(),
+            retry_tokens: None,
+            max_concurrency: None,
+            from_cache: None,
+            from_rng: Some(true),
+            cache: None,
+            reasoning: None,
+            strategy: None,
+            top_k: None,
+            provider: None,
+            seed: None,
+            stream: None,
+            backoff_max_elapsed_time: None,
+            first_chunk_timeout: None,
+            other_chunk_timeout: None,
+        });
+
+        let result = function_client
+            .create_batch_unary(ctx, params, 16)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(crate::functions::executions::Error::InvalidBatch(_))
+        ));
+    }
+
+    /// Tests that a cancelled execution still finishes with a retry token
+    /// and a cancellation error.
+    #[tokio::test]
+    async fn test_cancelled_streaming_execution() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let ctx = create_test_context();
+        ctx.cancel.cancel();
+
+        let request = Arc::new(objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+            body: objectiveai::functions::executions::request::FunctionInlineProfileInlineRequestBody {
+                function: create_simple_vector_function(),
+                profile: create_simple_profile(),
+                base: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+                    retry_token: None,
+                    from_cache: None,
+                    from_rng: Some(true),
+                    cache: None,
+                    reasoning: None,
+                    strategy: None,
+                    top_k: None,
+                    input: empty_input(),
+                    provider: None,
+                    seed: None,
+                    stream: Some(true),
+                    dry_run: None,
+                    max_cost: None,
+                    attribution: None,
+                    backoff_max_elapsed_time: None,
+                    first_chunk_timeout: None,
+                    other_chunk_timeout: None,
+                },
+            },
+        });
+
+        let mut stream = function_client
+            .clone()
+            .create_streaming_handle_usage(ctx, request)
+            .await
+            .unwrap();
+        let mut aggregate = stream.next().await.unwrap();
+        while let Some(chunk) = stream.next().await {
+            aggregate.push(&chunk);
+        }
+
+        let error = aggregate.error.expect("Should report cancellation");
+        assert_eq!(error.code, 499);
+        assert!(aggregate.retry_token.is_some());
+        assert!(function_client.cancellations.is_empty());
+    }
+
+    /// Tests that dropping the response stream mid-execution aborts the
+    /// outstanding upstream chat completion.
+    #[tokio::test]
+    async fn test_dropped_stream_aborts_upstream() {
+        /// Reports when the upstream response is dropped.
+        struct Disconnected(tokio::sync::mpsc::UnboundedSender<()>);
+
+        impl Drop for Disconnected {
+            fn drop(&mut self) {
+                let _ = self.0.send(());
+            }
+        }
+
+        // an upstream that streams two chunks, then hangs until disconnected
+        let (requests_tx, mut requests_rx) =
+            tokio::sync::mpsc::unbounded_channel();
+        let (dropped_tx, mut dropped_rx) =
+            tokio::sync::mpsc::unbounded_channel();
+        let upstream = axum::Router::new().route(
+            "/chat/completions",
+            axum::routing::post(move || {
+                let _ = requests_tx.send(());
+                let disconnected = Disconnected(dropped_tx.clone());
+                let chunk = serde_json::to_string(
+                    &chat::completions::upstream::openrouter::response::ChatCompletionChunk {
+                        id: "upstream".to_string(),
+                        choices: vec![Default::default()],
+                        model: "openai/gpt-4o".to_string(),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+                async move {
+                    axum::response::sse::Sse::new(
+                        futures::stream::iter([chunk.clone(), chunk])
+                            .map(|chunk| {
+                                Ok::<_, std::convert::Infallible>(
+                                    axum::response::sse::Event::default()
+                                        .data(chunk),
+                                )
+                            })
+                            .chain(futures::stream::pending())
+                        .map(move |event| {
+                            let _ = &disconnected;
+                            event
+                        }),
+                    )
+                }
+            }),
+        );
+        let listener =
+            tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, upstream).await.unwrap();
+        });
+
+        let chat_client = create_test_chat_client_with_upstream(api_base);
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let request = Arc::new(objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+            body: objectiveai::functions::executions::request::FunctionInlineProfileInlineRequestBody {
+                function: create_simple_scalar_function(),
+                profile: create_two_llm_scalar_profile(),
+                base: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+                    retry_token: None,
+                    from_cache: None,
+                    from_rng: None,
+                    cache: None,
+                    reasoning: None,
+                    strategy: None,
+                    top_k: None,
+                    input: empty_input(),
+                    provider: None,
+                    seed: None,
+                    stream: Some(true),
+                    dry_run: None,
+                    max_cost: None,
+                    attribution: None,
+                    backoff_max_elapsed_time: None,
+                    first_chunk_timeout: None,
+                    other_chunk_timeout: None,
+                },
+            },
+        });
+
+        let stream = function_client
+            .clone()
+            .create_streaming_handle_usage(create_test_context(), request)
+            .await
+            .unwrap();
+        // one upstream call per LLM in the ensemble
+        for _ in 0..2 {
+            requests_rx.recv().await.unwrap();
+        }
+        assert!(dropped_rx.try_recv().is_err());
+
+        // the client disconnects while the upstream calls are outstanding
+        drop(stream);
+        for _ in 0..2 {
+            tokio::time::timeout(
+                std::time::Duration::from_secs(10),
+                dropped_rx.recv(),
+            )
+            .await
+            .expect("Upstream call should be aborted");
+        }
+    }
+
+    /// Tests that a chat completion whose upstream stream ends on its own is
+    /// not reported as cancelled.
+    #[tokio::test]
+    async fn test_completed_upstream_not_cancelled() {
+        let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let chat_client = create_test_chat_client_with_upstream(
+            serve_upstream("A summary", None, requests.clone()).await,
+        );
+
+        let params = objectiveai::chat::completions::request::ChatCompletionCreateParams {
+            messages: vec![objectiveai::chat::completions::request::Message::User(
+                objectiveai::chat::completions::request::UserMessage {
+                    content: objectiveai::chat::completions::request::RichContent::Text(
+                        "Summarize this".to_string(),
+                    ),
+                    name: None,
+                },
+            )],
+            provider: None,
+            model: objectiveai::chat::completions::request::Model::Provided(
+                objectiveai::ensemble_llm::EnsembleLlmBase {
+                    model: "openai/gpt-4o".to_string(),
+                    ..Default::default()
+                },
+            ),
+            models: None,
+            top_logprobs: None,
+            response_format: None,
+            seed: None,
+            stream: Some(true),
+            tool_choice: None,
+            tools: None,
+            parallel_tool_calls: None,
+            prediction: None,
+            max_cost: None,
+            backoff_max_elapsed_time: None,
+            first_chunk_timeout: None,
+            other_chunk_timeout: None,
+        };
+        let chunks = chat_client
+            .create_streaming_for_chat_handle_usage(
+                create_test_context(),
+                Arc::new(params),
+            )
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(requests.lock().unwrap().len(), 1);
+        assert!(
+            chunks.iter().all(Result::is_ok),
+            "Completion should not be cancelled: {:?}",
+            chunks.iter().find_map(|chunk| chunk.as_ref().err()),
+        );
+    }
+
+    /// Tests that a retry reuses the output of a chat completion task kept in
+    /// the retry token instead of generating it again.
+    #[tokio::test]
+    async fn test_chat_completion_task_reused_on_retry() {
+        let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let chat_client = create_test_chat_client_with_upstream(
+            serve_upstream("A summary", None, requests.clone()).await,
+        );
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let request = |retry_token: Option<String>| {
+            Arc::new(objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+                body: objectiveai::functions::executions::request::FunctionInlineProfileInlineRequestBody {
+                    function: create_chat_scalar_function(),
+                    profile: create_chat_scalar_profile(),
+                    base: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+                        retry_token,
+                        from_cache: None,
+                        from_rng: Some(true),
+                        cache: None,
+                        reasoning: None,
+                        strategy: None,
+                        top_k: None,
+                        input: empty_input(),
+                        provider: None,
+                        seed: None,
+                        stream: None,
+                        dry_run: None,
+                        max_cost: None,
+                        attribution: None,
+                        backoff_max_elapsed_time: None,
+                        first_chunk_timeout: None,
+                        other_chunk_timeout: None,
+                    },
+                },
+            })
+        };
+
+        let first = function_client
+            .clone()
+            .create_unary_handle_usage(create_test_context(), request(None))
+            .await
+            .unwrap();
+        assert_eq!(requests.lock().unwrap().len(), 1);
+        let retry_token =
+            objectiveai::functions::executions::RetryToken::try_from_string(
+                first.retry_token.as_deref().unwrap(),
+            )
+            .unwrap();
+        assert!(retry_token.0[0].is_some());
+
+        let retried = function_client
+            .create_unary_handle_usage(
+                create_test_context(),
+                request(first.retry_token.clone()),
+            )
+            .await
+            .unwrap();
+        // the summary is not generated again, and is kept for later retries
+        assert_eq!(requests.lock().unwrap().len(), 1);
+        let retried_token =
+            objectiveai::functions::executions::RetryToken::try_from_string(
+                retried.retry_token.as_deref().unwrap(),
+            )
+            .unwrap();
+        assert_eq!(retried_token.0[0], retry_token.0[0]);
+    }
+
+    /// Tests that an execution whose budget is spent reports the maximum
+    /// cost with a retry token.
+    #[tokio::test]
+    async fn test_max_cost_exceeded_streaming_execution() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let ctx = create_test_context()
+            .child_with_max_cost(Some(Decimal::new(1, 2)));
+        ctx.spend(Decimal::new(2, 2));
+        assert!(ctx.cancel.is_cancelled());
+
+        let request = Arc::new(objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+            body: objectiveai::functions::executions::request::FunctionInlineProfileInlineRequestBody {
+                function: create_simple_vector_function(),
+                profile: create_simple_profile(),
+                base: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+                    retry_token: None,
+                    from_cache: None,
+                    from_rng: Some(true),
+                    cache: None,
+                    reasoning: None,
+                    strategy: None,
+                    top_k: None,
+                    input: empty_input(),
+                    provider: None,
+                    seed: None,
+                    stream: Some(true),
+                    dry_run: None,
+                    max_cost: Some(Decimal::ONE),
+                    attribution: None,
+                    backoff_max_elapsed_time: None,
+                    first_chunk_timeout: None,
+                    other_chunk_timeout: None,
+                },
+            },
+        });
+
+        let mut stream = function_client
+            .clone()
+            .create_streaming_handle_usage(ctx, request)
+            .await
+            .unwrap();
+        let mut aggregate = stream.next().await.unwrap();
+        while let Some(chunk) = stream.next().await {
+            aggregate.push(&chunk);
+        }
+
+        let error = aggregate.error.expect("Should report the maximum cost");
+        assert_eq!(error.code, 402);
+        assert_eq!(
+            error.message["error"]["kind"],
+            "max_cost_exceeded"
+        );
+        assert!(aggregate.retry_token.is_some());
+    }
+
+    /// Tests that an execution whose upstream usage reaches the maximum
+    /// cost midway cancels the calls after it, and reports the tasks that
+    /// completed with a retry token for them.
+    #[tokio::test]
+    async fn test_max_cost_reached_mid_execution() {
+        let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let chat_client = create_test_chat_client_with_upstream(
+            serve_upstream("A summary", Some(Decimal::ONE), requests.clone())
+                .await,
+        );
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let request = Arc::new(objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+            body: objectiveai::functions::executions::request::FunctionInlineProfileInlineRequestBody {
+                function: create_chat_scalar_function(),
+                profile: create_chat_scalar_profile(),
+                base: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+                    retry_token: None,
+                    from_cache: None,
+                    from_rng: None,
+                    cache: None,
+                    reasoning: None,
+                    strategy: None,
+                    top_k: None,
+                    input: empty_input(),
+                    provider: None,
+                    seed: None,
+                    stream: Some(true),
+                    dry_run: None,
+                    max_cost: Some(Decimal::new(5, 1)),
+                    attribution: None,
+                    backoff_max_elapsed_time: None,
+                    first_chunk_timeout: None,
+                    other_chunk_timeout: None,
+                },
+            },
+        });
+
+        let mut stream = function_client
+            .clone()
+            .create_streaming_handle_usage(create_test_context(), request)
+            .await
+            .unwrap();
+        let mut aggregate = stream.next().await.unwrap();
+        let mut last = None;
+        while let Some(chunk) = stream.next().await {
+            aggregate.push(&chunk);
+            last = Some(chunk);
+        }
+
+        // only the chat completion task reached the upstream
+        assert_eq!(requests.lock().unwrap().len(), 1);
+        let error = aggregate.error.expect("Should report the maximum cost");
+        assert_eq!(error.message["error"]["kind"], "max_cost_exceeded");
+        assert!(aggregate.tasks.iter().any(|task| matches!(
+            task,
+            objectiveai::functions::executions::response::streaming::TaskChunk::ChatCompletion(_)
+        )));
+        let last = last.expect("Should end with the cancellation");
+        assert!(last.error.is_some());
+        let retry_token =
+            objectiveai::functions::executions::RetryToken::try_from_string(
+                last.retry_token.as_deref().unwrap(),
+            )
+            .unwrap();
+        assert!(retry_token.0[0].is_some());
+    }
+
+    /// Executes the simple scalar Function, whose own reasoning summary
+    /// prompt is `Function prompt`, with `reasoning` against an upstream
+    /// generating `content`, returning the response and upstream requests.
+    async fn execute_with_reasoning(
+        content: &'static str,
+        reasoning: objectiveai::functions::executions::request::Reasoning,
+    ) -> (
+        objectiveai::functions::executions::response::unary::FunctionExecution,
+        Vec<serde_json::Value>,
+    ) {
+        let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let chat_client = create_test_chat_client_with_upstream(
+            serve_upstream(content, None, requests.clone()).await,
+        );
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let objectiveai::functions::InlineFunction::Scalar { tasks, .. } =
+            create_simple_scalar_function()
+        else {
+            unreachable!()
+        };
+        let response = function_client
+            .create_unary_handle_usage(
+                create_test_context(),
+                Arc::new(objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+                    body: objectiveai::functions::executions::request::FunctionInlineProfileInlineRequestBody {
+                        function: objectiveai::functions::InlineFunction::Scalar {
+                            input_maps: None,
+                            output: None,
+                            tasks,
+                            reasoning_summary: Some(objectiveai::functions::ReasoningSummaryPrompt {
+                                template: Some("Function prompt".to_string()),
+                                schema: None,
+                            }),
+                        },
+                        profile: create_two_llm_scalar_profile(),
+                        base: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+                            retry_token: None,
+                            from_cache: None,
+                            from_rng: Some(true),
+                            cache: None,
+                            reasoning: Some(reasoning),
+                            strategy: None,
+                            top_k: None,
+                            input: empty_input(),
+                            provider: None,
+                            seed: None,
+                            stream: None,
+                            dry_run: None,
+                            max_cost: None,
+                            attribution: None,
+                            backoff_max_elapsed_time: None,
+                            first_chunk_timeout: None,
+                            other_chunk_timeout: None,
+                        },
+                    },
+                }),
+            )
+            .await
+            .unwrap();
+        let requests = requests.lock().unwrap().clone();
+        (response, requests)
+    }
+
+    /// Creates a reasoning request prompted with `Request prompt` and a
+    /// schema of a single verdict.
+    fn create_structured_reasoning()
+    -> objectiveai::functions::executions::request::Reasoning {
+        objectiveai::functions::executions::request::Reasoning {
+            model: objectiveai::chat::completions::request::Model::Provided(
+                objectiveai::ensemble_llm::EnsembleLlmBase {
+                    model: "openai/gpt-4o".to_string(),
+                    ..Default::default()
+                },
+            ),
+            models: None,
+            prompt: Some(objectiveai::functions::ReasoningSummaryPrompt {
+                template: Some("Request prompt: {{output}}".to_string()),
+                schema: Some(objectiveai::chat::completions::request::JsonSchema {
+                    name: "summary".to_string(),
+                    description: None,
+                    schema: Some(serde_json::json!({
+                        "type": "object",
+                        "properties": {"verdict": {"type": "string"}},
+                        "required": ["verdict"],
+                    })),
+                    strict: Some(true),
+                }),
+            }),
+        }
+    }
+
+    /// Tests that the reasoning prompt of the request replaces the
+    /// Function's own, and that a structured summary is parsed into `data`.
+    #[tokio::test]
+    async fn test_reasoning_summary_prompt_override() {
+        let (response, requests) = execute_with_reasoning(
+            r#"{"verdict": "good"}"#,
+            create_structured_reasoning(),
+        )
+        .await;
+
+        assert_eq!(requests.len(), 1);
+        let messages = requests[0]["messages"].to_string();
+        assert!(messages.contains("Request prompt: "));
+        assert!(!messages.contains("Function prompt"));
+        assert_eq!(requests[0]["response_format"]["type"], "json_schema");
+        let reasoning = response.reasoning.expect("Should have a reasoning summary");
+        assert!(reasoning.error.is_none());
+        assert_eq!(
+            reasoning.data,
+            Some(serde_json::json!({"verdict": "good"})),
+        );
+    }
+
+    /// Tests that a summary which is not valid JSON despite its schema is
+    /// reported as an error.
+    #[tokio::test]
+    async fn test_reasoning_summary_invalid_data() {
+        let (response, _) =
+            execute_with_reasoning("A summary", create_structured_reasoning())
+                .await;
+
+        let reasoning = response.reasoning.expect("Should have a reasoning summary");
+        assert!(reasoning.data.is_none());
+        let error = reasoning.error.expect("Should report the invalid summary");
+        assert_eq!(error.message["error"]["kind"], "invalid_reasoning_summary");
+    }
+
+    /// Tests that a dependent task runs once its dependency has produced an
+    /// output.
+    #[tokio::test]
+    async fn test_dependent_task_runs() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let response = function_client
+            .create_unary_handle_usage(
+                create_test_context(),
+                create_gated_scalar_request(vec![0], "tasks[0].output == null"),
+            )
+            .await
+            .unwrap();
+
+        let task_paths = response
+            .tasks
+            .iter()
+            .map(|task| task.task_path().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(task_paths, vec![vec![0], vec![1]]);
+        match &response.output {
+            objectiveai::functions::expression::FunctionOutput::Scalar(score) => {
+                assert!(*score >= Decimal::ZERO && *score <= Decimal::ONE);
+            }
+            other => panic!("Expected scalar output, got {:?}", other),
+        }
+    }
+
+    /// Tests that a dependent task is skipped based on its dependency's
+    /// output.
+    #[tokio::test]
+    async fn test_dependent_task_skipped() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let response = function_client
+            .create_unary_handle_usage(
+                create_test_context(),
+                create_gated_scalar_request(
+                    vec![0],
+                    "tasks[0].output.scores[0] <= `1`",
+                ),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.tasks.len(), 1);
+        assert_eq!(response.tasks[0].task_path(), &vec![0]);
+        match &response.output {
+            objectiveai::functions::expression::FunctionOutput::Scalar(score) => {
+                assert!(*score >= Decimal::ZERO && *score <= Decimal::ONE);
+            }
+            other => panic!("Expected scalar output, got {:?}", other),
+        }
+    }
+
+    /// Tests that a dependency on a later task is rejected.
+    #[tokio::test]
+    async fn test_dependent_task_invalid_dependency() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let result = function_client
+            .create_unary_handle_usage(
+                create_test_context(),
+                create_gated_scalar_request(vec![1], "`false`"),
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(crate::functions::executions::Error::InvalidTaskDependencies(_))
+        ));
+    }
+
+    /// Tests that a cached execution is reused without usage, and replaced
+    /// when refreshed.
+    #[tokio::test]
+    async fn test_cached_function_execution() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let request = |refresh| {
+            Arc::new(objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+                body: objectiveai::functions::executions::request::FunctionInlineProfileInlineRequestBody {
+                    function: create_simple_scalar_function(),
+                    profile: create_two_llm_scalar_profile(),
+                    base: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+                        retry_token: None,
+                        from_cache: None,
+                        from_rng: Some(true),
+                        cache: Some(objectiveai::functions::executions::request::Cache {
+                            ttl: None,
+                            refresh: Some(refresh),
+                        }),
+                        reasoning: None,
+                        strategy: None,
+                        top_k: None,
+                        input: empty_input(),
+                        provider: None,
+                        seed: None,
+                        stream: None,
+                        dry_run: None,
+                        max_cost: None,
+                        attribution: None,
+                        backoff_max_elapsed_time: None,
+                        first_chunk_timeout: None,
+                        other_chunk_timeout: None,
+                    },
+                },
+            })
+        };
+
+        let first = function_client
+            .clone()
+            .create_unary_handle_usage(create_test_context(), request(false))
+            .await
+            .unwrap();
+        assert!(!first.cached);
+
+        let second = function_client
+            .clone()
+            .create_unary_handle_usage(create_test_context(), request(false))
+            .await
+            .unwrap();
+        assert!(second.cached);
+        assert_eq!(second.id, first.id);
+        assert!(!second.any_usage());
+        assert_eq!(second.tasks.len(), first.tasks.len());
+
+        let refreshed = function_client
+            .clone()
+            .create_unary_handle_usage(create_test_context(), request(true))
+            .await
+            .unwrap();
+        assert!(!refreshed.cached);
+
+        let invalidated = function_client.invalidate_cache(
+            create_test_context(),
+            &objectiveai::functions::executions::cache::request::InvalidateCacheRequest::default(),
+        );
+        assert_eq!(invalidated.invalidated, 1);
+    }
+
+    /// Tests that a dry run counts the calls of each LLM and prices them
+    /// at the maximum prompt price.
+    #[tokio::test]
+    async fn test_estimate_function_execution() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let request = Arc::new(objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+            body: objectiveai::functions::executions::request::FunctionInlineProfileInlineRequestBody {
+                function: create_simple_scalar_function(),
+                profile: create_two_llm_scalar_profile(),
+                base: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+                    retry_token: None,
+                    from_cache: None,
+                    from_rng: None,
+                    cache: None,
+                    reasoning: None,
+                    strategy: None,
+                    top_k: None,
+                    input: empty_input(),
+                    provider: Some(objectiveai::chat::completions::request::Provider {
+                        data_collection: None,
+                        zdr: None,
+                        sort: None,
+                        max_price: Some(objectiveai::chat::completions::request::ProviderMaxPrice {
+                            prompt: Some(Decimal::ONE),
+                            completion: None,
+                            image: None,
+                            audio: None,
+                            request: None,
+                        }),
+                        preferred_min_throughput: None,
+                        preferred_max_latency: None,
+                        min_throughput: None,
+                        max_latency: None,
+                    }),
+                    seed: None,
+                    stream: None,
+                    dry_run: Some(true),
+                    max_cost: None,
+                    attribution: None,
+                    backoff_max_elapsed_time: None,
+                    first_chunk_timeout: None,
+                    other_chunk_timeout: None,
+                },
+            },
+        });
+
+        let estimate = function_client
+            .estimate(create_test_context(), request)
+            .await
+            .unwrap();
+        assert_eq!(estimate.total_calls, 2);
+        assert_eq!(estimate.models.len(), 2);
+        assert_eq!(estimate.models["openai/gpt-4o"].calls, 1);
+        assert!(estimate.calls.iter().all(|call| {
+            call.kind == objectiveai::functions::executions::response::EstimatedCallKind::VectorCompletion
+                && call.task_path == vec![0]
+                && call.prompt_tokens > 0
+        }));
+        assert_eq!(
+            estimate.cost,
+            Some(Decimal::from(estimate.prompt_tokens)),
+        );
+        assert_eq!(estimate.rounds, None);
+    }
+
+    /// Tests that a dry run defers the tasks reading the output of a chat
+    /// completion task, rejects unknown Ensemble LLMs, and is rejected by
+    /// the execution itself.
+    #[tokio::test]
+    async fn test_dry_run_chat_completion_task() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        // the vector completion task prompts with the chat completion output
+        let mut function = serde_json::to_value(create_chat_scalar_function()).unwrap();
+        function["tasks"][1]["messages"] = serde_json::json!({
+            "$starlark": "[{'role': 'user', 'content': tasks[0]['output']['text']}]",
+        });
+        let function: objectiveai::functions::InlineFunction =
+            serde_json::from_value(function).unwrap();
+        let request = |reasoning: Option<
+            objectiveai::functions::executions::request::Reasoning,
+        >| {
+            Arc::new(objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+                body: objectiveai::functions::executions::request::FunctionInlineProfileInlineRequestBody {
+                    function: function.clone(),
+                    profile: create_chat_scalar_profile(),
+                    base: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+                        retry_token: None,
+                        from_cache: None,
+                        from_rng: None,
+                        cache: None,
+                        reasoning,
+                        strategy: None,
+                        top_k: None,
+                        input: empty_input(),
+                        provider: None,
+                        seed: None,
+                        stream: None,
+                        dry_run: Some(true),
+                        max_cost: None,
+                        attribution: None,
+                        backoff_max_elapsed_time: None,
+                        first_chunk_timeout: None,
+                        other_chunk_timeout: None,
+                    },
+                },
+            })
+        };
+
+        let estimate = function_client
+            .estimate(create_test_context(), request(None))
+            .await
+            .unwrap();
+        assert_eq!(estimate.total_calls, 1);
+        assert_eq!(
+            estimate.calls[0].kind,
+            objectiveai::functions::executions::response::EstimatedCallKind::ChatCompletion,
+        );
+        assert_eq!(estimate.deferred_tasks, vec![vec![1]]);
+
+        let unknown = function_client
+            .estimate(
+                create_test_context(),
+                request(Some(objectiveai::functions::executions::request::Reasoning {
+                    model: objectiveai::chat::completions::request::Model::Id(
+                        "unknown".to_string(),
+                    ),
+                    models: None,
+                    prompt: None,
+                })),
+            )
+            .await;
+        assert!(matches!(
+            unknown,
+            Err(crate::functions::executions::Error::EnsembleLlmNotFound(id))
+                if id == "unknown"
+        ));
+
+        assert!(matches!(
+            function_client
+                .create_unary_handle_usage(create_test_context(), request(None))
+                .await,
+            Err(crate::functions::executions::Error::UnsupportedDryRun)
+        ));
+    }
+
+    /// Tests that a skipped task referencing a missing Ensemble does not
+    /// fail the compatibility check of the execution.
+    #[tokio::test]
+    async fn test_skipped_task_not_checked() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let mut profile = create_gated_scalar_profile();
+        profile.tasks[1] = objectiveai::functions::TaskProfile::VectorCompletion {
+            ensemble: objectiveai::vector::completions::request::Ensemble::Id(
+                "missing".to_string(),
+            ),
+            profile: objectiveai::vector::completions::request::Profile::Weights(
+                vec![Decimal::ONE],
+            ),
+        };
+        let request = Arc::new(objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+            body: objectiveai::functions::executions::request::FunctionInlineProfileInlineRequestBody {
+                function: create_gated_scalar_function(Vec::new(), "`true`"),
+                profile,
+                base: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+                    retry_token: None,
+                    from_cache: None,
+                    from_rng: Some(true),
+                    cache: None,
+                    reasoning: None,
+                    strategy: None,
+                    top_k: None,
+                    input: empty_input(),
+                    provider: None,
+                    seed: None,
+                    stream: None,
+                    dry_run: None,
+                    max_cost: None,
+                    attribution: None,
+                    backoff_max_elapsed_time: None,
+                    first_chunk_timeout: None,
+                    other_chunk_timeout: None,
+                },
+            },
+        });
+
+        let response = function_client
+            .create_unary_handle_usage(create_test_context(), request)
+            .await
+            .unwrap();
+        assert!(response.error.is_none(), "{:?}", response.error);
+    }
+
+    /// Tests that cancelling an unknown execution fails.
+    #[tokio::test]
+    async fn test_cancel_unknown_execution() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        assert!(matches!(
+            function_client.cancel(&create_test_context(), "fnc-unknown"),
+            Err(crate::functions::executions::Error::ExecutionNotFound)
+        ));
+    }
+
+    /// Tests that an execution can only be cancelled by its caller.
+    #[tokio::test]
+    async fn test_cancel_other_callers_execution() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        let caller = |byok: &str| {
+            ctx::Context::new(
+                Arc::new(MockContextExt {
+                    byok: Some(byok.to_string()),
+                }),
+                Decimal::ONE,
+            )
+        };
+        let cancel = tokio_util::sync::CancellationToken::new();
+        function_client.cancellations.insert(
+            "fnc-running".to_string(),
+            (Some("alice".to_string()), cancel.clone()),
+        );
+
+        assert!(matches!(
+            function_client.cancel(&caller("bob"), "fnc-running"),
+            Err(crate::functions::executions::Error::ExecutionNotFound)
+        ));
+        assert!(matches!(
+            function_client.cancel(&create_test_context(), "fnc-running"),
+            Err(crate::functions::executions::Error::ExecutionNotFound)
+        ));
+        assert!(!cancel.is_cancelled());
+
+        function_client.cancel(&caller("alice"), "fnc-running").unwrap();
+        assert!(cancel.is_cancelled());
+    }
+
+    /// Tests that a Profile which does not fit the Function is reported by
+    /// the compatibility check and rejected before execution.
+    #[tokio::test]
+    async fn test_incompatible_profile() {
+        let chat_client = create_test_chat_client();
+        let vector_client = create_test_vector_client(chat_client.clone());
+        let function_client =
+            create_test_function_client(chat_client, vector_client);
+
+        // compatible
+        let response = function_client
+            .check_compatibility(
+                create_test_context(),
+                objectiveai::functions::CheckCompatibilityRequest {
+                    function: objectiveai::functions::executions::batch::request::BatchFunction::Inline(
+                        create_simple_scalar_function(),
+                    ),
+                    profile: objectiveai::functions::executions::batch::request::BatchProfile::Inline(
+                        create_simple_scalar_profile(),
+                    ),
+                },
+            )
+            .await
+            .unwrap();
+        assert!(response.compatible);
+        assert!(response.issues.is_empty());
+
+        // one weight too many for the ensemble
+        let mut profile = create_simple_scalar_profile();
+        profile.tasks[0] = objectiveai::functions::TaskProfile::VectorCompletion {
+            ensemble: create_simple_ensemble(),
+            profile: objectiveai::vector::completions::request::Profile::Weights(
+                vec![Decimal::ONE, Decimal::ONE],
+            ),
+        };
+        let response = function_client
+            .check_compatibility(
+                create_test_context(),
+                objectiveai::functions::CheckCompatibilityRequest {
+                    function: objectiveai::functions::executions::batch::request::BatchFunction::Inline(
+                        create_simple_scalar_function(),
+                    ),
+                    profile: objectiveai::functions::executions::batch::request::BatchProfile::Inline(
+                        profile.clone(),
+                    ),
+                },
+            )
+            .await
+            .unwrap();
+        assert!(!response.compatible);
+        assert_eq!(response.issues.len(), 1);
+        assert_eq!(response.issues[0].path, "tasks[0].profile");
+        assert_eq!(
+            response.issues[0].kind,
+            objectiveai::functions::CompatibilityIssueKind::EnsembleWeightCount,
+        );
+
+        let request = Arc::new(objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+            body: objectiveai::functions::executions::request::FunctionInlineProfileInlineRequestBody {
+                function: create_simple_scalar_function(),
+                profile,
+                base: objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+                    retry_token: None,
+                    from_cache: None,
+                    from_rng: Some(true),
+                    cache: None,
+                    reasoning: None,
+                    strategy: None,
+                    top_k: None,
+                    input: empty_input(),
+                    provider: None,
+                    seed: None,
+                    stream: None,
+                    dry_run: None,
+                    max_cost: None,
+                    attribution: None,
+                    backoff_max_elapsed_time: None,
+                    first_chunk_timeout: None,
+                    other_chunk_timeout: None,
+                },
+            },
+        });
+        let result = function_client
+            .create_unary_handle_usage(create_test_context(), request)
+            .await;
+        match result {
+            Err(crate::functions::executions::Error::IncompatibleProfile(issues)) => {
+                assert_eq!(issues, response.issues);
+            }
+            other => panic!("Expected incompatible profile, got {:?}", other.map(|_| ())),
+        }
+    }
 }
@@ -20,6 +20,9 @@ pub enum Error {
     /// The Profile is invalid for the Function.
     #[error("invalid profile: {0}")]
     InvalidProfile(String),
+    /// The Profile does not fit the Function's task structure.
+    #[error("incompatible profile: {}", display_issues(.0))]
+    IncompatibleProfile(Vec<objectiveai::functions::CompatibilityIssue>),
     /// Failed to fetch an Ensemble definition.
     #[error("fetch ensemble error: {0}")]
     FetchEnsemble(objectiveai::error::ResponseError),
@@ -29,6 +32,12 @@ pub enum Error {
     /// The Ensemble definition is invalid.
     #[error("invalid ensemble: {0}")]
     InvalidEnsemble(String),
+    /// Failed to fetch an Ensemble LLM definition.
+    #[error("fetch Ensemble LLM error: {0}")]
+    FetchEnsembleLlm(objectiveai::error::ResponseError),
+    /// The requested Ensemble LLM was not found.
+    #[error("Ensemble LLM not found: {0}")]
+    EnsembleLlmNotFound(String),
     /// Failed to fetch retry data.
     #[error("fetch retry error: {0}")]
     FetchRetry(objectiveai::error::ResponseError),
@@ -57,15 +66,51 @@ pub enum Error {
         "invalid vector output, expected vector of numbers summing to 1 of length {0}"
     )]
     InvalidVectorOutput(usize),
+    /// Multi-label output does not have a score in [0, 1] for each label.
+    #[error(
+        "invalid multi-label output, expected a number between 0 and 1 for each of the labels [{}]",
+        .0.join(", ")
+    )]
+    InvalidMultiLabelOutput(Vec<String>),
+    /// Categorical output does not sum to 1 over the categories.
+    #[error(
+        "invalid categorical output, expected numbers summing to 1 for the categories [{}]",
+        .0.join(", ")
+    )]
+    InvalidCategoricalOutput(Vec<String>),
     /// Invalid Function for Strategy
     #[error("invalid function for strategy: {0}")]
     InvalidFunctionForStrategy(String),
     /// Invalid Strategy
     #[error("invalid strategy: {0}")]
     InvalidStrategy(String),
+    /// A task's dependencies are invalid.
+    #[error("invalid task dependencies: {0}")]
+    InvalidTaskDependencies(String),
+    /// The labels or categories of a Function are empty or not unique.
+    #[error("invalid labels: {0}")]
+    InvalidLabels(String),
+    /// The batch is empty or inconsistent.
+    #[error("invalid batch: {0}")]
+    InvalidBatch(String),
+    /// The execution is a dry run, which only single executions support.
+    #[error("unsupported dry run")]
+    UnsupportedDryRun,
+    /// The execution was cancelled before it finished.
+    #[error("cancelled")]
+    Cancelled,
+    /// The execution was cancelled for reaching its maximum cost.
+    #[error("max cost exceeded: {0}")]
+    MaxCostExceeded(rust_decimal::Decimal),
+    /// The referenced execution is not running.
+    #[error("execution not found")]
+    ExecutionNotFound,
     /// No valid task outputs to combine.
     #[error("no valid task outputs")]
     NoValidTaskOutputs,
+    /// The reasoning summary does not parse as JSON despite its schema.
+    #[error("invalid reasoning summary: {0}")]
+    InvalidReasoningSummary(serde_json::Error),
     /// One or more task output expressions failed.
     #[error("task output expression errors: {0:?}")]
     TaskOutputExpressionErrors(Vec<TaskOutputExpressionError>),
@@ -88,9 +133,12 @@ impl objectiveai::error::StatusError for Error {
             Error::FetchProfile(e) => e.status(),
             Error::ProfileNotFound => 404,
             Error::InvalidProfile(_) => 400,
+            Error::IncompatibleProfile(_) => 400,
             Error::FetchEnsemble(e) => e.status(),
             Error::EnsembleNotFound => 404,
             Error::InvalidEnsemble(_) => 400,
+            Error::FetchEnsembleLlm(e) => e.status(),
+            Error::EnsembleLlmNotFound(_) => 404,
             Error::FetchRetry(e) => e.status(),
             Error::RetryNotFound => 404,
             Error::InvalidRetryToken => 400,
@@ -99,9 +147,19 @@ impl objectiveai::error::StatusError for Error {
             Error::InputSchemaMismatch => 400,
             Error::InvalidScalarOutput => 400,
             Error::InvalidVectorOutput(_) => 400,
+            Error::InvalidMultiLabelOutput(_) => 400,
+            Error::InvalidCategoricalOutput(_) => 400,
             Error::InvalidFunctionForStrategy(_) => 400,
             Error::InvalidStrategy(_) => 400,
+            Error::InvalidTaskDependencies(_) => 400,
+            Error::InvalidLabels(_) => 400,
+            Error::InvalidBatch(_) => 400,
+            Error::UnsupportedDryRun => 400,
+            Error::Cancelled => 499,
+            Error::MaxCostExceeded(_) => 402,
+            Error::ExecutionNotFound => 404,
             Error::NoValidTaskOutputs => 400,
+            Error::InvalidReasoningSummary(_) => 500,
             Error::TaskOutputExpressionErrors(_) => 400,
         }
     }
@@ -130,6 +188,11 @@ impl objectiveai::error::StatusError for Error {
                     "kind": "invalid_profile",
                     "error": msg,
                 }),
+                Error::IncompatibleProfile(issues) => serde_json::json!({
+                    "kind": "incompatible_profile",
+                    "error": display_issues(issues),
+                    "issues": issues,
+                }),
                 Error::FetchEnsemble(e) => serde_json::json!({
                     "kind": "fetch_ensemble",
                     "error": e.message(),
@@ -142,6 +205,14 @@ impl objectiveai::error::StatusError for Error {
                     "kind": "invalid_ensemble",
                     "error": msg,
                 }),
+                Error::FetchEnsembleLlm(e) => serde_json::json!({
+                    "kind": "fetch_ensemble_llm",
+                    "error": e.message(),
+                }),
+                Error::EnsembleLlmNotFound(id) => serde_json::json!({
+                    "kind": "ensemble_llm_not_found",
+                    "error": format!("Ensemble LLM not found: {}", id),
+                }),
                 Error::FetchRetry(e) => serde_json::json!({
                     "kind": "fetch_retry",
                     "error": e.message(),
@@ -157,6 +228,9 @@ impl objectiveai::error::StatusError for Error {
                 Error::InvalidAppExpression(e) => serde_json::json!({
                     "kind": "invalid_expression",
                     "error": e.to_string(),
+                    "location": e.location(),
+                    "dialect": e.dialect(),
+                    "span": e.span(),
                 }),
                 Error::Vector(e) => serde_json::json!({
                     "kind": "vector_completion",
@@ -174,6 +248,14 @@ impl objectiveai::error::StatusError for Error {
                     "kind": "invalid_vector_output",
                     "error": format!("invalid vector output, expected vector of numbers summing to 1 of length {}", len),
                 }),
+                Error::InvalidMultiLabelOutput(labels) => serde_json::json!({
+                    "kind": "invalid_multi_label_output",
+                    "error": format!("invalid multi-label output, expected a number between 0 and 1 for each of the labels [{}]", labels.join(", ")),
+                }),
+                Error::InvalidCategoricalOutput(categories) => serde_json::json!({
+                    "kind": "invalid_categorical_output",
+                    "error": format!("invalid categorical output, expected numbers summing to 1 for the categories [{}]", categories.join(", ")),
+                }),
                 Error::InvalidFunctionForStrategy(msg) => serde_json::json!({
                     "kind": "invalid_function_for_strategy",
                     "error": msg,
@@ -182,10 +264,45 @@ impl objectiveai::error::StatusError for Error {
                     "kind": "invalid_strategy",
                     "error": msg,
                 }),
+                Error::InvalidTaskDependencies(msg) => serde_json::json!({
+                    "kind": "invalid_task_dependencies",
+                    "error": msg,
+                }),
+                Error::InvalidLabels(msg) => serde_json::json!({
+                    "kind": "invalid_labels",
+                    "error": msg,
+                }),
+                Error::InvalidBatch(msg) => serde_json::json!({
+                    "kind": "invalid_batch",
+                    "error": msg,
+                }),
+                Error::UnsupportedDryRun => serde_json::json!({
+                    "kind": "unsupported_dry_run",
+                    "error": "dry runs are only supported for single executions",
+                }),
+                Error::Cancelled => serde_json::json!({
+                    "kind": "cancelled",
+                    "error": "execution was cancelled",
+                }),
+                Error::MaxCostExceeded(max_cost) => serde_json::json!({
+                    "kind": "max_cost_exceeded",
+                    "error": format!(
+                        "execution was cancelled for reaching its maximum cost of {}",
+                        max_cost,
+                    ),
+                }),
+                Error::ExecutionNotFound => serde_json::json!({
+                    "kind": "execution_not_found",
+                    "error": "execution not found",
+                }),
                 Error::NoValidTaskOutputs => serde_json::json!({
                     "kind": "no_valid_task_outputs",
                     "error": "no valid task outputs to combine",
                 }),
+                Error::InvalidReasoningSummary(e) => serde_json::json!({
+                    "kind": "invalid_reasoning_summary",
+                    "error": e.to_string(),
+                }),
                 Error::TaskOutputExpressionErrors(errors) => serde_json::json!({
                     "kind": "task_output_expression_errors",
                     "errors": errors.iter().map(|e| serde_json::json!({
@@ -197,3 +314,12 @@ impl objectiveai::error::StatusError for Error {
         }))
     }
 }
+
+/// Joins compatibility issues into a single message.
+fn display_issues(issues: &[objectiveai::functions::CompatibilityIssue]) -> String {
+    issues
+        .iter()
+        .map(|issue| issue.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
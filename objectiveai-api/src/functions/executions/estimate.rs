@@ -0,0 +1,309 @@
+//! Estimates of Function executions for dry runs.
+//!
+//! Walks flattened task profiles, counting the upstream calls they would
+//! make. Prompt tokens are estimated from the length of the text of the
+//! rendered messages, tools and responses, and costs are bounded by the
+//! maximum prices of the request's provider preferences.
+
+use crate::functions;
+use objectiveai::functions::executions::response::{
+    EstimatedCall, EstimatedCallKind, FunctionExecutionEstimate,
+};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// Rough number of characters per token of text.
+const CHARS_PER_TOKEN: u64 = 4;
+
+/// Tokens added for the role and delimiters of each message or response.
+const TOKENS_PER_ITEM: u64 = 4;
+
+/// Estimates the prompt tokens of messages, tools or responses.
+///
+/// Counts the text of every string value. Inline media (`data:` URLs) is
+/// not counted.
+pub fn estimate_tokens<T: serde::Serialize>(items: &[T]) -> u64 {
+    fn chars(value: &serde_json::Value) -> u64 {
+        match value {
+            serde_json::Value::String(s) if s.starts_with("data:") => 0,
+            serde_json::Value::String(s) => s.chars().count() as u64,
+            serde_json::Value::Array(values) => values.iter().map(chars).sum(),
+            serde_json::Value::Object(map) => map.values().map(chars).sum(),
+            _ => 0,
+        }
+    }
+    items
+        .iter()
+        .map(|item| {
+            let value = serde_json::to_value(item).unwrap_or_default();
+            TOKENS_PER_ITEM + chars(&value).div_ceil(CHARS_PER_TOKEN)
+        })
+        .sum()
+}
+
+/// Accumulates the upstream calls of a Function execution.
+#[derive(Debug, Clone)]
+pub struct Estimator {
+    /// Maximum prices of the request's provider preferences.
+    max_price: Option<objectiveai::chat::completions::request::ProviderMaxPrice>,
+    /// The calls, in order.
+    calls: Vec<EstimatedCall>,
+    /// Paths of the dependent tasks seen.
+    deferred_tasks: Vec<Vec<u64>>,
+}
+
+impl Estimator {
+    pub fn new(
+        max_price: Option<
+            objectiveai::chat::completions::request::ProviderMaxPrice,
+        >,
+    ) -> Self {
+        Self {
+            max_price,
+            calls: Vec::new(),
+            deferred_tasks: Vec::new(),
+        }
+    }
+
+    /// Adds a single call to `llm`.
+    pub fn push_call(
+        &mut self,
+        kind: EstimatedCallKind,
+        task_path: Vec<u64>,
+        round: Option<u64>,
+        llm: &objectiveai::ensemble_llm::EnsembleLlmBase,
+        prompt_tokens: u64,
+    ) {
+        let prompt_tokens = prompt_tokens
+            + llm.prefix_messages.as_deref().map_or(0, estimate_tokens)
+            + llm.suffix_messages.as_deref().map_or(0, estimate_tokens);
+        let max_completion_tokens =
+            llm.max_completion_tokens.or(llm.max_tokens);
+        self.calls.push(EstimatedCall {
+            kind,
+            task_path,
+            round,
+            model: llm.model.clone(),
+            prompt_tokens,
+            max_completion_tokens,
+            cost: self.cost(prompt_tokens, max_completion_tokens),
+        });
+    }
+
+    /// Adds the calls of a flattened Function.
+    ///
+    /// Without `vector_completions`, only its chat completion tasks are
+    /// counted, as they run while the Function is flattened. `llms` holds
+    /// the Ensemble LLMs of the chat completion tasks' models, by ID.
+    pub fn push_function(
+        &mut self,
+        function: &functions::FunctionFlatTaskProfile,
+        round: Option<u64>,
+        llms: &HashMap<String, objectiveai::ensemble_llm::EnsembleLlmBase>,
+        vector_completions: bool,
+    ) {
+        for task in function.tasks.iter().flatten() {
+            self.push_task(task, round, llms, vector_completions);
+        }
+    }
+
+    fn push_task(
+        &mut self,
+        task: &functions::FlatTaskProfile,
+        round: Option<u64>,
+        llms: &HashMap<String, objectiveai::ensemble_llm::EnsembleLlmBase>,
+        vector_completions: bool,
+    ) {
+        match task {
+            functions::FlatTaskProfile::Function(function) => {
+                self.push_function(function, round, llms, vector_completions)
+            }
+            functions::FlatTaskProfile::MapFunction(functions) => {
+                for function in &functions.functions {
+                    self.push_function(
+                        function,
+                        round,
+                        llms,
+                        vector_completions,
+                    );
+                }
+            }
+            functions::FlatTaskProfile::VectorCompletion(vector) => {
+                if vector_completions {
+                    self.push_vector_completion(vector, round);
+                }
+            }
+            functions::FlatTaskProfile::MapVectorCompletion(vectors) => {
+                if vector_completions {
+                    for vector in &vectors.vector_completions {
+                        self.push_vector_completion(vector, round);
+                    }
+                }
+            }
+            functions::FlatTaskProfile::ChatCompletion(chat) => {
+                let llm = match &chat.params.model {
+                    objectiveai::chat::completions::request::Model::Id(id) => {
+                        llms.get(id)
+                    }
+                    objectiveai::chat::completions::request::Model::Provided(
+                        llm,
+                    ) => Some(llm),
+                };
+                if let Some(llm) = llm {
+                    self.push_call(
+                        EstimatedCallKind::ChatCompletion,
+                        chat.path.clone(),
+                        round,
+                        llm,
+                        estimate_tokens(&chat.params.messages),
+                    );
+                }
+            }
+            functions::FlatTaskProfile::Dependent(dependent) => {
+                if vector_completions
+                    && !self.deferred_tasks.contains(&dependent.path)
+                {
+                    self.deferred_tasks.push(dependent.path.clone());
+                }
+            }
+        }
+    }
+
+    /// Adds one call per vote of each LLM with a positive weight.
+    fn push_vector_completion(
+        &mut self,
+        vector: &functions::VectorCompletionFlatTaskProfile,
+        round: Option<u64>,
+    ) {
+        let prompt_tokens = estimate_tokens(&vector.messages)
+            + vector.tools.as_deref().map_or(0, estimate_tokens)
+            + estimate_tokens(&vector.responses);
+        for (llm, weight) in vector.ensemble.llms.iter().zip(&vector.profile) {
+            if *weight <= Decimal::ZERO {
+                continue;
+            }
+            for _ in 0..llm.count {
+                self.push_call(
+                    EstimatedCallKind::VectorCompletion,
+                    vector.path.clone(),
+                    round,
+                    &llm.inner,
+                    prompt_tokens,
+                );
+            }
+        }
+    }
+
+    /// The maximum cost of a call, if the maximum prompt price is known.
+    fn cost(
+        &self,
+        prompt_tokens: u64,
+        max_completion_tokens: Option<u64>,
+    ) -> Option<Decimal> {
+        let max_price = self.max_price?;
+        let mut cost = Decimal::from(prompt_tokens) * max_price.prompt?;
+        if let (Some(tokens), Some(price)) =
+            (max_completion_tokens, max_price.completion)
+        {
+            cost += Decimal::from(tokens) * price;
+        }
+        if let Some(price) = max_price.request {
+            cost += price;
+        }
+        Some(cost)
+    }
+
+    /// Finishes the estimate.
+    pub fn finish(
+        self,
+        function: Option<String>,
+        profile: Option<String>,
+        rounds: Option<u64>,
+    ) -> FunctionExecutionEstimate {
+        FunctionExecutionEstimate::new(
+            function,
+            profile,
+            self.calls,
+            rounds,
+            self.deferred_tasks,
+        )
+    }
+}
+
+/// Returns the IDs of the Ensemble LLMs of the chat completion tasks of a
+/// flattened Function.
+pub fn chat_completion_model_ids(
+    function: &functions::FunctionFlatTaskProfile,
+) -> Vec<String> {
+    fn push(task: &functions::FlatTaskProfile, ids: &mut Vec<String>) {
+        match task {
+            functions::FlatTaskProfile::Function(function) => {
+                function.tasks.iter().flatten().for_each(|t| push(t, ids))
+            }
+            functions::FlatTaskProfile::MapFunction(functions) => functions
+                .functions
+                .iter()
+                .flat_map(|function| function.tasks.iter().flatten())
+                .for_each(|t| push(t, ids)),
+            functions::FlatTaskProfile::ChatCompletion(chat) => {
+                if let objectiveai::chat::completions::request::Model::Id(id) =
+                    &chat.params.model
+                    && !ids.contains(id)
+                {
+                    ids.push(id.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut ids = Vec::new();
+    function.tasks.iter().flatten().for_each(|t| push(t, &mut ids));
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    #[test]
+    fn test_estimate_tokens() {
+        let messages = vec![
+            objectiveai::chat::completions::request::Message::User(
+                objectiveai::chat::completions::request::UserMessage {
+                    content:
+                        objectiveai::chat::completions::request::RichContent::Text(
+                            "a".repeat(40),
+                        ),
+                    name: None,
+                },
+            ),
+        ];
+        // role "user" (4 chars) + 40 chars = 11 tokens, plus the overhead
+        assert_eq!(estimate_tokens(&messages), 11 + TOKENS_PER_ITEM);
+
+        // inline media is not counted
+        let responses = vec![
+            objectiveai::chat::completions::request::RichContent::Text(
+                "data:image/png;base64,AAAA".to_string(),
+            ),
+        ];
+        assert_eq!(estimate_tokens(&responses), TOKENS_PER_ITEM);
+    }
+
+    #[test]
+    fn test_cost() {
+        let estimator = Estimator::new(Some(
+            objectiveai::chat::completions::request::ProviderMaxPrice {
+                prompt: Some(dec!(0.001)),
+                completion: Some(dec!(0.002)),
+                image: None,
+                audio: None,
+                request: Some(dec!(0.01)),
+            },
+        ));
+        assert_eq!(estimator.cost(100, None), Some(dec!(0.11)));
+        assert_eq!(estimator.cost(100, Some(50)), Some(dec!(0.21)));
+        assert_eq!(Estimator::new(None).cost(100, Some(50)), None);
+    }
+}
@@ -2,14 +2,29 @@
 //!
 //! Executes Functions by flattening them into task profiles and running
 //! the tasks (Vector Completions or nested Functions) in parallel. Handles
-//! streaming output, retry tokens, and reasoning summaries.
+//! streaming output, retry tokens, reasoning summaries, and the cache of
+//! whole executions. Dry runs estimate the upstream calls of an execution
+//! without making them. Batches execute one Function and Profile against many
+//! inputs. Tournament strategies rank the items of vector Functions by
+//! executing them in pools. Attributions break the output of each Function
+//! down into the contributions of its tasks and Ensemble LLMs.
 
+mod attribution;
+mod batch;
+mod cache;
 mod client;
 mod error;
+mod estimate;
+mod tournament;
 pub mod usage_handler;
 
 #[cfg(test)]
-mod client_tests;
+pub(crate) mod client_tests;
 
+pub use attribution::*;
+pub use batch::*;
+pub use cache::*;
 pub use client::*;
 pub use error::*;
+pub use estimate::*;
+pub use tournament::*;
@@ -0,0 +1,832 @@
+//! Tournament strategies for ranking the items of vector Functions.
+//!
+//! A tournament splits the input of a vector Function into items with
+//! `input_split`, and runs the Function over pools of items merged with
+//! `input_merge`, round after round. Each strategy decides which items meet
+//! in the next round from the results of the previous ones, and ranks the
+//! items once no rounds remain.
+//!
+//! When only the best `top_k` items matter, the Swiss system stops pooling
+//! items that can no longer enter them, and every strategy reports them with
+//! a certainty estimate.
+
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+
+/// Pairs items into pools, round by round, and ranks them from the results.
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    kind: Kind,
+    /// How many of the best items matter, if not all of them.
+    top_k: Option<usize>,
+    /// Number of items.
+    len: usize,
+    /// Number of rounds started.
+    round: usize,
+    /// Pools of the round in progress, as item indices.
+    pools: Vec<Vec<usize>>,
+    /// Scores of the round in progress, by pool.
+    pool_scores: HashMap<usize, Vec<Decimal>>,
+    /// Raw scores of every finished round, by item.
+    round_scores: Vec<Vec<Decimal>>,
+    /// Scores relative to the pool size, by item, of every match played.
+    strengths: Vec<Vec<Decimal>>,
+    /// Every match played, as (item, score) pairs.
+    matches: Vec<Vec<(usize, Decimal)>>,
+    /// Losses taken, by item, in elimination strategies.
+    losses: Vec<usize>,
+    /// Round of elimination and score in the eliminating match, by item, or
+    /// for the Swiss system, round and cumulative score when the item could
+    /// no longer enter the top k.
+    eliminated: Vec<Option<(usize, Decimal)>>,
+    /// Items still competing, in seed order.
+    active: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    SwissSystem { pool: usize, rounds: usize },
+    SingleElimination { pool: usize },
+    DoubleElimination { pool: usize },
+    RoundRobin { pool: usize },
+    TopK { k: usize, pool: usize, rounds: usize },
+}
+
+impl Tournament {
+    /// Creates a tournament over `len` items, or `None` if the strategy is
+    /// not a tournament.
+    ///
+    /// With `top_k`, only the best `top_k` items matter. The `top_k` strategy
+    /// defaults it to its own `k`.
+    pub fn new(
+        strategy: &objectiveai::functions::executions::request::Strategy,
+        len: usize,
+        top_k: Option<usize>,
+    ) -> Result<Option<Self>, super::Error> {
+        use objectiveai::functions::executions::request::Strategy;
+        if top_k == Some(0) {
+            return Err(super::Error::InvalidStrategy(
+                "'top_k' must be > 0.".to_string(),
+            ));
+        }
+        let kind = match strategy {
+            Strategy::Default => return Ok(None),
+            Strategy::SwissSystem { pool, rounds } => {
+                let pool = pool.unwrap_or(10);
+                let rounds = rounds.unwrap_or(3);
+                if pool <= 1 || rounds == 0 {
+                    return Err(super::Error::InvalidStrategy(
+                        "For 'swiss_system' strategy, 'pool' must be > 1 and 'rounds' must be > 0."
+                            .to_string(),
+                    ));
+                }
+                Kind::SwissSystem { pool, rounds }
+            }
+            Strategy::SingleElimination { pool } => {
+                let pool = pool.unwrap_or(2);
+                if pool <= 1 {
+                    return Err(super::Error::InvalidStrategy(
+                        "For 'single_elimination' strategy, 'pool' must be > 1."
+                            .to_string(),
+                    ));
+                }
+                Kind::SingleElimination { pool }
+            }
+            Strategy::DoubleElimination { pool } => {
+                let pool = pool.unwrap_or(2);
+                if pool <= 1 {
+                    return Err(super::Error::InvalidStrategy(
+                        "For 'double_elimination' strategy, 'pool' must be > 1."
+                            .to_string(),
+                    ));
+                }
+                Kind::DoubleElimination { pool }
+            }
+            Strategy::RoundRobin { pool } => {
+                // every pair of a pool meets, so bound the default pool
+                // rather than comparing every pair of items
+                let pool = pool.unwrap_or(10);
+                if pool <= 1 {
+                    return Err(super::Error::InvalidStrategy(
+                        "For 'round_robin' strategy, 'pool' must be > 1."
+                            .to_string(),
+                    ));
+                }
+                Kind::RoundRobin { pool }
+            }
+            Strategy::TopK { k, pool, rounds } => {
+                let pool = pool.unwrap_or(10);
+                let rounds = rounds.unwrap_or(3);
+                if *k == 0 || pool <= 1 || rounds == 0 {
+                    return Err(super::Error::InvalidStrategy(
+                        "For 'top_k' strategy, 'k' must be > 0, 'pool' must be > 1 and 'rounds' must be > 0."
+                            .to_string(),
+                    ));
+                }
+                Kind::TopK {
+                    k: *k,
+                    pool,
+                    rounds,
+                }
+            }
+        };
+        let top_k = match kind {
+            Kind::TopK { k, .. } => top_k.or(Some(k)),
+            _ => top_k,
+        };
+        Ok(Some(Self {
+            kind,
+            top_k,
+            len,
+            round: 0,
+            pools: Vec::new(),
+            pool_scores: HashMap::new(),
+            round_scores: Vec::new(),
+            strengths: vec![Vec::new(); len],
+            matches: Vec::new(),
+            losses: vec![0; len],
+            eliminated: vec![None; len],
+            active: (0..len).collect(),
+        }))
+    }
+
+    /// Starts the next round and returns its pools, as item indices, or
+    /// `None` if the tournament is over.
+    pub fn next_round(&mut self) -> Option<&[Vec<usize>]> {
+        let pools = match self.kind {
+            Kind::SwissSystem { pool, rounds } => {
+                if self.round >= rounds {
+                    return None;
+                }
+                // re-pool by cumulative score after the first round
+                let mut order: Vec<usize> = (0..self.len).collect();
+                if self.round > 0 {
+                    let cumulative = self.cumulative_scores();
+                    order.sort_by(|&a, &b| {
+                        cumulative[b].cmp(&cumulative[a]).then_with(|| a.cmp(&b))
+                    });
+                    if let Some(k) = self.top_k {
+                        self.drop_out_of_contention(k, rounds, &cumulative);
+                        let eliminated = &self.eliminated;
+                        order.retain(|&item| eliminated[item].is_none());
+                        if order.len() <= 1 {
+                            return None;
+                        }
+                    }
+                }
+                chunk(&order, pool)
+            }
+            Kind::SingleElimination { pool } => {
+                if self.active.len() <= 1 {
+                    return None;
+                }
+                chunk(&self.active, pool)
+            }
+            Kind::DoubleElimination { pool } => {
+                if self.active.len() <= 1 {
+                    return None;
+                }
+                // the winners' and losers' brackets play apart, seeded by
+                // strength, until a single item remains in each
+                let mean = self.mean_strengths();
+                let mut brackets: [Vec<usize>; 2] = [Vec::new(), Vec::new()];
+                for &item in &self.active {
+                    brackets[self.losses[item]].push(item);
+                }
+                for bracket in &mut brackets {
+                    bracket.sort_by(|&a, &b| {
+                        mean[b].cmp(&mean[a]).then_with(|| a.cmp(&b))
+                    });
+                }
+                if brackets.iter().all(|bracket| bracket.len() <= 1) {
+                    // grand final
+                    vec![self.active.clone()]
+                } else {
+                    brackets
+                        .iter()
+                        .filter(|bracket| bracket.len() > 1)
+                        .flat_map(|bracket| chunk(bracket, pool))
+                        .collect()
+                }
+            }
+            Kind::RoundRobin { pool } => {
+                if self.round > 0 {
+                    return None;
+                }
+                // every pair within each pool meets once
+                let order: Vec<usize> = (0..self.len).collect();
+                chunk(&order, pool)
+                    .into_iter()
+                    .flat_map(|pool| {
+                        let mut pairs = Vec::new();
+                        for (i, &a) in pool.iter().enumerate() {
+                            for &b in &pool[i + 1..] {
+                                pairs.push(vec![a, b]);
+                            }
+                        }
+                        pairs
+                    })
+                    .collect()
+            }
+            Kind::TopK { k, pool, rounds } => {
+                if self.round >= rounds {
+                    return None;
+                }
+                if self.round == 0 {
+                    let order: Vec<usize> = (0..self.len).collect();
+                    chunk(&order, pool)
+                } else {
+                    // compare the items nearest the k-th place again, spread
+                    // across pools so that each one straddles the boundary
+                    let order = self.order_by_strength();
+                    let window = &order[k.saturating_sub(pool).min(self.len)
+                        ..(k + pool).min(self.len)];
+                    if window.len() <= 1 {
+                        return None;
+                    }
+                    let count = (window.len() / pool).max(1);
+                    let mut pools = vec![Vec::new(); count];
+                    for (i, &item) in window.iter().enumerate() {
+                        pools[i % count].push(item);
+                    }
+                    pools
+                }
+            }
+        };
+        if pools.is_empty() {
+            return None;
+        }
+        self.round += 1;
+        self.pools = pools;
+        self.pool_scores.clear();
+        Some(&self.pools)
+    }
+
+    /// Records the scores of a pool of the round in progress, in the order
+    /// of its items.
+    pub fn record(&mut self, pool_idx: usize, scores: Vec<Decimal>) {
+        if self
+            .pools
+            .get(pool_idx)
+            .is_some_and(|pool| pool.len() == scores.len())
+        {
+            self.pool_scores.insert(pool_idx, scores);
+        }
+    }
+
+    /// Ends the round in progress.
+    ///
+    /// A pool without scores counts as a tie, which its first item wins.
+    pub fn end_round(&mut self) {
+        let mut round_scores = vec![Decimal::ZERO; self.len];
+        let mut winners = Vec::new();
+        for (pool_idx, pool) in self.pools.iter().enumerate() {
+            let scores = self.pool_scores.get(&pool_idx);
+            if let Some(scores) = scores {
+                let size = Decimal::from(pool.len() as u64);
+                for (&item, &score) in pool.iter().zip(scores) {
+                    round_scores[item] = score;
+                    self.strengths[item].push(score * size);
+                }
+                self.matches.push(
+                    pool.iter().copied().zip(scores.iter().copied()).collect(),
+                );
+            }
+            // the best item wins the pool, the earliest on ties
+            let mut winner = 0;
+            if let Some(scores) = scores {
+                for (i, &score) in scores.iter().enumerate() {
+                    if score > scores[winner] {
+                        winner = i;
+                    }
+                }
+            }
+            winners.push(pool[winner]);
+            let lives = match self.kind {
+                Kind::SingleElimination { .. } => 1,
+                Kind::DoubleElimination { .. } => 2,
+                _ => continue,
+            };
+            for (i, &item) in pool.iter().enumerate() {
+                if i == winner {
+                    continue;
+                }
+                self.losses[item] += 1;
+                if self.losses[item] >= lives {
+                    let score = scores.map_or(Decimal::ZERO, |s| s[i]);
+                    self.eliminated[item] = Some((self.round, score));
+                }
+            }
+        }
+        match self.kind {
+            Kind::SingleElimination { .. } => self.active = winners,
+            Kind::DoubleElimination { .. } => {
+                let eliminated = &self.eliminated;
+                self.active.retain(|&item| eliminated[item].is_none());
+            }
+            _ => {}
+        }
+        self.round_scores.push(round_scores);
+        self.pools.clear();
+        self.pool_scores.clear();
+    }
+
+    /// Ends the tournament, returning the output and the full ranking.
+    pub fn finish(
+        self,
+    ) -> (
+        Vec<Decimal>,
+        objectiveai::functions::executions::response::Ranking,
+    ) {
+        let output = match self.kind {
+            Kind::SwissSystem { .. } => {
+                // average of the raw scores across rounds
+                let mut output = vec![Decimal::ZERO; self.len];
+                if !self.round_scores.is_empty() {
+                    let rounds = Decimal::from(self.round_scores.len() as u64);
+                    for (item, score) in output.iter_mut().enumerate() {
+                        *score = self
+                            .round_scores
+                            .iter()
+                            .map(|round| round[item])
+                            .sum::<Decimal>()
+                            / rounds;
+                    }
+                }
+                normalize(&mut output);
+                output
+            }
+            _ => {
+                let mut output = self.mean_strengths();
+                normalize(&mut output);
+                if output.iter().all(|score| score.is_zero()) && self.len > 0 {
+                    let share = Decimal::ONE / Decimal::from(self.len as u64);
+                    output.iter_mut().for_each(|score| *score = share);
+                }
+                output
+            }
+        };
+        let order = match self.kind {
+            Kind::SingleElimination { .. } | Kind::DoubleElimination { .. } => {
+                // survivors first, then by how late and how well each item
+                // was eliminated
+                let mut order: Vec<usize> = (0..self.len).collect();
+                order.sort_by(|&a, &b| {
+                    match (self.eliminated[a], self.eliminated[b]) {
+                        (None, None) => a.cmp(&b),
+                        (None, Some(_)) => std::cmp::Ordering::Less,
+                        (Some(_), None) => std::cmp::Ordering::Greater,
+                        (Some((ra, sa)), Some((rb, sb))) => rb
+                            .cmp(&ra)
+                            .then_with(|| sb.cmp(&sa))
+                            .then_with(|| a.cmp(&b)),
+                    }
+                });
+                order
+            }
+            _ => {
+                let mut order: Vec<usize> = (0..self.len).collect();
+                order.sort_by(|&a, &b| {
+                    output[b].cmp(&output[a]).then_with(|| a.cmp(&b))
+                });
+                order
+            }
+        };
+        let backed = self.backed(&order);
+        let confidence = if self.len <= 1 {
+            vec![Decimal::ONE; self.len]
+        } else {
+            let others = Decimal::from(self.len as u64 - 1);
+            backed
+                .iter()
+                .map(|row| {
+                    Decimal::from(row.iter().filter(|&&b| b).count() as u64)
+                        / others
+                })
+                .collect()
+        };
+        let top_k = self.top_k.map(|k| {
+            // the share of the items outside the top k backed below each
+            // item
+            let (inside, outside) = order.split_at(k.min(self.len));
+            let certainty = inside
+                .iter()
+                .map(|&item| {
+                    if outside.is_empty() {
+                        return Decimal::ONE;
+                    }
+                    let count = outside
+                        .iter()
+                        .filter(|&&other| backed[item][other])
+                        .count();
+                    Decimal::from(count as u64)
+                        / Decimal::from(outside.len() as u64)
+                })
+                .collect();
+            objectiveai::functions::executions::response::TopK {
+                order: inside.to_vec(),
+                certainty,
+            }
+        });
+        (
+            output,
+            objectiveai::functions::executions::response::Ranking {
+                order,
+                confidence,
+                top_k,
+            },
+        )
+    }
+
+    /// Drops the items that can no longer enter the top `k` of the Swiss
+    /// system, even by scoring 1, the most a pool can give, in every
+    /// remaining round.
+    fn drop_out_of_contention(
+        &mut self,
+        k: usize,
+        rounds: usize,
+        cumulative: &[Decimal],
+    ) {
+        let mut contenders: Vec<usize> = (0..self.len)
+            .filter(|&item| self.eliminated[item].is_none())
+            .collect();
+        if contenders.len() <= k {
+            return;
+        }
+        contenders.sort_by(|&a, &b| {
+            cumulative[b].cmp(&cumulative[a]).then_with(|| a.cmp(&b))
+        });
+        let threshold = cumulative[contenders[k - 1]];
+        let reachable = Decimal::from((rounds - self.round) as u64);
+        for &item in &contenders[k..] {
+            if cumulative[item] + reachable < threshold {
+                self.eliminated[item] = Some((self.round, cumulative[item]));
+            }
+        }
+    }
+
+    /// Sum of the raw scores of every finished round, by item.
+    fn cumulative_scores(&self) -> Vec<Decimal> {
+        let mut cumulative = vec![Decimal::ZERO; self.len];
+        for round in &self.round_scores {
+            for (total, score) in cumulative.iter_mut().zip(round) {
+                *total += *score;
+            }
+        }
+        cumulative
+    }
+
+    /// Mean score relative to the pool size, by item.
+    fn mean_strengths(&self) -> Vec<Decimal> {
+        self.strengths
+            .iter()
+            .map(|strengths| {
+                if strengths.is_empty() {
+                    Decimal::ZERO
+                } else {
+                    strengths.iter().sum::<Decimal>()
+                        / Decimal::from(strengths.len() as u64)
+                }
+            })
+            .collect()
+    }
+
+    /// Items ordered by mean strength, best first.
+    fn order_by_strength(&self) -> Vec<usize> {
+        let mean = self.mean_strengths();
+        let mut order: Vec<usize> = (0..self.len).collect();
+        order.sort_by(|&a, &b| mean[b].cmp(&mean[a]).then_with(|| a.cmp(&b)));
+        order
+    }
+
+    /// For each pair of items, whether their relative order is backed by
+    /// comparisons agreeing with the ranking, directly or through a chain of
+    /// them, and not contradicted by their head-to-head record.
+    fn backed(&self, order: &[usize]) -> Vec<Vec<bool>> {
+        let mut place = vec![0; self.len];
+        for (i, &item) in order.iter().enumerate() {
+            place[item] = i;
+        }
+
+        // net head-to-head wins of the first item of each pair
+        let mut net: HashMap<(usize, usize), i64> = HashMap::new();
+        for played in &self.matches {
+            for (i, &(a, sa)) in played.iter().enumerate() {
+                for &(b, sb) in &played[i + 1..] {
+                    let (key, sign) = if a < b { ((a, b), 1) } else { ((b, a), -1) };
+                    match sa.cmp(&sb) {
+                        std::cmp::Ordering::Greater => {
+                            *net.entry(key).or_default() += sign
+                        }
+                        std::cmp::Ordering::Less => {
+                            *net.entry(key).or_default() -= sign
+                        }
+                        std::cmp::Ordering::Equal => {}
+                    }
+                }
+            }
+        }
+
+        // comparisons agreeing with the ranking point from better to worse
+        let mut beats: Vec<Vec<usize>> = vec![Vec::new(); self.len];
+        let mut contradicted = vec![vec![false; self.len]; self.len];
+        for (&(a, b), &wins) in &net {
+            let (winner, loser) = match wins.cmp(&0) {
+                std::cmp::Ordering::Greater => (a, b),
+                std::cmp::Ordering::Less => (b, a),
+                std::cmp::Ordering::Equal => continue,
+            };
+            if place[winner] < place[loser] {
+                beats[winner].push(loser);
+            } else {
+                contradicted[a][b] = true;
+                contradicted[b][a] = true;
+            }
+        }
+
+        // every item reachable from each item through agreeing comparisons
+        let reachable: Vec<Vec<bool>> = (0..self.len)
+            .map(|start| {
+                let mut reached = vec![false; self.len];
+                let mut queue = VecDeque::from([start]);
+                while let Some(item) = queue.pop_front() {
+                    for &next in &beats[item] {
+                        if !reached[next] {
+                            reached[next] = true;
+                            queue.push_back(next);
+                        }
+                    }
+                }
+                reached
+            })
+            .collect();
+
+        (0..self.len)
+            .map(|item| {
+                (0..self.len)
+                    .map(|other| {
+                        other != item
+                            && (reachable[item][other] || reachable[other][item])
+                            && !contradicted[item][other]
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Splits items into consecutive pools of `pool` items, or `pool + 1` when
+/// one item would otherwise be left alone at the end.
+fn chunk(items: &[usize], pool: usize) -> Vec<Vec<usize>> {
+    let size = if items.len() % pool == 1 {
+        pool + 1
+    } else {
+        pool
+    };
+    items.chunks(size).map(<[usize]>::to_vec).collect()
+}
+
+/// Scales scores to sum to 1, unless they sum to 0.
+fn normalize(scores: &mut [Decimal]) {
+    let total: Decimal = scores.iter().copied().sum();
+    if total > Decimal::ZERO {
+        for score in scores {
+            *score /= total;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use objectiveai::functions::executions::request::Strategy;
+
+    /// Plays out a tournament where every pool scores its items in
+    /// proportion to their hidden values, returning the rounds played.
+    fn play(tournament: &mut Tournament, values: &[u64]) -> Vec<Vec<Vec<usize>>> {
+        let mut rounds = Vec::new();
+        while let Some(pools) = tournament.next_round() {
+            let pools = pools.to_vec();
+            for (pool_idx, pool) in pools.iter().enumerate() {
+                let total: u64 = pool.iter().map(|&i| values[i]).sum();
+                let scores = pool
+                    .iter()
+                    .map(|&i| Decimal::from(values[i]) / Decimal::from(total))
+                    .collect();
+                tournament.record(pool_idx, scores);
+            }
+            tournament.end_round();
+            rounds.push(pools);
+        }
+        rounds
+    }
+
+    #[test]
+    fn test_swiss_system_repools_by_score() {
+        let mut tournament = Tournament::new(
+            &Strategy::SwissSystem {
+                pool: Some(2),
+                rounds: Some(2),
+            },
+            5,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+        let rounds = play(&mut tournament, &[1, 2, 3, 4, 5]);
+        assert_eq!(rounds.len(), 2);
+        assert_eq!(rounds[0], vec![vec![0, 1, 2], vec![3, 4]]);
+        assert_eq!(rounds[1], vec![vec![4, 2, 3], vec![1, 0]]);
+        let (output, ranking) = tournament.finish();
+        assert_eq!(output.iter().copied().sum::<Decimal>(), Decimal::ONE);
+        // the ranking follows the averaged scores
+        for pair in ranking.order.windows(2) {
+            assert!(output[pair[0]] >= output[pair[1]]);
+        }
+    }
+
+    #[test]
+    fn test_single_elimination() {
+        let mut tournament = Tournament::new(
+            &Strategy::SingleElimination { pool: None },
+            4,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+        let rounds = play(&mut tournament, &[3, 1, 4, 2]);
+        assert_eq!(rounds, vec![vec![vec![0, 1], vec![2, 3]], vec![vec![0, 2]]]);
+        let (_, ranking) = tournament.finish();
+        assert_eq!(ranking.order, vec![2, 0, 3, 1]);
+        // the champion beat every item through a chain of wins, while the
+        // first-round losers were never compared with each other
+        assert_eq!(ranking.confidence[2], Decimal::ONE);
+        assert!(ranking.confidence[1] < Decimal::ONE);
+    }
+
+    #[test]
+    fn test_double_elimination_needs_two_losses() {
+        let mut tournament = Tournament::new(
+            &Strategy::DoubleElimination { pool: None },
+            4,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+        play(&mut tournament, &[3, 1, 4, 2]);
+        assert!(tournament.losses.iter().filter(|&&l| l >= 2).count() == 3);
+        let (_, ranking) = tournament.finish();
+        assert_eq!(ranking.order[0], 2);
+        assert_eq!(ranking.order.len(), 4);
+    }
+
+    #[test]
+    fn test_round_robin_compares_every_pair() {
+        let mut tournament =
+            Tournament::new(&Strategy::RoundRobin { pool: None }, 4, None)
+                .unwrap()
+                .unwrap();
+        let rounds = play(&mut tournament, &[3, 1, 4, 2]);
+        assert_eq!(rounds.len(), 1);
+        assert_eq!(rounds[0].len(), 6);
+        let (output, ranking) = tournament.finish();
+        assert_eq!(ranking.order, vec![2, 0, 3, 1]);
+        assert!(ranking.confidence.iter().all(|&c| c == Decimal::ONE));
+        assert!(output[2] > output[0] && output[0] > output[3]);
+    }
+
+    #[test]
+    fn test_round_robin_bounds_default_pool() {
+        let mut tournament =
+            Tournament::new(&Strategy::RoundRobin { pool: None }, 100, None)
+                .unwrap()
+                .unwrap();
+        let values: Vec<u64> = (1..=100).collect();
+        let rounds = play(&mut tournament, &values);
+        // ten pools of ten, rather than every pair of the hundred items
+        assert_eq!(rounds.len(), 1);
+        assert_eq!(rounds[0].len(), 10 * 45);
+    }
+
+    #[test]
+    fn test_top_k_focuses_on_boundary() {
+        let mut tournament = Tournament::new(
+            &Strategy::TopK {
+                k: 3,
+                pool: Some(2),
+                rounds: Some(2),
+            },
+            8,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+        let values = [8, 7, 6, 5, 4, 3, 2, 1];
+        let rounds = play(&mut tournament, &values);
+        assert_eq!(rounds.len(), 2);
+        assert_eq!(rounds[0].len(), 4);
+        // the second round only compares the items ranked around third place
+        let compared: Vec<usize> = rounds[1].iter().flatten().copied().collect();
+        assert_eq!(compared.len(), 4);
+        let (_, ranking) = tournament.finish();
+        assert_eq!(ranking.order.len(), 8);
+    }
+
+    #[test]
+    fn test_swiss_system_top_k_drops_out_of_contention() {
+        let mut tournament = Tournament::new(
+            &Strategy::SwissSystem {
+                pool: Some(4),
+                rounds: Some(4),
+            },
+            8,
+            Some(1),
+        )
+        .unwrap()
+        .unwrap();
+        let rounds = play(&mut tournament, &[1, 1, 1, 1, 1, 1, 1, 1000]);
+        // once no item can catch up with the leader, refinement stops
+        assert_eq!(rounds[0].iter().flatten().count(), 8);
+        assert!(rounds.len() < 4);
+        let (_, ranking) = tournament.finish();
+        let top_k = ranking.top_k.unwrap();
+        assert_eq!(top_k.order, vec![7]);
+        assert_eq!(top_k.certainty, vec![Decimal::ONE]);
+    }
+
+    #[test]
+    fn test_swiss_system_top_k_keeps_trailing_contenders() {
+        let mut tournament = Tournament::new(
+            &Strategy::SwissSystem {
+                pool: Some(4),
+                rounds: Some(3),
+            },
+            4,
+            Some(1),
+        )
+        .unwrap()
+        .unwrap();
+        // item 3 trails by more than any score seen in the first two
+        // rounds, but can still take the final round by a wide margin
+        let values: [&[u64]; 3] = [&[3, 3, 3, 1], &[3, 3, 3, 1], &[1, 1, 1, 97]];
+        let mut rounds = Vec::new();
+        while let Some(pools) = tournament.next_round() {
+            let pools = pools.to_vec();
+            let values = values[rounds.len()];
+            for (pool_idx, pool) in pools.iter().enumerate() {
+                let total: u64 = pool.iter().map(|&i| values[i]).sum();
+                let scores = pool
+                    .iter()
+                    .map(|&i| Decimal::from(values[i]) / Decimal::from(total))
+                    .collect();
+                tournament.record(pool_idx, scores);
+            }
+            tournament.end_round();
+            rounds.push(pools);
+        }
+        assert_eq!(rounds.len(), 3);
+        assert!(rounds[2].iter().flatten().any(|&item| item == 3));
+        let (_, ranking) = tournament.finish();
+        assert_eq!(ranking.order[0], 3);
+        assert_eq!(ranking.top_k.unwrap().order, vec![3]);
+    }
+
+    #[test]
+    fn test_top_k_strategy_reports_its_k() {
+        let mut tournament = Tournament::new(
+            &Strategy::TopK {
+                k: 2,
+                pool: Some(2),
+                rounds: Some(1),
+            },
+            4,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+        play(&mut tournament, &[1, 2, 3, 4]);
+        let (_, ranking) = tournament.finish();
+        assert_eq!(ranking.top_k.unwrap().order.len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_strategies() {
+        assert!(Tournament::new(&Strategy::Default, 4, None).unwrap().is_none());
+        assert!(matches!(
+            Tournament::new(&Strategy::SingleElimination { pool: Some(1) }, 4, None),
+            Err(super::super::Error::InvalidStrategy(_))
+        ));
+        assert!(matches!(
+            Tournament::new(
+                &Strategy::TopK {
+                    k: 0,
+                    pool: None,
+                    rounds: None,
+                },
+                4,
+                None,
+            ),
+            Err(super::super::Error::InvalidStrategy(_))
+        ));
+    }
+}
@@ -6,14 +6,15 @@
 //! into flattened executable tasks.
 
 use crate::ctx;
-use futures::FutureExt;
+use futures::{FutureExt, TryStreamExt};
 use std::{pin::Pin, sync::Arc, task::Poll};
 
 /// A flattened task ready for execution.
 ///
 /// Combines Function structure with Profile weights into an executable node.
 /// Can be a function (with nested tasks), a mapped array of functions, a vector
-/// completion, or a mapped array of vector completions.
+/// completion, a mapped array of vector completions, a completed chat
+/// completion, or a task that depends on the outputs of other tasks.
 #[derive(Debug, Clone)]
 pub enum FlatTaskProfile {
     /// A single function task with nested tasks.
@@ -24,6 +25,10 @@ pub enum FlatTaskProfile {
     VectorCompletion(VectorCompletionFlatTaskProfile),
     /// Multiple vector completion tasks from a mapped expression.
     MapVectorCompletion(MapVectorCompletionFlatTaskProfile),
+    /// A chat completion task that already ran during flattening.
+    ChatCompletion(ChatCompletionFlatTaskProfile),
+    /// A task that is flattened once its dependencies have run.
+    Dependent(DependentFlatTaskProfile),
 }
 
 impl FlatTaskProfile {
@@ -50,6 +55,7 @@ impl FlatTaskProfile {
             MapVectorCompletion(
                 std::slice::Iter<'a, VectorCompletionFlatTaskProfile>,
             ),
+            Empty,
         }
         impl<'a> Iterator for Iter<'a> {
             type Item = &'a VectorCompletionFlatTaskProfile;
@@ -59,6 +65,7 @@ impl FlatTaskProfile {
                     Iter::MapFunction(iter) => iter.next(),
                     Iter::VectorCompletion(opt) => opt.take(),
                     Iter::MapVectorCompletion(iter) => iter.next(),
+                    Iter::Empty => None,
                 }
             }
         }
@@ -86,6 +93,8 @@ impl FlatTaskProfile {
             FlatTaskProfile::MapVectorCompletion(vectors) => {
                 Iter::MapVectorCompletion(vectors.vector_completions.iter())
             }
+            FlatTaskProfile::ChatCompletion(_)
+            | FlatTaskProfile::Dependent(_) => Iter::Empty,
         }
     }
     /// Returns the total number of leaf tasks (vector completions).
@@ -95,6 +104,8 @@ impl FlatTaskProfile {
             FlatTaskProfile::MapFunction(functions) => functions.len(),
             FlatTaskProfile::VectorCompletion(vector) => vector.len(),
             FlatTaskProfile::MapVectorCompletion(vectors) => vectors.len(),
+            FlatTaskProfile::ChatCompletion(chat) => chat.len(),
+            FlatTaskProfile::Dependent(dependent) => dependent.len(),
         }
     }
 
@@ -111,6 +122,8 @@ impl FlatTaskProfile {
             FlatTaskProfile::MapVectorCompletion(vectors) => {
                 vectors.task_index_len()
             }
+            FlatTaskProfile::ChatCompletion(chat) => chat.task_index_len(),
+            FlatTaskProfile::Dependent(dependent) => dependent.task_index_len(),
         }
     }
 }
@@ -169,7 +182,7 @@ pub struct FunctionFlatTaskProfile {
     pub tasks: Vec<Option<FlatTaskProfile>>,
     /// The weights for each task from the Profile (for weighted averaging).
     pub profile: Vec<rust_decimal::Decimal>,
-    /// The Function type (scalar or vector).
+    /// The Function type (scalar, vector, multi-label or categorical).
     pub r#type: FunctionType,
     /// Expression to transform the task result from the parent task definition.
     /// Receives: `input` (function input), `output` (the raw FunctionOutput).
@@ -179,6 +192,14 @@ pub struct FunctionFlatTaskProfile {
     ///
     /// Only meaningful when `task_output` is `Some(_)`.
     pub invert_output: bool,
+    /// The Function's own output expression, combining the task outputs.
+    /// Receives: `input`, `tasks` (the output and profile weight of each task).
+    /// None to use the weighted average of the task outputs.
+    pub output: Option<objectiveai::functions::expression::Expression>,
+    /// How the Function's reasoning summary is prompted.
+    /// None to use the default prompt.
+    pub reasoning_summary:
+        Option<objectiveai::functions::ReasoningSummaryPrompt>,
 }
 
 impl FunctionFlatTaskProfile {
@@ -238,6 +259,16 @@ pub enum FunctionType {
             >,
         >,
     },
+    /// Produces an independent score in [0, 1] for each label.
+    MultiLabel {
+        /// The labels, in output order.
+        labels: Vec<String>,
+    },
+    /// Produces scores over the categories that sum to ~1.
+    Categorical {
+        /// The categories, in output order.
+        categories: Vec<String>,
+    },
 }
 
 /// Multiple vector completion tasks from a mapped expression.
@@ -303,6 +334,160 @@ impl VectorCompletionFlatTaskProfile {
     }
 }
 
+/// A chat completion task that already ran.
+///
+/// Chat completion tasks run while their Function is flattened, so that
+/// their outputs can be exposed to the expressions of the Function's other
+/// tasks as `tasks[i].output`. The completion is replayed as a single chunk
+/// when the Function is executed.
+///
+/// The output is kept in the retry token, and a retry with the same
+/// completion parameters reuses it instead of generating it again.
+///
+/// For dry runs, the completion is not created, and the task is flattened
+/// as if it failed without an error. The tasks that cannot be compiled
+/// without its output are deferred as if they were dependent.
+#[derive(Debug, Clone)]
+pub struct ChatCompletionFlatTaskProfile {
+    /// Path to this task in the Function tree (indices into tasks arrays).
+    pub path: Vec<u64>,
+    /// The parameters the completion was created with.
+    pub params:
+        Arc<objectiveai::chat::completions::request::ChatCompletionCreateParams>,
+    /// The aggregated chat completion.
+    pub completion:
+        objectiveai::chat::completions::response::streaming::ChatCompletionChunk,
+    /// The output exposed to the other tasks, None if the completion failed.
+    pub output: Option<serde_json::Value>,
+    /// The error, if the completion failed.
+    pub error: Option<objectiveai::error::ResponseError>,
+}
+
+impl ChatCompletionFlatTaskProfile {
+    pub fn len(&self) -> usize {
+        1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    pub fn task_index_len(&self) -> usize {
+        1
+    }
+
+    /// Returns the task's retry token entry, None if it has no output.
+    pub fn retry_token(&self) -> Option<String> {
+        self.output.as_ref()?;
+        let content = self
+            .completion
+            .choices
+            .first()
+            .and_then(|choice| choice.delta.content.clone())
+            .unwrap_or_default();
+        serde_json::to_string(&ChatCompletionRetry {
+            params: ChatCompletionRetry::key(&self.params),
+            id: self.completion.id.clone(),
+            content,
+        })
+        .ok()
+    }
+}
+
+/// The output of a chat completion task, kept in its retry token entry.
+///
+/// Chat completion tasks run before the task indices of the Function tree
+/// are known, so entries are matched by a hash of the completion's
+/// parameters rather than by their position in the token.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ChatCompletionRetry {
+    /// Hash of the parameters the completion was created with.
+    params: String,
+    /// ID of the completion.
+    id: String,
+    /// The generated content.
+    content: String,
+}
+
+impl ChatCompletionRetry {
+    fn key(
+        params: &objectiveai::chat::completions::request::ChatCompletionCreateParams,
+    ) -> String {
+        let mut hasher = twox_hash::XxHash3_128::with_seed(0);
+        hasher.write(serde_json::to_string(params).unwrap().as_bytes());
+        format!("{:032x}", hasher.finish_128())
+    }
+
+    /// Finds the entry for a completion among the entries of a retry token.
+    fn find(
+        retry_token: &str,
+        params: &objectiveai::chat::completions::request::ChatCompletionCreateParams,
+    ) -> Option<Self> {
+        let retry_token =
+            objectiveai::functions::executions::RetryToken::try_from_string(
+                retry_token,
+            )?;
+        let key = Self::key(params);
+        retry_token
+            .0
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| serde_json::from_str::<Self>(&entry).ok())
+            .find(|entry| entry.params == key)
+    }
+}
+
+/// A task that depends on the outputs of other tasks.
+///
+/// Dependent tasks are compiled and flattened during execution, once the
+/// tasks they depend on have run. They occupy a single task index, so the
+/// votes of a dependent Function task are not part of the retry token.
+#[derive(Debug, Clone)]
+pub struct DependentFlatTaskProfile {
+    /// Path to this task in the Function tree (indices into tasks arrays).
+    pub path: Vec<u64>,
+    /// Index of the task in the Function's tasks.
+    pub index: usize,
+    /// The Function the task belongs to.
+    pub function: Arc<objectiveai::functions::Function>,
+    /// The compiled input of the Function.
+    pub input: objectiveai::functions::expression::Input,
+    /// The Profile of the task.
+    pub profile: objectiveai::functions::TaskProfile,
+    /// Whether the Profile inverts the task's output.
+    pub invert_output: bool,
+}
+
+impl DependentFlatTaskProfile {
+    pub fn len(&self) -> usize {
+        1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    pub fn task_index_len(&self) -> usize {
+        1
+    }
+
+    /// Returns `true` if the task calls a Function.
+    pub fn is_function(&self) -> bool {
+        !matches!(
+            self.function.tasks()[self.index],
+            objectiveai::functions::TaskExpression::VectorCompletion(_)
+        )
+    }
+
+    /// Returns `true` if the task calls a vector Function.
+    pub fn is_vector_function(&self) -> bool {
+        matches!(
+            self.function.tasks()[self.index],
+            objectiveai::functions::TaskExpression::VectorFunction(_)
+        )
+    }
+}
+
 /// Parameter for specifying a function source.
 #[derive(Debug, Clone)]
 pub enum FunctionParam {
@@ -335,35 +520,84 @@ pub enum ProfileParam {
     },
 }
 
+/// What tasks are flattened with: the request's context, the fetchers for
+/// remote definitions, and the client and execution request that chat
+/// completion tasks are created with.
+pub struct FlatTaskProfileParams<CTXEXT, FFN, FPFL, FENS, FENSLLM, CUSG> {
+    /// Context of the request.
+    pub ctx: ctx::Context<CTXEXT>,
+    /// Fetcher for Function definitions.
+    pub function_fetcher: Arc<FFN>,
+    /// Fetcher for Profile definitions.
+    pub profile_fetcher: Arc<FPFL>,
+    /// Caching fetcher for Ensemble definitions.
+    pub ensemble_fetcher:
+        Arc<crate::ensemble::fetcher::CachingFetcher<CTXEXT, FENS>>,
+    /// Client for chat completion tasks.
+    pub chat_client:
+        Arc<crate::chat::completions::Client<CTXEXT, FENSLLM, CUSG>>,
+    /// The execution request whose options chat completion tasks use, if
+    /// any.
+    pub request:
+        Option<Arc<objectiveai::functions::executions::request::Request>>,
+}
+
+impl<CTXEXT, FFN, FPFL, FENS, FENSLLM, CUSG> Clone
+    for FlatTaskProfileParams<CTXEXT, FFN, FPFL, FENS, FENSLLM, CUSG>
+{
+    fn clone(&self) -> Self {
+        Self {
+            ctx: self.ctx.clone(),
+            function_fetcher: self.function_fetcher.clone(),
+            profile_fetcher: self.profile_fetcher.clone(),
+            ensemble_fetcher: self.ensemble_fetcher.clone(),
+            chat_client: self.chat_client.clone(),
+            request: self.request.clone(),
+        }
+    }
+}
+
 /// Recursively builds a flattened task from a Function and Profile.
 ///
 /// Fetches any remote Functions/Profiles/Ensembles, compiles task expressions
 /// with the input, and validates that the Profile structure matches the Function.
 /// The result is a flat tree of tasks ready for parallel execution.
+///
+/// Chat completion tasks are run here, before the Function's other tasks are
+/// compiled, using the options of `params.request` if given.
 pub async fn get_flat_task_profile<CTXEXT>(
-    ctx: ctx::Context<CTXEXT>,
+    params: FlatTaskProfileParams<
+        CTXEXT,
+        impl super::function_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+        impl super::profile_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+        impl crate::ensemble::fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+        impl crate::ensemble_llm::fetcher::Fetcher<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+        impl crate::chat::completions::usage_handler::UsageHandler<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    >,
     mut path: Vec<u64>,
     function: FunctionParam,
     profile: ProfileParam,
     input: objectiveai::functions::expression::Input,
     task_output: Option<objectiveai::functions::expression::Expression>,
     invert_output: bool,
-    function_fetcher: Arc<
-        impl super::function_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
-    >,
-    profile_fetcher: Arc<
-        impl super::profile_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
-    >,
-    ensemble_fetcher: Arc<
-        crate::ensemble::fetcher::CachingFetcher<
-            CTXEXT,
-            impl crate::ensemble::fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
-        >,
-    >,
 ) -> Result<super::FunctionFlatTaskProfile, super::executions::Error>
 where
-    CTXEXT: Send + Sync + 'static,
+    CTXEXT: ctx::ContextExt + Send + Sync + 'static,
 {
+    let FlatTaskProfileParams {
+        ctx,
+        function_fetcher,
+        profile_fetcher,
+        ensemble_fetcher,
+        chat_client,
+        request,
+    } = &params;
     // fetch function and profile if needed
     let (function_full_id, function, profile_full_id, profile): (
         Option<(String, String, String)>,
@@ -494,6 +728,33 @@ where
         }
     }
 
+    // at the root, check the whole task structure before anything runs,
+    // once per request for the items of a batch or tournament
+    if path.is_empty() {
+        let check = ctx
+            .compatibility_cache
+            .entry(serde_json::to_string(&(&function, &profile)).unwrap())
+            .or_default()
+            .clone();
+        let issues = check
+            .get_or_init(|| {
+                super::check_compatibility(
+                    ctx.clone(),
+                    &function,
+                    &profile,
+                    function_fetcher.clone(),
+                    profile_fetcher.clone(),
+                    ensemble_fetcher.clone(),
+                )
+            })
+            .await;
+        if !issues.is_empty() {
+            return Err(super::executions::Error::IncompatibleProfile(
+                issues.clone(),
+            ));
+        }
+    }
+
     // validate profile tasks length matches function tasks length
     let function_tasks_len = function.tasks().len();
     let profile_tasks_len = match &profile {
@@ -535,6 +796,12 @@ where
     // take description
     let description = function.description().map(str::to_owned);
 
+    // take output expression
+    let output = function.output().cloned();
+
+    // take reasoning summary prompt
+    let reasoning_summary = function.reasoning_summary().cloned();
+
     // take type, compile output_length if needed
     let r#type = match function {
         objectiveai::functions::Function::Remote(
@@ -553,6 +820,7 @@ where
                     input: &input,
                     output: None,
                     map: None,
+                    tasks: None,
                 },
             );
             FunctionType::Vector {
@@ -577,10 +845,153 @@ where
             input_split: input_split.clone(),
             input_merge: input_merge.clone(),
         },
+        objectiveai::functions::Function::Remote(
+            objectiveai::functions::RemoteFunction::MultiLabel {
+                ref labels,
+                ..
+            },
+        )
+        | objectiveai::functions::Function::Inline(
+            objectiveai::functions::InlineFunction::MultiLabel {
+                ref labels,
+                ..
+            },
+        ) => FunctionType::MultiLabel {
+            labels: labels.clone(),
+        },
+        objectiveai::functions::Function::Remote(
+            objectiveai::functions::RemoteFunction::Categorical {
+                ref categories,
+                ..
+            },
+        )
+        | objectiveai::functions::Function::Inline(
+            objectiveai::functions::InlineFunction::Categorical {
+                ref categories,
+                ..
+            },
+        ) => FunctionType::Categorical {
+            categories: categories.clone(),
+        },
+    };
+
+    // validate task dependencies
+    function
+        .validate_dependencies()
+        .map_err(super::executions::Error::InvalidTaskDependencies)?;
+
+    // validate labels or categories
+    function
+        .validate_labels()
+        .map_err(super::executions::Error::InvalidLabels)?;
+    let mut dependent = (0..function_tasks_len)
+        .map(|i| function.is_dependent_task(i))
+        .collect::<Vec<_>>();
+
+    // run chat completion tasks first, then compile the other tasks with
+    // their outputs, dependent tasks are compiled during execution
+    let mut chat_tasks: Vec<Option<ChatCompletionFlatTaskProfile>> =
+        vec![None; function_tasks_len];
+    let tasks = if function
+        .tasks()
+        .iter()
+        .any(objectiveai::functions::TaskExpression::is_chat_completion)
+    {
+        let task_profiles = match &profile {
+            objectiveai::functions::Profile::Remote(rp) => &rp.tasks,
+            objectiveai::functions::Profile::Inline(ip) => &ip.tasks,
+        };
+        let mut futs = Vec::new();
+        for (i, task) in function
+            .clone()
+            .compile_chat_completion_tasks(&input)?
+            .into_iter()
+            .enumerate()
+        {
+            // skipped or not a chat completion task
+            let Some(objectiveai::functions::CompiledTask::One(
+                objectiveai::functions::Task::ChatCompletion(task),
+            )) = task
+            else {
+                continue;
+            };
+            let (model, models) = match &task_profiles[i] {
+                objectiveai::functions::TaskProfile::ChatCompletion {
+                    model,
+                    models,
+                } => (model.clone(), models.clone()),
+                _ => return Err(super::executions::Error::InvalidProfile(
+                    "expected ChatCompletion profile for chat completion task".to_string()
+                )),
+            };
+            let mut task_path = path.clone();
+            task_path.push(i as u64);
+            futs.push(
+                get_chat_completion_flat_task_profile(
+                    ctx.clone(),
+                    task_path,
+                    task,
+                    model,
+                    models,
+                    chat_client.clone(),
+                    request.clone(),
+                )
+                .map(move |chat_task| (i, chat_task)),
+            );
+        }
+        let mut results = vec![None; function_tasks_len];
+        for (i, chat_task) in futures::future::join_all(futs).await {
+            results[i] = chat_task.output.clone().map(|output| {
+                objectiveai::functions::expression::TaskResult {
+                    output: objectiveai::functions::expression::TaskOutputOwned::ChatCompletion(
+                        output,
+                    ),
+                    weight: None,
+                }
+            });
+            chat_tasks[i] = Some(chat_task);
+        }
+        match function.clone().compile_tasks_with_results(&input, &results) {
+            Ok(tasks) => tasks,
+            // dry runs leave chat completion tasks without outputs, so the
+            // tasks that fail to compile without them are deferred as if
+            // they were dependent
+            Err(_)
+                if request
+                    .as_ref()
+                    .and_then(|request| request.base().dry_run)
+                    .unwrap_or(false) =>
+            {
+                let mut tasks = Vec::with_capacity(function_tasks_len);
+                for (i, task) in function.tasks().iter().enumerate() {
+                    if task.is_chat_completion() || dependent[i] {
+                        tasks.push(None);
+                        continue;
+                    }
+                    match function
+                        .clone()
+                        .compile_dependent_task(i, &input, &results)
+                    {
+                        Ok(task) => tasks.push(task),
+                        Err(_) => {
+                            dependent[i] = true;
+                            tasks.push(None);
+                        }
+                    }
+                }
+                tasks
+            }
+            Err(e) => return Err(e.into()),
+        }
+    } else {
+        function
+            .clone()
+            .compile_tasks_with_results(&input, &vec![None; function_tasks_len])?
     };
 
-    // compile function tasks
-    let tasks = function.compile_tasks(&input)?;
+    let dependent_function = dependent
+        .contains(&true)
+        .then(|| Arc::new(function.clone()));
 
     // initialize flat tasks / futs vector
     let mut flat_tasks_or_futs = Vec::with_capacity(tasks.len());
@@ -598,15 +1009,6 @@ where
         })
         .enumerate()
     {
-        // if skip, push None to flat tasks
-        let task = match task {
-            Some(task) => task,
-            None => {
-                flat_tasks_or_futs.push(TaskFut::SkipTask);
-                continue;
-            }
-        };
-
         // task path
         let task_path = {
             path.push(i as u64);
@@ -615,6 +1017,33 @@ where
             p
         };
 
+        // if skip, push None to flat tasks, chat completion tasks already ran
+        let task = match task {
+            Some(task) => task,
+            None if dependent[i] => {
+                flat_tasks_or_futs.push(TaskFut::Task(Some(
+                    FlatTaskProfile::Dependent(DependentFlatTaskProfile {
+                        path: task_path,
+                        index: i,
+                        function: dependent_function.clone().unwrap(),
+                        input: input.clone(),
+                        profile,
+                        invert_output: profile_invert_flags[i],
+                    }),
+                )));
+                continue;
+            }
+            None => {
+                flat_tasks_or_futs.push(match chat_tasks[i].take() {
+                    Some(chat_task) => TaskFut::Task(Some(
+                        FlatTaskProfile::ChatCompletion(chat_task),
+                    )),
+                    None => TaskFut::SkipTask,
+                });
+                continue;
+            }
+        };
+
         // switch by task type
         match task {
             objectiveai::functions::CompiledTask::One(
@@ -645,7 +1074,7 @@ where
                     invert_output || profile_invert_flags[i];
                 flat_tasks_or_futs.push(TaskFut::FunctionTaskFut(Box::pin(
                     get_flat_task_profile(
-                        ctx.clone(),
+                        params.clone(),
                         task_path,
                         FunctionParam::Remote {
                             owner,
@@ -677,12 +1106,12 @@ where
                         input,
                         Some(output),
                         effective_invert_output,
-                        function_fetcher.clone(),
-                        profile_fetcher.clone(),
-                        ensemble_fetcher.clone(),
                     )
                 )));
             }
+            objectiveai::functions::CompiledTask::One(
+                objectiveai::functions::Task::ChatCompletion(_),
+            ) => unreachable!("chat completion tasks are compiled separately"),
             objectiveai::functions::CompiledTask::One(
                 objectiveai::functions::Task::VectorCompletion(task),
             ) => {
@@ -723,6 +1152,9 @@ where
                     Some(objectiveai::functions::Task::VectorFunction(vf)) => {
                         (false, vf.output.clone(), vf.invert_output)
                     }
+                    Some(objectiveai::functions::Task::ChatCompletion(_)) => {
+                        unreachable!("chat completion tasks cannot be mapped")
+                    }
                     None => {
                         // Empty mapped task - need a placeholder expression
                         // This case shouldn't normally happen, but handle gracefully
@@ -775,7 +1207,7 @@ where
                         let mut task_path = task_path.clone();
                         task_path.push(j as u64);
                         futs.push(get_flat_task_profile(
-                            ctx.clone(),
+                            params.clone(),
                             task_path,
                             FunctionParam::Remote {
                                 owner: match &task {
@@ -840,9 +1272,6 @@ where
                             // Pass None for individual mapped functions - the task_output is stored on MapFunctionFlatTaskProfile
                             None,
                             false,
-                            function_fetcher.clone(),
-                            profile_fetcher.clone(),
-                            ensemble_fetcher.clone(),
                         ));
                     }
                     flat_tasks_or_futs.push(TaskFut::MapFunctionTaskFut((
@@ -871,6 +1300,8 @@ where
         r#type,
         task_output,
         invert_output,
+        output,
+        reasoning_summary,
     })
 }
 
@@ -958,6 +1389,256 @@ where
     })
 }
 
+/// Compiles and flattens a dependent task with the results of the tasks
+/// that ran before it.
+///
+/// `results` has one element per task of the dependent task's Function.
+/// Returns `None` if the task is skipped.
+pub async fn get_dependent_flat_task_profile<CTXEXT>(
+    params: FlatTaskProfileParams<
+        CTXEXT,
+        impl super::function_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+        impl super::profile_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+        impl crate::ensemble::fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+        impl crate::ensemble_llm::fetcher::Fetcher<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+        impl crate::chat::completions::usage_handler::UsageHandler<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    >,
+    task: DependentFlatTaskProfile,
+    results: &[Option<objectiveai::functions::expression::TaskResult>],
+) -> Result<Option<FlatTaskProfile>, super::executions::Error>
+where
+    CTXEXT: ctx::ContextExt + Send + Sync + 'static,
+{
+    let compiled = (*task.function).clone().compile_dependent_task(
+        task.index,
+        &task.input,
+        results,
+    )?;
+    match compiled {
+        None => Ok(None),
+        Some(objectiveai::functions::CompiledTask::One(
+            objectiveai::functions::Task::ScalarFunction(
+                objectiveai::functions::ScalarFunctionTask {
+                    owner,
+                    repository,
+                    commit,
+                    input,
+                    output,
+                    invert_output,
+                },
+            ),
+        ))
+        | Some(objectiveai::functions::CompiledTask::One(
+            objectiveai::functions::Task::VectorFunction(
+                objectiveai::functions::VectorFunctionTask {
+                    owner,
+                    repository,
+                    commit,
+                    input,
+                    output,
+                    invert_output,
+                },
+            ),
+        )) => {
+            let profile = match task.profile {
+                objectiveai::functions::TaskProfile::RemoteFunction {
+                    owner,
+                    repository,
+                    commit,
+                } => ProfileParam::Remote {
+                    owner,
+                    repository,
+                    commit,
+                },
+                objectiveai::functions::TaskProfile::InlineFunction(profile) => {
+                    ProfileParam::FetchedOrInline {
+                        full_id: None,
+                        profile: objectiveai::functions::Profile::Inline(
+                            profile,
+                        ),
+                    }
+                }
+                _ => return Err(super::executions::Error::InvalidProfile(
+                    "expected function profile (RemoteFunction or InlineFunction) for function task".to_string()
+                )),
+            };
+            Box::pin(get_flat_task_profile(
+                params,
+                task.path,
+                FunctionParam::Remote {
+                    owner,
+                    repository,
+                    commit: Some(commit),
+                },
+                profile,
+                input,
+                Some(output),
+                invert_output || task.invert_output,
+            ))
+            .await
+            .map(|function| Some(FlatTaskProfile::Function(function)))
+        }
+        Some(objectiveai::functions::CompiledTask::One(
+            objectiveai::functions::Task::VectorCompletion(vector_task),
+        )) => {
+            let (ensemble, profile) = match task.profile {
+                objectiveai::functions::TaskProfile::VectorCompletion {
+                    ensemble,
+                    profile,
+                } => (ensemble, profile),
+                _ => return Err(super::executions::Error::InvalidProfile(
+                    "expected VectorCompletion profile for vector completion task".to_string()
+                )),
+            };
+            let invert_output = vector_task.invert_output || task.invert_output;
+            get_vector_completion_flat_task_profile(
+                params.ctx,
+                task.path,
+                vector_task,
+                ensemble,
+                profile,
+                invert_output,
+                params.ensemble_fetcher,
+            )
+            .await
+            .map(|vector| Some(FlatTaskProfile::VectorCompletion(vector)))
+        }
+        // dependent tasks are neither mapped nor chat completions
+        Some(_) => unreachable!(),
+    }
+}
+
+async fn get_chat_completion_flat_task_profile<CTXEXT>(
+    ctx: ctx::Context<CTXEXT>,
+    path: Vec<u64>,
+    task: objectiveai::functions::ChatCompletionTask,
+    model: objectiveai::chat::completions::request::Model,
+    models: Option<Vec<objectiveai::chat::completions::request::Model>>,
+    chat_client: Arc<
+        crate::chat::completions::Client<
+            CTXEXT,
+            impl crate::ensemble_llm::fetcher::Fetcher<CTXEXT>
+            + Send
+            + Sync
+            + 'static,
+            impl crate::chat::completions::usage_handler::UsageHandler<CTXEXT>
+            + Send
+            + Sync
+            + 'static,
+        >,
+    >,
+    request: Option<Arc<objectiveai::functions::executions::request::Request>>,
+) -> super::ChatCompletionFlatTaskProfile
+where
+    CTXEXT: ctx::ContextExt + Send + Sync + 'static,
+{
+    let base = request.as_deref().map(|request| request.base());
+    let params =
+        objectiveai::chat::completions::request::ChatCompletionCreateParams {
+            messages: task.messages.clone(),
+            provider: base.and_then(|base| base.provider),
+            model,
+            models,
+            top_logprobs: None,
+            response_format: task.response_format.clone(),
+            seed: base.and_then(|base| base.seed),
+            stream: Some(true),
+            tool_choice: None,
+            tools: None,
+            parallel_tool_calls: None,
+            prediction: None,
+            max_cost: None,
+            backoff_max_elapsed_time: base
+                .and_then(|base| base.backoff_max_elapsed_time),
+            first_chunk_timeout: base.and_then(|base| base.first_chunk_timeout),
+            other_chunk_timeout: base.and_then(|base| base.other_chunk_timeout),
+        };
+    let params = Arc::new(params);
+
+    // dry runs make no upstream calls
+    if base.and_then(|base| base.dry_run).unwrap_or(false) {
+        return super::ChatCompletionFlatTaskProfile {
+            path,
+            params,
+            completion: Default::default(),
+            output: None,
+            error: None,
+        };
+    }
+
+    // reuse the output of the same completion from the retry token
+    if let Some(retry) = base
+        .and_then(|base| base.retry_token.as_deref())
+        .and_then(|retry_token| ChatCompletionRetry::find(retry_token, &params))
+    {
+        let output = task.output(retry.content.clone());
+        return super::ChatCompletionFlatTaskProfile {
+            path,
+            params,
+            completion:
+                objectiveai::chat::completions::response::streaming::ChatCompletionChunk {
+                    id: retry.id,
+                    choices: vec![
+                        objectiveai::chat::completions::response::streaming::Choice {
+                            delta: objectiveai::chat::completions::response::streaming::Delta {
+                                content: Some(retry.content),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        },
+                    ],
+                    ..Default::default()
+                },
+            output: Some(output),
+            error: None,
+        };
+    }
+
+    // run the completion, keeping whatever was generated before an error
+    let mut completion: Option<
+        objectiveai::chat::completions::response::streaming::ChatCompletionChunk,
+    > = None;
+    let error = match chat_client
+        .create_streaming_for_chat_handle_usage(ctx, params.clone())
+        .await
+    {
+        Ok(mut stream) => loop {
+            match stream.try_next().await {
+                Ok(Some(chunk)) => match &mut completion {
+                    Some(completion) => completion.push(&chunk),
+                    None => completion = Some(chunk),
+                },
+                Ok(None) => break None,
+                Err(e) => break Some(objectiveai::error::ResponseError::from(&e)),
+            }
+        },
+        Err(e) => Some(objectiveai::error::ResponseError::from(&e)),
+    };
+    let completion = completion.unwrap_or_default();
+
+    // extract the output exposed to the other tasks
+    let output = match error {
+        Some(_) => None,
+        None => completion.choices.first().map(|choice| {
+            task.output(choice.delta.content.clone().unwrap_or_default())
+        }),
+    };
+
+    super::ChatCompletionFlatTaskProfile {
+        path,
+        params,
+        completion,
+        output,
+        error,
+    }
+}
+
 enum TaskFut<
     VFUT: Future<
         Output = Result<
@@ -1,7 +1,9 @@
 //! Fetcher for Function definitions from GitHub.
 
+mod caching_fetcher;
 mod fetcher;
 mod objectiveai;
 
+pub use caching_fetcher::*;
 pub use fetcher::*;
 pub use objectiveai::*;
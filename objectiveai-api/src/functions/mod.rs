@@ -4,6 +4,7 @@
 //! and other functions to produce scores.
 
 mod client;
+mod compatibility;
 /// Function execution client and types.
 pub mod executions;
 mod flat_task_profile;
@@ -19,4 +20,5 @@ pub mod profiles;
 pub mod retrieval_client;
 
 pub use client::*;
+pub use compatibility::*;
 pub use flat_task_profile::*;
@@ -0,0 +1,76 @@
+//! Caching wrapper for Profile fetchers.
+
+use crate::ctx;
+use futures::FutureExt;
+use std::sync::Arc;
+
+/// Wraps a Profile fetcher with per-request deduplication caching.
+///
+/// When multiple parts of a request need the same Profile, such as the
+/// items of a batch execution, only one fetch is performed. Subsequent
+/// fetches for the same owner/repository/commit within the same request
+/// context share the result.
+#[derive(Debug, Clone)]
+pub struct CachingFetcher<FPFL> {
+    /// The underlying fetcher to delegate to on cache miss.
+    pub inner: Arc<FPFL>,
+}
+
+impl<FPFL> CachingFetcher<FPFL> {
+    /// Creates a new caching fetcher wrapping the given inner fetcher.
+    pub fn new(inner: Arc<FPFL>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait::async_trait]
+impl<CTXEXT, FPFL> super::Fetcher<CTXEXT> for CachingFetcher<FPFL>
+where
+    CTXEXT: Send + Sync + 'static,
+    FPFL: super::Fetcher<CTXEXT> + Send + Sync + 'static,
+{
+    async fn fetch(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        owner: &str,
+        repository: &str,
+        commit: Option<&str>,
+    ) -> Result<
+        Option<objectiveai::functions::profiles::response::GetProfile>,
+        objectiveai::error::ResponseError,
+    > {
+        let key = (
+            owner.to_owned(),
+            repository.to_owned(),
+            commit.map(str::to_owned),
+        );
+        // Clone the shared future while holding the lock, then release the lock before awaiting.
+        let shared = ctx
+            .profile_cache
+            .entry(key.clone())
+            .or_insert_with(|| {
+                let (tx, rx) = tokio::sync::oneshot::channel();
+                let inner = self.inner.clone();
+                let ctx = ctx.clone();
+                tokio::spawn(async move {
+                    let (owner, repository, commit) = key;
+                    let result = inner
+                        .fetch(ctx, &owner, &repository, commit.as_deref())
+                        .await;
+                    let _ = tx.send(result);
+                });
+                rx.shared()
+            })
+            .clone();
+        // Lock is now released, safe to await
+        shared.await.unwrap_or_else(|_| {
+            Err(objectiveai::error::ResponseError {
+                code: 500,
+                message: serde_json::json!({
+                    "kind": "profile_fetch",
+                    "error": "profile fetch ended without a result",
+                }),
+            })
+        })
+    }
+}
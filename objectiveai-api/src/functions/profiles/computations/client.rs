@@ -1,9 +1,13 @@
 //! Trait for Profile computation (training).
 
 use crate::ctx;
-use futures::Stream;
+use futures::{Stream, stream::BoxStream};
 use std::sync::Arc;
 
+/// The raw content of an uploaded dataset, as it arrives.
+pub type DatasetContent =
+    BoxStream<'static, Result<bytes::Bytes, objectiveai::error::ResponseError>>;
+
 /// Client for computing (training) Profiles.
 #[async_trait::async_trait]
 pub trait Client<CTXEXT> {
@@ -31,4 +35,17 @@ pub trait Client<CTXEXT> {
             + 'static,
         objectiveai::error::ResponseError,
     >;
+
+    /// Parses and stores an uploaded dataset for later computations.
+    ///
+    /// The content is parsed as it streams in, rather than buffered whole.
+    async fn create_dataset(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        params: objectiveai::functions::profiles::computations::datasets::request::DatasetCreateParams,
+        content: DatasetContent,
+    ) -> Result<
+        objectiveai::functions::profiles::computations::datasets::response::Dataset,
+        objectiveai::error::ResponseError,
+    >;
 }
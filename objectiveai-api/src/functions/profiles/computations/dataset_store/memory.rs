@@ -0,0 +1,108 @@
+//! In-memory dataset store.
+
+use crate::ctx;
+use indexmap::IndexMap;
+use std::{
+    sync::{Arc, Mutex},
+    time,
+};
+
+/// Generates a unique ID for an uploaded dataset.
+pub fn dataset_id(created: u64) -> String {
+    let uuid = uuid::Uuid::new_v4();
+    format!("prfdst-{}-{}", uuid.simple(), created)
+}
+
+/// Keeps uploaded datasets in memory, evicting the oldest once full.
+///
+/// Datasets are lost on restart, and an evicted dataset's ID is reported as
+/// not found. `capacity` is set by `PROFILE_COMPUTATIONS_MAX_DATASETS`.
+#[derive(Debug)]
+pub struct MemoryStore {
+    /// Datasets by ID, oldest first.
+    pub datasets: Mutex<
+        IndexMap<
+            String,
+            Arc<objectiveai::functions::profiles::computations::request::Dataset>,
+        >,
+    >,
+    /// Maximum number of datasets kept at once.
+    pub capacity: usize,
+}
+
+impl MemoryStore {
+    /// Creates a new in-memory store holding up to `capacity` datasets.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            datasets: Mutex::new(IndexMap::new()),
+            capacity,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<CTXEXT> super::Store<CTXEXT> for MemoryStore
+where
+    CTXEXT: Send + Sync + 'static,
+{
+    async fn create(
+        &self,
+        _ctx: ctx::Context<CTXEXT>,
+        dataset: objectiveai::functions::profiles::computations::request::Dataset,
+    ) -> Result<
+        objectiveai::functions::profiles::computations::datasets::response::Dataset,
+        objectiveai::error::ResponseError,
+    > {
+        let created = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let id = dataset_id(created);
+        let len = dataset.len();
+        let mut datasets = self.datasets.lock().unwrap();
+        while datasets.len() >= self.capacity.max(1) {
+            datasets.shift_remove_index(0);
+        }
+        datasets.insert(id.clone(), Arc::new(dataset));
+        Ok(
+            objectiveai::functions::profiles::computations::datasets::response::Dataset {
+                id,
+                created,
+                len,
+            },
+        )
+    }
+
+    async fn fetch(
+        &self,
+        _ctx: ctx::Context<CTXEXT>,
+        id: &str,
+    ) -> Result<
+        Option<
+            Arc<objectiveai::functions::profiles::computations::request::Dataset>,
+        >,
+        objectiveai::error::ResponseError,
+    > {
+        Ok(self.datasets.lock().unwrap().get(id).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::profiles::computations::dataset_store::Store;
+
+    #[tokio::test]
+    async fn test_evicts_oldest() {
+        let store = MemoryStore::new(2);
+        let ctx = ctx::Context::new(Arc::new(()), rust_decimal::Decimal::ONE);
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let dataset = store.create(ctx.clone(), Vec::new()).await.unwrap();
+            ids.push(dataset.id);
+        }
+        assert!(store.fetch(ctx.clone(), &ids[0]).await.unwrap().is_none());
+        assert!(store.fetch(ctx.clone(), &ids[1]).await.unwrap().is_some());
+        assert!(store.fetch(ctx, &ids[2]).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,7 @@
+//! Storage for uploaded Profile computation datasets.
+
+mod memory;
+mod store;
+
+pub use memory::*;
+pub use store::*;
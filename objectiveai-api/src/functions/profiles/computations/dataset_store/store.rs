@@ -0,0 +1,33 @@
+//! Trait for storing uploaded datasets.
+
+use crate::ctx;
+use std::sync::Arc;
+
+/// Stores uploaded datasets so that Profile computations can reference them
+/// by ID.
+#[async_trait::async_trait]
+pub trait Store<CTXEXT> {
+    /// Stores a parsed dataset, returning its ID and metadata.
+    async fn create(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        dataset: objectiveai::functions::profiles::computations::request::Dataset,
+    ) -> Result<
+        objectiveai::functions::profiles::computations::datasets::response::Dataset,
+        objectiveai::error::ResponseError,
+    >;
+
+    /// Fetches a dataset by ID.
+    ///
+    /// Returns None if the dataset is not found.
+    async fn fetch(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        id: &str,
+    ) -> Result<
+        Option<
+            Arc<objectiveai::functions::profiles::computations::request::Dataset>,
+        >,
+        objectiveai::error::ResponseError,
+    >;
+}
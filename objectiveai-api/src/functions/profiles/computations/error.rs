@@ -0,0 +1,202 @@
+//! Error types for Profile computation.
+
+use crate::functions;
+
+/// Errors that can occur during Profile computation.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The dataset contains no items.
+    #[error("dataset is empty")]
+    EmptyDataset,
+    /// Both an inline dataset and an uploaded dataset were given.
+    #[error("dataset and dataset_id are mutually exclusive")]
+    ConflictingDataset,
+    /// The uploaded dataset could not be parsed, or some of its items do not
+    /// match the Function's input schema.
+    #[error("invalid dataset: {0}")]
+    InvalidDataset(
+        #[from]
+        objectiveai::functions::profiles::computations::datasets::DatasetError,
+    ),
+    /// Reading the uploaded dataset's content failed.
+    #[error("dataset upload error: {0}")]
+    DatasetUpload(objectiveai::error::ResponseError),
+    /// Only some of `fowner`, `frepository` and `fcommit` were given to
+    /// validate an uploaded dataset against.
+    #[error("fowner and frepository are required to validate a dataset")]
+    InvalidDatasetFunction,
+    /// Failed to store or fetch an uploaded dataset.
+    #[error("dataset store error: {0}")]
+    DatasetStore(objectiveai::error::ResponseError),
+    /// The referenced uploaded dataset was not found.
+    #[error("dataset not found")]
+    DatasetNotFound,
+    /// `n` must be at least 1.
+    #[error("n must be at least 1")]
+    InvalidN,
+    /// A dataset item's target does not match the Function's output type.
+    #[error("invalid target for dataset item {index}: {message}")]
+    InvalidTarget {
+        /// Index of the dataset item.
+        index: usize,
+        /// Why the target is invalid.
+        message: String,
+    },
+    /// The validation split is invalid for the dataset.
+    #[error("invalid validation: {0}")]
+    InvalidValidation(String),
+    /// The retry token is malformed or does not match the dataset.
+    #[error("invalid retry token")]
+    InvalidRetryToken,
+    /// A dataset item's input is invalid for the Function.
+    #[error("invalid dataset item {index}: {error}")]
+    InvalidDatasetItem {
+        /// Index of the dataset item.
+        index: usize,
+        /// Why the Function could not be prepared for the input.
+        error: functions::executions::Error,
+    },
+    /// Fetching the Function or Ensemble failed.
+    #[error(transparent)]
+    Execution(#[from] functions::executions::Error),
+    /// Every execution of the dataset failed, leaving nothing to fit.
+    #[error("no successful executions")]
+    NoSuccessfulExecutions,
+    /// The fitting thread failed before producing weights.
+    #[error("fitting failed: {0}")]
+    Fitting(String),
+    /// The Function has a chat completion task, which has no weights to fit.
+    #[error("chat completion tasks are not supported")]
+    UnsupportedChatCompletionTask,
+    /// The Function has a task with dependencies, which is flattened during
+    /// execution and cannot be fitted.
+    #[error("tasks with dependencies are not supported")]
+    UnsupportedDependentTask,
+}
+
+impl objectiveai::error::StatusError for Error {
+    fn status(&self) -> u16 {
+        match self {
+            Error::EmptyDataset => 400,
+            Error::ConflictingDataset => 400,
+            Error::InvalidDataset(_) => 400,
+            Error::DatasetUpload(e) => e.status(),
+            Error::InvalidDatasetFunction => 400,
+            Error::DatasetStore(e) => e.status(),
+            Error::DatasetNotFound => 404,
+            Error::InvalidN => 400,
+            Error::InvalidTarget { .. } => 400,
+            Error::InvalidValidation(_) => 400,
+            Error::InvalidRetryToken => 400,
+            Error::InvalidDatasetItem { error, .. } => error.status(),
+            Error::Execution(e) => e.status(),
+            Error::NoSuccessfulExecutions => 500,
+            Error::Fitting(_) => 500,
+            Error::UnsupportedChatCompletionTask => 400,
+            Error::UnsupportedDependentTask => 400,
+        }
+    }
+
+    fn message(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "kind": "profile_computation",
+            "error": match self {
+                Error::EmptyDataset => serde_json::json!({
+                    "kind": "empty_dataset",
+                    "error": "dataset is empty",
+                }),
+                Error::ConflictingDataset => serde_json::json!({
+                    "kind": "conflicting_dataset",
+                    "error": "dataset and dataset_id are mutually exclusive",
+                }),
+                Error::InvalidDataset(
+                    objectiveai::functions::profiles::computations::datasets::DatasetError::Header(message),
+                ) => serde_json::json!({
+                    "kind": "invalid_dataset_header",
+                    "error": message,
+                }),
+                Error::InvalidDataset(
+                    objectiveai::functions::profiles::computations::datasets::DatasetError::Rows(rows),
+                ) => serde_json::json!({
+                    "kind": "invalid_dataset_rows",
+                    "error": format!("{} invalid dataset items", rows.len()),
+                    "rows": rows,
+                }),
+                Error::InvalidDataset(
+                    objectiveai::functions::profiles::computations::datasets::DatasetError::Encoding,
+                ) => serde_json::json!({
+                    "kind": "invalid_dataset_encoding",
+                    "error": "dataset is not valid UTF-8",
+                }),
+                Error::InvalidDataset(
+                    objectiveai::functions::profiles::computations::datasets::DatasetError::Inputs(rows),
+                ) => serde_json::json!({
+                    "kind": "invalid_dataset_inputs",
+                    "error": format!(
+                        "{} dataset inputs do not match the input schema",
+                        rows.len()
+                    ),
+                    "rows": rows,
+                }),
+                Error::DatasetUpload(e) => serde_json::json!({
+                    "kind": "dataset_upload",
+                    "error": e.message(),
+                }),
+                Error::InvalidDatasetFunction => serde_json::json!({
+                    "kind": "invalid_dataset_function",
+                    "error": "fowner and frepository are required to validate a dataset",
+                }),
+                Error::DatasetStore(e) => serde_json::json!({
+                    "kind": "dataset_store",
+                    "error": e.message(),
+                }),
+                Error::DatasetNotFound => serde_json::json!({
+                    "kind": "dataset_not_found",
+                    "error": "dataset not found",
+                }),
+                Error::InvalidN => serde_json::json!({
+                    "kind": "invalid_n",
+                    "error": "n must be at least 1",
+                }),
+                Error::InvalidTarget { index, message } => serde_json::json!({
+                    "kind": "invalid_target",
+                    "index": index,
+                    "error": message,
+                }),
+                Error::InvalidValidation(message) => serde_json::json!({
+                    "kind": "invalid_validation",
+                    "error": message,
+                }),
+                Error::InvalidRetryToken => serde_json::json!({
+                    "kind": "invalid_retry_token",
+                    "error": "invalid retry token",
+                }),
+                Error::InvalidDatasetItem { index, error } => serde_json::json!({
+                    "kind": "invalid_dataset_item",
+                    "index": index,
+                    "error": error.message(),
+                }),
+                Error::Execution(e) => serde_json::json!({
+                    "kind": "execution",
+                    "error": e.message(),
+                }),
+                Error::NoSuccessfulExecutions => serde_json::json!({
+                    "kind": "no_successful_executions",
+                    "error": "no successful executions",
+                }),
+                Error::Fitting(message) => serde_json::json!({
+                    "kind": "fitting",
+                    "error": message,
+                }),
+                Error::UnsupportedChatCompletionTask => serde_json::json!({
+                    "kind": "unsupported_chat_completion_task",
+                    "error": "chat completion tasks are not supported",
+                }),
+                Error::UnsupportedDependentTask => serde_json::json!({
+                    "kind": "unsupported_dependent_task",
+                    "error": "tasks with dependencies are not supported",
+                }),
+            }
+        }))
+    }
+}
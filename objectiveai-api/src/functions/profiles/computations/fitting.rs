@@ -0,0 +1,1336 @@
+//! Fitting of Profile weights against executed dataset items.
+//!
+//! Each dataset item is executed once with a uniform Profile. The recorded
+//! votes are then re-weighted in-process: changing a weight only requires
+//! re-scoring votes and re-evaluating task output expressions, never
+//! re-running an LLM. Weights are fitted by multi-start coordinate search.
+
+use crate::functions;
+use objectiveai::functions::expression::{
+    FunctionOutput, TaskOutputOwned, VectorCompletionOutput,
+};
+use objectiveai::functions::profiles::computations::request::{
+    CategoricalLoss, Cost, Loss, MultiLabelLoss, Regularization, ScalarLoss,
+    Target, VectorLoss, VectorWinnerLoss,
+};
+use objectiveai::vector::completions::response::Vote;
+use rand::{Rng, SeedableRng};
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+use std::collections::{BTreeMap, HashMap};
+
+/// Probability floor used when computing cross-entropy losses.
+pub const MIN_PROBABILITY: f64 = 1e-6;
+
+/// Minimum loss improvement for a coordinate step to be accepted.
+const MIN_IMPROVEMENT: f64 = 1e-12;
+
+/// Objective penalty per unit of expected cost over the cost budget.
+///
+/// Large enough that weights within budget always beat weights over it.
+pub const BUDGET_OVERRUN_PENALTY: f64 = 1e6;
+
+/// Smallest positive weight kept by [`Weights::normalized`].
+const MIN_NORMALIZED_WEIGHT: Decimal = Decimal::from_parts(1, 0, 0, false, 6);
+
+/// Task indices leading from the root Function to a node of its Profile.
+///
+/// Unlike task paths, profile paths never include map indices, as every
+/// element of a mapped task shares the same profile.
+pub type ProfilePath = Vec<u64>;
+
+/// The weights of a Profile being fitted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Weights {
+    /// Task weights of each Function in the Profile.
+    pub tasks: BTreeMap<ProfilePath, Vec<Decimal>>,
+    /// LLM weights of each Vector Completion task in the Profile.
+    pub llms: BTreeMap<ProfilePath, Vec<Decimal>>,
+}
+
+/// Identifies a single weight within [`Weights`].
+#[derive(Debug, Clone)]
+enum Coordinate {
+    Task(ProfilePath, usize),
+    Llm(ProfilePath, usize),
+}
+
+impl Coordinate {
+    /// Minimum number of positive weights in the coordinate's group.
+    ///
+    /// Vector Completions require two or more LLMs with positive weight.
+    fn min_positive(&self) -> usize {
+        match self {
+            Coordinate::Task(..) => 1,
+            Coordinate::Llm(..) => 2,
+        }
+    }
+}
+
+impl Weights {
+    /// Returns the weight group containing `coordinate`.
+    fn group_mut(&mut self, coordinate: &Coordinate) -> &mut Vec<Decimal> {
+        match coordinate {
+            Coordinate::Task(path, _) => self.tasks.get_mut(path).unwrap(),
+            Coordinate::Llm(path, _) => self.llms.get_mut(path).unwrap(),
+        }
+    }
+
+    fn coordinates(&self) -> Vec<Coordinate> {
+        let mut coordinates = Vec::new();
+        for (path, weights) in &self.tasks {
+            // a single weight is scale-invariant, there is nothing to fit
+            if weights.len() > 1 {
+                for i in 0..weights.len() {
+                    coordinates.push(Coordinate::Task(path.clone(), i));
+                }
+            }
+        }
+        for (path, weights) in &self.llms {
+            if weights.len() > 1 {
+                for i in 0..weights.len() {
+                    coordinates.push(Coordinate::Llm(path.clone(), i));
+                }
+            }
+        }
+        coordinates
+    }
+
+    fn groups_mut(&mut self) -> impl Iterator<Item = &mut Vec<Decimal>> {
+        self.tasks.values_mut().chain(self.llms.values_mut())
+    }
+
+    /// Returns a copy with every weight drawn uniformly from (0, 1].
+    fn randomized(&self, rng: &mut impl Rng) -> Self {
+        let mut weights = self.clone();
+        for group in weights.groups_mut() {
+            for weight in group.iter_mut() {
+                *weight = Decimal::from(rng.random_range(1..=100u32))
+                    / Decimal::ONE_HUNDRED;
+            }
+        }
+        weights
+    }
+
+    /// Computes the L1 and L2 penalties of the LLM weights.
+    ///
+    /// Each task's LLM weights are scaled so that the largest is 1. Sums of
+    /// normalized weights are constant, whereas scaled weights are smallest
+    /// when few LLMs carry the vote, so the penalties push unhelpful LLMs
+    /// to zero.
+    pub fn penalty(&self, regularization: &Regularization) -> (f64, f64) {
+        let l1 = regularization.l1.and_then(|l1| l1.to_f64()).unwrap_or(0.0);
+        let l2 = regularization.l2.and_then(|l2| l2.to_f64()).unwrap_or(0.0);
+        if l1 == 0.0 && l2 == 0.0 {
+            return (0.0, 0.0);
+        }
+        let (mut l1_sum, mut l2_sum) = (0.0, 0.0);
+        for group in self.llms.values() {
+            let max = group.iter().max().copied().unwrap_or_default();
+            if max <= Decimal::ZERO {
+                continue;
+            }
+            for weight in group {
+                let scaled = (*weight / max).to_f64().unwrap_or_default();
+                l1_sum += scaled;
+                l2_sum += scaled * scaled;
+            }
+        }
+        (l1 * l1_sum, l2 * l2_sum)
+    }
+
+    /// Returns a copy where each weight group sums to 1.
+    ///
+    /// Weights are L1-normalized during execution, so this does not change
+    /// any output. Positive weights stay positive after rounding.
+    pub fn normalized(&self) -> Self {
+        let mut weights = self.clone();
+        for group in weights.groups_mut() {
+            let sum: Decimal = group.iter().sum();
+            if sum > Decimal::ZERO {
+                for weight in group.iter_mut() {
+                    if *weight > Decimal::ZERO {
+                        *weight = (*weight / sum)
+                            .round_dp(6)
+                            .max(MIN_NORMALIZED_WEIGHT);
+                    }
+                }
+            }
+        }
+        weights
+    }
+
+    /// Builds the inline Profile these weights describe.
+    ///
+    /// Every Vector Completion task uses `ensemble`.
+    pub fn to_profile(
+        &self,
+        ensemble: &objectiveai::vector::completions::request::Ensemble,
+    ) -> objectiveai::functions::InlineProfile {
+        self.profile_at(&mut Vec::new(), ensemble)
+    }
+
+    fn profile_at(
+        &self,
+        path: &mut ProfilePath,
+        ensemble: &objectiveai::vector::completions::request::Ensemble,
+    ) -> objectiveai::functions::InlineProfile {
+        let weights = self.tasks.get(path).cloned().unwrap_or_default();
+        let mut tasks = Vec::with_capacity(weights.len());
+        for i in 0..weights.len() {
+            path.push(i as u64);
+            tasks.push(match self.llms.get(path) {
+                Some(llms) => {
+                    objectiveai::functions::TaskProfile::VectorCompletion {
+                        ensemble: ensemble.clone(),
+                        profile:
+                            objectiveai::vector::completions::request::Profile::Weights(
+                                llms.clone(),
+                            ),
+                    }
+                }
+                None => objectiveai::functions::TaskProfile::InlineFunction(
+                    self.profile_at(path, ensemble),
+                ),
+            });
+            path.pop();
+        }
+        objectiveai::functions::InlineProfile {
+            tasks,
+            profile: objectiveai::vector::completions::request::Profile::Weights(
+                weights,
+            ),
+        }
+    }
+}
+
+/// An executed dataset item.
+#[derive(Debug, Clone)]
+pub struct Sample {
+    /// The flattened Function the item was executed with.
+    pub ftp: functions::FunctionFlatTaskProfile,
+    /// The votes cast for each Vector Completion task, by task path.
+    pub votes: HashMap<Vec<u64>, Vec<Vote>>,
+    /// The LLM calls made for each Vector Completion task, by task path, as
+    /// ensemble indices and the cost of the call. Cached, retried and
+    /// RNG'd votes have no cost.
+    pub calls: HashMap<Vec<u64>, Vec<(u64, Option<Decimal>)>>,
+    /// The desired output.
+    pub target: Target,
+}
+
+impl Sample {
+    /// Creates a sample from the votes recorded in `execution`.
+    pub fn new(
+        ftp: functions::FunctionFlatTaskProfile,
+        execution: &objectiveai::functions::executions::response::unary::FunctionExecution,
+        target: Target,
+    ) -> Self {
+        fn collect_votes(
+            tasks: &[objectiveai::functions::executions::response::unary::Task],
+            votes: &mut HashMap<Vec<u64>, Vec<Vote>>,
+            calls: &mut HashMap<Vec<u64>, Vec<(u64, Option<Decimal>)>>,
+        ) {
+            for task in tasks {
+                match task {
+                    objectiveai::functions::executions::response::unary::Task::FunctionExecution(
+                        function,
+                    ) => collect_votes(&function.inner.tasks, votes, calls),
+                    objectiveai::functions::executions::response::unary::Task::VectorCompletion(
+                        vector,
+                    ) => {
+                        let cost = |vote: &Vote| {
+                            let index = vote.completion_index?;
+                            vector
+                                .inner
+                                .completions
+                                .iter()
+                                .find(|completion| completion.index == index)
+                                .map(|completion| {
+                                    completion.inner.usage.total_cost
+                                })
+                        };
+                        calls.entry(vector.task_path.clone()).or_default().extend(
+                            vector
+                                .inner
+                                .votes
+                                .iter()
+                                .map(|vote| (vote.ensemble_index, cost(vote))),
+                        );
+                        votes
+                            .entry(vector.task_path.clone())
+                            .or_default()
+                            .extend(vector.inner.votes.iter().cloned());
+                    }
+                    objectiveai::functions::executions::response::unary::Task::ChatCompletion(
+                        _,
+                    ) => {}
+                }
+            }
+        }
+        let mut votes = HashMap::new();
+        let mut calls = HashMap::new();
+        collect_votes(&execution.tasks, &mut votes, &mut calls);
+        Self {
+            ftp,
+            votes,
+            calls,
+            target,
+        }
+    }
+
+    /// Computes the Function's output under `weights`.
+    pub fn output(&self, weights: &Weights) -> FunctionOutput {
+        self.function_output(&self.ftp, &mut Vec::new(), weights)
+    }
+
+    /// Computes the loss of the Function's output under `weights`.
+    pub fn loss(&self, weights: &Weights, functions: &Loss) -> f64 {
+        loss(&self.output(weights), &self.target, functions)
+    }
+
+    fn function_output(
+        &self,
+        ftp: &functions::FunctionFlatTaskProfile,
+        path: &mut ProfilePath,
+        weights: &Weights,
+    ) -> FunctionOutput {
+        let mut outputs = Vec::with_capacity(ftp.tasks.len());
+        for (i, task) in ftp.tasks.iter().enumerate() {
+            path.push(i as u64);
+            outputs.push(task.as_ref().and_then(|task| {
+                let (raw, expression, invert_output) = match task {
+                    functions::FlatTaskProfile::Function(function) => (
+                        TaskOutputOwned::Function(
+                            self.function_output(function, path, weights),
+                        ),
+                        function.task_output.as_ref()?,
+                        function.invert_output,
+                    ),
+                    functions::FlatTaskProfile::MapFunction(functions) => (
+                        TaskOutputOwned::MapFunction(
+                            functions
+                                .functions
+                                .iter()
+                                .map(|function| {
+                                    self.function_output(function, path, weights)
+                                })
+                                .collect(),
+                        ),
+                        &functions.task_output,
+                        functions.invert_output,
+                    ),
+                    functions::FlatTaskProfile::VectorCompletion(vector) => (
+                        TaskOutputOwned::VectorCompletion(
+                            self.vector_output(vector, path, weights),
+                        ),
+                        &vector.output,
+                        vector.invert_output,
+                    ),
+                    // chat completion tasks have no output of their own,
+                    // dependent tasks are rejected before fitting
+                    functions::FlatTaskProfile::ChatCompletion(_)
+                    | functions::FlatTaskProfile::Dependent(_) => {
+                        return None;
+                    }
+                    functions::FlatTaskProfile::MapVectorCompletion(vectors) => (
+                        TaskOutputOwned::MapVectorCompletion(
+                            vectors
+                                .vector_completions
+                                .iter()
+                                .map(|vector| {
+                                    self.vector_output(vector, path, weights)
+                                })
+                                .collect(),
+                        ),
+                        &vectors.task_output,
+                        vectors.invert_output,
+                    ),
+                };
+                match functions::executions::apply_task_output_expression(
+                    &ftp.input,
+                    raw,
+                    expression,
+                    invert_output,
+                    &ftp.r#type,
+                ) {
+                    (output, None) => Some(output),
+                    (_, Some(_)) => None,
+                }
+            }));
+            path.pop();
+        }
+        let task_weights = weights
+            .tasks
+            .get(path)
+            .map(Vec::as_slice)
+            .unwrap_or(&ftp.profile);
+        functions::executions::compute_function_output(
+            &ftp.input,
+            ftp.output.as_ref(),
+            &ftp.r#type,
+            task_weights,
+            &outputs,
+        )
+        .0
+    }
+
+    /// Visits every Vector Completion task with its profile path.
+    fn visit_vector_completions<'a>(
+        ftp: &'a functions::FunctionFlatTaskProfile,
+        path: &mut ProfilePath,
+        visit: &mut impl FnMut(
+            &ProfilePath,
+            &'a functions::VectorCompletionFlatTaskProfile,
+        ),
+    ) {
+        for (i, task) in ftp.tasks.iter().enumerate() {
+            path.push(i as u64);
+            match task {
+                Some(functions::FlatTaskProfile::Function(function)) => {
+                    Self::visit_vector_completions(function, path, visit)
+                }
+                Some(functions::FlatTaskProfile::MapFunction(functions)) => {
+                    for function in &functions.functions {
+                        Self::visit_vector_completions(function, path, visit)
+                    }
+                }
+                Some(functions::FlatTaskProfile::VectorCompletion(vector)) => {
+                    visit(path, vector)
+                }
+                Some(functions::FlatTaskProfile::MapVectorCompletion(
+                    vectors,
+                )) => {
+                    for vector in &vectors.vector_completions {
+                        visit(path, vector)
+                    }
+                }
+                Some(functions::FlatTaskProfile::ChatCompletion(_))
+                | Some(functions::FlatTaskProfile::Dependent(_))
+                | None => {}
+            }
+            path.pop();
+        }
+    }
+
+    /// Re-scores the recorded votes of a Vector Completion task.
+    fn vector_output(
+        &self,
+        vector: &functions::VectorCompletionFlatTaskProfile,
+        path: &ProfilePath,
+        weights: &Weights,
+    ) -> VectorCompletionOutput {
+        let responses_len = vector.responses.len();
+        let votes = match self.votes.get(&vector.path) {
+            Some(votes) if !votes.is_empty() => votes,
+            _ => {
+                return VectorCompletionOutput::default_from_request_responses_len(
+                    responses_len,
+                );
+            }
+        };
+        let llm_weights = weights
+            .llms
+            .get(path)
+            .map(Vec::as_slice)
+            .unwrap_or(&vector.profile);
+        let mut response_weights = vec![Decimal::ZERO; responses_len];
+        let votes: Vec<Vote> = votes
+            .iter()
+            .map(|vote| {
+                let mut vote = vote.clone();
+                vote.weight = llm_weights
+                    .get(vote.ensemble_index as usize)
+                    .copied()
+                    .unwrap_or(Decimal::ZERO);
+                for (i, v) in vote.vote.iter().enumerate() {
+                    if let Some(weight) = response_weights.get_mut(i) {
+                        *weight += *v * vote.weight;
+                    }
+                }
+                vote
+            })
+            .collect();
+        let weight_sum: Decimal = response_weights.iter().sum();
+        let scores = if weight_sum > Decimal::ZERO {
+            response_weights.iter().map(|w| *w / weight_sum).collect()
+        } else {
+            VectorCompletionOutput::default_from_request_responses_len(
+                responses_len,
+            )
+            .scores
+        };
+        VectorCompletionOutput {
+            votes,
+            scores,
+            weights: response_weights,
+        }
+    }
+}
+
+/// Computes the loss of `output` against `target`.
+///
+/// `functions` selects the loss for each target type:
+///
+/// - `Scalar` targets use squared or absolute error.
+/// - `Vector` targets use the sum of squared errors, or cross-entropy.
+/// - `VectorWinner` targets use the cross-entropy of the winning index, the
+///   hinge loss of its margin over the highest other index, or the mean
+///   hinge loss of its margin over each other index.
+/// - `MultiLabel` targets use the binary cross-entropy or squared error of
+///   each label, averaged over labels.
+/// - `Categorical` targets use the cross-entropy of the desired category, or
+///   the hinge loss of its margin over the highest other category.
+///
+/// Error outputs, and outputs whose shape does not match the target, are
+/// assigned the worst-case loss.
+pub fn loss(output: &FunctionOutput, target: &Target, functions: &Loss) -> f64 {
+    fn f(decimal: Decimal) -> f64 {
+        decimal.to_f64().unwrap_or_default()
+    }
+    let max_cross_entropy = -MIN_PROBABILITY.ln();
+    match (target, output) {
+        (Target::Scalar { value }, FunctionOutput::Scalar(scalar)) => {
+            let error = f(*scalar) - f(*value);
+            match functions.scalar.unwrap_or_default() {
+                ScalarLoss::SquaredError => error.powi(2),
+                ScalarLoss::AbsoluteError => error.abs(),
+            }
+        }
+        (Target::Scalar { .. }, _) => 1.0,
+        (Target::Vector { value }, FunctionOutput::Vector(vector))
+            if vector.len() == value.len() =>
+        {
+            let pairs = vector.iter().zip(value).map(|(o, t)| (f(*o), f(*t)));
+            match functions.vector.unwrap_or_default() {
+                VectorLoss::SquaredError => {
+                    pairs.map(|(o, t)| (o - t).powi(2)).sum()
+                }
+                VectorLoss::CrossEntropy => pairs
+                    .map(|(o, t)| -t * o.max(MIN_PROBABILITY).ln())
+                    .sum(),
+            }
+        }
+        (Target::Vector { .. }, _) => match functions.vector.unwrap_or_default()
+        {
+            VectorLoss::SquaredError => 2.0,
+            VectorLoss::CrossEntropy => max_cross_entropy,
+        },
+        (Target::VectorWinner { value }, FunctionOutput::Vector(vector))
+            if *value < vector.len() =>
+        {
+            let winner = f(vector[*value]);
+            let margins = vector
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| i != value)
+                .map(|(_, score)| (1.0 - (winner - f(*score))).max(0.0));
+            match functions.vector_winner.unwrap_or_default() {
+                VectorWinnerLoss::CrossEntropy => {
+                    -winner.max(MIN_PROBABILITY).ln()
+                }
+                VectorWinnerLoss::Hinge => margins.fold(0.0, f64::max),
+                VectorWinnerLoss::Ranking => {
+                    let others = vector.len() - 1;
+                    if others == 0 {
+                        0.0
+                    } else {
+                        margins.sum::<f64>() / others as f64
+                    }
+                }
+            }
+        }
+        (Target::VectorWinner { .. }, _) => {
+            match functions.vector_winner.unwrap_or_default() {
+                VectorWinnerLoss::CrossEntropy => max_cross_entropy,
+                VectorWinnerLoss::Hinge | VectorWinnerLoss::Ranking => 2.0,
+            }
+        }
+        (Target::MultiLabel { value }, FunctionOutput::MultiLabel(scores))
+            if !scores.is_empty() =>
+        {
+            let pairs = scores.iter().map(|(label, score)| {
+                (f(*score), if value.contains(label) { 1.0 } else { 0.0 })
+            });
+            let sum: f64 = match functions.multi_label.unwrap_or_default() {
+                MultiLabelLoss::BinaryCrossEntropy => pairs
+                    .map(|(o, t)| {
+                        -t * o.max(MIN_PROBABILITY).ln()
+                            - (1.0 - t) * (1.0 - o).max(MIN_PROBABILITY).ln()
+                    })
+                    .sum(),
+                MultiLabelLoss::SquaredError => {
+                    pairs.map(|(o, t)| (o - t).powi(2)).sum()
+                }
+            };
+            sum / scores.len() as f64
+        }
+        (Target::MultiLabel { .. }, _) => {
+            match functions.multi_label.unwrap_or_default() {
+                MultiLabelLoss::BinaryCrossEntropy => max_cross_entropy,
+                MultiLabelLoss::SquaredError => 1.0,
+            }
+        }
+        (Target::Categorical { value }, FunctionOutput::Categorical(scores))
+            if scores.contains_key(value) =>
+        {
+            let winner = f(scores[value]);
+            match functions.categorical.unwrap_or_default() {
+                CategoricalLoss::CrossEntropy => {
+                    -winner.max(MIN_PROBABILITY).ln()
+                }
+                CategoricalLoss::Hinge => scores
+                    .iter()
+                    .filter(|(category, _)| *category != value)
+                    .map(|(_, score)| (1.0 - (winner - f(*score))).max(0.0))
+                    .fold(0.0, f64::max),
+            }
+        }
+        (Target::Categorical { .. }, _) => {
+            match functions.categorical.unwrap_or_default() {
+                CategoricalLoss::CrossEntropy => max_cross_entropy,
+                CategoricalLoss::Hinge => 2.0,
+            }
+        }
+    }
+}
+
+/// Computes the mean loss of `samples` under `weights`.
+pub fn mean_loss(samples: &[Sample], weights: &Weights, functions: &Loss) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    samples
+        .iter()
+        .map(|sample| sample.loss(weights, functions))
+        .sum::<f64>()
+        / samples.len() as f64
+}
+
+/// The expected cost per execution of each LLM.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Costs {
+    /// Expected cost per execution of each LLM of each Vector Completion
+    /// task, by profile path and ensemble index.
+    pub llms: BTreeMap<ProfilePath, Vec<f64>>,
+}
+
+impl Costs {
+    /// Estimates the costs from the LLM calls recorded in `samples`.
+    ///
+    /// An LLM's expected cost is its mean cost per paid call, times how
+    /// often it is called per execution. Calls answered from cache, retries
+    /// or RNG count towards how often it is called, but not its cost.
+    pub fn new(samples: &[Sample]) -> Self {
+        // (calls, paid calls, paid cost) by profile path and ensemble index
+        let mut totals = BTreeMap::<ProfilePath, Vec<(usize, usize, f64)>>::new();
+        for sample in samples {
+            Sample::visit_vector_completions(
+                &sample.ftp,
+                &mut Vec::new(),
+                &mut |path, vector| {
+                    let Some(calls) = sample.calls.get(&vector.path) else {
+                        return;
+                    };
+                    let group = totals.entry(path.clone()).or_default();
+                    for (ensemble_index, cost) in calls {
+                        let i = *ensemble_index as usize;
+                        if group.len() <= i {
+                            group.resize(i + 1, (0, 0, 0.0));
+                        }
+                        group[i].0 += 1;
+                        if let Some(cost) = cost {
+                            group[i].1 += 1;
+                            group[i].2 += cost.to_f64().unwrap_or_default();
+                        }
+                    }
+                },
+            );
+        }
+        let executions = samples.len().max(1) as f64;
+        Self {
+            llms: totals
+                .into_iter()
+                .map(|(path, group)| {
+                    let costs = group
+                        .into_iter()
+                        .map(|(calls, paid_calls, paid_cost)| {
+                            if paid_calls == 0 {
+                                0.0
+                            } else {
+                                paid_cost / paid_calls as f64 * calls as f64
+                                    / executions
+                            }
+                        })
+                        .collect();
+                    (path, costs)
+                })
+                .collect(),
+        }
+    }
+
+    /// Computes the expected cost per execution under `weights`.
+    ///
+    /// LLMs with zero weight are never called, so they cost nothing.
+    pub fn expected(&self, weights: &Weights) -> f64 {
+        self.llms
+            .iter()
+            .map(|(path, costs)| {
+                let llm_weights = weights.llms.get(path);
+                costs
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| {
+                        llm_weights
+                            .and_then(|weights| weights.get(*i))
+                            .is_none_or(|weight| *weight > Decimal::ZERO)
+                    })
+                    .map(|(_, cost)| cost)
+                    .sum::<f64>()
+            })
+            .sum()
+    }
+}
+
+/// Number of equal-width bins used to compute calibration error.
+pub const CALIBRATION_BINS: usize = 10;
+
+/// Evaluation metrics of samples under some weights.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Metrics {
+    /// The mean loss.
+    pub loss: f64,
+    /// Fraction of `VectorWinner` and `Categorical` targets whose index or
+    /// category scored strictly highest, if there are any.
+    pub accuracy: Option<f64>,
+    /// Expected calibration error over `Scalar` and `MultiLabel` targets,
+    /// each label counting as a prediction, if there are any.
+    pub calibration_error: Option<f64>,
+}
+
+impl Metrics {
+    /// Evaluates `samples` under `weights`.
+    pub fn new(samples: &[Sample], weights: &Weights, functions: &Loss) -> Self {
+        fn f(decimal: Decimal) -> f64 {
+            decimal.to_f64().unwrap_or_default()
+        }
+        let mut loss_sum = 0.0;
+        let (mut winners, mut hits) = (0usize, 0usize);
+        let mut bins = [(0usize, 0.0, 0.0); CALIBRATION_BINS];
+        let mut scalars = 0usize;
+        for sample in samples {
+            let output = sample.output(weights);
+            loss_sum += loss(&output, &sample.target, functions);
+            match (&sample.target, &output) {
+                (Target::VectorWinner { value }, output) => {
+                    winners += 1;
+                    if let FunctionOutput::Vector(vector) = output
+                        && *value < vector.len()
+                        && vector.iter().enumerate().all(|(i, score)| {
+                            i == *value || *score < vector[*value]
+                        })
+                    {
+                        hits += 1;
+                    }
+                }
+                (Target::Categorical { value }, output) => {
+                    winners += 1;
+                    if let FunctionOutput::Categorical(scores) = output
+                        && let Some(winner) = scores.get(value)
+                        && scores.iter().all(|(category, score)| {
+                            category == value || score < winner
+                        })
+                    {
+                        hits += 1;
+                    }
+                }
+                (Target::Scalar { value }, FunctionOutput::Scalar(scalar)) => {
+                    let predicted = f(*scalar).clamp(0.0, 1.0);
+                    let bin = ((predicted * CALIBRATION_BINS as f64) as usize)
+                        .min(CALIBRATION_BINS - 1);
+                    bins[bin].0 += 1;
+                    bins[bin].1 += predicted;
+                    bins[bin].2 += f(*value);
+                    scalars += 1;
+                }
+                (
+                    Target::MultiLabel { value },
+                    FunctionOutput::MultiLabel(scores),
+                ) => {
+                    for (label, score) in scores {
+                        let predicted = f(*score).clamp(0.0, 1.0);
+                        let bin = ((predicted * CALIBRATION_BINS as f64)
+                            as usize)
+                            .min(CALIBRATION_BINS - 1);
+                        bins[bin].0 += 1;
+                        bins[bin].1 += predicted;
+                        bins[bin].2 += if value.contains(label) { 1.0 } else { 0.0 };
+                        scalars += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Self {
+            loss: if samples.is_empty() {
+                0.0
+            } else {
+                loss_sum / samples.len() as f64
+            },
+            accuracy: (winners > 0).then(|| hits as f64 / winners as f64),
+            calibration_error: (scalars > 0).then(|| {
+                // weighting each bin's mean gap by its share of samples
+                // reduces to the gap between the bin's sums
+                bins.iter()
+                    .map(|(_, predicted, target)| {
+                        (predicted - target).abs() / scalars as f64
+                    })
+                    .sum()
+            }),
+        }
+    }
+}
+
+/// Options for [`fit`].
+#[derive(Debug, Clone, Copy)]
+pub struct FitOptions {
+    /// Number of starting points. The first start is the initial weights,
+    /// the rest are random.
+    pub starts: usize,
+    /// Maximum number of coordinate search rounds per start.
+    pub max_rounds: usize,
+    /// Seed for the random starting points.
+    pub seed: Option<i64>,
+    /// Loss functions to minimize.
+    pub loss: Loss,
+    /// Penalties on the LLM weights.
+    pub regularization: Regularization,
+    /// Cost budget or penalty on the expected cost per execution.
+    pub cost: Cost,
+}
+
+/// The best weights found so far by [`fit`].
+#[derive(Debug, Clone)]
+pub struct Fit {
+    /// The best weights, normalized.
+    pub weights: Weights,
+    /// The mean loss of the best weights.
+    pub loss: f64,
+    /// The L1 penalty of the best weights.
+    pub l1: f64,
+    /// The L2 penalty of the best weights.
+    pub l2: f64,
+    /// The expected cost per execution of the best weights.
+    pub cost: f64,
+    /// The loss plus penalties of the best weights, which is minimized.
+    pub objective: f64,
+    /// Number of starts performed.
+    pub starts: usize,
+    /// Number of rounds performed, across all starts.
+    pub rounds: usize,
+}
+
+/// The loss, penalties and cost of some weights.
+#[derive(Debug, Clone, Copy)]
+struct Objective {
+    loss: f64,
+    l1: f64,
+    l2: f64,
+    cost: f64,
+    objective: f64,
+}
+
+impl Fit {
+    fn new(weights: Weights, objective: Objective) -> Self {
+        Self {
+            weights,
+            loss: objective.loss,
+            l1: objective.l1,
+            l2: objective.l2,
+            cost: objective.cost,
+            objective: objective.objective,
+            starts: 0,
+            rounds: 0,
+        }
+    }
+}
+
+/// Fits `initial` to `samples` by multi-start coordinate search.
+///
+/// Each round tries halving, doubling, zeroing and stepping every weight in
+/// turn, keeping any change that lowers the objective: the mean loss plus
+/// the regularization penalties of the LLM weights, plus the cost penalty
+/// and any overrun of the cost budget. LLMs are dropped by zeroing their
+/// weight, keeping two per Vector Completion task. A start ends once a
+/// round makes no improvement. `on_round` is called after every round with
+/// the best fit so far.
+pub fn fit(
+    samples: &[Sample],
+    initial: &Weights,
+    options: &FitOptions,
+    mut on_round: impl FnMut(&Fit),
+) -> Fit {
+    let mut rng = match options.seed {
+        Some(seed) => rand::rngs::StdRng::seed_from_u64(seed as u64),
+        None => rand::rngs::StdRng::from_rng(&mut rand::rng()),
+    };
+    let costs = Costs::new(samples);
+    let cost_penalty = options
+        .cost
+        .penalty
+        .and_then(|penalty| penalty.to_f64())
+        .unwrap_or(0.0);
+    let budget = options.cost.budget.and_then(|budget| budget.to_f64());
+    let evaluate = |weights: &Weights| {
+        let loss = mean_loss(samples, weights, &options.loss);
+        let (l1, l2) = weights.penalty(&options.regularization);
+        let cost = costs.expected(weights);
+        let overrun = budget.map_or(0.0, |budget| (cost - budget).max(0.0));
+        Objective {
+            loss,
+            l1,
+            l2,
+            cost,
+            objective: loss
+                + l1
+                + l2
+                + cost_penalty * cost
+                + BUDGET_OVERRUN_PENALTY * overrun,
+        }
+    };
+    let coordinates = initial.coordinates();
+    let mut best = Fit::new(initial.normalized(), evaluate(initial));
+    for start in 0..options.starts.max(1) {
+        best.starts += 1;
+        let mut current = if start == 0 {
+            initial.clone()
+        } else {
+            initial.randomized(&mut rng)
+        };
+        let mut current_objective = evaluate(&current);
+        for _ in 0..options.max_rounds {
+            best.rounds += 1;
+            let mut improved = false;
+            for coordinate in &coordinates {
+                let i = match coordinate {
+                    Coordinate::Task(_, i) | Coordinate::Llm(_, i) => *i,
+                };
+                let group = current.group_mut(coordinate);
+                let weight = group[i];
+                let step = group.iter().sum::<Decimal>()
+                    / Decimal::from(group.len());
+                let mut candidates = vec![
+                    Decimal::ZERO,
+                    weight / Decimal::TWO,
+                    weight * Decimal::TWO,
+                    weight + step,
+                    (weight - step).max(Decimal::ZERO),
+                ];
+                candidates.dedup();
+                for candidate in candidates {
+                    let group = current.group_mut(coordinate);
+                    let previous = group[i];
+                    let other_positive = group
+                        .iter()
+                        .enumerate()
+                        .filter(|(j, w)| *j != i && **w > Decimal::ZERO)
+                        .count();
+                    if candidate == previous
+                        || (candidate == Decimal::ZERO
+                            && other_positive < coordinate.min_positive())
+                    {
+                        continue;
+                    }
+                    group[i] = candidate;
+                    let objective = evaluate(&current);
+                    if objective.objective
+                        < current_objective.objective - MIN_IMPROVEMENT
+                    {
+                        current_objective = objective;
+                        improved = true;
+                    } else {
+                        current.group_mut(coordinate)[i] = previous;
+                    }
+                }
+            }
+            current = current.normalized();
+            if current_objective.objective < best.objective {
+                best = Fit {
+                    starts: best.starts,
+                    rounds: best.rounds,
+                    ..Fit::new(current.clone(), current_objective)
+                };
+            }
+            on_round(&best);
+            if !improved {
+                break;
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    fn vote(ensemble_index: u64, vote: [Decimal; 2]) -> Vote {
+        Vote {
+            model: format!("model-{}", ensemble_index),
+            ensemble_index,
+            flat_ensemble_index: ensemble_index,
+            prompt_id: String::new(),
+            tools_id: None,
+            responses_ids: vec![String::new(), String::new()],
+            vote: vote.to_vec(),
+            weight: Decimal::ONE,
+            retry: None,
+            from_cache: None,
+            from_rng: None,
+            completion_index: None,
+        }
+    }
+
+    fn ftp() -> functions::FunctionFlatTaskProfile {
+        functions::FunctionFlatTaskProfile {
+            path: Vec::new(),
+            full_function_id: None,
+            full_profile_id: None,
+            description: None,
+            input: objectiveai::functions::expression::Input::Object(
+                Default::default(),
+            ),
+            tasks: vec![Some(functions::FlatTaskProfile::VectorCompletion(
+                functions::VectorCompletionFlatTaskProfile {
+                    path: vec![0],
+                    ensemble: objectiveai::ensemble::EnsembleBase {
+                        llms: Vec::new(),
+                    },
+                    profile: vec![Decimal::ONE, Decimal::ONE],
+                    messages: Vec::new(),
+                    tools: None,
+                    responses: vec![
+                        objectiveai::chat::completions::request::RichContent::Text(
+                            "yes".to_string(),
+                        ),
+                        objectiveai::chat::completions::request::RichContent::Text(
+                            "no".to_string(),
+                        ),
+                    ],
+                    output:
+                        objectiveai::functions::expression::Expression::JMESPath(
+                            "output.scores[0]".to_string(),
+                        ),
+                    invert_output: false,
+                },
+            ))],
+            profile: vec![Decimal::ONE],
+            r#type: functions::FunctionType::Scalar,
+            task_output: None,
+            invert_output: false,
+            output: None,
+            reasoning_summary: None,
+        }
+    }
+
+    // LLM 0 always votes for the target, LLM 1 always votes against it.
+    fn sample(target: bool) -> Sample {
+        let (right, wrong) = if target {
+            ([Decimal::ONE, Decimal::ZERO], [Decimal::ZERO, Decimal::ONE])
+        } else {
+            ([Decimal::ZERO, Decimal::ONE], [Decimal::ONE, Decimal::ZERO])
+        };
+        Sample {
+            ftp: ftp(),
+            votes: HashMap::from([(vec![0], vec![vote(0, right), vote(1, wrong)])]),
+            calls: HashMap::new(),
+            target: Target::Scalar {
+                value: if target { Decimal::ONE } else { Decimal::ZERO },
+            },
+        }
+    }
+
+    fn initial() -> Weights {
+        Weights {
+            tasks: BTreeMap::from([(vec![], vec![Decimal::ONE])]),
+            llms: BTreeMap::from([(vec![0], vec![Decimal::ONE, Decimal::ONE])]),
+        }
+    }
+
+    #[test]
+    fn test_loss() {
+        let defaults = Loss::default();
+        let scalar = Target::Scalar { value: dec!(1) };
+        assert_eq!(
+            loss(&FunctionOutput::Scalar(dec!(0.5)), &scalar, &defaults),
+            0.25
+        );
+        assert_eq!(
+            loss(
+                &FunctionOutput::Err(serde_json::Value::Null),
+                &scalar,
+                &defaults
+            ),
+            1.0
+        );
+        let winner = Target::VectorWinner { value: 1 };
+        let output = FunctionOutput::Vector(vec![dec!(0.5), dec!(0.5)]);
+        assert!((loss(&output, &winner, &defaults) - 2f64.ln()).abs() < 1e-9);
+        let vector = Target::Vector {
+            value: vec![dec!(1), dec!(0)],
+        };
+        assert_eq!(loss(&output, &vector, &defaults), 0.5);
+    }
+
+    #[test]
+    fn test_selected_loss() {
+        let functions = Loss {
+            scalar: Some(ScalarLoss::AbsoluteError),
+            vector: Some(VectorLoss::CrossEntropy),
+            vector_winner: Some(VectorWinnerLoss::Hinge),
+            multi_label: None,
+            categorical: None,
+        };
+        let scalar = Target::Scalar { value: dec!(1) };
+        assert_eq!(
+            loss(&FunctionOutput::Scalar(dec!(0.5)), &scalar, &functions),
+            0.5
+        );
+        let output =
+            FunctionOutput::Vector(vec![dec!(0.5), dec!(0.3), dec!(0.2)]);
+        let vector = Target::Vector {
+            value: vec![dec!(1), dec!(0), dec!(0)],
+        };
+        assert!((loss(&output, &vector, &functions) - 2f64.ln()).abs() < 1e-9);
+        let winner = Target::VectorWinner { value: 0 };
+        assert!((loss(&output, &winner, &functions) - 0.8).abs() < 1e-9);
+        let ranking = Loss {
+            vector_winner: Some(VectorWinnerLoss::Ranking),
+            ..functions
+        };
+        assert!((loss(&output, &winner, &ranking) - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_label_loss() {
+        let defaults = Loss::default();
+        let output = FunctionOutput::MultiLabel(indexmap::IndexMap::from([
+            ("a".to_string(), dec!(0.5)),
+            ("b".to_string(), dec!(0.5)),
+        ]));
+        let multi_label = Target::MultiLabel {
+            value: vec!["a".to_string()],
+        };
+        assert!(
+            (loss(&output, &multi_label, &defaults) - 2f64.ln()).abs() < 1e-9
+        );
+        let squared = Loss {
+            multi_label: Some(MultiLabelLoss::SquaredError),
+            ..defaults
+        };
+        assert_eq!(loss(&output, &multi_label, &squared), 0.25);
+        let output = FunctionOutput::Categorical(indexmap::IndexMap::from([
+            ("a".to_string(), dec!(0.6)),
+            ("b".to_string(), dec!(0.3)),
+            ("c".to_string(), dec!(0.1)),
+        ]));
+        let categorical = Target::Categorical {
+            value: "b".to_string(),
+        };
+        assert!(
+            (loss(&output, &categorical, &defaults) + 0.3f64.ln()).abs()
+                < 1e-9
+        );
+        let hinge = Loss {
+            categorical: Some(CategoricalLoss::Hinge),
+            ..defaults
+        };
+        assert!((loss(&output, &categorical, &hinge) - 1.3).abs() < 1e-9);
+        assert_eq!(
+            loss(&FunctionOutput::Scalar(dec!(0.5)), &categorical, &hinge),
+            2.0
+        );
+    }
+
+    #[test]
+    fn test_sample_output() {
+        let sample = sample(true);
+        assert!(matches!(
+            sample.output(&initial()),
+            FunctionOutput::Scalar(s) if s == dec!(0.5)
+        ));
+        let mut weights = initial();
+        weights.llms.insert(vec![0], vec![dec!(3), dec!(1)]);
+        assert!(matches!(
+            sample.output(&weights),
+            FunctionOutput::Scalar(s) if s == dec!(0.75)
+        ));
+    }
+
+    #[test]
+    fn test_metrics() {
+        let samples = vec![sample(true), sample(false), sample(true)];
+        let metrics = Metrics::new(&samples, &initial(), &Loss::default());
+        assert_eq!(metrics.loss, 0.25);
+        assert_eq!(metrics.accuracy, None);
+        let calibration_error = metrics.calibration_error.unwrap();
+        assert!((calibration_error - 0.5 / 3.0).abs() < 1e-9);
+        let mut weights = initial();
+        weights.llms.insert(vec![0], vec![dec!(1), dec!(0)]);
+        let metrics = Metrics::new(&samples, &weights, &Loss::default());
+        assert_eq!(metrics.loss, 0.0);
+        assert_eq!(metrics.calibration_error, Some(0.0));
+    }
+
+    // three LLMs always vote for the target, at the given cost per call
+    fn redundant(target: bool, costs: [Decimal; 3]) -> Sample {
+        let right = if target {
+            [Decimal::ONE, Decimal::ZERO]
+        } else {
+            [Decimal::ZERO, Decimal::ONE]
+        };
+        Sample {
+            votes: HashMap::from([(
+                vec![0],
+                (0..3).map(|i| vote(i, right)).collect(),
+            )]),
+            calls: HashMap::from([(
+                vec![0],
+                (0..3).map(|i| (i, Some(costs[i as usize]))).collect(),
+            )]),
+            ..sample(target)
+        }
+    }
+
+    fn options() -> FitOptions {
+        FitOptions {
+            starts: 1,
+            max_rounds: 10,
+            seed: Some(0),
+            loss: Loss::default(),
+            regularization: Regularization::default(),
+            cost: Cost::default(),
+        }
+    }
+
+    fn initial_redundant() -> Weights {
+        Weights {
+            llms: BTreeMap::from([(vec![0], vec![Decimal::ONE; 3])]),
+            ..initial()
+        }
+    }
+
+    #[test]
+    fn test_fit_prefers_accurate_llm() {
+        let samples = vec![sample(true), sample(false), sample(true)];
+        let mut rounds = 0;
+        let fit = fit(
+            &samples,
+            &initial(),
+            &FitOptions {
+                starts: 2,
+                ..options()
+            },
+            |_| rounds += 1,
+        );
+        assert_eq!(fit.starts, 2);
+        assert_eq!(fit.rounds, rounds);
+        assert!(fit.loss < 1e-3);
+        // vector completions need two LLMs with positive weight
+        let llms = &fit.weights.llms[&vec![0]];
+        assert!(llms[0] > llms[1] && llms[1] > Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_regularization_drops_redundant_llm() {
+        let samples = vec![
+            redundant(true, [Decimal::ZERO; 3]),
+            redundant(false, [Decimal::ZERO; 3]),
+        ];
+        let mut options = options();
+        let fit = fit(&samples, &initial_redundant(), &options, |_| {});
+        assert!(!fit.weights.llms[&vec![0]].contains(&Decimal::ZERO));
+        options.regularization.l1 = Some(dec!(0.1));
+        let fit = super::fit(&samples, &initial_redundant(), &options, |_| {});
+        assert_eq!(fit.loss, 0.0);
+        assert!(fit.l1 < 0.2);
+        assert_eq!(fit.objective, fit.loss + fit.l1 + fit.l2);
+        // regularization drops one LLM, but never below the two positive
+        // weights vector completions require
+        let llms = &fit.weights.llms[&vec![0]];
+        assert_eq!(llms.iter().filter(|w| **w == Decimal::ZERO).count(), 1);
+        assert_eq!(llms.iter().filter(|w| **w > Decimal::ZERO).count(), 2);
+        options.regularization.l1 = Some(dec!(10));
+        let fit = super::fit(&samples, &initial_redundant(), &options, |_| {});
+        let llms = &fit.weights.llms[&vec![0]];
+        assert_eq!(llms.iter().filter(|w| **w > Decimal::ZERO).count(), 2);
+    }
+
+    #[test]
+    fn test_costs() {
+        let costs = [dec!(1), dec!(0.1), dec!(0.1)];
+        let samples = vec![redundant(true, costs), redundant(false, costs)];
+        let costs = Costs::new(&samples);
+        assert_eq!(costs.llms[&vec![0]], vec![1.0, 0.1, 0.1]);
+        assert!((costs.expected(&initial_redundant()) - 1.2).abs() < 1e-9);
+        let mut weights = initial_redundant();
+        weights.llms.insert(vec![0], vec![dec!(0), dec!(1), dec!(1)]);
+        assert!((costs.expected(&weights) - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cost_drops_expensive_llm() {
+        let costs = [dec!(1), dec!(0.1), dec!(0.1)];
+        let samples = vec![redundant(true, costs), redundant(false, costs)];
+        for cost in [
+            Cost {
+                budget: None,
+                penalty: Some(dec!(1)),
+            },
+            Cost {
+                budget: Some(dec!(0.5)),
+                penalty: None,
+            },
+        ] {
+            let options = FitOptions { cost, ..options() };
+            let fit = fit(&samples, &initial_redundant(), &options, |_| {});
+            assert_eq!(fit.loss, 0.0);
+            assert!((fit.cost - 0.2).abs() < 1e-9);
+            assert_eq!(fit.weights.llms[&vec![0]][0], Decimal::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_cost_budget_below_two_llms() {
+        // the cheapest valid ensemble still needs two positive LLM weights,
+        // so an unreachable budget is overrun rather than violated
+        let costs = [dec!(1), dec!(0.1), dec!(0.1)];
+        let samples = vec![redundant(true, costs), redundant(false, costs)];
+        let options = FitOptions {
+            cost: Cost {
+                budget: Some(dec!(0.05)),
+                penalty: None,
+            },
+            ..options()
+        };
+        let fit = fit(&samples, &initial_redundant(), &options, |_| {});
+        assert!((fit.cost - 0.2).abs() < 1e-9);
+        let llms = &fit.weights.llms[&vec![0]];
+        assert_eq!(llms[0], Decimal::ZERO);
+        assert_eq!(llms.iter().filter(|w| **w > Decimal::ZERO).count(), 2);
+    }
+
+    #[test]
+    fn test_to_profile() {
+        let ensemble = objectiveai::vector::completions::request::Ensemble::Id(
+            "ensemble".to_string(),
+        );
+        let profile = initial().to_profile(&ensemble);
+        assert_eq!(profile.tasks.len(), 1);
+        assert!(matches!(
+            &profile.tasks[0],
+            objectiveai::functions::TaskProfile::VectorCompletion { profile, .. }
+                if profile.len() == 2
+        ));
+    }
+}
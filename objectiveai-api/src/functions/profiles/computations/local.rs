@@ -0,0 +1,1205 @@
+//! In-process implementation of the Profile computation client.
+
+use super::fitting;
+use crate::{chat, ctx, functions, vector};
+use futures::{FutureExt, Stream, StreamExt, TryStreamExt};
+use rust_decimal::{Decimal, prelude::FromPrimitive};
+use std::{sync::Arc, time};
+
+/// Generates a unique response ID for a Profile computation.
+pub fn profile_computation_response_id(created: u64) -> String {
+    let uuid = uuid::Uuid::new_v4();
+    format!("prfcmp-{}-{}", uuid.simple(), created)
+}
+
+/// The Function execution client that executes the dataset.
+type ExecutionsClient<
+    CTXEXT,
+    FENSLLM,
+    CUSG,
+    FENS,
+    FVVOTE,
+    FCVOTE,
+    VUSG,
+    FFN,
+    FPFL,
+    FUSG,
+> = Arc<
+    functions::executions::Client<
+        CTXEXT,
+        FENSLLM,
+        CUSG,
+        FENS,
+        FVVOTE,
+        FCVOTE,
+        VUSG,
+        FFN,
+        FPFL,
+        FUSG,
+    >,
+>;
+
+/// Computes Profiles by executing the dataset locally and fitting weights.
+///
+/// Every dataset item is executed `n` times with uniform weights. The votes
+/// cast by each LLM are recorded, and the task and LLM weights are then
+/// fitted in-process by re-scoring those votes, without further LLM calls.
+pub struct LocalClient<
+    CTXEXT,
+    FENSLLM,
+    CUSG,
+    FENS,
+    FVVOTE,
+    FCVOTE,
+    VUSG,
+    FFN,
+    FPFL,
+    FUSG,
+    FDS,
+> {
+    /// Function execution client for executing the dataset.
+    pub executions_client: ExecutionsClient<
+        CTXEXT, FENSLLM, CUSG, FENS, FVVOTE, FCVOTE, VUSG, FFN, FPFL, FUSG,
+    >,
+    /// Store of uploaded datasets.
+    pub dataset_store: Arc<FDS>,
+    /// Number of starting points for the weight search.
+    pub starts: usize,
+    /// Maximum number of search rounds per starting point.
+    pub max_rounds: usize,
+    /// Maximum number of dataset executions in flight at once.
+    pub max_concurrency: usize,
+}
+
+impl<
+    CTXEXT,
+    FENSLLM,
+    CUSG,
+    FENS,
+    FVVOTE,
+    FCVOTE,
+    VUSG,
+    FFN,
+    FPFL,
+    FUSG,
+    FDS,
+>
+    LocalClient<
+        CTXEXT,
+        FENSLLM,
+        CUSG,
+        FENS,
+        FVVOTE,
+        FCVOTE,
+        VUSG,
+        FFN,
+        FPFL,
+        FUSG,
+        FDS,
+    >
+{
+    /// Creates a new local Profile computation client.
+        pub fn new(
+        executions_client: ExecutionsClient<
+            CTXEXT, FENSLLM, CUSG, FENS, FVVOTE, FCVOTE, VUSG, FFN, FPFL, FUSG,
+        >,
+        dataset_store: Arc<FDS>,
+        starts: usize,
+        max_rounds: usize,
+        max_concurrency: usize,
+    ) -> Self {
+        Self {
+            executions_client,
+            dataset_store,
+            starts,
+            max_rounds,
+            max_concurrency,
+        }
+    }
+}
+
+/// What a single dataset slot executes.
+struct Slot {
+    /// The Profile computation request.
+    request:
+        Arc<objectiveai::functions::profiles::computations::request::Request>,
+    /// `(owner, repository, commit)` of a remote Function.
+    function_full_id: Option<(String, String, String)>,
+    /// The Profile executed, with uniform weights.
+    profile: objectiveai::functions::InlineProfile,
+    /// The input of the slot's dataset item.
+    input: objectiveai::functions::expression::Input,
+    /// Index into the `dataset × n` slots.
+    index: usize,
+    /// Retry token to resume the first attempt from.
+    retry_token: Option<String>,
+}
+
+/// Progress of a single dataset execution.
+enum ExecutionEvent {
+    /// A chunk of an execution attempt.
+    Chunk(
+        objectiveai::functions::profiles::computations::response::streaming::FunctionExecutionChunk,
+    ),
+    /// The final attempt for a dataset slot has completed.
+    Done {
+        /// Index into the `dataset × n` slots.
+        slot: usize,
+        /// The final attempt, if it produced any chunks.
+        execution: Option<
+            objectiveai::functions::executions::response::unary::FunctionExecution,
+        >,
+        /// Retry token of the final attempt.
+        retry_token: Option<String>,
+    },
+}
+
+impl<
+    CTXEXT,
+    FENSLLM,
+    CUSG,
+    FENS,
+    FVVOTE,
+    FCVOTE,
+    VUSG,
+    FFN,
+    FPFL,
+    FUSG,
+    FDS,
+>
+    LocalClient<
+        CTXEXT,
+        FENSLLM,
+        CUSG,
+        FENS,
+        FVVOTE,
+        FCVOTE,
+        VUSG,
+        FFN,
+        FPFL,
+        FUSG,
+        FDS,
+    >
+where
+    CTXEXT: ctx::ContextExt + Send + Sync + 'static,
+    FENSLLM:
+        crate::ensemble_llm::fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    CUSG: chat::completions::usage_handler::UsageHandler<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FENS: crate::ensemble::fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    FVVOTE: vector::completions::completion_votes_fetcher::Fetcher<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FCVOTE: vector::completions::cache_vote_fetcher::Fetcher<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    VUSG: vector::completions::usage_handler::UsageHandler<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FFN: functions::function_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    FPFL: functions::profile_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    FUSG: functions::executions::usage_handler::UsageHandler<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FDS: super::dataset_store::Store<CTXEXT> + Send + Sync + 'static,
+{
+    /// Computes a Profile and returns the complete result.
+    pub async fn create_unary(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        request: Arc<
+            objectiveai::functions::profiles::computations::request::Request,
+        >,
+    ) -> Result<
+        objectiveai::functions::profiles::computations::response::unary::FunctionProfileComputation,
+        super::Error,
+    > {
+        let mut aggregate: Option<
+            objectiveai::functions::profiles::computations::response::streaming::FunctionProfileComputationChunk,
+        > = None;
+        let stream = self.create_streaming(ctx, request).await?;
+        futures::pin_mut!(stream);
+        while let Some(chunk) = stream.try_next().await? {
+            match &mut aggregate {
+                Some(aggregate) => aggregate.push(&chunk),
+                None => aggregate = Some(chunk),
+            }
+        }
+        Ok(aggregate.unwrap().into())
+    }
+
+    /// Computes a Profile with streaming progress updates.
+    ///
+    /// Streams the chunks of every dataset execution, then fitting progress,
+    /// and finally the fitted Profile with the retry token and usage.
+    pub async fn create_streaming(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        request: Arc<
+            objectiveai::functions::profiles::computations::request::Request,
+        >,
+    ) -> Result<
+        impl Stream<Item = Result<
+            objectiveai::functions::profiles::computations::response::streaming::FunctionProfileComputationChunk,
+            super::Error,
+        >>
+            + Send
+            + 'static,
+        super::Error,
+    > {
+        // timestamp the computation
+        let created = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let response_id = profile_computation_response_id(created);
+
+        // resolve the dataset, fetching uploaded datasets by ID
+        let body = request.base();
+        let dataset = match &body.dataset_id {
+            Some(_) if !body.dataset.is_empty() => {
+                return Err(super::Error::ConflictingDataset);
+            }
+            Some(id) => self
+                .dataset_store
+                .fetch(ctx.clone(), id)
+                .await
+                .map_err(super::Error::DatasetStore)?
+                .ok_or(super::Error::DatasetNotFound)?,
+            None => Arc::new(body.dataset.clone()),
+        };
+
+        // validate the request
+        if dataset.is_empty() {
+            return Err(super::Error::EmptyDataset);
+        }
+        if body.n == 0 {
+            return Err(super::Error::InvalidN);
+        }
+
+        // holdout items are executed alongside the dataset, after it
+        let mut items = dataset.to_vec();
+        if let Some(
+            objectiveai::functions::profiles::computations::request::Validation::Holdout {
+                dataset,
+            },
+        ) = &body.validation
+        {
+            items.extend(dataset.iter().cloned());
+        }
+        let splits = match &body.validation {
+            Some(validation) => super::validation::splits(
+                validation,
+                dataset.len(),
+                items.len() - dataset.len(),
+                body.seed,
+            )?,
+            None => Vec::new(),
+        };
+        let items = Arc::new(items);
+        let slots = items.len() * body.n as usize;
+        let retry_token = match &body.retry_token {
+            Some(token) => {
+                match objectiveai::functions::profiles::computations::RetryToken::try_from_string(token) {
+                    Some(token) if token.0.len() == slots => token,
+                    _ => return Err(super::Error::InvalidRetryToken),
+                }
+            }
+            None => objectiveai::functions::profiles::computations::RetryToken(
+                vec![None; slots],
+            ),
+        };
+
+        // resolve the function, pinning remote functions to a commit
+        let (function_full_id, function) = match &*request {
+            objectiveai::functions::profiles::computations::request::Request::FunctionInline {
+                body,
+            } => (
+                None,
+                objectiveai::functions::Function::Inline(body.function.clone()),
+            ),
+            objectiveai::functions::profiles::computations::request::Request::FunctionRemote {
+                path,
+                ..
+            } => {
+                let function = self
+                    .fetch_function(
+                        ctx.clone(),
+                        &path.fowner,
+                        &path.frepository,
+                        path.fcommit.as_deref(),
+                    )
+                    .await?;
+                (
+                    Some((
+                        function.owner,
+                        function.repository,
+                        function.commit,
+                    )),
+                    objectiveai::functions::Function::Remote(function.inner),
+                )
+            }
+        };
+        validate_inputs(&function, &items)?;
+        validate_targets(&function, &items)?;
+
+        // start from uniform weights
+        let llms_len = self.ensemble_llms_len(ctx.clone(), &body.ensemble).await?;
+        let mut initial = fitting::Weights::default();
+        self.insert_uniform_weights(
+            ctx.clone(),
+            function.tasks().to_vec(),
+            Vec::new(),
+            llms_len,
+            &mut initial,
+        )
+        .await?;
+        let profile = initial.to_profile(&body.ensemble);
+
+        // flatten the function for every dataset item up front, so that
+        // invalid inputs fail before anything is executed
+        let ftps = futures::future::try_join_all(
+            items.iter().enumerate().map(|(index, item)| {
+                functions::get_flat_task_profile(
+                    functions::FlatTaskProfileParams {
+                        ctx: ctx.clone(),
+                        function_fetcher: self.executions_client.function_fetcher.clone(),
+                        profile_fetcher: self.executions_client.profile_fetcher.clone(),
+                        ensemble_fetcher: self.executions_client.ensemble_fetcher.clone(),
+                        chat_client: self.executions_client.chat_client.clone(),
+                        request: None,
+                    },
+                    Vec::new(),
+                    functions::FunctionParam::FetchedOrInline {
+                        full_id: function_full_id.clone(),
+                        function: function.clone(),
+                    },
+                    functions::ProfileParam::FetchedOrInline {
+                        full_id: None,
+                        profile: objectiveai::functions::Profile::Inline(
+                            profile.clone(),
+                        ),
+                    },
+                    item.input.clone(),
+                    None,
+                    false,
+                )
+                .map(move |result| {
+                    result.map_err(|error| super::Error::InvalidDatasetItem {
+                        index,
+                        error,
+                    })
+                })
+            }),
+        )
+        .await?;
+
+        // execute every dataset item n times
+        let function_id = function_full_id
+            .as_ref()
+            .map(|(owner, repository, commit)| {
+                format!("{}/{}/{}", owner, repository, commit)
+            });
+        let attempts = Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let executions = futures::stream::iter(0..slots)
+            .map({
+                let executions_client = self.executions_client.clone();
+                let request = request.clone();
+                let function_full_id = function_full_id.clone();
+                let profile = profile.clone();
+                let retry_token = retry_token.clone();
+                let items = items.clone();
+                let n = body.n as usize;
+                move |slot| {
+                    Box::pin(Self::execute_slot(
+                        executions_client.clone(),
+                        ctx.clone(),
+                        Slot {
+                            request: request.clone(),
+                            function_full_id: function_full_id.clone(),
+                            profile: profile.clone(),
+                            input: items[slot / n].input.clone(),
+                            index: slot,
+                            retry_token: retry_token.0[slot].clone(),
+                        },
+                        attempts.clone(),
+                    ))
+                }
+            })
+            .flatten_unordered(self.max_concurrency.max(1));
+
+        let options = fitting::FitOptions {
+            starts: self.starts.max(1),
+            max_rounds: self.max_rounds,
+            seed: body.seed,
+            loss: body.loss.unwrap_or_default(),
+            regularization: body.regularization.unwrap_or_default(),
+            cost: body.cost.unwrap_or_default(),
+        };
+        let ensemble = body.ensemble.clone();
+        let n = body.n as usize;
+        Ok(async_stream::stream! {
+            let chunk = |
+                executions: Vec<
+                    objectiveai::functions::profiles::computations::response::streaming::FunctionExecutionChunk,
+                >
+            | objectiveai::functions::profiles::computations::response::streaming::FunctionProfileComputationChunk {
+                id: response_id.clone(),
+                executions,
+                executions_errors: None,
+                profile: None,
+                fitting_stats: None,
+                validation: None,
+                retry_token: None,
+                created,
+                function: function_id.clone(),
+                object: objectiveai::functions::profiles::computations::response::streaming::Object::FunctionProfileComputationChunk,
+                usage: None,
+            };
+
+            let mut stats =
+                objectiveai::functions::profiles::computations::response::FittingStats::default();
+            let mut retry_token = retry_token;
+            let mut results: Vec<
+                Option<objectiveai::functions::executions::response::unary::FunctionExecution>,
+            > = vec![None; slots];
+            let mut usage = objectiveai::vector::completions::response::Usage::default();
+            futures::pin_mut!(executions);
+            while let Some(event) = executions.next().await {
+                match event {
+                    ExecutionEvent::Chunk(execution) => {
+                        if let Some(execution_usage) = &execution.inner.usage {
+                            usage.push(execution_usage);
+                        }
+                        yield Ok(chunk(vec![execution]));
+                    }
+                    ExecutionEvent::Done { slot, execution, retry_token: slot_retry_token } => {
+                        stats.executions += 1;
+                        let failed = execution.as_ref().is_none_or(|execution| {
+                            execution.error.is_some() || execution.tasks_errors
+                        });
+                        if failed {
+                            stats.errors += 1;
+                        }
+                        retry_token.0[slot] = slot_retry_token;
+                        results[slot] = execution;
+                        let mut progress = chunk(Vec::new());
+                        progress.fitting_stats = Some(stats);
+                        if failed {
+                            progress.executions_errors = Some(true);
+                        }
+                        yield Ok(progress);
+                    }
+                }
+            }
+
+            // build samples from every execution that produced an output
+            let samples = results
+                .into_iter()
+                .enumerate()
+                .filter_map(|(slot, execution)| {
+                    let execution = execution?;
+                    if matches!(
+                        execution.output,
+                        objectiveai::functions::expression::FunctionOutput::Err(_),
+                    ) {
+                        return None;
+                    }
+                    let item = slot / n;
+                    Some((
+                        item,
+                        fitting::Sample::new(
+                            ftps[item].clone(),
+                            &execution,
+                            items[item].target.clone(),
+                        ),
+                    ))
+                })
+                .collect::<Vec<_>>();
+            if samples.is_empty() {
+                yield Err(super::Error::NoSuccessfulExecutions);
+                return;
+            }
+
+            // fit on a blocking thread, streaming progress after each round
+            // and each validated fold
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            let handle = tokio::task::spawn_blocking(move || {
+                fit_and_validate(&samples, &splits, &initial, &options, tx)
+            });
+            let mut folds = Vec::new();
+            while let Some(progress) = rx.recv().await {
+                let mut progress_chunk = chunk(Vec::new());
+                match progress {
+                    FitProgress::Round(fit) => {
+                        set_fit_stats(&mut stats, &fit);
+                        progress_chunk.fitting_stats = Some(stats);
+                    }
+                    FitProgress::Fold(fold) => {
+                        folds.push(fold);
+                        progress_chunk.validation =
+                            Some(validation_report(folds.clone()));
+                    }
+                }
+                yield Ok(progress_chunk);
+            }
+            let fit = match handle.await {
+                Ok(fit) => fit,
+                Err(e) => {
+                    yield Err(super::Error::Fitting(e.to_string()));
+                    return;
+                }
+            };
+            set_fit_stats(&mut stats, &fit);
+
+            let mut last = chunk(Vec::new());
+            last.profile = Some(fit.weights.to_profile(&ensemble));
+            last.fitting_stats = Some(stats);
+            if !folds.is_empty() {
+                last.validation = Some(validation_report(folds));
+            }
+            last.retry_token = Some(retry_token.to_string());
+            last.usage = Some(usage);
+            yield Ok(last);
+        })
+    }
+
+    /// Parses an uploaded dataset and stores it for later computations.
+    ///
+    /// The content is parsed as it arrives. If the params reference a
+    /// Function, every item is validated against its input schema.
+    pub async fn create_dataset(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        params: objectiveai::functions::profiles::computations::datasets::request::DatasetCreateParams,
+        mut content: super::DatasetContent,
+    ) -> Result<
+        objectiveai::functions::profiles::computations::datasets::response::Dataset,
+        super::Error,
+    > {
+        let function = match (&params.fowner, &params.frepository) {
+            (Some(owner), Some(repository)) => Some(
+                self.fetch_function(
+                    ctx.clone(),
+                    owner,
+                    repository,
+                    params.fcommit.as_deref(),
+                )
+                .await?,
+            ),
+            (None, None) if params.fcommit.is_none() => None,
+            _ => return Err(super::Error::InvalidDatasetFunction),
+        };
+
+        // parse as the content arrives, stopping at the first fatal error
+        let mut parser =
+            objectiveai::functions::profiles::computations::datasets::DatasetParser::new(
+                &params,
+            )?;
+        while let Some(chunk) = content.next().await {
+            parser.push(&chunk.map_err(super::Error::DatasetUpload)?);
+            if parser.failed() {
+                break;
+            }
+        }
+        let dataset = parser.finish()?;
+        if dataset.is_empty() {
+            return Err(super::Error::EmptyDataset);
+        }
+        if let Some(function) = function {
+            validate_inputs(
+                &objectiveai::functions::Function::Remote(function.inner),
+                &dataset,
+            )?;
+        }
+        self.dataset_store
+            .create(ctx, dataset)
+            .await
+            .map_err(super::Error::DatasetStore)
+    }
+
+    /// Executes a single dataset slot, retrying failed attempts.
+    ///
+    /// Each retry resumes from the previous attempt's retry token, so votes
+    /// that already succeeded are not requested again.
+    fn execute_slot(
+        executions_client: ExecutionsClient<
+            CTXEXT, FENSLLM, CUSG, FENS, FVVOTE, FCVOTE, VUSG, FFN, FPFL, FUSG,
+        >,
+        ctx: ctx::Context<CTXEXT>,
+        slot: Slot,
+        attempts: Arc<std::sync::atomic::AtomicU64>,
+    ) -> impl Stream<Item = ExecutionEvent> + Send + 'static {
+        let Slot {
+            request,
+            function_full_id,
+            profile,
+            input,
+            index: slot,
+            mut retry_token,
+        } = slot;
+        async_stream::stream! {
+            let body = request.base();
+            let n = body.n;
+            let max_retries = body.max_retries.unwrap_or(0);
+            let mut retry = 0;
+            loop {
+                let index = attempts
+                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                let execution_request = Arc::new(execution_request(
+                    &request,
+                    function_full_id.as_ref(),
+                    profile.clone(),
+                    input.clone(),
+                    retry_token.clone(),
+                ));
+                let mut aggregate: Option<
+                    objectiveai::functions::executions::response::streaming::FunctionExecutionChunk,
+                > = None;
+                if let Ok(mut stream) = executions_client
+                    .clone()
+                    .create_streaming_handle_usage(ctx.clone(), execution_request)
+                    .await
+                {
+                    while let Some(inner) = stream.next().await {
+                        match &mut aggregate {
+                            Some(aggregate) => aggregate.push(&inner),
+                            None => aggregate = Some(inner.clone()),
+                        }
+                        yield ExecutionEvent::Chunk(
+                            objectiveai::functions::profiles::computations::response::streaming::FunctionExecutionChunk {
+                                index,
+                                dataset: slot as u64 / n,
+                                n: slot as u64 % n,
+                                retry,
+                                inner,
+                            },
+                        );
+                    }
+                }
+                let execution: Option<
+                    objectiveai::functions::executions::response::unary::FunctionExecution,
+                > = aggregate.map(Into::into);
+                if let Some(token) = execution
+                    .as_ref()
+                    .and_then(|execution| execution.retry_token.clone())
+                {
+                    retry_token = Some(token);
+                }
+                let succeeded = execution.as_ref().is_some_and(|execution| {
+                    execution.error.is_none() && !execution.tasks_errors
+                });
+                if succeeded || retry >= max_retries {
+                    yield ExecutionEvent::Done {
+                        slot,
+                        execution,
+                        retry_token,
+                    };
+                    break;
+                }
+                retry += 1;
+            }
+        }
+    }
+
+    /// Fetches a Function, failing if it does not exist.
+    async fn fetch_function(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        owner: &str,
+        repository: &str,
+        commit: Option<&str>,
+    ) -> Result<objectiveai::functions::response::GetFunction, super::Error>
+    {
+        match self
+            .executions_client
+            .function_fetcher
+            .fetch(ctx, owner, repository, commit)
+            .await
+        {
+            Ok(Some(function)) => Ok(function),
+            Ok(None) => Err(functions::executions::Error::FunctionNotFound.into()),
+            Err(e) => Err(functions::executions::Error::FetchFunction(e).into()),
+        }
+    }
+
+    /// Returns the number of LLMs in the request's Ensemble.
+    async fn ensemble_llms_len(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        ensemble: &objectiveai::vector::completions::request::Ensemble,
+    ) -> Result<usize, super::Error> {
+        match ensemble {
+            objectiveai::vector::completions::request::Ensemble::Id(id) => {
+                match self.executions_client.ensemble_fetcher.fetch(ctx, id).await {
+                    Ok(Some((ensemble, _))) => Ok(ensemble.llms.len()),
+                    Ok(None) => {
+                        Err(functions::executions::Error::EnsembleNotFound.into())
+                    }
+                    Err(e) => {
+                        Err(functions::executions::Error::FetchEnsemble(e).into())
+                    }
+                }
+            }
+            objectiveai::vector::completions::request::Ensemble::Provided(
+                ensemble,
+            ) => {
+                let ensemble: objectiveai::ensemble::Ensemble = ensemble
+                    .clone()
+                    .try_into()
+                    .map_err(functions::executions::Error::InvalidEnsemble)?;
+                Ok(ensemble.llms.len())
+            }
+        }
+    }
+
+    /// Inserts uniform weights for `tasks` at `path`, recursing into nested
+    /// Functions.
+    fn insert_uniform_weights<'a>(
+        &'a self,
+        ctx: ctx::Context<CTXEXT>,
+        tasks: Vec<objectiveai::functions::TaskExpression>,
+        path: fitting::ProfilePath,
+        llms_len: usize,
+        weights: &'a mut fitting::Weights,
+    ) -> futures::future::BoxFuture<'a, Result<(), super::Error>> {
+        async move {
+            weights
+                .tasks
+                .insert(path.clone(), vec![Decimal::ONE; tasks.len()]);
+            for (i, task) in tasks.into_iter().enumerate() {
+                let mut path = path.clone();
+                path.push(i as u64);
+                if !task.dependencies().is_empty() {
+                    return Err(super::Error::UnsupportedDependentTask);
+                }
+                let (owner, repository, commit) = match task {
+                    objectiveai::functions::TaskExpression::VectorCompletion(_) => {
+                        weights.llms.insert(path, vec![Decimal::ONE; llms_len]);
+                        continue;
+                    }
+                    objectiveai::functions::TaskExpression::ChatCompletion(_) => {
+                        return Err(super::Error::UnsupportedChatCompletionTask);
+                    }
+                    objectiveai::functions::TaskExpression::ScalarFunction(
+                        task,
+                    ) => (task.owner, task.repository, task.commit),
+                    objectiveai::functions::TaskExpression::VectorFunction(
+                        task,
+                    ) => (task.owner, task.repository, task.commit),
+                };
+                let function = self
+                    .fetch_function(
+                        ctx.clone(),
+                        &owner,
+                        &repository,
+                        Some(&commit),
+                    )
+                    .await?;
+                self.insert_uniform_weights(
+                    ctx.clone(),
+                    function.inner.tasks().to_vec(),
+                    path,
+                    llms_len,
+                    weights,
+                )
+                .await?;
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+/// Progress of [`fit_and_validate`].
+enum FitProgress {
+    /// A search round of the fit in progress has completed, with the best
+    /// fit so far. Its weights are omitted.
+    Round(fitting::Fit),
+    /// A validation fold has been fitted and evaluated.
+    Fold(
+        objectiveai::functions::profiles::computations::response::ValidationFold,
+    ),
+}
+
+/// Fits the final weights, first fitting and evaluating every split.
+///
+/// With a single split, the final weights are those fitted on its train
+/// items. With k folds, they are fitted again on every dataset item.
+fn fit_and_validate(
+    samples: &[(usize, fitting::Sample)],
+    splits: &[super::validation::Split],
+    initial: &fitting::Weights,
+    options: &fitting::FitOptions,
+    tx: tokio::sync::mpsc::UnboundedSender<FitProgress>,
+) -> fitting::Fit {
+    let mut on_round = |fit: &fitting::Fit| {
+        let _ = tx.send(FitProgress::Round(fitting::Fit {
+            weights: fitting::Weights::default(),
+            ..*fit
+        }));
+    };
+    let select = |items: &[usize]| {
+        samples
+            .iter()
+            .filter(|(item, _)| items.binary_search(item).is_ok())
+            .map(|(_, sample)| sample.clone())
+            .collect::<Vec<_>>()
+    };
+    let mut fits = Vec::with_capacity(splits.len());
+    for (fold, split) in splits.iter().enumerate() {
+        let train = select(&split.train);
+        let validation = select(&split.validation);
+        let fit = fitting::fit(&train, initial, options, &mut on_round);
+        let _ = tx.send(FitProgress::Fold(
+            objectiveai::functions::profiles::computations::response::ValidationFold {
+                fold,
+                train_items: split.train.len(),
+                validation_items: split.validation.len(),
+                train: validation_metrics(fitting::Metrics::new(
+                    &train,
+                    &fit.weights,
+                    &options.loss,
+                )),
+                validation: validation_metrics(fitting::Metrics::new(
+                    &validation,
+                    &fit.weights,
+                    &options.loss,
+                )),
+            },
+        ));
+        fits.push(fit);
+    }
+    if fits.len() == 1 {
+        return fits.pop().unwrap();
+    }
+    let samples = samples
+        .iter()
+        .map(|(_, sample)| sample.clone())
+        .collect::<Vec<_>>();
+    fitting::fit(&samples, initial, options, on_round)
+}
+
+/// Summarizes the validated folds, averaging their validation metrics.
+fn validation_report(
+    folds: Vec<
+        objectiveai::functions::profiles::computations::response::ValidationFold,
+    >,
+) -> objectiveai::functions::profiles::computations::response::ValidationReport
+{
+    fn mean(values: impl Iterator<Item = Decimal>) -> Option<Decimal> {
+        let (sum, count) = values
+            .fold((Decimal::ZERO, 0), |(sum, count), value| {
+                (sum + value, count + 1)
+            });
+        (count > 0).then(|| sum / Decimal::from(count))
+    }
+    let validation =
+        objectiveai::functions::profiles::computations::response::ValidationMetrics {
+            loss: mean(folds.iter().map(|fold| fold.validation.loss))
+                .unwrap_or_default(),
+            accuracy: mean(
+                folds.iter().filter_map(|fold| fold.validation.accuracy),
+            ),
+            calibration_error: mean(
+                folds
+                    .iter()
+                    .filter_map(|fold| fold.validation.calibration_error),
+            ),
+        };
+    objectiveai::functions::profiles::computations::response::ValidationReport {
+        folds,
+        validation,
+    }
+}
+
+fn validation_metrics(
+    metrics: fitting::Metrics,
+) -> objectiveai::functions::profiles::computations::response::ValidationMetrics
+{
+    objectiveai::functions::profiles::computations::response::ValidationMetrics {
+        loss: decimal(metrics.loss),
+        accuracy: metrics.accuracy.map(decimal),
+        calibration_error: metrics.calibration_error.map(decimal),
+    }
+}
+
+fn set_fit_stats(
+    stats: &mut objectiveai::functions::profiles::computations::response::FittingStats,
+    fit: &fitting::Fit,
+) {
+    stats.loss = decimal(fit.loss);
+    stats.l1 = decimal(fit.l1);
+    stats.l2 = decimal(fit.l2);
+    stats.cost = decimal(fit.cost);
+    stats.objective = decimal(fit.objective);
+    stats.starts = fit.starts;
+    stats.rounds = fit.rounds;
+}
+
+fn decimal(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default()
+}
+
+/// Builds the execution request for one dataset item under `profile`.
+fn execution_request(
+    request: &objectiveai::functions::profiles::computations::request::Request,
+    function_full_id: Option<&(String, String, String)>,
+    profile: objectiveai::functions::InlineProfile,
+    input: objectiveai::functions::expression::Input,
+    retry_token: Option<String>,
+) -> objectiveai::functions::executions::request::Request {
+    let body = request.base();
+    let base = objectiveai::functions::executions::request::FunctionRemoteProfileRemoteRequestBody {
+        retry_token,
+        from_cache: body.from_cache,
+        from_rng: body.from_rng,
+        cache: None,
+        reasoning: None,
+        strategy: None,
+        top_k: None,
+        input,
+        provider: body.provider,
+        seed: body.seed,
+        stream: Some(true),
+        dry_run: None,
+        max_cost: None,
+        attribution: None,
+        backoff_max_elapsed_time: body.backoff_max_elapsed_time,
+        first_chunk_timeout: body.first_chunk_timeout,
+        other_chunk_timeout: body.other_chunk_timeout,
+    };
+    match (request.inline_function(), function_full_id) {
+        (Some(function), _) => {
+            objectiveai::functions::executions::request::Request::FunctionInlineProfileInline {
+                body: objectiveai::functions::executions::request::FunctionInlineProfileInlineRequestBody {
+                    function: function.clone(),
+                    profile,
+                    base,
+                },
+            }
+        }
+        (None, Some((owner, repository, commit))) => {
+            objectiveai::functions::executions::request::Request::FunctionRemoteProfileInline {
+                path: objectiveai::functions::executions::request::FunctionRemoteProfileInlineRequestPath {
+                    fowner: owner.clone(),
+                    frepository: repository.clone(),
+                    fcommit: Some(commit.clone()),
+                },
+                body: objectiveai::functions::executions::request::FunctionRemoteProfileInlineRequestBody {
+                    profile,
+                    base,
+                },
+            }
+        }
+        (None, None) => unreachable!(),
+    }
+}
+
+/// Checks every dataset input against the Function's input schema, reporting
+/// each invalid item.
+fn validate_inputs(
+    function: &objectiveai::functions::Function,
+    dataset: &[objectiveai::functions::profiles::computations::request::DatasetItem],
+) -> Result<(), super::Error> {
+    let Some(input_schema) = function.input_schema() else {
+        return Ok(());
+    };
+    let rows = dataset
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| !input_schema.validate_input(&item.input))
+        .map(|(index, _)| {
+            objectiveai::functions::profiles::computations::datasets::RowError {
+                index,
+                message: "input does not match the Function's input schema"
+                    .to_string(),
+            }
+        })
+        .collect::<Vec<_>>();
+    if rows.is_empty() {
+        Ok(())
+    } else {
+        Err(super::Error::InvalidDataset(
+            objectiveai::functions::profiles::computations::datasets::DatasetError::Inputs(rows),
+        ))
+    }
+}
+
+/// Checks that every dataset target matches the Function's output type.
+fn validate_targets(
+    function: &objectiveai::functions::Function,
+    dataset: &[objectiveai::functions::profiles::computations::request::DatasetItem],
+) -> Result<(), super::Error> {
+    use objectiveai::functions::profiles::computations::request::Target;
+    use objectiveai::functions::{Function, InlineFunction, RemoteFunction};
+    let function_type = match function {
+        Function::Remote(RemoteFunction::Scalar { .. })
+        | Function::Inline(InlineFunction::Scalar { .. }) => "scalar",
+        Function::Remote(RemoteFunction::Vector { .. })
+        | Function::Inline(InlineFunction::Vector { .. }) => "vector",
+        Function::Remote(RemoteFunction::MultiLabel { .. })
+        | Function::Inline(InlineFunction::MultiLabel { .. }) => "multi-label",
+        Function::Remote(RemoteFunction::Categorical { .. })
+        | Function::Inline(InlineFunction::Categorical { .. }) => "categorical",
+    };
+    let labels = function.labels().unwrap_or_default();
+    for (index, item) in dataset.iter().enumerate() {
+        let message = match (&item.target, function_type) {
+            (Target::Scalar { value }, "scalar") => {
+                if *value < Decimal::ZERO || *value > Decimal::ONE {
+                    Some("scalar target must be between 0 and 1".to_string())
+                } else {
+                    None
+                }
+            }
+            (Target::Vector { value }, "vector") => {
+                let sum: Decimal = value.iter().sum();
+                if value.iter().any(|v| *v < Decimal::ZERO)
+                    || (sum - Decimal::ONE).abs() > rust_decimal::dec!(0.01)
+                {
+                    Some("vector target must be non-negative and sum to 1".to_string())
+                } else {
+                    None
+                }
+            }
+            (Target::VectorWinner { .. }, "vector") => None,
+            (Target::MultiLabel { value }, "multi-label") => value
+                .iter()
+                .find(|label| !labels.contains(label))
+                .map(|label| format!("unknown label \"{}\"", label)),
+            (Target::Categorical { value }, "categorical") => {
+                (!labels.contains(value))
+                    .then(|| format!("unknown category \"{}\"", value))
+            }
+            (target, function_type) => Some(format!(
+                "{} target for a {} function",
+                match target {
+                    Target::Scalar { .. } => "scalar",
+                    Target::Vector { .. } | Target::VectorWinner { .. } => "vector",
+                    Target::MultiLabel { .. } => "multi-label",
+                    Target::Categorical { .. } => "categorical",
+                },
+                function_type,
+            )),
+        };
+        if let Some(message) = message {
+            return Err(super::Error::InvalidTarget { index, message });
+        }
+    }
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl<
+    CTXEXT,
+    FENSLLM,
+    CUSG,
+    FENS,
+    FVVOTE,
+    FCVOTE,
+    VUSG,
+    FFN,
+    FPFL,
+    FUSG,
+    FDS,
+>
+    super::Client<CTXEXT>
+    for LocalClient<
+        CTXEXT,
+        FENSLLM,
+        CUSG,
+        FENS,
+        FVVOTE,
+        FCVOTE,
+        VUSG,
+        FFN,
+        FPFL,
+        FUSG,
+        FDS,
+    >
+where
+    CTXEXT: ctx::ContextExt + Send + Sync + 'static,
+    FENSLLM:
+        crate::ensemble_llm::fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    CUSG: chat::completions::usage_handler::UsageHandler<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FENS: crate::ensemble::fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    FVVOTE: vector::completions::completion_votes_fetcher::Fetcher<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FCVOTE: vector::completions::cache_vote_fetcher::Fetcher<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    VUSG: vector::completions::usage_handler::UsageHandler<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FFN: functions::function_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    FPFL: functions::profile_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    FUSG: functions::executions::usage_handler::UsageHandler<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FDS: super::dataset_store::Store<CTXEXT> + Send + Sync + 'static,
+{
+    async fn create_unary(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        request: Arc<
+            objectiveai::functions::profiles::computations::request::Request,
+        >,
+    ) -> Result<
+        objectiveai::functions::profiles::computations::response::unary::FunctionProfileComputation,
+        objectiveai::error::ResponseError,
+    >{
+        LocalClient::create_unary(self, ctx, request)
+            .await
+            .map_err(|e| objectiveai::error::ResponseError::from(&e))
+    }
+
+    async fn create_streaming(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        request: Arc<
+            objectiveai::functions::profiles::computations::request::Request,
+        >,
+    ) -> Result<
+        impl Stream<Item = Result<
+            objectiveai::functions::profiles::computations::response::streaming::FunctionProfileComputationChunk,
+            objectiveai::error::ResponseError,
+        >>
+            + Send
+            + 'static,
+        objectiveai::error::ResponseError,
+    >{
+        let stream = LocalClient::create_streaming(self, ctx, request)
+            .await
+            .map_err(|e| objectiveai::error::ResponseError::from(&e))?;
+        Ok(stream.map_err(|e| objectiveai::error::ResponseError::from(&e)))
+    }
+
+    async fn create_dataset(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        params: objectiveai::functions::profiles::computations::datasets::request::DatasetCreateParams,
+        content: super::DatasetContent,
+    ) -> Result<
+        objectiveai::functions::profiles::computations::datasets::response::Dataset,
+        objectiveai::error::ResponseError,
+    > {
+        LocalClient::create_dataset(self, ctx, params, content)
+            .await
+            .map_err(|e| objectiveai::error::ResponseError::from(&e))
+    }
+}
@@ -1,9 +1,21 @@
 //! Profile computation client.
 //!
-//! Provides client for computing (training) Profiles from datasets.
+//! Provides clients for computing (training) Profiles from datasets, either
+//! locally by executing the dataset and fitting weights in-process, or by
+//! proxying to the ObjectiveAI API.
 
 mod client;
+/// Storage for uploaded datasets.
+pub mod dataset_store;
+mod error;
+/// Weight fitting over recorded executions.
+pub mod fitting;
+mod local;
 mod objectiveai;
+/// Train/validation splits of a dataset.
+pub mod validation;
 
 pub use client::*;
+pub use error::*;
+pub use local::*;
 pub use objectiveai::*;
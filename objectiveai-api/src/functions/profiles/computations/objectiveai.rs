@@ -63,4 +63,22 @@ where
         .map_err(|e| objectiveai::error::ResponseError::from(&e))?;
         Ok(stream.map_err(|e| objectiveai::error::ResponseError::from(&e)))
     }
+
+    async fn create_dataset(
+        &self,
+        _ctx: ctx::Context<CTXEXT>,
+        params: objectiveai::functions::profiles::computations::datasets::request::DatasetCreateParams,
+        content: super::DatasetContent,
+    ) -> Result<
+        objectiveai::functions::profiles::computations::datasets::response::Dataset,
+        objectiveai::error::ResponseError,
+    > {
+        objectiveai::functions::profiles::computations::datasets::upload_dataset(
+            &self.client,
+            params,
+            reqwest::Body::wrap_stream(content),
+        )
+        .await
+        .map_err(|e| objectiveai::error::ResponseError::from(&e))
+    }
 }
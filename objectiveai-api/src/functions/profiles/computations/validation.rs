@@ -0,0 +1,153 @@
+//! Train/validation splits of a Profile computation dataset.
+
+use objectiveai::functions::profiles::computations::request::Validation;
+use rand::{SeedableRng, seq::SliceRandom};
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+
+/// Dataset item indices to fit on and to validate against.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Split {
+    /// Items the weights are fitted on.
+    pub train: Vec<usize>,
+    /// Items the fitted weights are validated against.
+    pub validation: Vec<usize>,
+}
+
+/// Splits the dataset into train/validation folds.
+///
+/// `len` is the length of the request's dataset. Holdout items are indexed
+/// after it, from `len` to `len + holdout_len`. Fractions and folds shuffle
+/// the dataset with `seed`, so the same seed always yields the same splits.
+pub fn splits(
+    validation: &Validation,
+    len: usize,
+    holdout_len: usize,
+    seed: Option<i64>,
+) -> Result<Vec<Split>, super::Error> {
+    let shuffled = || {
+        let mut indices = (0..len).collect::<Vec<_>>();
+        match seed {
+            Some(seed) => indices
+                .shuffle(&mut rand::rngs::StdRng::seed_from_u64(seed as u64)),
+            None => indices.shuffle(&mut rand::rng()),
+        }
+        indices
+    };
+    match validation {
+        Validation::Holdout { .. } => {
+            if holdout_len == 0 {
+                return Err(super::Error::InvalidValidation(
+                    "holdout dataset is empty".to_string(),
+                ));
+            }
+            Ok(vec![Split {
+                train: (0..len).collect(),
+                validation: (len..len + holdout_len).collect(),
+            }])
+        }
+        Validation::Fraction { fraction } => {
+            if *fraction <= Decimal::ZERO || *fraction >= Decimal::ONE {
+                return Err(super::Error::InvalidValidation(
+                    "fraction must be between 0 and 1".to_string(),
+                ));
+            }
+            if len < 2 {
+                return Err(super::Error::InvalidValidation(
+                    "dataset must have at least 2 items".to_string(),
+                ));
+            }
+            let validation_len = (*fraction * Decimal::from(len))
+                .round()
+                .to_usize()
+                .unwrap_or_default()
+                .clamp(1, len - 1);
+            let mut train = shuffled();
+            let mut validation = train.split_off(len - validation_len);
+            train.sort_unstable();
+            validation.sort_unstable();
+            Ok(vec![Split { train, validation }])
+        }
+        Validation::KFold { k } => {
+            if *k < 2 || *k > len {
+                return Err(super::Error::InvalidValidation(format!(
+                    "k must be between 2 and the dataset length ({})",
+                    len
+                )));
+            }
+            let shuffled = shuffled();
+            Ok((0..*k)
+                .map(|fold| {
+                    let (mut validation, mut train): (Vec<_>, Vec<_>) =
+                        shuffled
+                            .iter()
+                            .enumerate()
+                            .partition(|(i, _)| i % k == fold);
+                    validation.sort_unstable_by_key(|(_, item)| *item);
+                    train.sort_unstable_by_key(|(_, item)| *item);
+                    Split {
+                        train: train.into_iter().map(|(_, item)| *item).collect(),
+                        validation: validation
+                            .into_iter()
+                            .map(|(_, item)| *item)
+                            .collect(),
+                    }
+                })
+                .collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    #[test]
+    fn test_holdout() {
+        let validation = Validation::Holdout {
+            dataset: Vec::new(),
+        };
+        let splits = splits(&validation, 3, 2, None).unwrap();
+        assert_eq!(
+            splits,
+            vec![Split {
+                train: vec![0, 1, 2],
+                validation: vec![3, 4],
+            }]
+        );
+        assert!(super::splits(&validation, 3, 0, None).is_err());
+    }
+
+    #[test]
+    fn test_fraction() {
+        let validation = Validation::Fraction {
+            fraction: dec!(0.25),
+        };
+        let splits = splits(&validation, 8, 0, Some(1)).unwrap();
+        assert_eq!(splits.len(), 1);
+        assert_eq!(splits[0].train.len(), 6);
+        assert_eq!(splits[0].validation.len(), 2);
+        assert_eq!(
+            splits,
+            super::splits(&validation, 8, 0, Some(1)).unwrap()
+        );
+        let invalid = Validation::Fraction { fraction: dec!(1) };
+        assert!(super::splits(&invalid, 8, 0, None).is_err());
+    }
+
+    #[test]
+    fn test_k_fold() {
+        let splits = splits(&Validation::KFold { k: 3 }, 7, 0, Some(0)).unwrap();
+        assert_eq!(splits.len(), 3);
+        let mut validated = splits
+            .iter()
+            .flat_map(|split| {
+                assert_eq!(split.train.len() + split.validation.len(), 7);
+                split.validation.clone()
+            })
+            .collect::<Vec<_>>();
+        validated.sort_unstable();
+        assert_eq!(validated, (0..7).collect::<Vec<_>>());
+        assert!(super::splits(&Validation::KFold { k: 8 }, 7, 0, None).is_err());
+    }
+}
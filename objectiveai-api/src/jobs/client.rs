@@ -0,0 +1,616 @@
+//! Job client.
+
+use crate::{chat, ctx, functions, vector};
+use futures::{Stream, StreamExt};
+use std::{
+    sync::{Arc, Mutex},
+    time,
+};
+
+/// Generates a unique ID for a job.
+pub fn job_id(created: u64) -> String {
+    let uuid = uuid::Uuid::new_v4();
+    format!("job-{}-{}", uuid.simple(), created)
+}
+
+fn now() -> u64 {
+    time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// The in-memory state of a job.
+#[derive(Debug)]
+pub struct JobState {
+    /// The persisted state of the job.
+    pub record: super::store::JobRecord,
+    /// Every chunk recorded by the job, across all attempts.
+    pub chunks: Vec<objectiveai::jobs::response::JobChunk>,
+    /// Aborts the task running the current attempt.
+    pub abort: Option<tokio::task::AbortHandle>,
+}
+
+/// A job tracked by the client.
+#[derive(Debug)]
+pub struct JobEntry {
+    /// The job's state.
+    pub state: Mutex<JobState>,
+    /// Notified whenever a chunk is recorded or the job finishes.
+    pub changed: tokio::sync::watch::Sender<()>,
+}
+
+/// The Function execution client that runs execution jobs.
+type ExecutionsClient<
+    CTXEXT,
+    FENSLLM,
+    CUSG,
+    FENS,
+    FVVOTE,
+    FCVOTE,
+    VUSG,
+    FFN,
+    FPFL,
+    FUSG,
+> = Arc<
+    functions::executions::Client<
+        CTXEXT,
+        FENSLLM,
+        CUSG,
+        FENS,
+        FVVOTE,
+        FCVOTE,
+        VUSG,
+        FFN,
+        FPFL,
+        FUSG,
+    >,
+>;
+
+/// Runs Function executions and Profile computations as durable jobs.
+///
+/// Every chunk is persisted to the job store as it is produced. Jobs that
+/// were still running when the server stopped are resumed by [`resume`],
+/// re-submitting the request with the latest retry token the job produced,
+/// so that votes which were already cast are not paid for again. Jobs
+/// submitted with BYOK credentials fail instead, as credentials are never
+/// persisted.
+///
+/// A job is only accessible to the caller that submitted it, as identified
+/// by [`ContextExt::caller`](ctx::ContextExt::caller).
+///
+/// [`resume`]: Client::resume
+pub struct Client<
+    CTXEXT,
+    FENSLLM,
+    CUSG,
+    FENS,
+    FVVOTE,
+    FCVOTE,
+    VUSG,
+    FFN,
+    FPFL,
+    FUSG,
+    PCOMP,
+    FJS,
+> {
+    /// Function execution client for execution jobs.
+    pub executions_client: ExecutionsClient<
+        CTXEXT, FENSLLM, CUSG, FENS, FVVOTE, FCVOTE, VUSG, FFN, FPFL, FUSG,
+    >,
+    /// Profile computation client for computation jobs.
+    pub computations_client: Arc<PCOMP>,
+    /// Persistent job store.
+    pub store: Arc<FJS>,
+    /// How long finished jobs are kept before being deleted.
+    pub retention: time::Duration,
+    /// Jobs by ID.
+    pub jobs: dashmap::DashMap<String, Arc<JobEntry>>,
+}
+
+impl<
+    CTXEXT,
+    FENSLLM,
+    CUSG,
+    FENS,
+    FVVOTE,
+    FCVOTE,
+    VUSG,
+    FFN,
+    FPFL,
+    FUSG,
+    PCOMP,
+    FJS,
+>
+    Client<
+        CTXEXT,
+        FENSLLM,
+        CUSG,
+        FENS,
+        FVVOTE,
+        FCVOTE,
+        VUSG,
+        FFN,
+        FPFL,
+        FUSG,
+        PCOMP,
+        FJS,
+    >
+where
+    CTXEXT: ctx::ContextExt,
+{
+    /// Creates a new job client with no jobs loaded.
+    pub fn new(
+        executions_client: ExecutionsClient<
+            CTXEXT, FENSLLM, CUSG, FENS, FVVOTE, FCVOTE, VUSG, FFN, FPFL, FUSG,
+        >,
+        computations_client: Arc<PCOMP>,
+        store: Arc<FJS>,
+        retention: time::Duration,
+    ) -> Self {
+        Self {
+            executions_client,
+            computations_client,
+            store,
+            retention,
+            jobs: dashmap::DashMap::new(),
+        }
+    }
+
+    /// Returns a job, as not found unless the caller submitted it.
+    fn entry(
+        &self,
+        ctx: &ctx::Context<CTXEXT>,
+        id: &str,
+    ) -> Result<Arc<JobEntry>, super::Error> {
+        let entry = self
+            .jobs
+            .get(id)
+            .map(|entry| entry.clone())
+            .ok_or(super::Error::JobNotFound)?;
+        if entry.state.lock().unwrap().record.owner != ctx.ext.caller() {
+            return Err(super::Error::JobNotFound);
+        }
+        Ok(entry)
+    }
+
+    /// Gets the status of a job.
+    pub fn get(
+        &self,
+        ctx: &ctx::Context<CTXEXT>,
+        id: &str,
+    ) -> Result<objectiveai::jobs::response::Job, super::Error> {
+        let entry = self.entry(ctx, id)?;
+        let state = entry.state.lock().unwrap();
+        Ok(state.record.job.clone())
+    }
+
+    /// Streams a job's chunks from `offset`, following the job until it
+    /// finishes.
+    pub fn events(
+        &self,
+        ctx: &ctx::Context<CTXEXT>,
+        id: &str,
+        offset: usize,
+    ) -> Result<
+        impl Stream<Item = objectiveai::jobs::response::JobEvent>
+        + Send
+        + 'static,
+        super::Error,
+    > {
+        let entry = self.entry(ctx, id)?;
+        Ok(async_stream::stream! {
+            let mut changed = entry.changed.subscribe();
+            let mut offset = offset;
+            loop {
+                let (chunks, finished) = {
+                    let state = entry.state.lock().unwrap();
+                    (
+                        state.chunks.get(offset..).unwrap_or_default().to_vec(),
+                        state.record.job.status.is_finished(),
+                    )
+                };
+                for chunk in chunks {
+                    yield objectiveai::jobs::response::JobEvent { offset, chunk };
+                    offset += 1;
+                }
+                if finished || changed.changed().await.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Gets the final result of a completed job.
+    pub fn result(
+        &self,
+        ctx: &ctx::Context<CTXEXT>,
+        id: &str,
+    ) -> Result<objectiveai::jobs::response::JobResult, super::Error> {
+        let entry = self.entry(ctx, id)?;
+        let state = entry.state.lock().unwrap();
+        match state.record.job.status {
+            objectiveai::jobs::response::JobStatus::Running => {
+                Err(super::Error::JobRunning)
+            }
+            objectiveai::jobs::response::JobStatus::Cancelled => {
+                Err(super::Error::JobCancelled)
+            }
+            objectiveai::jobs::response::JobStatus::Failed => {
+                Err(super::Error::JobFailed(
+                    state.record.job.error.clone().unwrap_or_else(|| {
+                        objectiveai::error::ResponseError {
+                            code: 500,
+                            message: serde_json::Value::Null,
+                        }
+                    }),
+                ))
+            }
+            objectiveai::jobs::response::JobStatus::Completed => {
+                objectiveai::jobs::response::JobResult::from_chunks(
+                    &state.chunks[state.record.attempt_offset..],
+                )
+                .ok_or(super::Error::JobRunning)
+            }
+        }
+    }
+}
+
+impl<
+    CTXEXT,
+    FENSLLM,
+    CUSG,
+    FENS,
+    FVVOTE,
+    FCVOTE,
+    VUSG,
+    FFN,
+    FPFL,
+    FUSG,
+    PCOMP,
+    FJS,
+>
+    Client<
+        CTXEXT,
+        FENSLLM,
+        CUSG,
+        FENS,
+        FVVOTE,
+        FCVOTE,
+        VUSG,
+        FFN,
+        FPFL,
+        FUSG,
+        PCOMP,
+        FJS,
+    >
+where
+    CTXEXT: ctx::ContextExt + Send + Sync + 'static,
+    FENSLLM:
+        crate::ensemble_llm::fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    CUSG: chat::completions::usage_handler::UsageHandler<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FENS: crate::ensemble::fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    FVVOTE: vector::completions::completion_votes_fetcher::Fetcher<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FCVOTE: vector::completions::cache_vote_fetcher::Fetcher<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    VUSG: vector::completions::usage_handler::UsageHandler<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FFN: functions::function_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    FPFL: functions::profile_fetcher::Fetcher<CTXEXT> + Send + Sync + 'static,
+    FUSG: functions::executions::usage_handler::UsageHandler<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    PCOMP: functions::profiles::computations::Client<CTXEXT>
+        + Send
+        + Sync
+        + 'static,
+    FJS: super::store::Store + Send + Sync + 'static,
+{
+    /// Submits a job and starts running it in the background.
+    pub async fn create(
+        self: Arc<Self>,
+        ctx: ctx::Context<CTXEXT>,
+        params: objectiveai::jobs::request::JobCreateParams,
+    ) -> Result<objectiveai::jobs::response::Job, super::Error> {
+        self.purge().await?;
+
+        let created = now();
+        let byok = matches!(
+            ctx.ext
+                .get_byok(chat::completions::upstream::Upstream::OpenRouter)
+                .await,
+            Ok(Some(_))
+        );
+        let record = super::store::JobRecord {
+            job: objectiveai::jobs::response::Job {
+                id: job_id(created),
+                r#type: params.job_type(),
+                status: objectiveai::jobs::response::JobStatus::Running,
+                chunks: 0,
+                attempts: 1,
+                retry_token: None,
+                error: None,
+                created,
+                updated: created,
+                object: objectiveai::jobs::response::Object::Job,
+            },
+            params,
+            attempt_offset: 0,
+            owner: ctx.ext.caller(),
+            byok,
+        };
+        self.store
+            .save(&record)
+            .await
+            .map_err(super::Error::Store)?;
+
+        let job = record.job.clone();
+        let entry = Arc::new(JobEntry {
+            state: Mutex::new(JobState {
+                record,
+                chunks: Vec::new(),
+                abort: None,
+            }),
+            changed: tokio::sync::watch::Sender::new(()),
+        });
+        self.jobs.insert(job.id.clone(), entry.clone());
+        self.spawn(ctx, entry);
+        Ok(job)
+    }
+
+    /// Loads every persisted job, resuming those that were still running.
+    ///
+    /// Resumed jobs run with `ctx` rather than the context they were
+    /// submitted with, as request credentials are never persisted. Running
+    /// jobs submitted with BYOK credentials fail instead, keeping their
+    /// retry token so that they can be resubmitted.
+    pub async fn resume(
+        self: Arc<Self>,
+        ctx: ctx::Context<CTXEXT>,
+    ) -> Result<(), super::Error> {
+        let jobs = self.store.load().await.map_err(super::Error::Store)?;
+        for (mut record, chunks) in jobs {
+            record.job.chunks = chunks.len();
+            let mut running = !record.job.status.is_finished();
+            if running && record.byok {
+                running = false;
+                record.job.status =
+                    objectiveai::jobs::response::JobStatus::Failed;
+                record.job.error = Some(objectiveai::error::ResponseError::from(
+                    &super::Error::CredentialsUnavailable,
+                ));
+                record.job.updated = now();
+                self.store
+                    .save(&record)
+                    .await
+                    .map_err(super::Error::Store)?;
+            } else if running {
+                record.job.attempts += 1;
+                record.job.updated = now();
+                record.attempt_offset = chunks.len();
+                record.params.set_retry_token(record.job.retry_token.clone());
+                self.store
+                    .save(&record)
+                    .await
+                    .map_err(super::Error::Store)?;
+            }
+            let id = record.job.id.clone();
+            let entry = Arc::new(JobEntry {
+                state: Mutex::new(JobState {
+                    record,
+                    chunks,
+                    abort: None,
+                }),
+                changed: tokio::sync::watch::Sender::new(()),
+            });
+            self.jobs.insert(id, entry.clone());
+            if running {
+                self.clone().spawn(ctx.clone(), entry);
+            }
+        }
+        Ok(())
+    }
+
+    /// Cancels a running job. Finished jobs are left unchanged.
+    pub async fn cancel(
+        &self,
+        ctx: &ctx::Context<CTXEXT>,
+        id: &str,
+    ) -> Result<objectiveai::jobs::response::Job, super::Error> {
+        let entry = self.entry(ctx, id)?;
+        let record = {
+            let mut state = entry.state.lock().unwrap();
+            if state.record.job.status.is_finished() {
+                return Ok(state.record.job.clone());
+            }
+            if let Some(abort) = state.abort.take() {
+                abort.abort();
+            }
+            state.record.job.status =
+                objectiveai::jobs::response::JobStatus::Cancelled;
+            state.record.job.updated = now();
+            state.record.clone()
+        };
+        entry.changed.send_replace(());
+        self.store
+            .save(&record)
+            .await
+            .map_err(super::Error::Store)?;
+        Ok(record.job)
+    }
+
+    /// Deletes finished jobs older than the retention period.
+    async fn purge(&self) -> Result<(), super::Error> {
+        let cutoff = now().saturating_sub(self.retention.as_secs());
+        let expired = self
+            .jobs
+            .iter()
+            .filter(|entry| {
+                let state = entry.state.lock().unwrap();
+                state.record.job.status.is_finished()
+                    && state.record.job.updated < cutoff
+            })
+            .map(|entry| entry.key().clone())
+            .collect::<Vec<_>>();
+        for id in expired {
+            self.jobs.remove(&id);
+            self.store.delete(&id).await.map_err(super::Error::Store)?;
+        }
+        Ok(())
+    }
+
+    /// Runs the current attempt of a job on a background task.
+    fn spawn(self: Arc<Self>, ctx: ctx::Context<CTXEXT>, entry: Arc<JobEntry>) {
+        let mut state = entry.state.lock().unwrap();
+        let handle = tokio::spawn({
+            let entry = entry.clone();
+            async move { self.run(ctx, entry).await }
+        });
+        state.abort = Some(handle.abort_handle());
+    }
+
+    async fn run(&self, ctx: ctx::Context<CTXEXT>, entry: Arc<JobEntry>) {
+        let (id, params) = {
+            let state = entry.state.lock().unwrap();
+            (state.record.job.id.clone(), state.record.params.clone())
+        };
+        let result = match self.stream(ctx, params).await {
+            Ok(stream) => self.record(&id, &entry, stream).await,
+            Err(e) => Err(e),
+        };
+
+        // finish the job, unless it was cancelled in the meantime
+        let record = {
+            let mut state = entry.state.lock().unwrap();
+            if state.record.job.status.is_finished() {
+                return;
+            }
+            match result {
+                Ok(()) => {
+                    state.record.job.status =
+                        objectiveai::jobs::response::JobStatus::Completed;
+                }
+                Err(e) => {
+                    state.record.job.status =
+                        objectiveai::jobs::response::JobStatus::Failed;
+                    state.record.job.error = Some(e);
+                }
+            }
+            state.record.job.updated = now();
+            state.abort = None;
+            state.record.clone()
+        };
+        entry.changed.send_replace(());
+        // the job is finished in memory either way, and a resumed job
+        // would still find its retry token
+        let _ = self.store.save(&record).await;
+    }
+
+    /// Starts the work behind a job.
+    async fn stream(
+        &self,
+        ctx: ctx::Context<CTXEXT>,
+        params: objectiveai::jobs::request::JobCreateParams,
+    ) -> Result<
+        futures::stream::BoxStream<
+            'static,
+            Result<
+                objectiveai::jobs::response::JobChunk,
+                objectiveai::error::ResponseError,
+            >,
+        >,
+        objectiveai::error::ResponseError,
+    > {
+        match params {
+            objectiveai::jobs::request::JobCreateParams::FunctionExecution {
+                request,
+            } => Ok(self
+                .executions_client
+                .clone()
+                .create_streaming_handle_usage(ctx, Arc::new(request))
+                .await
+                .map_err(|e| objectiveai::error::ResponseError::from(&e))?
+                .map(|chunk| {
+                    Ok(objectiveai::jobs::response::JobChunk::FunctionExecution(
+                        chunk,
+                    ))
+                })
+                .boxed()),
+            objectiveai::jobs::request::JobCreateParams::FunctionProfileComputation {
+                request,
+            } => Ok(functions::profiles::computations::Client::create_streaming(
+                &*self.computations_client,
+                ctx,
+                Arc::new(request),
+            )
+            .await?
+            .map(|result| {
+                result.map(
+                    objectiveai::jobs::response::JobChunk::FunctionProfileComputation,
+                )
+            })
+            .boxed()),
+        }
+    }
+
+    /// Persists and publishes every chunk of the current attempt.
+    async fn record(
+        &self,
+        id: &str,
+        entry: &JobEntry,
+        mut stream: futures::stream::BoxStream<
+            'static,
+            Result<
+                objectiveai::jobs::response::JobChunk,
+                objectiveai::error::ResponseError,
+            >,
+        >,
+    ) -> Result<(), objectiveai::error::ResponseError> {
+        let mut any_chunks = false;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            self.store.append(id, &chunk).await?;
+            let record = {
+                let mut state = entry.state.lock().unwrap();
+                let retry_token = chunk.retry_token().map(str::to_owned);
+                state.chunks.push(chunk);
+                state.record.job.chunks = state.chunks.len();
+                match retry_token {
+                    Some(retry_token) => {
+                        state.record.job.retry_token = Some(retry_token);
+                        state.record.job.updated = now();
+                        Some(state.record.clone())
+                    }
+                    None => None,
+                }
+            };
+            entry.changed.send_replace(());
+            if let Some(record) = record {
+                self.store.save(&record).await?;
+            }
+            any_chunks = true;
+        }
+        if any_chunks {
+            Ok(())
+        } else {
+            Err(objectiveai::error::ResponseError {
+                code: 500,
+                message: serde_json::json!({
+                    "kind": "job",
+                    "error": "job produced no chunks",
+                }),
+            })
+        }
+    }
+}
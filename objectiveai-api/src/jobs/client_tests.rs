@@ -0,0 +1,453 @@
+//! Tests for the job client.
+//!
+//! These tests run Profile computation jobs against a mock computation
+//! client, persisting them to a file store in a temporary directory.
+
+use crate::{ctx, functions};
+use futures::{Stream, StreamExt, stream::BoxStream};
+use functions::executions::client_tests::{
+    MockContextExt, create_test_chat_client, create_test_function_client,
+    create_test_vector_client,
+};
+use rust_decimal::Decimal;
+use std::sync::{Arc, Mutex};
+
+// ============================================================================
+// Mock Types
+// ============================================================================
+
+/// Mock computation client that streams chunks without computing anything.
+#[derive(Debug, Default)]
+struct MockComputationsClient {
+    /// Whether the computation hangs after its first chunk.
+    hang: bool,
+    /// The retry token of every request received.
+    retry_tokens: Mutex<Vec<Option<String>>>,
+}
+
+#[async_trait::async_trait]
+impl functions::profiles::computations::Client<MockContextExt>
+    for MockComputationsClient
+{
+    async fn create_unary(
+        &self,
+        _ctx: ctx::Context<MockContextExt>,
+        _request: Arc<objectiveai::functions::profiles::computations::request::Request>,
+    ) -> Result<
+        objectiveai::functions::profiles::computations::response::unary::FunctionProfileComputation,
+        objectiveai::error::ResponseError,
+    > {
+        Err(unsupported())
+    }
+
+    async fn create_streaming(
+        &self,
+        _ctx: ctx::Context<MockContextExt>,
+        request: Arc<objectiveai::functions::profiles::computations::request::Request>,
+    ) -> Result<
+        impl Stream<Item = Result<
+            objectiveai::functions::profiles::computations::response::streaming::FunctionProfileComputationChunk,
+            objectiveai::error::ResponseError,
+        >>
+            + Send
+            + 'static,
+        objectiveai::error::ResponseError,
+    > {
+        let attempt = {
+            let mut retry_tokens = self.retry_tokens.lock().unwrap();
+            retry_tokens.push(request.base().retry_token.clone());
+            retry_tokens.len()
+        };
+        let stream: BoxStream<'static, _> = if self.hang {
+            futures::stream::iter([Ok(chunk(Some("token-hang")))])
+                .chain(futures::stream::pending())
+                .boxed()
+        } else {
+            let retry_token = format!("token-{}", attempt);
+            futures::stream::iter([
+                Ok(chunk(None)),
+                Ok(chunk(Some(&retry_token))),
+            ])
+            .boxed()
+        };
+        Ok(stream)
+    }
+
+    async fn create_dataset(
+        &self,
+        _ctx: ctx::Context<MockContextExt>,
+        _params: objectiveai::functions::profiles::computations::datasets::request::DatasetCreateParams,
+        _content: functions::profiles::computations::DatasetContent,
+    ) -> Result<
+        objectiveai::functions::profiles::computations::datasets::response::Dataset,
+        objectiveai::error::ResponseError,
+    > {
+        Err(unsupported())
+    }
+}
+
+/// The error returned by the computation client methods jobs never call.
+fn unsupported() -> objectiveai::error::ResponseError {
+    objectiveai::error::ResponseError {
+        code: 501,
+        message: serde_json::json!("not supported by the mock client"),
+    }
+}
+
+// ============================================================================
+// Type Aliases
+// ============================================================================
+
+type TestJobClient = super::Client<
+    MockContextExt,
+    functions::executions::client_tests::MockEnsembleLlmFetcher,
+    functions::executions::client_tests::MockChatUsageHandler,
+    functions::executions::client_tests::MockEnsembleFetcher,
+    functions::executions::client_tests::MockCompletionVotesFetcher,
+    functions::executions::client_tests::MockCacheVoteFetcher,
+    functions::executions::client_tests::MockVectorUsageHandler,
+    functions::executions::client_tests::MockFunctionFetcher,
+    functions::executions::client_tests::MockProfileFetcher,
+    functions::executions::client_tests::MockFunctionUsageHandler,
+    MockComputationsClient,
+    super::store::FileStore,
+>;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Creates a test context, identifying the caller by an optional BYOK key.
+fn create_test_context(byok: Option<&str>) -> ctx::Context<MockContextExt> {
+    ctx::Context::new(
+        Arc::new(MockContextExt {
+            byok: byok.map(str::to_owned),
+        }),
+        Decimal::ONE,
+    )
+}
+
+/// Creates a job client persisting jobs to `dir`.
+fn create_test_job_client(
+    dir: &std::path::Path,
+    computations_client: MockComputationsClient,
+) -> Arc<TestJobClient> {
+    let chat_client = create_test_chat_client();
+    let vector_client = create_test_vector_client(chat_client.clone());
+    Arc::new(super::Client::new(
+        create_test_function_client(chat_client, vector_client),
+        Arc::new(computations_client),
+        Arc::new(super::store::FileStore::new(dir).unwrap()),
+        std::time::Duration::from_secs(3600),
+    ))
+}
+
+fn test_dir() -> std::path::PathBuf {
+    std::env::temp_dir().join(super::job_id(0))
+}
+
+/// Removes a test directory. A job's final save may still be writing to it
+/// after its followers finish, so failures are ignored.
+fn remove_test_dir(dir: &std::path::Path) {
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+fn chunk(
+    retry_token: Option<&str>,
+) -> objectiveai::functions::profiles::computations::response::streaming::FunctionProfileComputationChunk{
+    objectiveai::functions::profiles::computations::response::streaming::FunctionProfileComputationChunk {
+        id: "prfcmp-test".to_string(),
+        executions: Vec::new(),
+        executions_errors: None,
+        profile: None,
+        fitting_stats: None,
+        validation: None,
+        retry_token: retry_token.map(str::to_owned),
+        created: 0,
+        function: None,
+        object: objectiveai::functions::profiles::computations::response::streaming::Object::FunctionProfileComputationChunk,
+        usage: None,
+    }
+}
+
+fn create_params() -> objectiveai::jobs::request::JobCreateParams {
+    serde_json::from_value(serde_json::json!({
+        "type": "function.profile.computation",
+        "request": {
+            "path": {
+                "fowner": "owner",
+                "frepository": "repository",
+                "fcommit": null,
+            },
+            "body": {
+                "dataset": [],
+                "n": 1,
+                "ensemble": "ensemble",
+            },
+        },
+    }))
+    .unwrap()
+}
+
+/// Waits until a job has recorded at least one chunk.
+async fn first_event(
+    client: &TestJobClient,
+    ctx: &ctx::Context<MockContextExt>,
+    id: &str,
+) -> objectiveai::jobs::response::JobEvent {
+    client.events(ctx, id, 0).unwrap().boxed().next().await.unwrap()
+}
+
+fn retry_token(result: objectiveai::jobs::response::JobResult) -> Option<String> {
+    match result {
+        objectiveai::jobs::response::JobResult::FunctionProfileComputation(
+            computation,
+        ) => computation.retry_token,
+        objectiveai::jobs::response::JobResult::FunctionExecution(_) => {
+            panic!("expected a profile computation result")
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_create_and_result() {
+    let dir = test_dir();
+    let client =
+        create_test_job_client(&dir, MockComputationsClient::default());
+    let ctx = create_test_context(None);
+
+    let job = client.clone().create(ctx.clone(), create_params()).await.unwrap();
+    assert_eq!(
+        job.status,
+        objectiveai::jobs::response::JobStatus::Running
+    );
+
+    let events = client
+        .events(&ctx, &job.id, 0)
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].offset, 1);
+
+    let job = client.get(&ctx, &job.id).unwrap();
+    assert_eq!(
+        job.status,
+        objectiveai::jobs::response::JobStatus::Completed
+    );
+    assert_eq!(job.chunks, 2);
+    assert_eq!(job.retry_token.as_deref(), Some("token-1"));
+    let result = client.result(&ctx, &job.id).unwrap();
+    assert_eq!(retry_token(result).as_deref(), Some("token-1"));
+
+    remove_test_dir(&dir);
+}
+
+#[tokio::test]
+async fn test_events_from_offset() {
+    let dir = test_dir();
+    let client =
+        create_test_job_client(&dir, MockComputationsClient::default());
+    let ctx = create_test_context(None);
+
+    let job = client.clone().create(ctx.clone(), create_params()).await.unwrap();
+    let events = client
+        .events(&ctx, &job.id, 1)
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].offset, 1);
+    assert_eq!(events[0].chunk.retry_token(), Some("token-1"));
+
+    // re-attaching past the end replays nothing
+    let events = client
+        .events(&ctx, &job.id, 5)
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert!(events.is_empty());
+
+    remove_test_dir(&dir);
+}
+
+#[tokio::test]
+async fn test_cancel() {
+    let dir = test_dir();
+    let client = create_test_job_client(
+        &dir,
+        MockComputationsClient {
+            hang: true,
+            ..Default::default()
+        },
+    );
+    let ctx = create_test_context(None);
+
+    let job = client.clone().create(ctx.clone(), create_params()).await.unwrap();
+    first_event(&client, &ctx, &job.id).await;
+    assert!(matches!(
+        client.result(&ctx, &job.id),
+        Err(super::Error::JobRunning)
+    ));
+
+    let job = client.cancel(&ctx, &job.id).await.unwrap();
+    assert_eq!(
+        job.status,
+        objectiveai::jobs::response::JobStatus::Cancelled
+    );
+    // followers stop once the job is cancelled
+    let events = client
+        .events(&ctx, &job.id, 0)
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        client.result(&ctx, &job.id),
+        Err(super::Error::JobCancelled)
+    ));
+
+    remove_test_dir(&dir);
+}
+
+#[tokio::test]
+async fn test_resume_after_restart() {
+    let dir = test_dir();
+    let ctx = create_test_context(None);
+
+    // the first server stops while the job is still running
+    let before = create_test_job_client(
+        &dir,
+        MockComputationsClient {
+            hang: true,
+            ..Default::default()
+        },
+    );
+    let job = before.clone().create(ctx.clone(), create_params()).await.unwrap();
+    first_event(&before, &ctx, &job.id).await;
+    before.cancel(&ctx, &job.id).await.unwrap();
+    // un-cancel the persisted record, as if the server had stopped instead
+    let store = super::store::FileStore::new(&dir).unwrap();
+    let (mut record, _) = super::store::Store::load(&store)
+        .await
+        .unwrap()
+        .into_iter()
+        .next()
+        .unwrap();
+    record.job.status = objectiveai::jobs::response::JobStatus::Running;
+    super::store::Store::save(&store, &record).await.unwrap();
+
+    let after =
+        create_test_job_client(&dir, MockComputationsClient::default());
+    after.clone().resume(ctx.clone()).await.unwrap();
+    let events = after
+        .events(&ctx, &job.id, 0)
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(events.len(), 3);
+
+    // the request was re-submitted with the job's latest retry token
+    assert_eq!(
+        *after.computations_client.retry_tokens.lock().unwrap(),
+        vec![Some("token-hang".to_string())]
+    );
+    let job = after.get(&ctx, &job.id).unwrap();
+    assert_eq!(
+        job.status,
+        objectiveai::jobs::response::JobStatus::Completed
+    );
+    assert_eq!(job.attempts, 2);
+    // the result only covers the resumed attempt
+    let result = after.result(&ctx, &job.id).unwrap();
+    assert_eq!(retry_token(result).as_deref(), Some("token-1"));
+
+    remove_test_dir(&dir);
+}
+
+#[tokio::test]
+async fn test_resume_byok_job_fails() {
+    let dir = test_dir();
+    let ctx = create_test_context(Some("byok"));
+
+    let before = create_test_job_client(
+        &dir,
+        MockComputationsClient {
+            hang: true,
+            ..Default::default()
+        },
+    );
+    let job = before.clone().create(ctx.clone(), create_params()).await.unwrap();
+    first_event(&before, &ctx, &job.id).await;
+
+    // resumed jobs run without request credentials
+    let after =
+        create_test_job_client(&dir, MockComputationsClient::default());
+    after
+        .clone()
+        .resume(create_test_context(None))
+        .await
+        .unwrap();
+    assert!(after.computations_client.retry_tokens.lock().unwrap().is_empty());
+
+    let job = after.get(&ctx, &job.id).unwrap();
+    assert_eq!(job.status, objectiveai::jobs::response::JobStatus::Failed);
+    assert_eq!(job.retry_token.as_deref(), Some("token-hang"));
+    match after.result(&ctx, &job.id) {
+        Err(super::Error::JobFailed(e)) => {
+            assert_eq!(
+                e.message["error"]["kind"],
+                "job_credentials_unavailable"
+            );
+        }
+        other => panic!("expected a failed job, got {:?}", other.map(|_| ())),
+    }
+
+    remove_test_dir(&dir);
+}
+
+#[tokio::test]
+async fn test_jobs_scoped_to_caller() {
+    let dir = test_dir();
+    let client =
+        create_test_job_client(&dir, MockComputationsClient::default());
+    let owner = create_test_context(Some("owner"));
+    let other = create_test_context(Some("other"));
+    let anonymous = create_test_context(None);
+
+    let job = client
+        .clone()
+        .create(owner.clone(), create_params())
+        .await
+        .unwrap();
+    for ctx in [&other, &anonymous] {
+        assert!(matches!(
+            client.get(ctx, &job.id),
+            Err(super::Error::JobNotFound)
+        ));
+        assert!(matches!(
+            client.events(ctx, &job.id, 0).map(|_| ()),
+            Err(super::Error::JobNotFound)
+        ));
+        assert!(matches!(
+            client.result(ctx, &job.id),
+            Err(super::Error::JobNotFound)
+        ));
+        assert!(matches!(
+            client.cancel(ctx, &job.id).await,
+            Err(super::Error::JobNotFound)
+        ));
+    }
+    client
+        .events(&owner, &job.id, 0)
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert!(client.result(&owner, &job.id).is_ok());
+
+    remove_test_dir(&dir);
+}
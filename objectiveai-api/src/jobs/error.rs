@@ -0,0 +1,70 @@
+//! Error types for jobs.
+
+/// Errors that can occur when managing jobs.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The referenced job was not found.
+    #[error("job not found")]
+    JobNotFound,
+    /// The job has not finished yet.
+    #[error("job is still running")]
+    JobRunning,
+    /// The job was cancelled before producing a result.
+    #[error("job was cancelled")]
+    JobCancelled,
+    /// The job failed before producing a result.
+    #[error("job failed: {0}")]
+    JobFailed(objectiveai::error::ResponseError),
+    /// The job was submitted with BYOK credentials, which are not persisted,
+    /// so it could not be resumed after a restart.
+    #[error("job credentials are unavailable after a restart")]
+    CredentialsUnavailable,
+    /// Failed to persist or load a job.
+    #[error("job store error: {0}")]
+    Store(objectiveai::error::ResponseError),
+}
+
+impl objectiveai::error::StatusError for Error {
+    fn status(&self) -> u16 {
+        match self {
+            Error::JobNotFound => 404,
+            Error::JobRunning => 409,
+            Error::JobCancelled => 409,
+            Error::JobFailed(e) => e.status(),
+            Error::CredentialsUnavailable => 409,
+            Error::Store(e) => e.status(),
+        }
+    }
+
+    fn message(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "kind": "job",
+            "error": match self {
+                Error::JobNotFound => serde_json::json!({
+                    "kind": "job_not_found",
+                    "error": "job not found",
+                }),
+                Error::JobRunning => serde_json::json!({
+                    "kind": "job_running",
+                    "error": "job is still running",
+                }),
+                Error::JobCancelled => serde_json::json!({
+                    "kind": "job_cancelled",
+                    "error": "job was cancelled",
+                }),
+                Error::JobFailed(e) => serde_json::json!({
+                    "kind": "job_failed",
+                    "error": e.message(),
+                }),
+                Error::CredentialsUnavailable => serde_json::json!({
+                    "kind": "job_credentials_unavailable",
+                    "error": "job was submitted with BYOK credentials, which are not persisted; resubmit it with its retry token",
+                }),
+                Error::Store(e) => serde_json::json!({
+                    "kind": "job_store",
+                    "error": e.message(),
+                }),
+            }
+        }))
+    }
+}
@@ -0,0 +1,15 @@
+//! Durable asynchronous jobs.
+//!
+//! Jobs run Function executions and Profile computations in the background,
+//! recording every chunk so that clients can poll, re-attach, cancel, and
+//! fetch results independently of the connection that submitted them.
+
+mod client;
+#[cfg(test)]
+mod client_tests;
+mod error;
+/// Persistent storage for jobs.
+pub mod store;
+
+pub use client::*;
+pub use error::*;
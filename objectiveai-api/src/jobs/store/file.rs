@@ -0,0 +1,233 @@
+//! Filesystem job store.
+
+use std::{
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+/// Persists each job as a JSON file holding its state, next to a JSONL file
+/// holding its chunks.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    /// Directory the job files are written to.
+    pub dir: PathBuf,
+}
+
+impl FileStore {
+    /// Creates a new store writing to `dir`, creating it if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn record_path(dir: &Path, id: &str) -> PathBuf {
+        dir.join(format!("{}.json", id))
+    }
+
+    fn chunks_path(dir: &Path, id: &str) -> PathBuf {
+        dir.join(format!("{}.jsonl", id))
+    }
+
+    fn load_dir(
+        dir: &Path,
+    ) -> std::io::Result<
+        Vec<(super::JobRecord, Vec<objectiveai::jobs::response::JobChunk>)>,
+    > {
+        let mut jobs = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            // skip records that were never fully written
+            let Ok(record) = serde_json::from_slice::<super::JobRecord>(
+                &std::fs::read(&path)?,
+            ) else {
+                continue;
+            };
+            let mut chunks = Vec::new();
+            match std::fs::File::open(Self::chunks_path(dir, &record.job.id)) {
+                Ok(file) => {
+                    for line in std::io::BufReader::new(file).lines() {
+                        // a crash may leave the last line partially written
+                        match serde_json::from_str(&line?) {
+                            Ok(chunk) => chunks.push(chunk),
+                            Err(_) => break,
+                        }
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+            jobs.push((record, chunks));
+        }
+        jobs.sort_by_key(|(record, _)| record.job.created);
+        Ok(jobs)
+    }
+}
+
+/// Converts a filesystem error into a response error.
+fn io_error(e: std::io::Error) -> objectiveai::error::ResponseError {
+    objectiveai::error::ResponseError {
+        code: 500,
+        message: serde_json::json!({
+            "kind": "io",
+            "error": e.to_string(),
+        }),
+    }
+}
+
+/// Runs blocking filesystem work off the async runtime.
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> std::io::Result<T> + Send + 'static,
+) -> Result<T, objectiveai::error::ResponseError> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| io_error(std::io::Error::other(e)))?
+        .map_err(io_error)
+}
+
+#[async_trait::async_trait]
+impl super::Store for FileStore {
+    async fn save(
+        &self,
+        record: &super::JobRecord,
+    ) -> Result<(), objectiveai::error::ResponseError> {
+        let path = Self::record_path(&self.dir, &record.job.id);
+        let json = serde_json::to_vec(record).unwrap();
+        blocking(move || {
+            // write then rename, so that a crash never leaves a torn record
+            let tmp = path.with_extension("json.tmp");
+            std::fs::write(&tmp, json)?;
+            std::fs::rename(&tmp, &path)
+        })
+        .await
+    }
+
+    async fn append(
+        &self,
+        id: &str,
+        chunk: &objectiveai::jobs::response::JobChunk,
+    ) -> Result<(), objectiveai::error::ResponseError> {
+        let path = Self::chunks_path(&self.dir, id);
+        let mut line = serde_json::to_vec(chunk).unwrap();
+        line.push(b'\n');
+        blocking(move || {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)?
+                .write_all(&line)
+        })
+        .await
+    }
+
+    async fn delete(
+        &self,
+        id: &str,
+    ) -> Result<(), objectiveai::error::ResponseError> {
+        let record_path = Self::record_path(&self.dir, id);
+        let chunks_path = Self::chunks_path(&self.dir, id);
+        blocking(move || {
+            for path in [record_path, chunks_path] {
+                match std::fs::remove_file(&path) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn load(
+        &self,
+    ) -> Result<
+        Vec<(super::JobRecord, Vec<objectiveai::jobs::response::JobChunk>)>,
+        objectiveai::error::ResponseError,
+    > {
+        let dir = self.dir.clone();
+        blocking(move || Self::load_dir(&dir)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::store::{JobRecord, Store};
+
+    fn chunk(retry_token: Option<&str>) -> objectiveai::jobs::response::JobChunk {
+        objectiveai::jobs::response::JobChunk::FunctionProfileComputation(
+            objectiveai::functions::profiles::computations::response::streaming::FunctionProfileComputationChunk {
+                id: "prfcmp-test".to_string(),
+                executions: Vec::new(),
+                executions_errors: None,
+                profile: None,
+                fitting_stats: None,
+                validation: None,
+                retry_token: retry_token.map(str::to_owned),
+                created: 0,
+                function: None,
+                object: objectiveai::functions::profiles::computations::response::streaming::Object::FunctionProfileComputationChunk,
+                usage: None,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_file_store_round_trip() {
+        let dir = std::env::temp_dir().join(crate::jobs::job_id(0));
+        let store = FileStore::new(&dir).unwrap();
+        let record = JobRecord {
+            job: objectiveai::jobs::response::Job {
+                id: crate::jobs::job_id(0),
+                r#type: objectiveai::jobs::response::JobType::FunctionProfileComputation,
+                status: objectiveai::jobs::response::JobStatus::Running,
+                chunks: 0,
+                attempts: 1,
+                retry_token: None,
+                error: None,
+                created: 0,
+                updated: 0,
+                object: objectiveai::jobs::response::Object::Job,
+            },
+            params: serde_json::from_value(serde_json::json!({
+                "type": "function.profile.computation",
+                "request": {
+                    "path": {
+                        "fowner": "owner",
+                        "frepository": "repository",
+                        "fcommit": null,
+                    },
+                    "body": {
+                        "dataset": [],
+                        "n": 1,
+                        "ensemble": "ensemble",
+                    },
+                },
+            }))
+            .unwrap(),
+            attempt_offset: 0,
+            owner: None,
+            byok: false,
+        };
+        store.save(&record).await.unwrap();
+        store.append(&record.job.id, &chunk(None)).await.unwrap();
+        store
+            .append(&record.job.id, &chunk(Some("token")))
+            .await
+            .unwrap();
+
+        let jobs = store.load().await.unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].0.job.id, record.job.id);
+        assert_eq!(jobs[0].1.len(), 2);
+        assert_eq!(jobs[0].1[1].retry_token(), Some("token"));
+
+        store.delete(&record.job.id).await.unwrap();
+        assert!(store.load().await.unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,7 @@
+//! Persistent storage for jobs.
+
+mod file;
+mod store;
+
+pub use file::*;
+pub use store::*;
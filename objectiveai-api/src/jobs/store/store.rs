@@ -0,0 +1,54 @@
+//! Trait for persisting jobs.
+
+use serde::{Deserialize, Serialize};
+
+/// The persisted state of a job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRecord {
+    /// The job's status.
+    pub job: objectiveai::jobs::response::Job,
+    /// The parameters the job was submitted with. When the job is resumed,
+    /// its latest retry token is set on these.
+    pub params: objectiveai::jobs::request::JobCreateParams,
+    /// Offset of the first chunk recorded by the current attempt.
+    pub attempt_offset: usize,
+    /// Identity of the caller that submitted the job, the only one allowed
+    /// to access it.
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Whether the job was submitted with BYOK credentials. They are never
+    /// persisted, so such a job cannot be resumed after a restart.
+    #[serde(default)]
+    pub byok: bool,
+}
+
+/// Persists jobs and their chunks so that they survive a server restart.
+#[async_trait::async_trait]
+pub trait Store {
+    /// Saves a job's state, creating it if it does not exist.
+    async fn save(
+        &self,
+        record: &JobRecord,
+    ) -> Result<(), objectiveai::error::ResponseError>;
+
+    /// Appends a chunk to a job.
+    async fn append(
+        &self,
+        id: &str,
+        chunk: &objectiveai::jobs::response::JobChunk,
+    ) -> Result<(), objectiveai::error::ResponseError>;
+
+    /// Deletes a job and its chunks.
+    async fn delete(
+        &self,
+        id: &str,
+    ) -> Result<(), objectiveai::error::ResponseError>;
+
+    /// Loads every job with its chunks, oldest first.
+    async fn load(
+        &self,
+    ) -> Result<
+        Vec<(JobRecord, Vec<objectiveai::jobs::response::JobChunk>)>,
+        objectiveai::error::ResponseError,
+    >;
+}
@@ -12,6 +12,7 @@
 //! - [`ensemble_llm`] - Ensemble LLM management and retrieval
 //! - [`error`] - Error response handling
 //! - [`functions`] - Function execution and profile management
+//! - [`jobs`] - Durable asynchronous jobs
 //! - [`util`] - Utility types for streaming and indexing
 //! - [`vector`] - Vector completions for scoring and ranking
 
@@ -29,6 +30,8 @@ pub mod ensemble_llm;
 pub mod error;
 /// Function execution, profile management, and computations.
 pub mod functions;
+/// Durable asynchronous jobs for long-running executions and computations.
+pub mod jobs;
 /// Utility types for streaming and choice indexing.
 pub mod util;
 /// Vector completions for scoring and ranking responses.
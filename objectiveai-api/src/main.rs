@@ -5,7 +5,7 @@
 
 use axum::{
     Json,
-    extract::Path,
+    extract::{Path, Query},
     http::HeaderMap,
     response::{IntoResponse, Sse, sse::Event},
 };
@@ -15,6 +15,7 @@ use objectiveai_api::{
     auth, chat, ctx, ensemble, ensemble_llm,
     error::ResponseErrorExt,
     functions::{self, profiles::computations::Client},
+    jobs,
     util::StreamOnce,
     vector,
 };
@@ -70,6 +71,64 @@ struct Config {
         default = "40000" // 40 seconds
     )]
     chat_completions_backoff_max_elapsed_time: u64,
+    #[envconfig(from = "STARLARK_MAX_STEPS", default = "1000000")]
+    starlark_max_steps: u64,
+    #[envconfig(
+        from = "STARLARK_MAX_HEAP_BYTES",
+        default = "67108864" // 64 MiB
+    )]
+    starlark_max_heap_bytes: usize,
+    #[envconfig(
+        from = "STARLARK_TIMEOUT",
+        default = "1000" // 1 second
+    )]
+    starlark_timeout: u64,
+    #[envconfig(
+        from = "STARLARK_MAX_OUTPUT_BYTES",
+        default = "4194304" // 4 MiB
+    )]
+    starlark_max_output_bytes: usize,
+    #[envconfig(from = "STARLARK_MAX_CALLSTACK_SIZE", default = "64")]
+    starlark_max_callstack_size: usize,
+    #[envconfig(from = "EXPRESSIONS_EVALUATE_MAX_CONCURRENCY", default = "4")]
+    expressions_evaluate_max_concurrency: usize,
+    #[envconfig(
+        from = "FUNCTION_EXECUTIONS_BATCH_MAX_CONCURRENCY",
+        default = "16"
+    )]
+    function_executions_batch_max_concurrency: usize,
+    #[envconfig(
+        from = "FUNCTION_EXECUTIONS_BATCH_MAX_BYTES",
+        default = "67108864" // 64 MiB
+    )]
+    function_executions_batch_max_bytes: usize,
+    #[envconfig(from = "FUNCTION_EXECUTIONS_CACHE_CAPACITY", default = "4096")]
+    function_executions_cache_capacity: usize,
+    #[envconfig(
+        from = "FUNCTION_EXECUTIONS_CACHE_TTL",
+        default = "86400000" // 1 day
+    )]
+    function_executions_cache_ttl: u64,
+    #[envconfig(from = "PROFILE_COMPUTATIONS_STARTS", default = "4")]
+    profile_computations_starts: usize,
+    #[envconfig(from = "PROFILE_COMPUTATIONS_MAX_ROUNDS", default = "50")]
+    profile_computations_max_rounds: usize,
+    #[envconfig(from = "PROFILE_COMPUTATIONS_MAX_CONCURRENCY", default = "16")]
+    profile_computations_max_concurrency: usize,
+    #[envconfig(from = "PROFILE_COMPUTATIONS_MAX_DATASETS", default = "1024")]
+    profile_computations_max_datasets: usize,
+    #[envconfig(
+        from = "PROFILE_COMPUTATIONS_MAX_DATASET_BYTES",
+        default = "67108864" // 64 MiB
+    )]
+    profile_computations_max_dataset_bytes: usize,
+    #[envconfig(from = "JOBS_DIR", default = "jobs")]
+    jobs_dir: String,
+    #[envconfig(
+        from = "JOBS_RETENTION",
+        default = "604800" // 7 days
+    )]
+    jobs_retention: u64,
     #[envconfig(from = "ADDRESS", default = "0.0.0.0")]
     address: String,
     #[envconfig(from = "PORT", default = "5000")]
@@ -96,10 +155,38 @@ async fn main() {
         chat_completions_backoff_multiplier,
         chat_completions_backoff_max_interval,
         chat_completions_backoff_max_elapsed_time,
+        starlark_max_steps,
+        starlark_max_heap_bytes,
+        starlark_timeout,
+        starlark_max_output_bytes,
+        starlark_max_callstack_size,
+        expressions_evaluate_max_concurrency,
+        function_executions_batch_max_concurrency,
+        function_executions_batch_max_bytes,
+        function_executions_cache_capacity,
+        function_executions_cache_ttl,
+        profile_computations_starts,
+        profile_computations_max_rounds,
+        profile_computations_max_concurrency,
+        profile_computations_max_datasets,
+        profile_computations_max_dataset_bytes,
+        jobs_dir,
+        jobs_retention,
         address,
         port,
     } = Config::init_from_env().unwrap();
 
+    // Starlark Expression Limits
+    objectiveai::functions::expression::set_starlark_limits(
+        objectiveai::functions::expression::StarlarkLimits {
+            max_steps: starlark_max_steps,
+            max_heap_bytes: starlark_max_heap_bytes,
+            timeout: std::time::Duration::from_millis(starlark_timeout),
+            max_output_bytes: starlark_max_output_bytes,
+            max_callstack_size: starlark_max_callstack_size,
+        },
+    );
+
     // HTTP Client
     let http_client = reqwest::Client::new();
 
@@ -192,15 +279,19 @@ async fn main() {
 
     // Function Fetcher
     let function_fetcher =
-        Arc::new(functions::function_fetcher::ObjectiveAiFetcher::new(
-            objectiveai_http_client.clone(),
-        ));
+        Arc::new(functions::function_fetcher::CachingFetcher::new(Arc::new(
+            functions::function_fetcher::ObjectiveAiFetcher::new(
+                objectiveai_http_client.clone(),
+            ),
+        )));
 
     // Function Profile Fetcher
     let profile_fetcher =
-        Arc::new(functions::profile_fetcher::ObjectiveAiFetcher::new(
-            objectiveai_http_client.clone(),
-        ));
+        Arc::new(functions::profile_fetcher::CachingFetcher::new(Arc::new(
+            functions::profile_fetcher::ObjectiveAiFetcher::new(
+                objectiveai_http_client.clone(),
+            ),
+        )));
 
     // Function Executions Client
     let function_executions_client =
@@ -211,14 +302,41 @@ async fn main() {
             function_fetcher.clone(),
             profile_fetcher.clone(),
             Arc::new(functions::executions::usage_handler::LogUsageHandler),
+            functions::executions::ExecutionCache::new(
+                function_executions_cache_capacity,
+                Some(std::time::Duration::from_millis(
+                    function_executions_cache_ttl,
+                )),
+            ),
         ));
 
     // Functions Profiles Computations Client
     let profile_computations_client =
-        Arc::new(functions::profiles::computations::ObjectiveAiClient::new(
-            objectiveai_http_client.clone(),
+        Arc::new(functions::profiles::computations::LocalClient::new(
+            function_executions_client.clone(),
+            Arc::new(
+                functions::profiles::computations::dataset_store::MemoryStore::new(
+                    profile_computations_max_datasets,
+                ),
+            ),
+            profile_computations_starts,
+            profile_computations_max_rounds,
+            profile_computations_max_concurrency,
         ));
 
+    // Jobs Client
+    let jobs_client = Arc::new(jobs::Client::new(
+        function_executions_client.clone(),
+        profile_computations_client.clone(),
+        Arc::new(jobs::store::FileStore::new(jobs_dir).unwrap()),
+        std::time::Duration::from_secs(jobs_retention),
+    ));
+    jobs_client
+        .clone()
+        .resume(context(&HeaderMap::new()))
+        .await
+        .unwrap();
+
     // Functions Client
     let functions_client = Arc::new(functions::Client::new(
         function_fetcher.clone(),
@@ -394,6 +512,55 @@ async fn main() {
                 }
             }),
         )
+        // Function Executions - cancel
+        .route(
+            "/functions/executions/{id}/cancel",
+            axum::routing::post({
+                let function_executions_client = function_executions_client.clone();
+                move |headers: HeaderMap, Path(id): Path<String>| {
+                    cancel_function_execution(
+                        function_executions_client,
+                        headers,
+                        id,
+                    )
+                }
+            }),
+        )
+        // Function Executions - invalidate cache
+        .route(
+            "/functions/executions/cache/invalidate",
+            axum::routing::post({
+                let function_executions_client = function_executions_client.clone();
+                move |headers: HeaderMap,
+                      Json(body): Json<
+                    objectiveai::functions::executions::cache::request::InvalidateCacheRequest,
+                >| {
+                    invalidate_function_execution_cache(
+                        function_executions_client,
+                        headers,
+                        body,
+                    )
+                }
+            }),
+        )
+        // Function Executions - create batch
+        .route(
+            "/functions/executions/batch",
+            axum::routing::post({
+                let function_executions_client = function_executions_client.clone();
+                move |headers: HeaderMap, body: String| {
+                    execute_function_batch(
+                        function_executions_client,
+                        headers,
+                        body,
+                        function_executions_batch_max_concurrency,
+                    )
+                }
+            })
+            .layer(axum::extract::DefaultBodyLimit::max(
+                function_executions_batch_max_bytes,
+            )),
+        )
         // Function Executions - create
         // inline function
         // inline profile
@@ -851,6 +1018,27 @@ async fn main() {
                 }
             }),
         )
+        // Function Profile Computations - upload dataset
+        .route(
+            "/functions/profiles/compute/datasets",
+            axum::routing::post({
+                let profile_computations_client =
+                    profile_computations_client.clone();
+                move |headers: HeaderMap,
+                      Query(params): Query<
+                    objectiveai::functions::profiles::computations::datasets::request::DatasetCreateParams,
+                >,
+                      body: axum::body::Body| {
+                    create_profile_computation_dataset(
+                        profile_computations_client,
+                        headers,
+                        params,
+                        body,
+                        profile_computations_max_dataset_bytes,
+                    )
+                }
+            }),
+        )
         // Function Profile Computations - create
         // inline function
         .route(
@@ -922,6 +1110,88 @@ async fn main() {
                 }
             }),
         )
+        // Functions - check compatibility
+        .route(
+            "/functions/compatibility",
+            axum::routing::post({
+                let function_executions_client = function_executions_client.clone();
+                move |headers: HeaderMap,
+                      Json(body): Json<
+                    objectiveai::functions::CheckCompatibilityRequest,
+                >| {
+                    check_function_compatibility(
+                        function_executions_client,
+                        headers,
+                        body,
+                    )
+                }
+            }),
+        )
+        // Function Expressions - evaluate
+        .route(
+            "/functions/expressions/evaluate",
+            axum::routing::post({
+                let permits = Arc::new(tokio::sync::Semaphore::new(
+                    expressions_evaluate_max_concurrency,
+                ));
+                move |Json(body): Json<
+                    objectiveai::functions::expression::EvaluateExpressionRequest,
+                >| evaluate_expression(permits, body)
+            }),
+        )
+        // Jobs - create
+        .route(
+            "/jobs",
+            axum::routing::post({
+                let jobs_client = jobs_client.clone();
+                move |headers: HeaderMap,
+                      Json(body): Json<
+                    objectiveai::jobs::request::JobCreateParams,
+                >| { create_job(jobs_client, headers, body) }
+            }),
+        )
+        // Jobs - get
+        .route(
+            "/jobs/{id}",
+            axum::routing::get({
+                let jobs_client = jobs_client.clone();
+                move |headers: HeaderMap, Path(id): Path<String>| {
+                    get_job(jobs_client, headers, id)
+                }
+            }),
+        )
+        // Jobs - attach to events
+        .route(
+            "/jobs/{id}/events",
+            axum::routing::get({
+                let jobs_client = jobs_client.clone();
+                move |headers: HeaderMap,
+                      Path(id): Path<String>,
+                      Query(params): Query<
+                    objectiveai::jobs::request::JobEventsParams,
+                >| { get_job_events(jobs_client, headers, id, params) }
+            }),
+        )
+        // Jobs - cancel
+        .route(
+            "/jobs/{id}/cancel",
+            axum::routing::post({
+                let jobs_client = jobs_client.clone();
+                move |headers: HeaderMap, Path(id): Path<String>| {
+                    cancel_job(jobs_client, headers, id)
+                }
+            }),
+        )
+        // Jobs - get result
+        .route(
+            "/jobs/{id}/result",
+            axum::routing::get({
+                let jobs_client = jobs_client.clone();
+                move |headers: HeaderMap, Path(id): Path<String>| {
+                    get_job_result(jobs_client, headers, id)
+                }
+            }),
+        )
         // Auth - create API key
         .route(
             "/auth/keys",
@@ -1319,7 +1589,12 @@ async fn execute_function(
     request: objectiveai::functions::executions::request::Request,
 ) -> axum::response::Response {
     let ctx = context(&headers);
-    if request.base().stream.unwrap_or(false) {
+    if request.base().dry_run.unwrap_or(false) {
+        match client.estimate(ctx, Arc::new(request)).await {
+            Ok(r) => Json(r).into_response(),
+            Err(e) => ResponseError::from(&e).into_response(),
+        }
+    } else if request.base().stream.unwrap_or(false) {
         match client
             .create_streaming_handle_usage(ctx, Arc::new(request))
             .await
@@ -1350,17 +1625,47 @@ async fn execute_function(
     }
 }
 
-// Profiles
-
-async fn list_profiles(
+async fn cancel_function_execution(
     client: Arc<
-        functions::profiles::Client<
+        functions::executions::Client<
             ctx::DefaultContextExt,
+            impl ensemble_llm::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl chat::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl ensemble::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl vector::completions::completion_votes_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::cache_vote_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::function_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
             impl functions::profile_fetcher::Fetcher<ctx::DefaultContextExt>
             + Send
             + Sync
             + 'static,
-            impl functions::profiles::retrieval_client::Client<
+            impl functions::executions::usage_handler::UsageHandler<
                 ctx::DefaultContextExt,
             > + Send
             + Sync
@@ -1368,23 +1673,56 @@ async fn list_profiles(
         >,
     >,
     headers: HeaderMap,
+    id: String,
 ) -> axum::response::Response {
     let ctx = context(&headers);
-    match client.list_profiles(ctx).await {
-        Ok(r) => Json(r).into_response(),
+    match client.cancel(&ctx, &id) {
+        Ok(()) => axum::http::StatusCode::OK.into_response(),
         Err(e) => ResponseError::from(&e).into_response(),
     }
 }
 
-async fn get_profile_usage(
+async fn invalidate_function_execution_cache(
     client: Arc<
-        functions::profiles::Client<
+        functions::executions::Client<
             ctx::DefaultContextExt,
+            impl ensemble_llm::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl chat::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl ensemble::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl vector::completions::completion_votes_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::cache_vote_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::function_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
             impl functions::profile_fetcher::Fetcher<ctx::DefaultContextExt>
             + Send
             + Sync
             + 'static,
-            impl functions::profiles::retrieval_client::Client<
+            impl functions::executions::usage_handler::UsageHandler<
                 ctx::DefaultContextExt,
             > + Send
             + Sync
@@ -1392,55 +1730,266 @@ async fn get_profile_usage(
         >,
     >,
     headers: HeaderMap,
-    owner: String,
-    repository: String,
-    commit: Option<String>,
-) -> axum::response::Response {
-    let ctx = context(&headers);
-    match client
-        .get_profile_usage(ctx, &owner, &repository, commit.as_deref())
-        .await
-    {
-        Ok(r) => Json(r).into_response(),
-        Err(e) => ResponseError::from(&e).into_response(),
-    }
-}
-
-// Function-Profile Pairs
-
-async fn list_function_profile_pairs(
-    client: Arc<
-        impl functions::pair_retrieval_client::Client<ctx::DefaultContextExt>
-        + Send
-        + Sync
-        + 'static,
-    >,
-    headers: HeaderMap,
+    request: objectiveai::functions::executions::cache::request::InvalidateCacheRequest,
 ) -> axum::response::Response {
-    let ctx = context(&headers);
-    match client.list_function_profile_pairs(ctx).await {
-        Ok(r) => Json(r).into_response(),
-        Err(e) => e.into_response(),
-    }
+    Json(client.invalidate_cache(context(&headers), &request)).into_response()
 }
 
-async fn get_function_profile_pair(
+async fn check_function_compatibility(
     client: Arc<
-        impl functions::pair_retrieval_client::Client<ctx::DefaultContextExt>
-        + Send
-        + Sync
-        + 'static,
-    >,
-    headers: HeaderMap,
-    fowner: String,
-    frepository: String,
-    fcommit: Option<String>,
-    powner: String,
-    prepository: String,
-    pcommit: Option<String>,
-) -> axum::response::Response {
-    let ctx = context(&headers);
-    match client
+        functions::executions::Client<
+            ctx::DefaultContextExt,
+            impl ensemble_llm::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl chat::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl ensemble::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl vector::completions::completion_votes_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::cache_vote_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::function_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::profile_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::executions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+        >,
+    >,
+    headers: HeaderMap,
+    request: objectiveai::functions::CheckCompatibilityRequest,
+) -> axum::response::Response {
+    let ctx = context(&headers);
+    match client.check_compatibility(ctx, request).await {
+        Ok(r) => Json(r).into_response(),
+        Err(e) => ResponseError::from(&e).into_response(),
+    }
+}
+
+async fn execute_function_batch(
+    client: Arc<
+        functions::executions::Client<
+            ctx::DefaultContextExt,
+            impl ensemble_llm::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl chat::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl ensemble::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl vector::completions::completion_votes_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::cache_vote_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::function_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::profile_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::executions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+        >,
+    >,
+    headers: HeaderMap,
+    body: String,
+    max_concurrency: usize,
+) -> axum::response::Response {
+    // JSONL batches hold the parameters on the first line, then one input
+    // per line
+    let jsonl = headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value.starts_with("application/x-ndjson")
+                || value.starts_with("application/jsonl")
+        });
+    let params = if jsonl {
+        objectiveai::functions::executions::batch::request::FunctionExecutionBatchCreateParams::from_jsonl(&body)
+            .map_err(|e| e.to_string())
+    } else {
+        serde_json::from_str::<
+            objectiveai::functions::executions::batch::request::FunctionExecutionBatchCreateParams,
+        >(&body)
+        .map_err(|e| e.to_string())
+    };
+    let params = match params {
+        Ok(params) => Arc::new(params),
+        Err(e) => {
+            return ResponseError::from(
+                &functions::executions::Error::InvalidBatch(e),
+            )
+            .into_response();
+        }
+    };
+    let ctx = context(&headers);
+    if params.stream.unwrap_or(false) {
+        match client
+            .create_batch_streaming(ctx, params, max_concurrency)
+            .await
+        {
+            Ok(stream) => Sse::new(
+                stream
+                    .map(|chunk| {
+                        Ok::<Event, Infallible>(
+                            Event::default()
+                                .data(serde_json::to_string(&chunk).unwrap()),
+                        )
+                    })
+                    .chain(StreamOnce::new(
+                        Ok(Event::default().data("[DONE]")),
+                    )),
+            )
+            .into_response(),
+            Err(e) => ResponseError::from(&e).into_response(),
+        }
+    } else {
+        match client.create_batch_unary(ctx, params, max_concurrency).await {
+            Ok(r) => Json(r).into_response(),
+            Err(e) => ResponseError::from(&e).into_response(),
+        }
+    }
+}
+
+// Profiles
+
+async fn list_profiles(
+    client: Arc<
+        functions::profiles::Client<
+            ctx::DefaultContextExt,
+            impl functions::profile_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::profiles::retrieval_client::Client<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+        >,
+    >,
+    headers: HeaderMap,
+) -> axum::response::Response {
+    let ctx = context(&headers);
+    match client.list_profiles(ctx).await {
+        Ok(r) => Json(r).into_response(),
+        Err(e) => ResponseError::from(&e).into_response(),
+    }
+}
+
+async fn get_profile_usage(
+    client: Arc<
+        functions::profiles::Client<
+            ctx::DefaultContextExt,
+            impl functions::profile_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::profiles::retrieval_client::Client<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+        >,
+    >,
+    headers: HeaderMap,
+    owner: String,
+    repository: String,
+    commit: Option<String>,
+) -> axum::response::Response {
+    let ctx = context(&headers);
+    match client
+        .get_profile_usage(ctx, &owner, &repository, commit.as_deref())
+        .await
+    {
+        Ok(r) => Json(r).into_response(),
+        Err(e) => ResponseError::from(&e).into_response(),
+    }
+}
+
+// Function-Profile Pairs
+
+async fn list_function_profile_pairs(
+    client: Arc<
+        impl functions::pair_retrieval_client::Client<ctx::DefaultContextExt>
+        + Send
+        + Sync
+        + 'static,
+    >,
+    headers: HeaderMap,
+) -> axum::response::Response {
+    let ctx = context(&headers);
+    match client.list_function_profile_pairs(ctx).await {
+        Ok(r) => Json(r).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_function_profile_pair(
+    client: Arc<
+        impl functions::pair_retrieval_client::Client<ctx::DefaultContextExt>
+        + Send
+        + Sync
+        + 'static,
+    >,
+    headers: HeaderMap,
+    fowner: String,
+    frepository: String,
+    fcommit: Option<String>,
+    powner: String,
+    prepository: String,
+    pcommit: Option<String>,
+) -> axum::response::Response {
+    let ctx = context(&headers);
+    match client
         .get_function_profile_pair(
             ctx,
             &fowner,
@@ -1628,38 +2177,487 @@ async fn create_profile_computation(
     // >,
     // https://github.com/rust-lang/rust/issues/100013
     // using a concrete type for client instead
-    client: Arc<functions::profiles::computations::ObjectiveAiClient>,
-    headers: HeaderMap,
-    request: objectiveai::functions::profiles::computations::request::Request,
-) -> axum::response::Response {
-    let ctx = context(&headers);
-    if request.base().stream.unwrap_or(false) {
-        match client.create_streaming(ctx, Arc::new(request)).await {
-            Ok(stream) => Sse::new(
-                stream
-                    .map(|result| {
-                        Ok::<Event, Infallible>(
-                            Event::default().data(
-                                match result {
-                                    Ok(chunk) => serde_json::to_string(&chunk),
-                                    Err(e) => serde_json::to_string(&e),
-                                }
-                                .unwrap(),
-                            ),
-                        )
-                    })
-                    .chain(StreamOnce::new(
-                        Ok(Event::default().data("[DONE]")),
-                    )),
-            )
-            .into_response(),
-            Err(e) => e.into_response(),
-        }
-    } else {
-        match client.create_unary(ctx, Arc::new(request)).await {
-            Ok(r) => Json(r).into_response(),
-            Err(e) => e.into_response(),
-        }
+    client: Arc<
+        functions::profiles::computations::LocalClient<
+            ctx::DefaultContextExt,
+            impl ensemble_llm::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl chat::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl ensemble::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl vector::completions::completion_votes_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::cache_vote_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::function_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::profile_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::executions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::profiles::computations::dataset_store::Store<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+        >,
+    >,
+    headers: HeaderMap,
+    request: objectiveai::functions::profiles::computations::request::Request,
+) -> axum::response::Response {
+    let ctx = context(&headers);
+    if request.base().stream.unwrap_or(false) {
+        match Client::create_streaming(&*client, ctx, Arc::new(request)).await
+        {
+            Ok(stream) => Sse::new(
+                stream
+                    .map(|result| {
+                        Ok::<Event, Infallible>(
+                            Event::default().data(
+                                match result {
+                                    Ok(chunk) => serde_json::to_string(&chunk),
+                                    Err(e) => serde_json::to_string(&e),
+                                }
+                                .unwrap(),
+                            ),
+                        )
+                    })
+                    .chain(StreamOnce::new(
+                        Ok(Event::default().data("[DONE]")),
+                    )),
+            )
+            .into_response(),
+            Err(e) => e.into_response(),
+        }
+    } else {
+        match Client::create_unary(&*client, ctx, Arc::new(request)).await {
+            Ok(r) => Json(r).into_response(),
+            Err(e) => e.into_response(),
+        }
+    }
+}
+
+async fn create_profile_computation_dataset(
+    client: Arc<
+        impl functions::profiles::computations::Client<ctx::DefaultContextExt>
+        + Send
+        + Sync
+        + 'static,
+    >,
+    headers: HeaderMap,
+    params: objectiveai::functions::profiles::computations::datasets::request::DatasetCreateParams,
+    body: axum::body::Body,
+    max_bytes: usize,
+) -> axum::response::Response {
+    let ctx = context(&headers);
+    // stream the body to the parser, bounded by max_bytes
+    let mut len = 0;
+    let content = body.into_data_stream().map(move |chunk| {
+        let chunk = chunk.map_err(|e| ResponseError {
+            code: 400,
+            message: serde_json::json!({
+                "kind": "dataset_read",
+                "error": e.to_string(),
+            }),
+        })?;
+        len += chunk.len();
+        if len > max_bytes {
+            return Err(ResponseError {
+                code: 413,
+                message: serde_json::json!({
+                    "kind": "dataset_too_large",
+                    "error": format!("dataset exceeds {} bytes", max_bytes),
+                }),
+            });
+        }
+        Ok(chunk)
+    });
+    match client.create_dataset(ctx, params, Box::pin(content)).await {
+        Ok(r) => Json(r).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+// Jobs
+
+async fn create_job(
+    client: Arc<
+        jobs::Client<
+            ctx::DefaultContextExt,
+            impl ensemble_llm::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl chat::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl ensemble::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl vector::completions::completion_votes_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::cache_vote_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::function_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::profile_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::executions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::profiles::computations::Client<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl jobs::store::Store + Send + Sync + 'static,
+        >,
+    >,
+    headers: HeaderMap,
+    params: objectiveai::jobs::request::JobCreateParams,
+) -> axum::response::Response {
+    let ctx = context(&headers);
+    match client.create(ctx, params).await {
+        Ok(r) => Json(r).into_response(),
+        Err(e) => ResponseError::from(&e).into_response(),
+    }
+}
+
+async fn get_job(
+    client: Arc<
+        jobs::Client<
+            ctx::DefaultContextExt,
+            impl ensemble_llm::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl chat::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl ensemble::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl vector::completions::completion_votes_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::cache_vote_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::function_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::profile_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::executions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::profiles::computations::Client<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl jobs::store::Store + Send + Sync + 'static,
+        >,
+    >,
+    headers: HeaderMap,
+    id: String,
+) -> axum::response::Response {
+    let ctx = context(&headers);
+    match client.get(&ctx, &id) {
+        Ok(r) => Json(r).into_response(),
+        Err(e) => ResponseError::from(&e).into_response(),
+    }
+}
+
+async fn get_job_events(
+    client: Arc<
+        jobs::Client<
+            ctx::DefaultContextExt,
+            impl ensemble_llm::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl chat::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl ensemble::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl vector::completions::completion_votes_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::cache_vote_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::function_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::profile_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::executions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::profiles::computations::Client<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl jobs::store::Store + Send + Sync + 'static,
+        >,
+    >,
+    headers: HeaderMap,
+    id: String,
+    params: objectiveai::jobs::request::JobEventsParams,
+) -> axum::response::Response {
+    let ctx = context(&headers);
+    match client.events(&ctx, &id, params.offset.unwrap_or(0)) {
+        Ok(stream) => Sse::new(
+            stream
+                .map(|event| {
+                    Ok::<Event, Infallible>(
+                        Event::default()
+                            .data(serde_json::to_string(&event).unwrap()),
+                    )
+                })
+                .chain(StreamOnce::new(Ok(Event::default().data("[DONE]")))),
+        )
+        .into_response(),
+        Err(e) => ResponseError::from(&e).into_response(),
+    }
+}
+
+async fn cancel_job(
+    client: Arc<
+        jobs::Client<
+            ctx::DefaultContextExt,
+            impl ensemble_llm::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl chat::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl ensemble::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl vector::completions::completion_votes_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::cache_vote_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::function_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::profile_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::executions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::profiles::computations::Client<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl jobs::store::Store + Send + Sync + 'static,
+        >,
+    >,
+    headers: HeaderMap,
+    id: String,
+) -> axum::response::Response {
+    let ctx = context(&headers);
+    match client.cancel(&ctx, &id).await {
+        Ok(r) => Json(r).into_response(),
+        Err(e) => ResponseError::from(&e).into_response(),
+    }
+}
+
+async fn get_job_result(
+    client: Arc<
+        jobs::Client<
+            ctx::DefaultContextExt,
+            impl ensemble_llm::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl chat::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl ensemble::fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl vector::completions::completion_votes_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::cache_vote_fetcher::Fetcher<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl vector::completions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::function_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::profile_fetcher::Fetcher<ctx::DefaultContextExt>
+            + Send
+            + Sync
+            + 'static,
+            impl functions::executions::usage_handler::UsageHandler<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl functions::profiles::computations::Client<
+                ctx::DefaultContextExt,
+            > + Send
+            + Sync
+            + 'static,
+            impl jobs::store::Store + Send + Sync + 'static,
+        >,
+    >,
+    headers: HeaderMap,
+    id: String,
+) -> axum::response::Response {
+    let ctx = context(&headers);
+    match client.result(&ctx, &id) {
+        Ok(r) => Json(r).into_response(),
+        Err(e) => ResponseError::from(&e).into_response(),
+    }
+}
+
+// Function Expressions
+
+async fn evaluate_expression(
+    permits: Arc<tokio::sync::Semaphore>,
+    request: objectiveai::functions::expression::EvaluateExpressionRequest,
+) -> axum::response::Response {
+    // bound how many evaluations occupy blocking threads at once
+    let Ok(permit) = permits.try_acquire_owned() else {
+        return ResponseError {
+            code: 429,
+            message: serde_json::json!({
+                "kind": "expression_evaluation_busy",
+                "error": "too many concurrent expression evaluations",
+            }),
+        }
+        .into_response();
+    };
+    // Starlark evaluation may run up to its configured timeout
+    match tokio::task::spawn_blocking(move || {
+        let _permit = permit;
+        request.evaluate()
+    })
+    .await
+    {
+        Ok(r) => Json(r).into_response(),
+        Err(_) => axum::http::StatusCode::INTERNAL_SERVER_ERROR.into_response(),
     }
 }
 
@@ -65,3 +65,43 @@ where
         std::task::Poll::Ready(self.as_mut().get_mut().0.take())
     }
 }
+
+/// A stream that cancels a token if it is dropped before it completes.
+///
+/// Used to propagate client disconnects: when a response stream is dropped
+/// mid-way, the work producing it is cancelled rather than left running.
+pub struct CancelOnDrop<S> {
+    inner: S,
+    guard: Option<tokio_util::sync::DropGuard>,
+}
+
+impl<S> CancelOnDrop<S> {
+    /// Wraps `inner`, cancelling `cancel` if dropped before `inner` ends.
+    pub fn new(inner: S, cancel: tokio_util::sync::CancellationToken) -> Self {
+        Self {
+            inner,
+            guard: Some(cancel.drop_guard()),
+        }
+    }
+}
+
+impl<S> Stream for CancelOnDrop<S>
+where
+    S: Stream + Unpin,
+{
+    type Item = S::Item;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let this = self.as_mut().get_mut();
+        let poll = std::pin::Pin::new(&mut this.inner).poll_next(cx);
+        if let std::task::Poll::Ready(None) = poll
+            && let Some(guard) = this.guard.take()
+        {
+            guard.disarm();
+        }
+        poll
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::{
     chat, ctx,
-    util::{ChoiceIndexer, StreamOnce},
+    util::{CancelOnDrop, ChoiceIndexer, StreamOnce},
 };
 use futures::{FutureExt, Stream, StreamExt, TryStreamExt};
 use rand::Rng;
@@ -159,6 +159,10 @@ where
         + 'static,
         super::Error,
     >{
+        // cancel the completion if the response is dropped before it ends,
+        // or once it reaches its maximum cost
+        let ctx = ctx.child_with_max_cost(request.max_cost);
+        let cancel = ctx.cancel.clone();
         let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
         tokio::spawn(async move {
             let mut aggregate: Option<
@@ -203,9 +207,10 @@ where
         let mut stream =
             tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
         match stream.next().await {
-            Some(Ok(chunk)) => {
-                Ok(StreamOnce::new(chunk).chain(stream.map(Result::unwrap)))
-            }
+            Some(Ok(chunk)) => Ok(CancelOnDrop::new(
+                StreamOnce::new(chunk).chain(stream.map(Result::unwrap)),
+                cancel,
+            )),
             Some(Err(e)) => Err(e),
             None => unreachable!(),
         }
@@ -305,6 +305,7 @@ fn create_base_request(
             RichContent::Text("Option B".to_string()),
             RichContent::Text("Option C".to_string()),
         ],
+        max_cost: None,
         backoff_max_elapsed_time: None,
         first_chunk_timeout: None,
         other_chunk_timeout: None,
@@ -19,6 +19,7 @@
 //! - [`validateEnsemble`] - Validate and compute ID for an Ensemble
 //! - [`compileFunctionTasks`] - Compile function tasks for a given input
 //! - [`compileFunctionOutput`] - Compile function output from task results
+//! - [`evaluateExpression`] - Evaluate a standalone expression against params
 //! - [`promptId`] - Compute content-addressed ID for chat messages
 //! - [`toolsId`] - Compute content-addressed ID for tools
 //! - [`vectorResponseId`] - Compute content-addressed ID for a response option
@@ -367,6 +368,51 @@ pub fn compileFunctionInputMerge(
     Ok(input_merge)
 }
 
+/// Evaluates a standalone expression against the given params.
+///
+/// Uses the same JMESPath and Starlark runtime as Function compilation, so
+/// authoring tools can preview an expression's result as it is written.
+///
+/// # Arguments
+///
+/// * `expression` - A `{"$jmespath": ...}` or `{"$starlark": ...}` expression
+/// * `input` - The `input` available to the expression
+/// * `map` - The `map` element available to the expression, or `null`
+/// * `output` - The task `output` available to the expression, or `null`
+/// * `tasks` - The task results available to the expression as `tasks`, or
+///   `null`
+///
+/// # Returns
+///
+/// - `{ value: ... }` with the raw value the expression evaluated to
+/// - `{ error: { message, dialect, span } }` if parsing or evaluation fails
+///
+/// # Errors
+///
+/// Returns an error if the arguments cannot be deserialized.
+#[wasm_bindgen]
+pub fn evaluateExpression(
+    expression: JsValue,
+    input: JsValue,
+    map: JsValue,
+    output: JsValue,
+    tasks: JsValue,
+) -> Result<JsValue, JsValue> {
+    // deserialize
+    let request = objectiveai::functions::expression::EvaluateExpressionRequest {
+        expression: serde_wasm_bindgen::from_value(expression)?,
+        input: serde_wasm_bindgen::from_value(input)?,
+        map: serde_wasm_bindgen::from_value(map)?,
+        output: serde_wasm_bindgen::from_value(output)?,
+        tasks: serde_wasm_bindgen::from_value(tasks)?,
+    };
+    // evaluate
+    let response = request.evaluate();
+    // serialize
+    let response: JsValue = serde_wasm_bindgen::to_value(&response)?;
+    Ok(response)
+}
+
 /// Computes a content-addressed ID for chat messages.
 ///
 /// Normalizes the messages (consolidates text parts, removes empty content)
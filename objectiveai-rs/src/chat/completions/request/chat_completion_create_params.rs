@@ -39,6 +39,11 @@ pub struct ChatCompletionCreateParams {
     /// Predicted output for speculative decoding.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prediction: Option<super::Prediction>,
+    /// If present, the maximum total cost of the request, including every
+    /// upstream call made for it. Once reached, the remaining calls are
+    /// cancelled and the request ends with what completed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_cost: Option<rust_decimal::Decimal>,
 
     // --- Retry configuration ---
 
@@ -0,0 +1,538 @@
+//! Compatibility checks between Functions and Profiles.
+//!
+//! [`Function::check_compatibility`] walks the tasks of a Function alongside
+//! the tasks of a Profile, reporting mismatches that would otherwise only
+//! surface when the Function is executed:
+//!
+//! - the number of task profiles and weights against the number of tasks
+//! - the kind of each task profile against the kind of its task
+//! - for function tasks, the nested Function against its nested Profile
+//! - for vector completion tasks, the number of weights against the number
+//!   of LLMs in the ensemble
+//!
+//! Nested Functions, remote Profiles and Ensembles referenced by ID are
+//! looked up with a [`CompatibilityResolver`]. References it cannot resolve
+//! are not checked.
+//!
+//! [`Function::check_compatibility`]: super::Function::check_compatibility
+
+use crate::{ensemble, functions, vector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A mismatch between a Function and a Profile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompatibilityIssue {
+    /// Location of the mismatch in the Profile, e.g. `tasks[1].profile`.
+    pub path: String,
+    /// The category of the mismatch.
+    pub kind: CompatibilityIssueKind,
+    /// Human-readable description of the mismatch.
+    pub message: String,
+}
+
+impl std::fmt::Display for CompatibilityIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// The category of a [`CompatibilityIssue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompatibilityIssueKind {
+    /// The Profile has a different number of task profiles than the Function
+    /// has tasks.
+    TaskCount,
+    /// The Profile has a different number of weights than the Function has
+    /// tasks.
+    WeightCount,
+    /// A task profile is of a different kind than its task.
+    TaskKind,
+    /// A vector completion task profile has a different number of weights
+    /// than its ensemble has LLMs.
+    EnsembleWeightCount,
+    /// A vector completion task profile's ensemble is invalid.
+    InvalidEnsemble,
+}
+
+/// Looks up the definitions referenced by Functions and Profiles.
+pub trait CompatibilityResolver {
+    /// Returns the Function at the given commit, if known.
+    fn function(
+        &self,
+        owner: &str,
+        repository: &str,
+        commit: &str,
+    ) -> Option<&functions::RemoteFunction>;
+
+    /// Returns the Profile at the given commit, or the latest Profile
+    /// without a commit, if known.
+    fn profile(
+        &self,
+        owner: &str,
+        repository: &str,
+        commit: Option<&str>,
+    ) -> Option<&functions::RemoteProfile>;
+
+    /// Returns the Ensemble with the given ID, if known.
+    fn ensemble(&self, id: &str) -> Option<&ensemble::Ensemble>;
+}
+
+/// Resolves nothing, checking only what is defined inline.
+impl CompatibilityResolver for () {
+    fn function(
+        &self,
+        _owner: &str,
+        _repository: &str,
+        _commit: &str,
+    ) -> Option<&functions::RemoteFunction> {
+        None
+    }
+
+    fn profile(
+        &self,
+        _owner: &str,
+        _repository: &str,
+        _commit: Option<&str>,
+    ) -> Option<&functions::RemoteProfile> {
+        None
+    }
+
+    fn ensemble(&self, _id: &str) -> Option<&ensemble::Ensemble> {
+        None
+    }
+}
+
+/// Definitions referenced by Functions and Profiles, collected up front.
+#[derive(Debug, Clone, Default)]
+pub struct CompatibilityDefinitions {
+    /// Functions by `owner/repository/commit`.
+    pub functions: HashMap<String, functions::RemoteFunction>,
+    /// Profiles by `owner/repository/commit`, or `owner/repository` for the
+    /// latest Profile.
+    pub profiles: HashMap<String, functions::RemoteProfile>,
+    /// Ensembles by ID.
+    pub ensembles: HashMap<String, ensemble::Ensemble>,
+}
+
+impl CompatibilityDefinitions {
+    /// The key of a Function or Profile.
+    pub fn key(owner: &str, repository: &str, commit: Option<&str>) -> String {
+        match commit {
+            Some(commit) => format!("{}/{}/{}", owner, repository, commit),
+            None => format!("{}/{}", owner, repository),
+        }
+    }
+}
+
+impl CompatibilityResolver for CompatibilityDefinitions {
+    fn function(
+        &self,
+        owner: &str,
+        repository: &str,
+        commit: &str,
+    ) -> Option<&functions::RemoteFunction> {
+        self.functions
+            .get(&Self::key(owner, repository, Some(commit)))
+    }
+
+    fn profile(
+        &self,
+        owner: &str,
+        repository: &str,
+        commit: Option<&str>,
+    ) -> Option<&functions::RemoteProfile> {
+        self.profiles.get(&Self::key(owner, repository, commit))
+    }
+
+    fn ensemble(&self, id: &str) -> Option<&ensemble::Ensemble> {
+        self.ensembles.get(id)
+    }
+}
+
+impl functions::Function {
+    /// Checks that `profile` fits the task structure of this Function.
+    ///
+    /// Returns an empty vector if no mismatches were found. See the
+    /// [module documentation](self) for the checks performed.
+    pub fn check_compatibility(
+        &self,
+        profile: &functions::Profile,
+        resolver: &impl CompatibilityResolver,
+    ) -> Vec<CompatibilityIssue> {
+        let (tasks, weights) = match profile {
+            functions::Profile::Remote(profile) => {
+                (&profile.tasks, &profile.profile)
+            }
+            functions::Profile::Inline(profile) => {
+                (&profile.tasks, &profile.profile)
+            }
+        };
+        let mut issues = Vec::new();
+        check_tasks(
+            self.tasks(),
+            tasks,
+            weights,
+            String::new(),
+            resolver,
+            &mut issues,
+        );
+        issues
+    }
+}
+
+fn check_tasks(
+    tasks: &[functions::TaskExpression],
+    task_profiles: &[functions::TaskProfile],
+    weights: &vector::completions::request::Profile,
+    path: String,
+    resolver: &impl CompatibilityResolver,
+    issues: &mut Vec<CompatibilityIssue>,
+) {
+    if task_profiles.len() != tasks.len() {
+        issues.push(CompatibilityIssue {
+            path: join(&path, "tasks"),
+            kind: CompatibilityIssueKind::TaskCount,
+            message: format!(
+                "profile tasks length ({}) does not match function tasks length ({})",
+                task_profiles.len(),
+                tasks.len(),
+            ),
+        });
+    }
+    let weights_len = weights.to_weights_and_invert().len();
+    if weights_len != tasks.len() {
+        issues.push(CompatibilityIssue {
+            path: join(&path, "profile"),
+            kind: CompatibilityIssueKind::WeightCount,
+            message: format!(
+                "profile weights length ({}) does not match function tasks length ({})",
+                weights_len,
+                tasks.len(),
+            ),
+        });
+    }
+    for (i, (task, task_profile)) in tasks.iter().zip(task_profiles).enumerate()
+    {
+        let path = join(&path, &format!("tasks[{}]", i));
+        check_task(task, task_profile, path, resolver, issues);
+    }
+}
+
+fn check_task(
+    task: &functions::TaskExpression,
+    task_profile: &functions::TaskProfile,
+    path: String,
+    resolver: &impl CompatibilityResolver,
+    issues: &mut Vec<CompatibilityIssue>,
+) {
+    match (task, task_profile) {
+        (
+            functions::TaskExpression::ScalarFunction(
+                functions::ScalarFunctionTaskExpression {
+                    owner,
+                    repository,
+                    commit,
+                    ..
+                },
+            )
+            | functions::TaskExpression::VectorFunction(
+                functions::VectorFunctionTaskExpression {
+                    owner,
+                    repository,
+                    commit,
+                    ..
+                },
+            ),
+            functions::TaskProfile::RemoteFunction { .. }
+            | functions::TaskProfile::InlineFunction(_),
+        ) => {
+            let Some(function) = resolver.function(owner, repository, commit)
+            else {
+                return;
+            };
+            let (tasks, weights) = match task_profile {
+                functions::TaskProfile::RemoteFunction {
+                    owner,
+                    repository,
+                    commit,
+                } => {
+                    match resolver.profile(owner, repository, commit.as_deref())
+                    {
+                        Some(profile) => (&profile.tasks, &profile.profile),
+                        None => return,
+                    }
+                }
+                functions::TaskProfile::InlineFunction(profile) => {
+                    (&profile.tasks, &profile.profile)
+                }
+                _ => unreachable!(),
+            };
+            check_tasks(
+                function.tasks(),
+                tasks,
+                weights,
+                path,
+                resolver,
+                issues,
+            );
+        }
+        (
+            functions::TaskExpression::VectorCompletion(_),
+            functions::TaskProfile::VectorCompletion { ensemble, profile },
+        ) => {
+            let llms_len = match ensemble {
+                vector::completions::request::Ensemble::Id(id) => {
+                    match resolver.ensemble(id) {
+                        Some(ensemble) => ensemble.llms.len(),
+                        None => return,
+                    }
+                }
+                vector::completions::request::Ensemble::Provided(base) => {
+                    match ensemble::Ensemble::try_from(base.clone()) {
+                        Ok(ensemble) => ensemble.llms.len(),
+                        Err(e) => {
+                            issues.push(CompatibilityIssue {
+                                path: join(&path, "ensemble"),
+                                kind: CompatibilityIssueKind::InvalidEnsemble,
+                                message: e,
+                            });
+                            return;
+                        }
+                    }
+                }
+            };
+            let weights_len = profile.to_weights_and_invert().len();
+            if weights_len != llms_len {
+                issues.push(CompatibilityIssue {
+                    path: join(&path, "profile"),
+                    kind: CompatibilityIssueKind::EnsembleWeightCount,
+                    message: format!(
+                        "vector completion profile weights length ({}) does not match ensemble LLMs length ({})",
+                        weights_len, llms_len,
+                    ),
+                });
+            }
+        }
+        (
+            functions::TaskExpression::ChatCompletion(_),
+            functions::TaskProfile::ChatCompletion { .. },
+        ) => {}
+        (task, _) => {
+            let expected = match task {
+                functions::TaskExpression::ScalarFunction(_)
+                | functions::TaskExpression::VectorFunction(_) => {
+                    "function profile (RemoteFunction or InlineFunction)"
+                }
+                functions::TaskExpression::VectorCompletion(_) => {
+                    "VectorCompletion profile"
+                }
+                functions::TaskExpression::ChatCompletion(_) => {
+                    "ChatCompletion profile"
+                }
+            };
+            let found = match task_profile {
+                functions::TaskProfile::RemoteFunction { .. } => {
+                    "RemoteFunction"
+                }
+                functions::TaskProfile::InlineFunction(_) => "InlineFunction",
+                functions::TaskProfile::VectorCompletion { .. } => {
+                    "VectorCompletion"
+                }
+                functions::TaskProfile::ChatCompletion { .. } => {
+                    "ChatCompletion"
+                }
+            };
+            issues.push(CompatibilityIssue {
+                path,
+                kind: CompatibilityIssueKind::TaskKind,
+                message: format!("expected {}, found {}", expected, found),
+            });
+        }
+    }
+}
+
+/// Appends a field to a path.
+fn join(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{}.{}", path, field)
+    }
+}
+
+/// A Function and Profile to check for compatibility.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckCompatibilityRequest {
+    /// The Function.
+    pub function: functions::executions::batch::request::BatchFunction,
+    /// The Profile.
+    pub profile: functions::executions::batch::request::BatchProfile,
+}
+
+/// The result of a compatibility check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckCompatibilityResponse {
+    /// Whether no mismatches were found.
+    pub compatible: bool,
+    /// The mismatches found.
+    pub issues: Vec<CompatibilityIssue>,
+}
+
+impl From<Vec<CompatibilityIssue>> for CheckCompatibilityResponse {
+    fn from(issues: Vec<CompatibilityIssue>) -> Self {
+        Self {
+            compatible: issues.is_empty(),
+            issues,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(tasks: serde_json::Value) -> functions::RemoteFunction {
+        serde_json::from_value(serde_json::json!({
+            "type": "scalar.function",
+            "description": "test",
+            "input_schema": { "type": "object", "properties": {} },
+            "tasks": tasks
+        }))
+        .unwrap()
+    }
+
+    fn profile(value: serde_json::Value) -> functions::InlineProfile {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn vector_completion_task() -> serde_json::Value {
+        serde_json::json!({
+            "type": "vector.completion",
+            "messages": [{ "role": "user", "content": "hi" }],
+            "responses": ["yes", "no"],
+            "output": { "$jmespath": "output.scores[0]" }
+        })
+    }
+
+    fn function_task() -> serde_json::Value {
+        serde_json::json!({
+            "type": "scalar.function",
+            "owner": "owner",
+            "repository": "nested",
+            "commit": "abc",
+            "input": { "$jmespath": "input" },
+            "output": { "$jmespath": "output" }
+        })
+    }
+
+    fn vector_completion_profile(weights: usize) -> serde_json::Value {
+        serde_json::json!({
+            "ensemble": {
+                "llms": [
+                    { "model": "openai/gpt-4o", "count": 1 },
+                    { "model": "anthropic/claude-3-5-sonnet", "count": 1 }
+                ]
+            },
+            "profile": vec![1; weights]
+        })
+    }
+
+    fn check(
+        function: functions::RemoteFunction,
+        profile: functions::InlineProfile,
+        resolver: &impl CompatibilityResolver,
+    ) -> Vec<CompatibilityIssue> {
+        functions::Function::Remote(function)
+            .check_compatibility(&functions::Profile::Inline(profile), resolver)
+    }
+
+    #[test]
+    fn test_compatible() {
+        let issues = check(
+            function(serde_json::json!([vector_completion_task()])),
+            profile(serde_json::json!({
+                "tasks": [vector_completion_profile(2)],
+                "profile": [1]
+            })),
+            &(),
+        );
+        assert_eq!(issues, Vec::new());
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let issues = check(
+            function(serde_json::json!([
+                vector_completion_task(),
+                vector_completion_task()
+            ])),
+            profile(serde_json::json!({
+                "tasks": [vector_completion_profile(3)],
+                "profile": [1]
+            })),
+            &(),
+        );
+        let kinds = issues
+            .iter()
+            .map(|issue| (issue.path.as_str(), issue.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ("tasks", CompatibilityIssueKind::TaskCount),
+                ("profile", CompatibilityIssueKind::WeightCount),
+                (
+                    "tasks[0].profile",
+                    CompatibilityIssueKind::EnsembleWeightCount
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_nested_function() {
+        let nested_profile = serde_json::json!({
+            "tasks": [vector_completion_profile(1)],
+            "profile": [1]
+        });
+        let root = function(serde_json::json!([function_task()]));
+        let root_profile = profile(serde_json::json!({
+            "tasks": [nested_profile],
+            "profile": [1]
+        }));
+
+        // unresolved nested Functions are not checked
+        assert_eq!(check(root.clone(), root_profile.clone(), &()), Vec::new());
+
+        let mut definitions = CompatibilityDefinitions::default();
+        definitions.functions.insert(
+            CompatibilityDefinitions::key("owner", "nested", Some("abc")),
+            function(serde_json::json!([vector_completion_task()])),
+        );
+        let issues = check(root, root_profile, &definitions);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "tasks[0].tasks[0].profile");
+        assert_eq!(issues[0].kind, CompatibilityIssueKind::EnsembleWeightCount);
+    }
+
+    #[test]
+    fn test_task_kind() {
+        let issues = check(
+            function(serde_json::json!([function_task()])),
+            profile(serde_json::json!({
+                "tasks": [vector_completion_profile(2)],
+                "profile": [1]
+            })),
+            &(),
+        );
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "tasks[0]");
+        assert_eq!(issues[0].kind, CompatibilityIssueKind::TaskKind);
+    }
+}
@@ -0,0 +1,50 @@
+//! HTTP functions for batch function executions.
+
+use crate::{HttpClient, HttpError};
+use futures::Stream;
+
+/// Creates a batch function execution (non-streaming).
+///
+/// Executes every input and waits for the complete response.
+pub async fn create_function_execution_batch_unary(
+    client: &HttpClient,
+    mut params: super::request::FunctionExecutionBatchCreateParams,
+) -> Result<super::response::unary::FunctionExecutionBatch, HttpError> {
+    params.stream = None;
+    client
+        .send_unary(
+            reqwest::Method::POST,
+            "functions/executions/batch",
+            Some(params),
+        )
+        .await
+}
+
+/// Creates a batch function execution with streaming output.
+///
+/// Streams each item as soon as its execution completes, followed by the
+/// aggregated usage.
+pub async fn create_function_execution_batch_streaming(
+    client: &HttpClient,
+    mut params: super::request::FunctionExecutionBatchCreateParams,
+) -> Result<
+    impl Stream<
+        Item = Result<
+            super::response::streaming::FunctionExecutionBatchChunk,
+            HttpError,
+        >,
+    >
+    + Send
+    + 'static
+    + use<>,
+    HttpError,
+> {
+    params.stream = Some(true);
+    client
+        .send_streaming(
+            reqwest::Method::POST,
+            "functions/executions/batch",
+            Some(params),
+        )
+        .await
+}
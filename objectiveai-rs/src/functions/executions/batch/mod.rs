@@ -0,0 +1,14 @@
+//! Batch function execution request and response types.
+//!
+//! A batch executes one Function and Profile against many inputs. Each
+//! input is executed independently, with bounded concurrency, and its
+//! result is streamed as soon as it completes.
+
+pub mod request;
+pub mod response;
+
+#[cfg(feature = "http")]
+mod http;
+
+#[cfg(feature = "http")]
+pub use http::*;
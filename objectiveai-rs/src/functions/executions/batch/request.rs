@@ -0,0 +1,288 @@
+//! Request types for batch function executions.
+
+use crate::{
+    chat,
+    functions::{self, executions::request},
+};
+use serde::{Deserialize, Serialize};
+
+/// A Function referenced by GitHub repository, or defined inline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BatchFunction {
+    /// A remote Function.
+    Remote {
+        /// Function repository owner.
+        owner: String,
+        /// Function repository name.
+        repository: String,
+        /// Function Git commit SHA (optional).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        commit: Option<String>,
+    },
+    /// An inline Function definition.
+    Inline(functions::InlineFunction),
+}
+
+/// A Profile referenced by GitHub repository, or defined inline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BatchProfile {
+    /// A remote Profile.
+    Remote {
+        /// Profile repository owner.
+        owner: String,
+        /// Profile repository name.
+        repository: String,
+        /// Profile Git commit SHA (optional).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        commit: Option<String>,
+    },
+    /// An inline Profile definition.
+    Inline(functions::InlineProfile),
+}
+
+/// Parameters for creating a batch function execution.
+///
+/// Every input is executed with the same Function, Profile and options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionExecutionBatchCreateParams {
+    /// The Function to execute.
+    pub function: BatchFunction,
+    /// The Profile to execute the Function with.
+    pub profile: BatchProfile,
+    /// The inputs to execute the Function against.
+    #[serde(default)]
+    pub inputs: Vec<functions::expression::Input>,
+    /// Retry tokens from a previous batch, aligned with `inputs`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_tokens: Option<Vec<Option<String>>>,
+    /// Maximum number of inputs executed at once.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<usize>,
+
+    // --- Caching options ---
+    /// If true, uses cached votes when available.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_cache: Option<bool>,
+    /// If true, remaining votes are generated randomly (for testing/simulation).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_rng: Option<bool>,
+    /// If present, reuses the results of previous identical executions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<request::Cache>,
+
+    // --- Reasoning configuration ---
+    /// Reasoning summary configuration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<request::Reasoning>,
+
+    // --- Core configuration ---
+    /// Execution strategy.
+    /// Defaults to `Default` strategy if not specified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<request::Strategy>,
+    /// If present, only the best `top_k` items of each input matter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<usize>,
+    /// Provider routing preferences.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<chat::completions::request::Provider>,
+    /// Random seed for deterministic results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    /// Whether to stream the response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+
+    // --- Retry configuration ---
+    /// Maximum elapsed time (ms) for exponential backoff retries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backoff_max_elapsed_time: Option<u64>,
+    /// Timeout (ms) for receiving the first chunk of a streaming response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_chunk_timeout: Option<u64>,
+    /// Timeout (ms) between subsequent chunks of a streaming response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub other_chunk_timeout: Option<u64>,
+}
+
+/// A line of a JSONL batch that could not be parsed.
+#[derive(Debug, thiserror::Error)]
+#[error("invalid line {line}: {source}")]
+pub struct JsonlError {
+    /// The 1-based line number.
+    pub line: usize,
+    /// Why the line could not be parsed.
+    pub source: serde_json::Error,
+}
+
+impl FunctionExecutionBatchCreateParams {
+    /// Parses a JSONL batch.
+    ///
+    /// The first line holds the parameters, and every following line holds
+    /// one input, appended after any `inputs` in the parameters. Blank lines
+    /// are skipped.
+    pub fn from_jsonl(content: &str) -> Result<Self, JsonlError> {
+        let mut lines = content
+            .trim_start_matches('\u{feff}')
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| (i + 1, line));
+        let mut params = match lines.next() {
+            Some((line, params)) => serde_json::from_str::<Self>(params)
+                .map_err(|source| JsonlError { line, source })?,
+            None => {
+                return Err(JsonlError {
+                    line: 1,
+                    source: serde::de::Error::custom("missing parameters"),
+                });
+            }
+        };
+        for (line, input) in lines {
+            params.inputs.push(
+                serde_json::from_str(input)
+                    .map_err(|source| JsonlError { line, source })?,
+            );
+        }
+        Ok(params)
+    }
+
+    /// Builds the execution request for the input at `index`.
+    ///
+    /// Returns None if `index` is out of bounds.
+    pub fn request(&self, index: usize) -> Option<request::Request> {
+        let base = request::FunctionRemoteProfileRemoteRequestBody {
+            retry_token: self
+                .retry_tokens
+                .as_ref()
+                .and_then(|tokens| tokens.get(index).cloned().flatten()),
+            from_cache: self.from_cache,
+            from_rng: self.from_rng,
+            cache: self.cache.clone(),
+            reasoning: self.reasoning.clone(),
+            strategy: self.strategy.clone(),
+            top_k: self.top_k,
+            input: self.inputs.get(index)?.clone(),
+            provider: self.provider,
+            seed: self.seed,
+            stream: None,
+            dry_run: None,
+            max_cost: None,
+            attribution: None,
+            backoff_max_elapsed_time: self.backoff_max_elapsed_time,
+            first_chunk_timeout: self.first_chunk_timeout,
+            other_chunk_timeout: self.other_chunk_timeout,
+        };
+        Some(match (&self.function, &self.profile) {
+            (
+                BatchFunction::Inline(function),
+                BatchProfile::Inline(profile),
+            ) => request::Request::FunctionInlineProfileInline {
+                body: request::FunctionInlineProfileInlineRequestBody {
+                    function: function.clone(),
+                    profile: profile.clone(),
+                    base,
+                },
+            },
+            (
+                BatchFunction::Inline(function),
+                BatchProfile::Remote {
+                    owner,
+                    repository,
+                    commit,
+                },
+            ) => request::Request::FunctionInlineProfileRemote {
+                path: request::FunctionInlineProfileRemoteRequestPath {
+                    powner: owner.clone(),
+                    prepository: repository.clone(),
+                    pcommit: commit.clone(),
+                },
+                body: request::FunctionInlineProfileRemoteRequestBody {
+                    function: function.clone(),
+                    base,
+                },
+            },
+            (
+                BatchFunction::Remote {
+                    owner,
+                    repository,
+                    commit,
+                },
+                BatchProfile::Inline(profile),
+            ) => request::Request::FunctionRemoteProfileInline {
+                path: request::FunctionRemoteProfileInlineRequestPath {
+                    fowner: owner.clone(),
+                    frepository: repository.clone(),
+                    fcommit: commit.clone(),
+                },
+                body: request::FunctionRemoteProfileInlineRequestBody {
+                    profile: profile.clone(),
+                    base,
+                },
+            },
+            (
+                BatchFunction::Remote {
+                    owner: fowner,
+                    repository: frepository,
+                    commit: fcommit,
+                },
+                BatchProfile::Remote {
+                    owner: powner,
+                    repository: prepository,
+                    commit: pcommit,
+                },
+            ) => request::Request::FunctionRemoteProfileRemote {
+                path: request::FunctionRemoteProfileRemoteRequestPath {
+                    fowner: fowner.clone(),
+                    frepository: frepository.clone(),
+                    fcommit: fcommit.clone(),
+                    powner: powner.clone(),
+                    prepository: prepository.clone(),
+                    pcommit: pcommit.clone(),
+                },
+                body: base,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_jsonl() {
+        let params = FunctionExecutionBatchCreateParams::from_jsonl(
+            "{\"function\":{\"owner\":\"o\",\"repository\":\"f\"},\
+             \"profile\":{\"owner\":\"o\",\"repository\":\"p\"},\
+             \"inputs\":[1],\"retry_tokens\":[null,\"token\"]}\n\
+             \n\
+             \"two\"\n",
+        )
+        .unwrap();
+        assert_eq!(params.inputs.len(), 2);
+        let Some(request::Request::FunctionRemoteProfileRemote { path, body }) =
+            params.request(1)
+        else {
+            panic!("expected remote function and profile");
+        };
+        assert_eq!(path.frepository, "f");
+        assert_eq!(path.prepository, "p");
+        assert_eq!(body.retry_token.as_deref(), Some("token"));
+        assert!(matches!(
+            body.input,
+            functions::expression::Input::String(s) if s == "two"
+        ));
+        assert!(params.request(2).is_none());
+
+        let error = FunctionExecutionBatchCreateParams::from_jsonl(
+            "{\"function\":{\"owner\":\"o\",\"repository\":\"f\"},\
+             \"profile\":{\"owner\":\"o\",\"repository\":\"p\"}}\n\
+             {\n",
+        )
+        .unwrap_err();
+        assert_eq!(error.line, 2);
+    }
+}
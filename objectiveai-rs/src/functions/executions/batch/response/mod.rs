@@ -0,0 +1,7 @@
+//! Response types for batch function executions.
+//!
+//! - [`unary`] - Complete (non-streaming) responses
+//! - [`streaming`] - Per-item chunk-based responses
+
+pub mod streaming;
+pub mod unary;
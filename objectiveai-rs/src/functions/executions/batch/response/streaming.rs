@@ -0,0 +1,45 @@
+//! Streaming batch function execution response.
+
+use crate::vector;
+use serde::{Deserialize, Serialize};
+
+/// A chunk of a streaming batch function execution.
+///
+/// Each item is streamed once, when its execution completes. The final
+/// chunk holds the usage aggregated across every item.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionExecutionBatchChunk {
+    /// Unique identifier for this batch.
+    pub id: String,
+    /// Items completed since the previous chunk.
+    pub items: Vec<super::unary::FunctionExecutionBatchItem>,
+    /// Unix timestamp when the batch was created.
+    pub created: u64,
+    /// Object type identifier.
+    pub object: Object,
+    /// Aggregated token and cost usage, present on the final chunk.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<vector::completions::response::Usage>,
+}
+
+impl FunctionExecutionBatchChunk {
+    /// Merges another chunk into this one.
+    pub fn push(&mut self, other: &FunctionExecutionBatchChunk) {
+        self.items.extend(other.items.iter().cloned());
+        match (&mut self.usage, &other.usage) {
+            (Some(self_usage), Some(other_usage)) => {
+                self_usage.push(other_usage);
+            }
+            (None, Some(other_usage)) => {
+                self.usage = Some(other_usage.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Object {
+    #[serde(rename = "function.execution.batch.chunk")]
+    FunctionExecutionBatchChunk,
+}
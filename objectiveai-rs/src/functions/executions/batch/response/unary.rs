@@ -0,0 +1,76 @@
+//! Complete batch function execution response.
+
+use crate::{error, functions::executions::response, vector};
+use serde::{Deserialize, Serialize};
+
+/// The result of executing a single input of a batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionExecutionBatchItem {
+    /// Index of the input in the batch.
+    pub index: usize,
+    /// The execution, if it could be started.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution: Option<response::unary::FunctionExecution>,
+    /// Error details if the execution could not be started.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<error::ResponseError>,
+}
+
+impl FunctionExecutionBatchItem {
+    /// Returns the token for retrying this item, if any.
+    pub fn retry_token(&self) -> Option<&str> {
+        self.execution
+            .as_ref()
+            .and_then(|execution| execution.retry_token.as_deref())
+    }
+}
+
+/// A complete batch function execution response (non-streaming).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionExecutionBatch {
+    /// Unique identifier for this batch.
+    pub id: String,
+    /// The result for every input, in input order.
+    pub items: Vec<FunctionExecutionBatchItem>,
+    /// Tokens for retrying each item, aligned with the inputs.
+    pub retry_tokens: Vec<Option<String>>,
+    /// Unix timestamp when the batch was created.
+    pub created: u64,
+    /// Object type identifier.
+    pub object: Object,
+    /// Aggregated token and cost usage across every item.
+    pub usage: vector::completions::response::Usage,
+}
+
+impl From<super::streaming::FunctionExecutionBatchChunk>
+    for FunctionExecutionBatch
+{
+    fn from(
+        super::streaming::FunctionExecutionBatchChunk {
+            id,
+            mut items,
+            created,
+            usage,
+            ..
+        }: super::streaming::FunctionExecutionBatchChunk,
+    ) -> Self {
+        items.sort_unstable_by_key(|item| item.index);
+        Self {
+            id,
+            retry_tokens: items
+                .iter()
+                .map(|item| item.retry_token().map(str::to_owned))
+                .collect(),
+            items,
+            created,
+            object: Object::FunctionExecutionBatch,
+            usage: usage.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Object {
+    #[serde(rename = "function.execution.batch")]
+    FunctionExecutionBatch,
+}
@@ -0,0 +1,22 @@
+//! HTTP functions for the function execution cache.
+
+use crate::{HttpClient, HttpError};
+
+/// Removes stored function executions matching the request's filters.
+///
+/// # Arguments
+///
+/// * `client` - The HTTP client to use
+/// * `request` - The Function and Profile filters
+pub async fn invalidate_function_execution_cache(
+    client: &HttpClient,
+    request: &super::request::InvalidateCacheRequest,
+) -> Result<super::response::InvalidateCacheResponse, HttpError> {
+    client
+        .send_unary(
+            reqwest::Method::POST,
+            "functions/executions/cache/invalidate",
+            Some(request),
+        )
+        .await
+}
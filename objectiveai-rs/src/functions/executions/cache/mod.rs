@@ -0,0 +1,14 @@
+//! Types for managing the cache of whole function executions.
+//!
+//! Executions requested with `cache` are stored by the server and reused by
+//! later identical executions. Stored executions can be removed by Function
+//! or Profile.
+
+pub mod request;
+pub mod response;
+
+#[cfg(feature = "http")]
+mod http;
+
+#[cfg(feature = "http")]
+pub use http::*;
@@ -0,0 +1,19 @@
+//! Request types for invalidating cached function executions.
+
+use serde::{Deserialize, Serialize};
+
+/// Removes the caller's stored executions of remote Functions and Profiles.
+///
+/// Each filter is either `owner/repository`, matching every commit, or
+/// `owner/repository/commit`. Executions matching both filters are removed.
+/// Without filters, every stored execution of the caller is removed.
+/// Executions stored for other callers are never removed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InvalidateCacheRequest {
+    /// The Function whose executions are removed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    /// The Profile whose executions are removed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
@@ -0,0 +1,10 @@
+//! Response types for invalidating cached function executions.
+
+use serde::{Deserialize, Serialize};
+
+/// Result of invalidating cached function executions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvalidateCacheResponse {
+    /// Number of stored executions removed.
+    pub invalidated: u64,
+}
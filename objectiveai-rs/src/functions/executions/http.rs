@@ -288,3 +288,133 @@ pub async fn create_function_execution_streaming(
         }
     }
 }
+
+/// Cancels a running streaming function execution.
+///
+/// Outstanding chat completions across the whole execution are aborted. The
+/// execution's stream ends with the tasks that completed, a retry token for
+/// them, and a cancellation error.
+///
+/// # Arguments
+///
+/// * `client` - The HTTP client to use
+/// * `id` - The ID of the function execution, as found on its chunks
+pub async fn cancel_function_execution(
+    client: &HttpClient,
+    id: &str,
+) -> Result<(), HttpError> {
+    client
+        .send_unary_no_response(
+            reqwest::Method::POST,
+            format!("functions/executions/{}/cancel", id),
+            None::<String>,
+        )
+        .await
+}
+
+/// Estimates a function execution without executing it.
+///
+/// Sends the request as a dry run to the same endpoint as
+/// [`create_function_execution_unary`]. No upstream calls are made; the
+/// response is the estimated call plan and cost.
+///
+/// # Arguments
+///
+/// * `client` - The HTTP client to use
+/// * `request` - The function execution request
+///
+/// # Returns
+///
+/// The estimate of the function execution.
+pub async fn estimate_function_execution(
+    client: &HttpClient,
+    mut request: super::request::Request,
+) -> Result<super::response::FunctionExecutionEstimate, HttpError> {
+    let base = request.base_mut();
+    base.stream = None;
+    base.dry_run = Some(true);
+    let path = execution_path(&request);
+    match request {
+        super::request::Request::FunctionInlineProfileInline { body } => {
+            client
+                .send_unary(reqwest::Method::POST, path, Some(body))
+                .await
+        }
+        super::request::Request::FunctionInlineProfileRemote {
+            body, ..
+        } => {
+            client
+                .send_unary(reqwest::Method::POST, path, Some(body))
+                .await
+        }
+        super::request::Request::FunctionRemoteProfileInline {
+            body, ..
+        } => {
+            client
+                .send_unary(reqwest::Method::POST, path, Some(body))
+                .await
+        }
+        super::request::Request::FunctionRemoteProfileRemote {
+            body, ..
+        } => {
+            client
+                .send_unary(reqwest::Method::POST, path, Some(body))
+                .await
+        }
+    }
+}
+
+/// Returns the endpoint path of a function execution request.
+fn execution_path(request: &super::request::Request) -> String {
+    match request {
+        super::request::Request::FunctionInlineProfileInline { .. } => {
+            "functions".to_string()
+        }
+        super::request::Request::FunctionInlineProfileRemote {
+            path, ..
+        } => match &path.pcommit {
+            Some(pcommit) => format!(
+                "functions/profiles/{}/{}/{}",
+                path.powner, path.prepository, pcommit
+            ),
+            None => {
+                format!(
+                    "functions/profiles/{}/{}",
+                    path.powner, path.prepository
+                )
+            }
+        },
+        super::request::Request::FunctionRemoteProfileInline {
+            path, ..
+        } => match &path.fcommit {
+            Some(fcommit) => format!(
+                "functions/{}/{}/{}",
+                path.fowner, path.frepository, fcommit
+            ),
+            None => format!("functions/{}/{}", path.fowner, path.frepository),
+        },
+        super::request::Request::FunctionRemoteProfileRemote {
+            path, ..
+        } => {
+            let function = match &path.fcommit {
+                Some(fcommit) => format!(
+                    "functions/{}/{}/{}",
+                    path.fowner, path.frepository, fcommit
+                ),
+                None => {
+                    format!("functions/{}/{}", path.fowner, path.frepository)
+                }
+            };
+            match &path.pcommit {
+                Some(pcommit) => format!(
+                    "{}/profiles/{}/{}/{}",
+                    function, path.powner, path.prepository, pcommit
+                ),
+                None => format!(
+                    "{}/profiles/{}/{}",
+                    function, path.powner, path.prepository
+                ),
+            }
+        }
+    }
+}
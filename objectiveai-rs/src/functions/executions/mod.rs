@@ -7,7 +7,13 @@
 //! - Remote Function + Inline Profile
 //! - Inline Function + Remote Profile
 //! - Inline Function + Inline Profile
+//!
+//! The [`batch`] module executes one Function and Profile against many
+//! inputs in a single request. The [`cache`] module manages the stored
+//! results of executions requested with `cache`.
 
+pub mod batch;
+pub mod cache;
 pub mod request;
 pub mod response;
 mod retry_token;
@@ -51,6 +51,9 @@ pub struct FunctionRemoteProfileRemoteRequestBody {
     /// If true, remaining votes are generated randomly (for testing/simulation).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub from_rng: Option<bool>,
+    /// If present, reuses the result of a previous identical execution.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<super::Cache>,
 
     // --- Reasoning configuration ---
     /// Reasoning summary configuration.
@@ -62,6 +65,11 @@ pub struct FunctionRemoteProfileRemoteRequestBody {
     /// Defaults to `Default` strategy if not specified.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub strategy: Option<super::Strategy>,
+    /// If present, only the best `top_k` items of a vector Function matter.
+    /// Implies the Swiss system strategy if no other tournament strategy is
+    /// given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<usize>,
     /// The input data to pass to the Function.
     pub input: functions::expression::Input,
     /// Provider routing preferences.
@@ -73,6 +81,26 @@ pub struct FunctionRemoteProfileRemoteRequestBody {
     /// Whether to stream the response.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stream: Option<bool>,
+    /// If true, returns a [`FunctionExecutionEstimate`] of the execution
+    /// instead of running it. Only supported for single executions, batches
+    /// and jobs reject it.
+    ///
+    /// [`FunctionExecutionEstimate`]: crate::functions::executions::response::FunctionExecutionEstimate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<bool>,
+    /// If present, the maximum total cost of the request, including every
+    /// upstream call made for it. Once reached, the remaining calls are
+    /// cancelled and the request ends with the tasks that completed and a
+    /// retry token for them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_cost: Option<rust_decimal::Decimal>,
+    /// If true, each Function execution in the response includes an
+    /// [`Attribution`] of its output to its tasks and, within vector
+    /// completion tasks, to each Ensemble LLM.
+    ///
+    /// [`Attribution`]: crate::functions::executions::response::Attribution
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<bool>,
 
     // --- Retry configuration ---
     /// Maximum elapsed time (ms) for exponential backoff retries.
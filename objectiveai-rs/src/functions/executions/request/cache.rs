@@ -0,0 +1,22 @@
+//! Execution cache configuration for function executions.
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration for reusing the results of previous executions.
+///
+/// When present, a successful execution is stored by the server, keyed by
+/// the caller, the Function and Profile (including their commits), the
+/// canonical ID of the input, and the options that affect the result. A later execution
+/// with the same key returns the stored execution, marked as `cached` and
+/// without any new usage.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Cache {
+    /// How long (ms) the result of this execution may be reused once stored.
+    /// The server's default TTL applies if not specified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u64>,
+    /// If true, any stored execution is ignored and replaced by the result
+    /// of this execution.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh: Option<bool>,
+}
@@ -1,6 +1,6 @@
 //! Reasoning configuration for function executions.
 
-use crate::chat;
+use crate::{chat, functions};
 use serde::{Deserialize, Serialize};
 
 /// Configuration for generating reasoning summaries during execution.
@@ -13,4 +13,7 @@ pub struct Reasoning {
     /// Fallback models tried in order if the primary is rate-limited or errors.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub models: Option<Vec<chat::completions::request::Model>>,
+    /// How the summary is prompted, replacing the Function's own prompt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<functions::ReasoningSummaryPrompt>,
 }
@@ -12,4 +12,50 @@ pub enum Strategy {
         /// How many sequential rounds of comparison
         rounds: Option<usize>, // default is 3
     },
+    /// Vector
+    SingleElimination {
+        /// How many items meet in each match, of which only the best advances
+        pool: Option<usize>, // default is 2
+    },
+    /// Vector
+    DoubleElimination {
+        /// How many items meet in each match, of which all but the best take
+        /// a loss, and items are eliminated after their second loss
+        pool: Option<usize>, // default is 2
+    },
+    /// Vector
+    RoundRobin {
+        /// How many items share a pool, within which every pair is compared
+        pool: Option<usize>, // default is 10
+    },
+    /// Vector
+    TopK {
+        /// How many of the best items are sought
+        k: usize,
+        /// How many vector responses for each execution
+        pool: Option<usize>, // default is 10
+        /// How many sequential rounds of comparison, of which every round
+        /// after the first only compares the items nearest the k-th place
+        rounds: Option<usize>, // default is 3
+    },
+}
+
+impl Strategy {
+    /// The name of the strategy, as found in its `type` field.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Default => "default",
+            Strategy::SwissSystem { .. } => "swiss_system",
+            Strategy::SingleElimination { .. } => "single_elimination",
+            Strategy::DoubleElimination { .. } => "double_elimination",
+            Strategy::RoundRobin { .. } => "round_robin",
+            Strategy::TopK { .. } => "top_k",
+        }
+    }
+
+    /// Whether the strategy ranks the items of a vector Function by
+    /// comparing them in pools, using its `input_split` and `input_merge`.
+    pub fn is_tournament(&self) -> bool {
+        !matches!(self, Strategy::Default)
+    }
 }
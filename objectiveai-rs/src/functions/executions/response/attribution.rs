@@ -0,0 +1,158 @@
+//! Attributions of Function outputs to their tasks and Ensemble LLMs.
+
+use crate::functions;
+use serde::{Deserialize, Serialize};
+
+/// How the tasks of a Function execution contributed to its output.
+///
+/// Without an output expression, the Function's output is the sum of the
+/// contributions of its tasks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attribution {
+    /// The attribution of each task, in order. `None` for tasks which were
+    /// skipped or produced no valid output.
+    pub tasks: Vec<Option<TaskAttribution>>,
+}
+
+/// How a task contributed to the output of its Function.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskAttribution {
+    /// The task's profile weight, normalized over the tasks with an output.
+    pub weight: rust_decimal::Decimal,
+    /// Whether the profile inverted the task's output.
+    pub inverted: bool,
+    /// The task's output, after its output expression and any inversion.
+    pub output: functions::expression::FunctionOutput,
+    /// The task's output scaled by its weight. `None` if the Function
+    /// combines its task outputs with its own output expression.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contribution: Option<functions::expression::FunctionOutput>,
+    /// For vector completion tasks, how each Ensemble LLM contributed to the
+    /// scores of the vector completions.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub llms: Vec<LlmAttribution>,
+}
+
+/// How an Ensemble LLM contributed to the scores of a vector completion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LlmAttribution {
+    /// Path of the vector completion task.
+    pub task_path: Vec<u64>,
+    /// Index of the LLM within the ensemble.
+    pub ensemble_index: u64,
+    /// The model that voted.
+    pub model: String,
+    /// The number of votes of the LLM.
+    pub votes: u64,
+    /// The LLM's share of the total vote weight.
+    pub weight: rust_decimal::Decimal,
+    /// The LLM's weighted votes, normalized as the scores are. The
+    /// contributions of every LLM sum to the scores.
+    pub contribution: Vec<rust_decimal::Decimal>,
+}
+
+impl LlmAttribution {
+    /// Attributes the scores of a vector completion to the LLMs whose votes
+    /// produced them, in order of their ensemble index.
+    pub fn from_votes(
+        task_path: &[u64],
+        votes: &[crate::vector::completions::response::Vote],
+    ) -> Vec<LlmAttribution> {
+        let weight_sum: rust_decimal::Decimal = votes
+            .iter()
+            .map(|vote| {
+                vote.vote.iter().sum::<rust_decimal::Decimal>() * vote.weight
+            })
+            .sum();
+        if weight_sum <= rust_decimal::Decimal::ZERO {
+            return Vec::new();
+        }
+        let mut llms: Vec<LlmAttribution> = Vec::new();
+        for vote in votes {
+            let llm = match llms
+                .iter_mut()
+                .find(|llm| llm.ensemble_index == vote.ensemble_index)
+            {
+                Some(llm) => llm,
+                None => {
+                    llms.push(LlmAttribution {
+                        task_path: task_path.to_vec(),
+                        ensemble_index: vote.ensemble_index,
+                        model: vote.model.clone(),
+                        votes: 0,
+                        weight: rust_decimal::Decimal::ZERO,
+                        contribution: vec![
+                            rust_decimal::Decimal::ZERO;
+                            vote.vote.len()
+                        ],
+                    });
+                    llms.last_mut().unwrap()
+                }
+            };
+            llm.votes += 1;
+            for (i, v) in vote.vote.iter().enumerate() {
+                let weighted = *v * vote.weight / weight_sum;
+                llm.weight += weighted;
+                if let Some(contribution) = llm.contribution.get_mut(i) {
+                    *contribution += weighted;
+                }
+            }
+        }
+        llms.sort_by_key(|llm| llm.ensemble_index);
+        llms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    fn vote(
+        ensemble_index: u64,
+        vote: Vec<rust_decimal::Decimal>,
+        weight: rust_decimal::Decimal,
+    ) -> crate::vector::completions::response::Vote {
+        crate::vector::completions::response::Vote {
+            model: format!("llm-{}", ensemble_index),
+            ensemble_index,
+            flat_ensemble_index: ensemble_index,
+            prompt_id: String::new(),
+            tools_id: None,
+            responses_ids: Vec::new(),
+            vote,
+            weight,
+            retry: None,
+            from_cache: None,
+            from_rng: None,
+            completion_index: None,
+        }
+    }
+
+    #[test]
+    fn test_from_votes() {
+        let votes = vec![
+            vote(1, vec![dec!(0), dec!(1)], dec!(2)),
+            vote(0, vec![dec!(1), dec!(0)], dec!(1)),
+            vote(1, vec![dec!(1), dec!(0)], dec!(1)),
+        ];
+        let llms = LlmAttribution::from_votes(&[0], &votes);
+        assert_eq!(llms.len(), 2);
+        assert_eq!(llms[0].ensemble_index, 0);
+        assert_eq!(llms[0].votes, 1);
+        assert_eq!(llms[0].weight, dec!(0.25));
+        assert_eq!(llms[0].contribution, vec![dec!(0.25), dec!(0)]);
+        assert_eq!(llms[1].votes, 2);
+        assert_eq!(llms[1].weight, dec!(0.75));
+        assert_eq!(llms[1].contribution, vec![dec!(0.25), dec!(0.5)]);
+
+        // no weight, nothing to attribute
+        assert!(
+            LlmAttribution::from_votes(
+                &[0],
+                &[vote(0, vec![dec!(1)], dec!(0))]
+            )
+            .is_empty()
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Estimates of function executions, returned for dry runs.
+
+use indexmap::IndexMap;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// The upstream calls a function execution would make, and their estimated
+/// prompt tokens and cost, without making any of them.
+///
+/// Prompt tokens are estimated from the text of the rendered messages,
+/// tools and responses. The cost is an upper bound at the maximum prices of
+/// the request's provider preferences, counting the completion tokens of
+/// LLMs that limit them, and is absent without a maximum prompt price.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionExecutionEstimate {
+    /// ID of the function (if remote).
+    pub function: Option<String>,
+    /// ID of the profile (if remote).
+    pub profile: Option<String>,
+    /// The calls, in the order they would be made.
+    pub calls: Vec<EstimatedCall>,
+    /// Totals of the calls, by model.
+    pub models: IndexMap<String, ModelEstimate>,
+    /// Total number of calls.
+    pub total_calls: u64,
+    /// Total estimated prompt tokens.
+    pub prompt_tokens: u64,
+    /// Total estimated cost, if every call could be priced.
+    pub cost: Option<Decimal>,
+    /// Number of tournament rounds, if a tournament strategy is used.
+    ///
+    /// Rounds are simulated as if every pool were a tie, so strategies that
+    /// prune items by their results may make fewer calls.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rounds: Option<u64>,
+    /// Paths of the tasks that depend on the outputs of other tasks.
+    ///
+    /// They are compiled once their dependencies have run, so their calls
+    /// are not included.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub deferred_tasks: Vec<Vec<u64>>,
+}
+
+/// A single upstream call of a function execution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstimatedCall {
+    /// What the call is for.
+    pub kind: EstimatedCallKind,
+    /// Path of the task making the call, empty for the reasoning summary.
+    pub task_path: Vec<u64>,
+    /// The tournament round of the call, starting at 1, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub round: Option<u64>,
+    /// The model called.
+    pub model: String,
+    /// Estimated prompt tokens.
+    pub prompt_tokens: u64,
+    /// Maximum completion tokens, if the LLM limits them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<u64>,
+    /// Estimated cost, if the call could be priced.
+    pub cost: Option<Decimal>,
+}
+
+/// What an upstream call is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimatedCallKind {
+    /// A vote of an LLM of a vector completion task.
+    VectorCompletion,
+    /// A chat completion task.
+    ChatCompletion,
+    /// The reasoning summary.
+    ReasoningSummary,
+}
+
+/// Totals of the calls to a single model.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelEstimate {
+    /// Number of calls.
+    pub calls: u64,
+    /// Estimated prompt tokens.
+    pub prompt_tokens: u64,
+    /// Estimated cost, if every call could be priced.
+    pub cost: Option<Decimal>,
+}
+
+impl FunctionExecutionEstimate {
+    /// Creates an estimate from its calls, computing the totals.
+    pub fn new(
+        function: Option<String>,
+        profile: Option<String>,
+        calls: Vec<EstimatedCall>,
+        rounds: Option<u64>,
+        deferred_tasks: Vec<Vec<u64>>,
+    ) -> Self {
+        let mut models = IndexMap::<String, ModelEstimate>::new();
+        for call in &calls {
+            let model = models.entry(call.model.clone()).or_insert_with(|| {
+                ModelEstimate {
+                    cost: Some(Decimal::ZERO),
+                    ..Default::default()
+                }
+            });
+            model.calls += 1;
+            model.prompt_tokens += call.prompt_tokens;
+            model.cost = model.cost.zip(call.cost).map(|(a, b)| a + b);
+        }
+        Self {
+            function,
+            profile,
+            total_calls: calls.len() as u64,
+            prompt_tokens: calls.iter().map(|call| call.prompt_tokens).sum(),
+            cost: calls.iter().try_fold(Decimal::ZERO, |total, call| {
+                call.cost.map(|cost| total + cost)
+            }),
+            calls,
+            models,
+            rounds,
+            deferred_tasks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    fn call(
+        model: &str,
+        prompt_tokens: u64,
+        cost: Option<Decimal>,
+    ) -> EstimatedCall {
+        EstimatedCall {
+            kind: EstimatedCallKind::VectorCompletion,
+            task_path: vec![0],
+            round: None,
+            model: model.to_string(),
+            prompt_tokens,
+            max_completion_tokens: None,
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_estimate_totals() {
+        let estimate = FunctionExecutionEstimate::new(
+            None,
+            None,
+            vec![
+                call("a", 10, Some(dec!(0.1))),
+                call("b", 20, None),
+                call("a", 30, Some(dec!(0.3))),
+            ],
+            None,
+            Vec::new(),
+        );
+        assert_eq!(estimate.total_calls, 3);
+        assert_eq!(estimate.prompt_tokens, 60);
+        assert_eq!(estimate.cost, None);
+        assert_eq!(estimate.models["a"].calls, 2);
+        assert_eq!(estimate.models["a"].prompt_tokens, 40);
+        assert_eq!(estimate.models["a"].cost, Some(dec!(0.4)));
+        assert_eq!(estimate.models["b"].cost, None);
+    }
+}
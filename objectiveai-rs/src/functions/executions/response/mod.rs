@@ -2,6 +2,15 @@
 //!
 //! - [`unary`] - Complete (non-streaming) responses
 //! - [`streaming`] - Incremental chunk-based responses
+//! - [`FunctionExecutionEstimate`] - Estimates returned for dry runs
+//! - [`Attribution`] - How tasks and Ensemble LLMs contributed to outputs
 
+mod attribution;
+mod estimate;
+mod ranking;
 pub mod streaming;
 pub mod unary;
+
+pub use attribution::*;
+pub use estimate::*;
+pub use ranking::*;
@@ -0,0 +1,33 @@
+//! Rankings produced by tournament strategies.
+
+use serde::{Deserialize, Serialize};
+
+/// The full ranking of the items of a vector Function execution, produced by
+/// a tournament strategy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ranking {
+    /// Indices of the items, from best to worst.
+    pub order: Vec<usize>,
+    /// Confidence in each item's place, in the order of the items.
+    ///
+    /// The share of the other items whose order relative to the item is
+    /// backed by comparisons agreeing with the ranking, directly or through
+    /// a chain of them, and not contradicted by their head-to-head record.
+    pub confidence: Vec<rust_decimal::Decimal>,
+    /// The best items, if only the best `top_k` were requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<TopK>,
+}
+
+/// The best `top_k` items of a ranking.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopK {
+    /// Indices of the best items, from best to worst.
+    pub order: Vec<usize>,
+    /// Certainty that each of the best items belongs among them, in the
+    /// order of `order`.
+    ///
+    /// The share of the items outside the top k whose order below the item
+    /// is backed as in [`Ranking::confidence`].
+    pub certainty: Vec<rust_decimal::Decimal>,
+}
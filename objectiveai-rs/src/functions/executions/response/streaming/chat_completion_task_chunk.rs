@@ -0,0 +1,28 @@
+use crate::{chat, error};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ChatCompletionTaskChunk {
+    pub index: u64,
+    pub task_index: u64,
+    pub task_path: Vec<u64>,
+    #[serde(flatten)]
+    pub inner: chat::completions::response::streaming::ChatCompletionChunk,
+    /// The output exposed to the Function's other tasks as `tasks[i].output`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<error::ResponseError>,
+}
+
+impl ChatCompletionTaskChunk {
+    pub fn push(&mut self, other: &ChatCompletionTaskChunk) {
+        self.inner.push(&other.inner);
+        if let Some(output) = &other.output {
+            self.output = Some(output.clone());
+        }
+        if let (None, Some(other_error)) = (&self.error, &other.error) {
+            self.error = Some(other_error.clone());
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{error, functions, vector};
+use crate::{
+    error,
+    functions::{self, executions::response},
+    vector,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +16,10 @@ pub struct FunctionExecutionChunk {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output: Option<functions::expression::FunctionOutput>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub ranking: Option<response::Ranking>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<response::Attribution>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<error::ResponseError>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub retry_token: Option<String>,
@@ -21,6 +29,8 @@ pub struct FunctionExecutionChunk {
     pub object: super::Object,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub usage: Option<vector::completions::response::Usage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached: Option<bool>,
 }
 
 impl FunctionExecutionChunk {
@@ -45,9 +55,12 @@ impl FunctionExecutionChunk {
             tasks_errors,
             reasoning,
             output,
+            ranking,
+            attribution,
             retry_token,
             error,
             usage,
+            cached,
             ..
         }: &FunctionExecutionChunk,
     ) {
@@ -55,6 +68,9 @@ impl FunctionExecutionChunk {
         if let Some(true) = tasks_errors {
             self.tasks_errors = Some(true);
         }
+        if let Some(true) = cached {
+            self.cached = Some(true);
+        }
         match (&mut self.reasoning, &reasoning) {
             (Some(self_reasoning), Some(other_reasoning)) => {
                 self_reasoning.push(other_reasoning);
@@ -67,6 +83,12 @@ impl FunctionExecutionChunk {
         if let Some(output) = output {
             self.output = Some(output.clone());
         }
+        if let Some(ranking) = ranking {
+            self.ranking = Some(ranking.clone());
+        }
+        if let Some(attribution) = attribution {
+            self.attribution = Some(attribution.clone());
+        }
         if let Some(retry_token) = retry_token {
             self.retry_token = Some(retry_token.clone());
         }
@@ -1,3 +1,4 @@
+mod chat_completion_task_chunk;
 mod function_execution_chunk;
 mod function_execution_task_chunk;
 mod object;
@@ -5,6 +6,7 @@ mod reasoning_summary_chunk;
 mod task_chunk;
 mod vector_completion_task_chunk;
 
+pub use chat_completion_task_chunk::*;
 pub use function_execution_chunk::*;
 pub use function_execution_task_chunk::*;
 pub use object::*;
@@ -6,4 +6,8 @@ pub enum Object {
     ScalarFunctionExecutionChunk,
     #[serde(rename = "vector.function.execution.chunk")]
     VectorFunctionExecutionChunk,
+    #[serde(rename = "multi_label.function.execution.chunk")]
+    MultiLabelFunctionExecutionChunk,
+    #[serde(rename = "categorical.function.execution.chunk")]
+    CategoricalFunctionExecutionChunk,
 }
@@ -5,6 +5,11 @@ use serde::{Deserialize, Serialize};
 pub struct ReasoningSummaryChunk {
     #[serde(flatten)]
     pub inner: chat::completions::response::streaming::ChatCompletionChunk,
+    /// The summary parsed as JSON, if it was prompted with a schema.
+    /// Present on the last chunk, which has an error instead if the summary
+    /// is not valid JSON.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<error::ResponseError>,
 }
@@ -12,6 +17,9 @@ pub struct ReasoningSummaryChunk {
 impl ReasoningSummaryChunk {
     pub fn push(&mut self, other: &ReasoningSummaryChunk) {
         self.inner.push(&other.inner);
+        if other.data.is_some() {
+            self.data = other.data.clone();
+        }
         match (&mut self.error, &other.error) {
             (None, Some(other_error)) => {
                 self.error = Some(other_error.clone());
@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 pub enum TaskChunk {
     FunctionExecution(super::FunctionExecutionTaskChunk),
     VectorCompletion(super::VectorCompletionTaskChunk),
+    ChatCompletion(super::ChatCompletionTaskChunk),
 }
 
 impl TaskChunk {
@@ -19,6 +20,7 @@ impl TaskChunk {
                 >,
             ),
             VectorCompletion(Option<&'a super::VectorCompletionTaskChunk>),
+            ChatCompletion,
         }
         impl<'a> Iterator for Iter<'a> {
             type Item = &'a super::VectorCompletionTaskChunk;
@@ -26,6 +28,7 @@ impl TaskChunk {
                 match self {
                     Iter::FunctionExecution(iter) => iter.next(),
                     Iter::VectorCompletion(opt) => opt.take(),
+                    Iter::ChatCompletion => None,
                 }
             }
         }
@@ -36,6 +39,7 @@ impl TaskChunk {
             TaskChunk::VectorCompletion(vector_completion) => {
                 Iter::VectorCompletion(Some(&vector_completion))
             }
+            TaskChunk::ChatCompletion(_) => Iter::ChatCompletion,
         }
     }
 
@@ -43,6 +47,7 @@ impl TaskChunk {
         match self {
             TaskChunk::FunctionExecution(chunk) => chunk.index,
             TaskChunk::VectorCompletion(chunk) => chunk.index,
+            TaskChunk::ChatCompletion(chunk) => chunk.index,
         }
     }
 
@@ -60,6 +65,12 @@ impl TaskChunk {
             ) => {
                 self_chunk.push(other_chunk);
             }
+            (
+                TaskChunk::ChatCompletion(self_chunk),
+                TaskChunk::ChatCompletion(other_chunk),
+            ) => {
+                self_chunk.push(other_chunk);
+            }
             _ => {}
         }
     }
@@ -0,0 +1,36 @@
+use crate::{chat, error, functions::executions::response};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ChatCompletionTask {
+    pub index: u64,
+    pub task_index: u64,
+    pub task_path: Vec<u64>,
+    #[serde(flatten)]
+    pub inner: chat::completions::response::unary::ChatCompletion,
+    /// The output exposed to the Function's other tasks as `tasks[i].output`.
+    pub output: Option<serde_json::Value>,
+    pub error: Option<error::ResponseError>,
+}
+
+impl From<response::streaming::ChatCompletionTaskChunk> for ChatCompletionTask {
+    fn from(
+        response::streaming::ChatCompletionTaskChunk {
+            index,
+            task_index,
+            task_path,
+            inner,
+            output,
+            error,
+        }: response::streaming::ChatCompletionTaskChunk,
+    ) -> Self {
+        Self {
+            index,
+            task_index,
+            task_path,
+            inner: inner.into(),
+            output,
+            error,
+        }
+    }
+}
@@ -20,6 +20,11 @@ pub struct FunctionExecution {
     pub reasoning: Option<super::ReasoningSummary>,
     /// The final output (scalar or vector score).
     pub output: functions::expression::FunctionOutput,
+    /// The full ranking of the items, if a tournament strategy was used.
+    pub ranking: Option<response::Ranking>,
+    /// How the tasks and Ensemble LLMs contributed to the output, if
+    /// requested.
+    pub attribution: Option<response::Attribution>,
     /// Error details if the execution failed.
     pub error: Option<error::ResponseError>,
     /// Token for retrying this execution with cached votes.
@@ -34,6 +39,9 @@ pub struct FunctionExecution {
     pub object: super::Object,
     /// Aggregated token and cost usage.
     pub usage: vector::completions::response::Usage,
+    /// Whether this is the stored result of a previous execution.
+    #[serde(default)]
+    pub cached: bool,
 }
 
 impl FunctionExecution {
@@ -50,6 +58,8 @@ impl From<response::streaming::FunctionExecutionChunk> for FunctionExecution {
             tasks_errors,
             reasoning,
             output,
+            ranking,
+            attribution,
             error,
             retry_token,
             created,
@@ -57,6 +67,7 @@ impl From<response::streaming::FunctionExecutionChunk> for FunctionExecution {
             profile,
             object,
             usage,
+            cached,
         }: response::streaming::FunctionExecutionChunk,
     ) -> Self {
         Self {
@@ -69,6 +80,8 @@ impl From<response::streaming::FunctionExecutionChunk> for FunctionExecution {
                     serde_json::Value::Null,
                 ),
             ),
+            ranking,
+            attribution,
             error,
             retry_token,
             created,
@@ -76,6 +89,7 @@ impl From<response::streaming::FunctionExecutionChunk> for FunctionExecution {
             profile,
             object: object.into(),
             usage: usage.unwrap_or_default(),
+            cached: cached.unwrap_or(false),
         }
     }
 }
@@ -3,6 +3,7 @@
 //! - [`FunctionExecution`] - Complete function execution response
 //! - [`Task`] - Result of a single task within the execution
 
+mod chat_completion_task;
 mod function_execution;
 mod function_execution_task;
 mod object;
@@ -10,6 +11,7 @@ mod reasoning_summary;
 mod task;
 mod vector_completion_task;
 
+pub use chat_completion_task::*;
 pub use function_execution::*;
 pub use function_execution_task::*;
 pub use object::*;
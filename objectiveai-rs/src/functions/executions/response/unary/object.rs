@@ -7,6 +7,10 @@ pub enum Object {
     ScalarFunctionExecution,
     #[serde(rename = "vector.function.execution")]
     VectorFunctionExecution,
+    #[serde(rename = "multi_label.function.execution")]
+    MultiLabelFunctionExecution,
+    #[serde(rename = "categorical.function.execution")]
+    CategoricalFunctionExecution,
 }
 
 impl From<response::streaming::Object> for Object {
@@ -18,6 +22,12 @@ impl From<response::streaming::Object> for Object {
             response::streaming::Object::VectorFunctionExecutionChunk => {
                 Object::VectorFunctionExecution
             }
+            response::streaming::Object::MultiLabelFunctionExecutionChunk => {
+                Object::MultiLabelFunctionExecution
+            }
+            response::streaming::Object::CategoricalFunctionExecutionChunk => {
+                Object::CategoricalFunctionExecution
+            }
         }
     }
 }
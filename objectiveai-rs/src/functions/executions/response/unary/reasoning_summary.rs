@@ -6,6 +6,10 @@ use serde::{Deserialize, Serialize};
 pub struct ReasoningSummary {
     #[serde(flatten)]
     pub inner: chat::completions::response::unary::ChatCompletion,
+    /// The summary parsed as JSON, if it was prompted with a schema. If the
+    /// summary is not valid JSON, `error` is set instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
     pub error: Option<error::ResponseError>,
 }
 
@@ -13,11 +17,13 @@ impl From<response::streaming::ReasoningSummaryChunk> for ReasoningSummary {
     fn from(
         response::streaming::ReasoningSummaryChunk {
             inner,
+            data,
             error,
         }: response::streaming::ReasoningSummaryChunk,
     ) -> Self {
         Self {
             inner: inner.into(),
+            data,
             error,
         }
     }
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 pub enum Task {
     FunctionExecution(super::FunctionExecutionTask),
     VectorCompletion(super::VectorCompletionTask),
+    ChatCompletion(super::ChatCompletionTask),
 }
 
 impl Task {
@@ -13,6 +14,7 @@ impl Task {
         match self {
             Task::FunctionExecution(f) => &f.task_path,
             Task::VectorCompletion(v) => &v.task_path,
+            Task::ChatCompletion(c) => &c.task_path,
         }
     }
 }
@@ -26,6 +28,9 @@ impl From<response::streaming::TaskChunk> for Task {
             response::streaming::TaskChunk::VectorCompletion(chunk) => {
                 Task::VectorCompletion(chunk.into())
             }
+            response::streaming::TaskChunk::ChatCompletion(chunk) => {
+                Task::ChatCompletion(chunk.into())
+            }
         }
     }
 }
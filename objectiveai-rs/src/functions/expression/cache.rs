@@ -0,0 +1,123 @@
+//! Per-thread caches of parsed expressions.
+//!
+//! Mapped tasks and profile computations evaluate the same expression
+//! source many times with different parameters. Parsed JMESPath
+//! expressions and Starlark ASTs are cached by their source so that each
+//! source is parsed once per thread.
+//!
+//! JMESPath expressions are not `Send`, so caches are thread-local.
+//! Each cache is cleared once it reaches [`CACHE_CAPACITY`] entries.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::ExpressionError;
+use super::starlark::StarlarkProgram;
+
+/// Maximum number of entries held by each per-thread cache.
+pub const CACHE_CAPACITY: usize = 4096;
+
+thread_local! {
+    static JMESPATH_CACHE: RefCell<
+        HashMap<String, Rc<jmespath::Expression<'static>>>,
+    > = RefCell::new(HashMap::new());
+    static STARLARK_CACHE: RefCell<HashMap<String, StarlarkProgram>> =
+        RefCell::new(HashMap::new());
+}
+
+fn insert_bounded<V>(cache: &mut HashMap<String, V>, key: &str, value: V) {
+    if cache.len() >= CACHE_CAPACITY {
+        cache.clear();
+    }
+    cache.insert(key.to_string(), value);
+}
+
+/// Returns the compiled JMESPath expression for `source`, compiling it
+/// with [`JMESPATH_RUNTIME`](super::JMESPATH_RUNTIME) on first use.
+pub(crate) fn jmespath_expression(
+    source: &str,
+) -> Result<Rc<jmespath::Expression<'static>>, ExpressionError> {
+    if let Some(expr) =
+        JMESPATH_CACHE.with_borrow(|cache| cache.get(source).cloned())
+    {
+        return Ok(expr);
+    }
+    let expr = Rc::new(super::JMESPATH_RUNTIME.compile(source)?);
+    JMESPATH_CACHE
+        .with_borrow_mut(|cache| insert_bounded(cache, source, expr.clone()));
+    Ok(expr)
+}
+
+/// Returns the parsed Starlark program for `source`, parsing it on first
+/// use.
+///
+/// Evaluation consumes the AST, so a clone of the cached program is
+/// returned.
+pub(crate) fn starlark_ast(
+    source: &str,
+) -> Result<StarlarkProgram, ExpressionError> {
+    if let Some(ast) =
+        STARLARK_CACHE.with_borrow(|cache| cache.get(source).cloned())
+    {
+        return Ok(ast);
+    }
+    let ast = StarlarkProgram::parse(source)?;
+    STARLARK_CACHE
+        .with_borrow_mut(|cache| insert_bounded(cache, source, ast.clone()));
+    Ok(ast)
+}
+
+/// Clears the current thread's expression caches.
+pub fn clear_expression_caches() {
+    JMESPATH_CACHE.with_borrow_mut(|cache| cache.clear());
+    STARLARK_CACHE.with_borrow_mut(|cache| cache.clear());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jmespath_cached() {
+        clear_expression_caches();
+        let a = jmespath_expression("input.a").unwrap();
+        let b = jmespath_expression("input.a").unwrap();
+        assert!(Rc::ptr_eq(&a, &b));
+        let c = jmespath_expression("input.b").unwrap();
+        assert!(!Rc::ptr_eq(&a, &c));
+    }
+
+    #[test]
+    fn test_starlark_cached() {
+        clear_expression_caches();
+        starlark_ast("1 + 2").unwrap();
+        assert_eq!(STARLARK_CACHE.with_borrow(|cache| cache.len()), 1);
+        starlark_ast("1 + 2").unwrap();
+        assert_eq!(STARLARK_CACHE.with_borrow(|cache| cache.len()), 1);
+    }
+
+    #[test]
+    fn test_keyed_by_source() {
+        clear_expression_caches();
+        starlark_ast("1 + 2").unwrap();
+        starlark_ast("1 + 3").unwrap();
+        STARLARK_CACHE.with_borrow(|cache| {
+            assert_eq!(cache.len(), 2);
+            assert!(cache.contains_key("1 + 2"));
+            assert!(cache.contains_key("1 + 3"));
+        });
+    }
+
+    #[test]
+    fn test_parse_errors_not_cached() {
+        clear_expression_caches();
+        assert!(matches!(
+            starlark_ast("invalid syntax [[["),
+            Err(ExpressionError::StarlarkParseError(_))
+        ));
+        assert!(jmespath_expression("input.[").is_err());
+        assert_eq!(STARLARK_CACHE.with_borrow(|cache| cache.len()), 0);
+        assert_eq!(JMESPATH_CACHE.with_borrow(|cache| cache.len()), 0);
+    }
+}
@@ -1,5 +1,7 @@
 //! Errors that can occur during expression compilation.
 
+use serde::{Deserialize, Serialize};
+
 /// Errors that can occur when compiling expressions.
 #[derive(Debug, thiserror::Error)]
 pub enum ExpressionError {
@@ -8,10 +10,13 @@ pub enum ExpressionError {
     JmespathError(#[from] jmespath::JmespathError),
     /// The Starlark expression failed to parse.
     #[error("starlark parse error: {0}")]
-    StarlarkParseError(String),
+    StarlarkParseError(StarlarkDiagnostic),
     /// The Starlark expression failed to evaluate.
     #[error("starlark evaluation error: {0}")]
-    StarlarkEvalError(String),
+    StarlarkEvalError(StarlarkDiagnostic),
+    /// The Starlark expression exceeded an execution limit.
+    #[error("starlark limit exceeded: {0}")]
+    StarlarkLimitExceeded(super::StarlarkLimit),
     /// The Starlark result could not be converted to JSON.
     #[error("starlark conversion error: {0}")]
     StarlarkConversionError(String),
@@ -21,4 +26,269 @@ pub enum ExpressionError {
     /// Expected a single value but the expression returned multiple.
     #[error("expected one value, found many")]
     ExpectedOneValueFoundMany,
+    /// An error annotated with where the failing expression lives.
+    #[error("{location}: {source}")]
+    Located {
+        /// Where the failing expression lives.
+        location: ExpressionLocation,
+        /// The underlying error.
+        source: Box<ExpressionError>,
+    },
+}
+
+impl ExpressionError {
+    /// Returns the underlying error, without location annotations.
+    pub fn inner(&self) -> &ExpressionError {
+        match self {
+            ExpressionError::Located { source, .. } => source.inner(),
+            other => other,
+        }
+    }
+
+    /// Returns where the failing expression lives, if known.
+    pub fn location(&self) -> Option<&ExpressionLocation> {
+        match self {
+            ExpressionError::Located { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+
+    /// Returns the dialect of the failing expression, if known.
+    pub fn dialect(&self) -> Option<ExpressionDialect> {
+        match self {
+            ExpressionError::JmespathError(_) => {
+                Some(ExpressionDialect::JMESPath)
+            }
+            ExpressionError::StarlarkParseError(_)
+            | ExpressionError::StarlarkEvalError(_)
+            | ExpressionError::StarlarkLimitExceeded(_)
+            | ExpressionError::StarlarkConversionError(_) => {
+                Some(ExpressionDialect::Starlark)
+            }
+            ExpressionError::DeserializationError(_)
+            | ExpressionError::ExpectedOneValueFoundMany => None,
+            ExpressionError::Located { location, source } => {
+                location.dialect.or_else(|| source.dialect())
+            }
+        }
+    }
+
+    /// Returns the span within the expression source that failed, if known.
+    pub fn span(&self) -> Option<SourceSpan> {
+        match self.inner() {
+            ExpressionError::JmespathError(e) => Some(SourceSpan {
+                line: e.line,
+                column: e.column,
+                end_line: e.line,
+                end_column: e.column,
+            }),
+            ExpressionError::StarlarkParseError(d)
+            | ExpressionError::StarlarkEvalError(d) => d.span,
+            _ => None,
+        }
+    }
+
+    /// Annotates the error with the dialect of the failing expression.
+    pub fn with_dialect(self, dialect: ExpressionDialect) -> Self {
+        self.with_location(|location| {
+            location.dialect.get_or_insert(dialect);
+        })
+    }
+
+    /// Annotates the error with the field containing the failing expression.
+    ///
+    /// An existing field annotation is kept, as it is more specific.
+    pub fn with_field(self, field: ExpressionField) -> Self {
+        self.with_location(|location| {
+            location.field.get_or_insert(field);
+        })
+    }
+
+    /// Annotates the error with the task and map element being compiled.
+    pub fn with_task(
+        self,
+        task_index: usize,
+        map_index: Option<usize>,
+    ) -> Self {
+        self.with_location(|location| {
+            location.task_index.get_or_insert(task_index);
+            if location.map_index.is_none() {
+                location.map_index = map_index;
+            }
+        })
+    }
+
+    fn with_location(self, f: impl FnOnce(&mut ExpressionLocation)) -> Self {
+        match self {
+            ExpressionError::Located {
+                mut location,
+                source,
+            } => {
+                f(&mut location);
+                ExpressionError::Located { location, source }
+            }
+            other => {
+                let mut location = ExpressionLocation::default();
+                f(&mut location);
+                ExpressionError::Located {
+                    location,
+                    source: Box::new(other),
+                }
+            }
+        }
+    }
+}
+
+/// A Starlark error message and the span of source that caused it.
+#[derive(Debug, Clone)]
+pub struct StarlarkDiagnostic {
+    /// The error message.
+    pub message: String,
+    /// The span within the expression source, if known.
+    pub span: Option<SourceSpan>,
+}
+
+impl StarlarkDiagnostic {
+    pub(crate) fn new(error: starlark::Error) -> Self {
+        let span = error.span().map(|span| {
+            let resolved = span.resolve_span();
+            SourceSpan {
+                line: resolved.begin.line,
+                column: resolved.begin.column,
+                end_line: resolved.end.line,
+                end_column: resolved.end.column,
+            }
+        });
+        Self {
+            message: error.to_string(),
+            span,
+        }
+    }
+}
+
+impl std::fmt::Display for StarlarkDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// A span within an expression's source. Lines and columns are 0-indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceSpan {
+    /// Line of the first character.
+    pub line: usize,
+    /// Column of the first character.
+    pub column: usize,
+    /// Line of the end of the span.
+    pub end_line: usize,
+    /// Column of the end of the span.
+    pub end_column: usize,
+}
+
+/// The language an expression is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpressionDialect {
+    /// A `{"$jmespath": ...}` expression.
+    #[serde(rename = "jmespath")]
+    JMESPath,
+    /// A `{"$starlark": ...}` expression.
+    Starlark,
+}
+
+impl std::fmt::Display for ExpressionDialect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExpressionDialect::JMESPath => f.write_str("jmespath"),
+            ExpressionDialect::Starlark => f.write_str("starlark"),
+        }
+    }
+}
+
+/// The Function field that contains an expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpressionField {
+    /// The function's `input_maps`.
+    InputMaps,
+    /// A task's `skip`.
+    Skip,
+    /// A function task's `input`.
+    Input,
+    /// A vector completion task's `messages`.
+    Messages,
+    /// A vector completion task's `tools`.
+    Tools,
+    /// A vector completion task's `responses`.
+    Responses,
+    /// A task's `output`.
+    Output,
+    /// A vector function's `output_length`.
+    OutputLength,
+    /// A vector function's `input_split`.
+    InputSplit,
+    /// A vector function's `input_merge`.
+    InputMerge,
+}
+
+impl std::fmt::Display for ExpressionField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ExpressionField::InputMaps => "input_maps",
+            ExpressionField::Skip => "skip",
+            ExpressionField::Input => "input",
+            ExpressionField::Messages => "messages",
+            ExpressionField::Tools => "tools",
+            ExpressionField::Responses => "responses",
+            ExpressionField::Output => "output",
+            ExpressionField::OutputLength => "output_length",
+            ExpressionField::InputSplit => "input_split",
+            ExpressionField::InputMerge => "input_merge",
+        })
+    }
+}
+
+/// Where a failing expression lives within a Function.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpressionLocation {
+    /// Index of the task containing the expression.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_index: Option<usize>,
+    /// The field containing the expression.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<ExpressionField>,
+    /// Index of the `map` element being compiled, for mapped tasks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub map_index: Option<usize>,
+    /// The dialect of the expression.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dialect: Option<ExpressionDialect>,
+}
+
+impl std::fmt::Display for ExpressionLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut path = String::new();
+        if let Some(task_index) = self.task_index {
+            path.push_str(&format!("tasks[{}]", task_index));
+        }
+        if let Some(field) = self.field {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(&field.to_string());
+        }
+        if let Some(map_index) = self.map_index {
+            path.push_str(&format!(" (map index {})", map_index));
+        }
+        if let Some(dialect) = self.dialect {
+            if !path.is_empty() {
+                path.push(' ');
+            }
+            path.push_str(&format!("[{}]", dialect));
+        }
+        if path.is_empty() {
+            path.push_str("expression");
+        }
+        f.write_str(&path)
+    }
 }
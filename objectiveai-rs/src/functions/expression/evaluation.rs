@@ -0,0 +1,151 @@
+//! Standalone evaluation of a single expression.
+//!
+//! Authoring tools evaluate an [`Expression`](super::Expression) against
+//! hand-written `input`, `map`, `output` and `tasks` params to preview its
+//! result.
+//! Evaluation uses the same runtime, caches and limits as Function
+//! compilation.
+
+use serde::{Deserialize, Serialize};
+
+/// Request to evaluate an expression against the given params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluateExpressionRequest {
+    /// The expression to evaluate.
+    pub expression: super::Expression,
+    /// The `input` available to the expression.
+    pub input: super::Input,
+    /// The `map` element available to the expression.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub map: Option<super::Input>,
+    /// The task `output` available to the expression.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<super::TaskOutputOwned>,
+    /// The results of the Function's tasks available to the expression as
+    /// `tasks`, `None` for tasks without a result.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tasks: Option<Vec<Option<super::TaskResult>>>,
+}
+
+impl EvaluateExpressionRequest {
+    /// Evaluates the expression, returning its raw JSON value or the error.
+    pub fn evaluate(&self) -> EvaluateExpressionResponse {
+        let params = super::Params::Ref(super::ParamsRef {
+            input: &self.input,
+            output: self.output.clone().map(super::TaskOutput::Owned),
+            map: self.map.as_ref(),
+            tasks: self.tasks.as_deref(),
+        });
+        match self.expression.evaluate(&params) {
+            Ok(value) => EvaluateExpressionResponse::Value(value),
+            Err(e) => EvaluateExpressionResponse::Error((&e).into()),
+        }
+    }
+}
+
+/// The result of evaluating an expression.
+///
+/// Serialized as `{"value": ...}` or `{"error": {...}}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvaluateExpressionResponse {
+    /// The raw JSON value the expression evaluated to.
+    Value(serde_json::Value),
+    /// The expression failed to parse or evaluate.
+    Error(EvaluateExpressionError),
+}
+
+/// A structured expression error.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluateExpressionError {
+    /// The error message.
+    pub message: String,
+    /// The dialect of the failing expression.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dialect: Option<super::ExpressionDialect>,
+    /// The span within the expression source that failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<super::SourceSpan>,
+}
+
+impl From<&super::ExpressionError> for EvaluateExpressionError {
+    fn from(error: &super::ExpressionError) -> Self {
+        Self {
+            message: error.inner().to_string(),
+            dialect: error.dialect(),
+            span: error.span(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(value: serde_json::Value) -> EvaluateExpressionRequest {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_evaluate_value() {
+        let response = request(serde_json::json!({
+            "expression": {"$starlark": "input['a'] + map"},
+            "input": {"a": 1},
+            "map": 2,
+        }))
+        .evaluate();
+        assert!(matches!(
+            response,
+            EvaluateExpressionResponse::Value(v) if v == serde_json::json!(3)
+        ));
+    }
+
+    #[test]
+    fn test_evaluate_output() {
+        let response = request(serde_json::json!({
+            "expression": {"$jmespath": "length(output)"},
+            "input": {},
+            "output": [0.25, 0.75],
+        }))
+        .evaluate();
+        assert!(matches!(
+            response,
+            EvaluateExpressionResponse::Value(v) if v == serde_json::json!(2)
+        ));
+    }
+
+    #[test]
+    fn test_evaluate_tasks() {
+        let response = request(serde_json::json!({
+            "expression": {"$starlark": "tasks[0]['output'] + len(tasks)"},
+            "input": {},
+            "tasks": [{"output": 0.5}, null],
+        }))
+        .evaluate();
+        assert!(matches!(
+            response,
+            EvaluateExpressionResponse::Value(v) if v == serde_json::json!(2.5)
+        ));
+    }
+
+    #[test]
+    fn test_evaluate_error() {
+        let response = request(serde_json::json!({
+            "expression": {"$starlark": "1 +"},
+            "input": {},
+        }))
+        .evaluate();
+        let EvaluateExpressionResponse::Error(error) = response else {
+            panic!("expected error");
+        };
+        assert_eq!(
+            error.dialect,
+            Some(super::super::ExpressionDialect::Starlark)
+        );
+        assert!(error.span.is_some());
+        let json =
+            serde_json::to_value(EvaluateExpressionResponse::Error(error))
+                .unwrap();
+        assert_eq!(json["error"]["dialect"], "starlark");
+    }
+}
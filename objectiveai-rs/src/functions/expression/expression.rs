@@ -108,6 +108,9 @@ impl<'de> Deserialize<'de> for Expression {
 impl Expression {
     /// Compiles the expression, allowing array results.
     ///
+    /// The parsed source is cached per thread, so repeated evaluations of
+    /// the same expression only pay for evaluation.
+    ///
     /// Returns `OneOrMany::One` for single values or `OneOrMany::Many` for arrays.
     /// Null values are filtered out from array results.
     /// A Single Null value is treated as an empty array.
@@ -118,15 +121,44 @@ impl Expression {
     where
         T: DeserializeOwned,
     {
-        let value = match self {
+        self.compile_value(params)
+            .and_then(Self::deserialize_result)
+            .map_err(|e| e.with_dialect(self.dialect()))
+    }
+
+    /// Returns the language the expression is written in.
+    pub fn dialect(&self) -> super::ExpressionDialect {
+        match self {
+            Expression::JMESPath(_) => super::ExpressionDialect::JMESPath,
+            Expression::Starlark(_) => super::ExpressionDialect::Starlark,
+        }
+    }
+
+    /// Evaluates the expression to its raw JSON value.
+    ///
+    /// Unlike [`compile_one_or_many`](Self::compile_one_or_many), the result
+    /// is returned as-is, without filtering nulls or unwrapping arrays.
+    pub fn evaluate(
+        &self,
+        params: &super::Params,
+    ) -> Result<serde_json::Value, super::ExpressionError> {
+        self.compile_value(params)
+            .map_err(|e| e.with_dialect(self.dialect()))
+    }
+
+    /// Evaluates the expression to a raw JSON value.
+    fn compile_value(
+        &self,
+        params: &super::Params,
+    ) -> Result<serde_json::Value, super::ExpressionError> {
+        Ok(match self {
             Expression::JMESPath(jmespath) => {
-                let expr = super::JMESPATH_RUNTIME.compile(jmespath)?;
+                let expr = super::jmespath_expression(jmespath)?;
                 let value = expr.search(params)?;
                 serde_json::to_value(value)?
             }
             Expression::Starlark(starlark) => super::starlark_eval(starlark, params)?,
-        };
-        Self::deserialize_result(value)
+        })
     }
 
     /// Deserialize expression result to the expected type.
@@ -168,7 +200,8 @@ impl Expression {
         let result = self.compile_one_or_many(params)?;
         match result {
             OneOrMany::One(value) => Ok(value),
-            OneOrMany::Many(_) => Err(super::ExpressionError::ExpectedOneValueFoundMany),
+            OneOrMany::Many(_) => Err(super::ExpressionError::ExpectedOneValueFoundMany
+                .with_dialect(self.dialect())),
         }
     }
 }
@@ -267,6 +300,7 @@ mod tests {
             input: Input::Object(IndexMap::new()),
             output: None,
             map: None,
+            tasks: None,
         })
     }
 
@@ -290,6 +324,7 @@ mod tests {
             input: Input::Object(map),
             output: None,
             map: None,
+            tasks: None,
         })
     }
 
@@ -351,7 +386,7 @@ mod tests {
         let expr = Expression::Starlark("[1, 2]".to_string());
 
         let err = expr.compile_one::<i64>(&params).unwrap_err();
-        match err {
+        match err.inner() {
             ExpressionError::ExpectedOneValueFoundMany => {}
             other => panic!("expected ExpectedOneValueFoundMany, got {:?}", other),
         }
@@ -428,7 +463,7 @@ mod tests {
         let err = Expression::Starlark("None".to_string())
             .compile_one::<Option<String>>(&params)
             .unwrap_err();
-        assert!(matches!(err, ExpressionError::ExpectedOneValueFoundMany));
+        assert!(matches!(err.inner(), ExpressionError::ExpectedOneValueFoundMany));
 
         let some_b: Option<bool> = starlark_one("False", &params);
         assert_eq!(some_b, Some(false));
@@ -439,7 +474,7 @@ mod tests {
         let err = Expression::Starlark("None".to_string())
             .compile_one::<Option<bool>>(&params)
             .unwrap_err();
-        assert!(matches!(err, ExpressionError::ExpectedOneValueFoundMany));
+        assert!(matches!(err.inner(), ExpressionError::ExpectedOneValueFoundMany));
     }
 
     #[test]
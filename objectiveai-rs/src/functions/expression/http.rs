@@ -0,0 +1,27 @@
+//! HTTP functions for expression evaluation.
+
+use crate::{HttpClient, HttpError};
+
+/// Evaluates an expression server-side against the given params.
+///
+/// # Arguments
+///
+/// * `client` - The HTTP client to use
+/// * `request` - The expression and its `input`, `map` and `output` params
+///
+/// # Returns
+///
+/// The raw value the expression evaluated to, or a structured error with
+/// its dialect and source span.
+pub async fn evaluate_expression(
+    client: &HttpClient,
+    request: super::EvaluateExpressionRequest,
+) -> Result<super::EvaluateExpressionResponse, HttpError> {
+    client
+        .send_unary(
+            reqwest::Method::POST,
+            "functions/expressions/evaluate",
+            Some(request),
+        )
+        .await
+}
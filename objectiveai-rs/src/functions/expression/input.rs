@@ -294,6 +294,34 @@ impl Input {
         }
         Iter::new(self, depth)
     }
+
+    /// Computes the canonical content-addressed ID of the input.
+    ///
+    /// Object keys are sorted before hashing, so inputs that differ only in
+    /// key order share an ID. The ID is a base62-encoded XXHash3-128 hash of
+    /// the canonical JSON serialization, padded to 22 characters.
+    pub fn id(&self) -> String {
+        fn canonical(input: &Input) -> Input {
+            match input {
+                Input::Object(object) => {
+                    let mut object = object
+                        .iter()
+                        .map(|(key, value)| (key.clone(), canonical(value)))
+                        .collect::<IndexMap<_, _>>();
+                    object.sort_unstable_keys();
+                    Input::Object(object)
+                }
+                Input::Array(array) => {
+                    Input::Array(array.iter().map(canonical).collect())
+                }
+                input => input.clone(),
+            }
+        }
+        let mut hasher = twox_hash::XxHash3_128::with_seed(0);
+        hasher
+            .write(serde_json::to_string(&canonical(self)).unwrap().as_bytes());
+        format!("{:0>22}", base62::encode(hasher.finish_128()))
+    }
 }
 
 /// An input value that may contain expressions (pre-compilation).
@@ -950,3 +978,30 @@ impl FileInputSchema {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_id_canonical() {
+        let a: Input = serde_json::from_value(serde_json::json!({
+            "a": 1,
+            "b": { "c": [1, 2], "d": "x" }
+        }))
+        .unwrap();
+        let b: Input = serde_json::from_value(serde_json::json!({
+            "b": { "d": "x", "c": [1, 2] },
+            "a": 1
+        }))
+        .unwrap();
+        let c: Input = serde_json::from_value(serde_json::json!({
+            "a": 1,
+            "b": { "c": [2, 1], "d": "x" }
+        }))
+        .unwrap();
+        assert_eq!(a.id(), b.id());
+        assert_ne!(a.id(), c.id());
+        assert_eq!(a.id().len(), 22);
+    }
+}
@@ -0,0 +1,89 @@
+//! Execution limits for Starlark expressions.
+//!
+//! Remote Functions are third-party code, so every Starlark evaluation is
+//! bounded by a [`StarlarkLimits`]. The process-wide limits default to
+//! [`StarlarkLimits::default`] and can be replaced with
+//! [`set_starlark_limits`].
+
+use std::sync::{LazyLock, RwLock};
+use std::time::Duration;
+
+/// Bounds enforced on a single Starlark evaluation.
+///
+/// Steps, heap size and elapsed time are checked before every statement,
+/// including statements inside `def` bodies and `for` loops. Work done
+/// within a single expression is charged as steps before it is done: one
+/// per item of a comprehension's iterable, of a `range` and of a sequence
+/// repeated with `*`. Heap size is checked once more after evaluation, and
+/// output size is checked against the serialized JSON result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StarlarkLimits {
+    /// Maximum number of statements executed.
+    pub max_steps: u64,
+    /// Maximum number of bytes allocated on the Starlark heap.
+    pub max_heap_bytes: usize,
+    /// Maximum wall-clock time spent evaluating. Not enforced on
+    /// `wasm32`, which has no clock.
+    pub timeout: Duration,
+    /// Maximum size in bytes of the JSON-serialized result.
+    pub max_output_bytes: usize,
+    /// Maximum call stack depth.
+    pub max_callstack_size: usize,
+}
+
+impl Default for StarlarkLimits {
+    fn default() -> Self {
+        Self {
+            max_steps: 1_000_000,
+            max_heap_bytes: 64 * 1024 * 1024, // 64 MiB
+            timeout: Duration::from_secs(1),
+            max_output_bytes: 4 * 1024 * 1024, // 4 MiB
+            max_callstack_size: 64,
+        }
+    }
+}
+
+/// The limit that a Starlark evaluation exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StarlarkLimit {
+    /// [`StarlarkLimits::max_steps`] was exceeded.
+    Steps(u64),
+    /// [`StarlarkLimits::max_heap_bytes`] was exceeded.
+    HeapBytes(usize),
+    /// [`StarlarkLimits::timeout`] was exceeded.
+    Timeout(Duration),
+    /// [`StarlarkLimits::max_output_bytes`] was exceeded.
+    OutputBytes(usize),
+}
+
+impl std::fmt::Display for StarlarkLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StarlarkLimit::Steps(max) => {
+                write!(f, "exceeded maximum of {} steps", max)
+            }
+            StarlarkLimit::HeapBytes(max) => {
+                write!(f, "exceeded maximum heap size of {} bytes", max)
+            }
+            StarlarkLimit::Timeout(max) => {
+                write!(f, "exceeded timeout of {}ms", max.as_millis())
+            }
+            StarlarkLimit::OutputBytes(max) => {
+                write!(f, "exceeded maximum output size of {} bytes", max)
+            }
+        }
+    }
+}
+
+static STARLARK_LIMITS: LazyLock<RwLock<StarlarkLimits>> =
+    LazyLock::new(|| RwLock::new(StarlarkLimits::default()));
+
+/// Returns the process-wide limits applied to Starlark evaluations.
+pub fn starlark_limits() -> StarlarkLimits {
+    *STARLARK_LIMITS.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Replaces the process-wide limits applied to Starlark evaluations.
+pub fn set_starlark_limits(limits: StarlarkLimits) {
+    *STARLARK_LIMITS.write().unwrap_or_else(|e| e.into_inner()) = limits;
+}
@@ -15,6 +15,7 @@
 //! - [`WithExpression<T>`] - Either a literal value or an expression
 //! - [`Input`] - The input data structure passed to expressions
 //! - [`Params`] - Context available during expression evaluation
+//! - [`EvaluateExpressionRequest`] - Evaluates a standalone expression for previewing
 //!
 //! # Expression Context
 //!
@@ -23,16 +24,30 @@
 //! - `tasks` - Results from previously executed tasks
 //! - `map` - Current map element (when in mapped task context)
 
+mod cache;
 mod error;
+mod evaluation;
 mod expression;
 mod input;
+mod limits;
 mod params;
 mod runtime;
 mod starlark;
 
+pub use cache::clear_expression_caches;
+pub(crate) use cache::{jmespath_expression, starlark_ast};
 pub use error::*;
+pub use evaluation::*;
 pub use expression::*;
 pub use input::*;
+pub use limits::*;
 pub use params::*;
 pub use runtime::*;
-pub(crate) use starlark::starlark_eval;
+pub(crate) use starlark::{starlark_eval, starlark_undefined_names};
+pub use starlark::starlark_eval_with_limits;
+
+#[cfg(feature = "http")]
+mod http;
+
+#[cfg(feature = "http")]
+pub use http::*;
@@ -4,11 +4,13 @@
 //! compilation, including the function input, task outputs, and current map element.
 
 use crate::vector;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 /// Context for evaluating expressions (JMESPath or Starlark).
 ///
-/// Contains all data accessible within expressions: `input`, `tasks`, and `map`.
+/// Contains all data accessible within expressions: `input`, `output`, `map`
+/// and `tasks`.
 #[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
 pub enum Params<'i, 'to, 'm> {
@@ -37,6 +39,11 @@ pub struct ParamsOwned {
     pub output: Option<TaskOutputOwned>,
     /// Current map element. Only populated for mapped task expressions.
     pub map: Option<super::Input>,
+    /// Results of the tasks that ran before this expression was compiled,
+    /// indexed like the Function's `tasks`. Only populated for task
+    /// expressions of Functions with chat completion tasks or dependencies,
+    /// and for the Function's `output` expression.
+    pub tasks: Option<Vec<Option<TaskResult>>>,
 }
 
 /// Borrowed version of expression parameters.
@@ -48,6 +55,28 @@ pub struct ParamsRef<'i, 'to, 'm> {
     pub output: Option<TaskOutput<'to>>,
     /// Current map element. Only populated for mapped task expressions.
     pub map: Option<&'m super::Input>,
+    /// Results of the tasks that ran before this expression was compiled,
+    /// indexed like the Function's `tasks`. Only populated for task
+    /// expressions of Functions with chat completion tasks or dependencies,
+    /// and for the Function's `output` expression.
+    pub tasks: Option<&'i [Option<TaskResult>]>,
+}
+
+/// The result of a task that ran before the current expression was compiled.
+///
+/// Exposed to expressions as `tasks[i]`. Tasks that were skipped or have
+/// not run yet are `null`.
+///
+/// For a Function's `output` expression, `output` is the task's transformed
+/// [`FunctionOutput`] and `weight` is the task's profile weight.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskResult {
+    /// The raw output of the task.
+    pub output: TaskOutputOwned,
+    /// The task's profile weight. Only populated for a Function's `output`
+    /// expression.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<rust_decimal::Decimal>,
 }
 
 /// Output from an executed task.
@@ -82,6 +111,9 @@ pub enum TaskOutputOwned {
     VectorCompletion(VectorCompletionOutput),
     /// Outputs from a mapped vector completion task.
     MapVectorCompletion(Vec<VectorCompletionOutput>),
+    /// Output from a chat completion task: the generated text, or the parsed
+    /// JSON value if the task requested a JSON response format.
+    ChatCompletion(serde_json::Value),
 }
 
 /// Borrowed task output variants.
@@ -96,6 +128,8 @@ pub enum TaskOutputRef<'a> {
     VectorCompletion(&'a VectorCompletionOutput),
     /// Outputs from a mapped vector completion task.
     MapVectorCompletion(&'a [VectorCompletionOutput]),
+    /// Output from a chat completion task.
+    ChatCompletion(&'a serde_json::Value),
 }
 
 /// Output from a vector completion task.
@@ -167,18 +201,85 @@ impl From<vector::completions::response::unary::VectorCompletion>
     }
 }
 
-/// Output from a function (scalar or vector).
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
+/// Output from a function (scalar, vector, multi-label or categorical).
+///
+/// Scalar and vector outputs are a number and an array of numbers.
+/// Multi-label and categorical outputs are tagged with their type, as
+/// `{"multi_label": {<label>: <score>}}` and
+/// `{"categorical": {<category>: <score>}}`. Untagged labelled scores, as
+/// returned by `output` expressions, deserialize as
+/// [`MultiLabel`](Self::MultiLabel) and are interpreted according to the
+/// Function's type when validated.
+#[derive(Debug, Clone)]
 pub enum FunctionOutput {
     /// A single score in [0, 1].
     Scalar(rust_decimal::Decimal),
     /// A vector of scores that sums to 1.
     Vector(Vec<rust_decimal::Decimal>),
+    /// An independent score in [0, 1] for each label of a multi-label
+    /// function, keyed by label.
+    MultiLabel(IndexMap<String, rust_decimal::Decimal>),
+    /// Scores that sum to 1 over the categories of a categorical function,
+    /// keyed by category.
+    Categorical(IndexMap<String, rust_decimal::Decimal>),
     /// An error occurred during execution.
     Err(serde_json::Value),
 }
 
+/// The tagged representation of labelled outputs.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LabelledOutput<S> {
+    MultiLabel(S),
+    Categorical(S),
+}
+
+impl Serialize for FunctionOutput {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Scalar(scalar) => Serialize::serialize(scalar, serializer),
+            Self::Vector(vector) => vector.serialize(serializer),
+            Self::MultiLabel(scores) => {
+                LabelledOutput::MultiLabel(scores).serialize(serializer)
+            }
+            Self::Categorical(scores) => {
+                LabelledOutput::Categorical(scores).serialize(serializer)
+            }
+            Self::Err(err) => err.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FunctionOutput {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        if let Ok(scalar) = serde_json::from_value(value.clone()) {
+            return Ok(Self::Scalar(scalar));
+        }
+        if let Ok(vector) = serde_json::from_value(value.clone()) {
+            return Ok(Self::Vector(vector));
+        }
+        if let Ok(labelled) = serde_json::from_value(value.clone()) {
+            return Ok(match labelled {
+                LabelledOutput::MultiLabel(scores) => Self::MultiLabel(scores),
+                LabelledOutput::Categorical(scores) => {
+                    Self::Categorical(scores)
+                }
+            });
+        }
+        if let Ok(scores) = serde_json::from_value(value.clone()) {
+            return Ok(Self::MultiLabel(scores));
+        }
+        Ok(Self::Err(value))
+    }
+}
+
 impl FunctionOutput {
     /// Converts the output into an error variant (wrapping the value as JSON).
     pub fn into_err(self) -> Self {
@@ -189,9 +290,39 @@ impl FunctionOutput {
             Self::Vector(vector) => {
                 Self::Err(serde_json::to_value(vector).unwrap())
             }
+            Self::MultiLabel(scores) | Self::Categorical(scores) => {
+                Self::Err(serde_json::to_value(scores).unwrap())
+            }
             Self::Err(err) => Self::Err(err),
         }
     }
+
+    /// Converts the output into scores keyed by `labels`, in label order.
+    ///
+    /// Accepts a vector with one score per label, or labelled scores with
+    /// exactly the given labels. Any other output is returned as the error.
+    pub fn into_labelled(
+        self,
+        labels: &[String],
+    ) -> Result<IndexMap<String, rust_decimal::Decimal>, Self> {
+        match self {
+            Self::Vector(vector) if vector.len() == labels.len() => {
+                Ok(labels.iter().cloned().zip(vector).collect())
+            }
+            Self::MultiLabel(scores) | Self::Categorical(scores)
+                if scores.len() == labels.len()
+                    && labels
+                        .iter()
+                        .all(|label| scores.contains_key(label)) =>
+            {
+                Ok(labels
+                    .iter()
+                    .map(|label| (label.clone(), scores[label]))
+                    .collect())
+            }
+            other => Err(other),
+        }
+    }
 }
 
 // /// Result of compiling a function's output expression.
@@ -2,26 +2,116 @@
 //!
 //! Provides a sandboxed Starlark runtime for evaluating expressions.
 //! Variables `input`, `output`, and `map` are injected into the global scope.
+//! Every evaluation is bounded by [`StarlarkLimits`](super::StarlarkLimits).
 
 use serde_json::Value;
+use starlark::any::ProvidesStaticType;
+use starlark::codemap::{CodeMap, FileSpanRef, Pos, Span};
 use starlark::environment::{Globals, GlobalsBuilder, Module};
-use starlark::eval::Evaluator;
+use starlark::eval::{BeforeStmtFuncDyn, Evaluator};
 use starlark::starlark_module;
 use starlark::syntax::{AstModule, Dialect};
 use starlark::values::float::UnpackFloat;
 use starlark::values::list::ListRef;
+use starlark::values::range::Range;
 use starlark::values::{Heap, UnpackValue, Value as SValue};
+use starlark_syntax::syntax::ast::{
+    AssignOp, AssignTargetP, AstExpr, AstStmt, ClauseP, ExprP, StmtP,
+};
+use starlark_syntax::syntax::module::AstModuleFields;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::num::NonZeroI32;
+use std::rc::Rc;
 use std::sync::LazyLock;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
 
-use super::ExpressionError;
+use super::{ExpressionError, StarlarkLimit, StarlarkLimits};
 
 /// Global Starlark globals with custom functions.
 pub static STARLARK_GLOBALS: LazyLock<Globals> = LazyLock::new(|| {
     let mut builder = GlobalsBuilder::standard();
     register_custom_functions(&mut builder);
+    register_limit_functions(&mut builder);
     builder.build()
 });
 
+/// Prefix of the globals that bound evaluation. Expressions may not use it.
+const LIMIT_PREFIX: &str = "__limit_";
+
+/// Builtins that charge their work to the [`LimitState`] of the evaluation
+/// before doing it, so that a single expression cannot run unbounded.
+#[starlark_module]
+fn register_limit_functions(builder: &mut GlobalsBuilder) {
+    /// Charges one step per item of a comprehension's iterable.
+    fn __limit_iter<'v>(
+        #[starlark(require = pos)] over: SValue<'v>,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> starlark::Result<SValue<'v>> {
+        if let Ok(len) = over.length() {
+            charge(eval, len as u64)?;
+        }
+        Ok(over)
+    }
+
+    /// Multiplies, charging one step per item of a repeated sequence.
+    fn __limit_mul<'v>(
+        #[starlark(require = pos)] lhs: SValue<'v>,
+        #[starlark(require = pos)] rhs: SValue<'v>,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> starlark::Result<SValue<'v>> {
+        limited_mul(eval, lhs, rhs)
+    }
+
+    /// `collection[index] *= rhs`, charged like `__limit_mul`.
+    fn __limit_mul_at<'v>(
+        #[starlark(require = pos)] collection: SValue<'v>,
+        #[starlark(require = pos)] index: SValue<'v>,
+        #[starlark(require = pos)] rhs: SValue<'v>,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> starlark::Result<starlark::values::none::NoneType> {
+        let lhs = collection.at(index, eval.heap())?;
+        let product = limited_mul(eval, lhs, rhs)?;
+        collection.set_at(index, product)?;
+        Ok(starlark::values::none::NoneType)
+    }
+
+    /// `object.attribute *= rhs`, charged like `__limit_mul`.
+    fn __limit_mul_attr<'v>(
+        #[starlark(require = pos)] object: SValue<'v>,
+        #[starlark(require = pos)] attribute: &str,
+        #[starlark(require = pos)] rhs: SValue<'v>,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> starlark::Result<starlark::values::none::NoneType> {
+        let lhs = object.get_attr_error(attribute, eval.heap())?;
+        let product = limited_mul(eval, lhs, rhs)?;
+        object.set_attr(attribute, product)?;
+        Ok(starlark::values::none::NoneType)
+    }
+
+    /// `range`, charging one step per item.
+    fn range<'v>(
+        #[starlark(require = pos)] a1: i32,
+        #[starlark(require = pos)] a2: Option<i32>,
+        #[starlark(require = pos, default = 1)] step: i32,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> starlark::Result<SValue<'v>> {
+        let (start, stop) = match a2 {
+            Some(stop) => (a1, stop),
+            None => (0, a1),
+        };
+        let step = NonZeroI32::new(step).ok_or_else(|| {
+            starlark::Error::new_other(anyhow::anyhow!(
+                "Third argument of range (step) cannot be zero"
+            ))
+        })?;
+        let range = eval.heap().alloc(Range::new(start, stop, step));
+        charge(eval, range.length()? as u64)?;
+        Ok(range)
+    }
+}
+
 /// Register custom functions that extend Starlark's standard library.
 #[starlark_module]
 fn register_custom_functions(builder: &mut GlobalsBuilder) {
@@ -126,6 +216,20 @@ impl ToStarlarkValue for super::FunctionOutput {
             super::FunctionOutput::Vector(ds) => {
                 decimals_to_starlark(heap, ds)
             }
+            // tagged with their type, as in JSON
+            super::FunctionOutput::MultiLabel(scores)
+            | super::FunctionOutput::Categorical(scores) => {
+                let tag = match self {
+                    super::FunctionOutput::MultiLabel(_) => "multi_label",
+                    _ => "categorical",
+                };
+                let pairs: Vec<(&str, SValue)> = scores
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), decimal_to_starlark(heap, v)))
+                    .collect();
+                let scores = heap.alloc(starlark::values::dict::AllocDict(pairs));
+                heap.alloc(starlark::values::dict::AllocDict([(tag, scores)]))
+            }
             super::FunctionOutput::Err(json) => json_to_starlark(heap, json),
         }
     }
@@ -170,6 +274,9 @@ impl ToStarlarkValue for super::TaskOutputOwned {
                     .collect();
                 heap.alloc(items)
             }
+            super::TaskOutputOwned::ChatCompletion(json) => {
+                json_to_starlark(heap, json)
+            }
         }
     }
 }
@@ -195,6 +302,9 @@ impl<'a> ToStarlarkValue for super::TaskOutputRef<'a> {
                     .collect();
                 heap.alloc(items)
             }
+            super::TaskOutputRef::ChatCompletion(json) => {
+                json_to_starlark(heap, json)
+            }
         }
     }
 }
@@ -208,11 +318,358 @@ impl<'a> ToStarlarkValue for super::TaskOutput<'a> {
     }
 }
 
+impl ToStarlarkValue for [Option<super::TaskResult>] {
+    fn to_starlark_value<'v>(&self, heap: &'v Heap) -> SValue<'v> {
+        let items: Vec<SValue> = self
+            .iter()
+            .map(|task| match task {
+                Some(task) => {
+                    let mut pairs =
+                        vec![("output", task.output.to_starlark_value(heap))];
+                    if let Some(weight) = &task.weight {
+                        pairs.push(("weight", decimal_to_starlark(heap, weight)));
+                    }
+                    heap.alloc(starlark::values::dict::AllocDict(pairs))
+                }
+                None => SValue::new_none(),
+            })
+            .collect();
+        heap.alloc(items)
+    }
+}
+
+/// The steps taken by one evaluation, shared by the statement hook and the
+/// limit builtins.
+///
+/// The exceeded limit is recorded in `exceeded` so that the resulting
+/// evaluation error can be reported as
+/// [`ExpressionError::StarlarkLimitExceeded`].
+#[derive(ProvidesStaticType)]
+struct LimitState {
+    limits: StarlarkLimits,
+    #[cfg(not(target_arch = "wasm32"))]
+    started: Instant,
+    steps: Cell<u64>,
+    exceeded: Cell<Option<StarlarkLimit>>,
+}
+
+impl LimitState {
+    fn new(limits: StarlarkLimits) -> Self {
+        Self {
+            limits,
+            #[cfg(not(target_arch = "wasm32"))]
+            started: Instant::now(),
+            steps: Cell::new(0),
+            exceeded: Cell::new(None),
+        }
+    }
+
+    /// Adds `steps`, then checks steps, heap size and elapsed time.
+    fn charge(&self, steps: u64, heap: &Heap) -> starlark::Result<()> {
+        let steps = self.steps.get().saturating_add(steps);
+        self.steps.set(steps);
+        let exceeded = if steps > self.limits.max_steps {
+            Some(StarlarkLimit::Steps(self.limits.max_steps))
+        } else if heap.allocated_bytes() > self.limits.max_heap_bytes {
+            Some(StarlarkLimit::HeapBytes(self.limits.max_heap_bytes))
+        } else if self.timed_out() {
+            Some(StarlarkLimit::Timeout(self.limits.timeout))
+        } else {
+            None
+        };
+        match exceeded {
+            Some(limit) => {
+                self.exceeded.set(Some(limit));
+                Err(starlark::Error::new_other(anyhow::anyhow!("{}", limit)))
+            }
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn timed_out(&self) -> bool {
+        self.started.elapsed() > self.limits.timeout
+    }
+
+    // there is no clock on wasm32-unknown-unknown
+    #[cfg(target_arch = "wasm32")]
+    fn timed_out(&self) -> bool {
+        false
+    }
+}
+
+/// Charges `steps` to the evaluation's [`LimitState`], if it has one.
+fn charge(eval: &Evaluator, steps: u64) -> starlark::Result<()> {
+    match eval
+        .extra
+        .and_then(|extra| extra.downcast_ref::<LimitState>())
+    {
+        Some(state) => state.charge(steps, eval.heap()),
+        None => Ok(()),
+    }
+}
+
+/// Multiplies, charging one step per item of a repeated sequence.
+fn limited_mul<'v>(
+    eval: &mut Evaluator<'v, '_, '_>,
+    lhs: SValue<'v>,
+    rhs: SValue<'v>,
+) -> starlark::Result<SValue<'v>> {
+    let repeated = match (i32::unpack_value(lhs), i32::unpack_value(rhs)) {
+        (Ok(Some(_)), Ok(Some(_))) => None,
+        (Ok(Some(n)), _) => Some((rhs, n)),
+        (_, Ok(Some(n))) => Some((lhs, n)),
+        _ => None,
+    };
+    if let Some((sequence, n)) = repeated
+        && let Ok(len) = sequence.length()
+    {
+        charge(eval, len.max(0) as u64 * n.max(0) as u64)?;
+    }
+    lhs.mul(rhs, eval.heap())
+}
+
+/// Charges one step before every statement.
+struct LimitGuard(Rc<LimitState>);
+
+impl<'a, 'e: 'a> BeforeStmtFuncDyn<'a, 'e> for LimitGuard {
+    fn call<'v>(
+        &mut self,
+        _span: FileSpanRef,
+        eval: &mut Evaluator<'v, 'a, 'e>,
+    ) -> starlark::Result<()> {
+        self.0.charge(1, eval.heap())
+    }
+}
+
+/// A parsed Starlark expression, rewritten so that its evaluation is
+/// bounded.
+///
+/// Comprehensions do not execute statements, so each comprehension's
+/// iterable is wrapped in `__limit_iter(...)`. `*` is replaced with
+/// `__limit_mul`, and `*=` with `__limit_mul`, `__limit_mul_at` or
+/// `__limit_mul_attr` depending on its target. Spans of errors in the
+/// rewritten source are mapped back to the original source.
+#[derive(Clone)]
+pub(crate) struct StarlarkProgram {
+    pub(crate) ast: AstModule,
+    rewrite: Option<Rc<Rewrite>>,
+}
+
+/// Where text of the original source was replaced.
+struct Rewrite {
+    original: CodeMap,
+    /// Offsets in the rewritten source and lengths of the inserted text,
+    /// and lengths of the original text it replaced.
+    insertions: Vec<(u32, u32, u32)>,
+}
+
+impl Rewrite {
+    /// Maps an offset in the rewritten source to the original source.
+    fn original_pos(&self, pos: Pos) -> Pos {
+        let pos = pos.get() as i64;
+        let mut shift = 0;
+        for &(at, len, removed) in &self.insertions {
+            let (at, len) = (at as i64, len as i64);
+            if pos >= at + len {
+                shift += len - removed as i64;
+            } else {
+                if pos >= at {
+                    return Pos::new((at - shift) as u32);
+                }
+                break;
+            }
+        }
+        Pos::new((pos - shift) as u32)
+    }
+}
+
+impl StarlarkProgram {
+    /// Parses `source` and rewrites it to bound its evaluation.
+    pub(crate) fn parse(source: &str) -> Result<Self, ExpressionError> {
+        if source.contains(LIMIT_PREFIX) {
+            return Err(ExpressionError::StarlarkParseError(
+                super::StarlarkDiagnostic {
+                    message: format!(
+                        "names starting with `{}` are reserved",
+                        LIMIT_PREFIX
+                    ),
+                    span: None,
+                },
+            ));
+        }
+        let parse = |source: String| {
+            AstModule::parse("expression", source, &Dialect::Extended).map_err(
+                |e| {
+                    ExpressionError::StarlarkParseError(
+                        super::StarlarkDiagnostic::new(e),
+                    )
+                },
+            )
+        };
+        let mut ast = parse(source.to_string())?;
+
+        // wrap the iterable of every comprehension, and turn every `*=`
+        // into a call
+        let mut edits: Vec<(Span, String)> = Vec::new();
+        let mut overs = Vec::new();
+        ast.statement()
+            .visit_expr(|expr| comprehension_iterables(expr, &mut overs));
+        for span in overs {
+            edits.push((
+                Span::new(span.begin(), span.begin()),
+                "__limit_iter(".to_string(),
+            ));
+            edits.push((Span::new(span.end(), span.end()), ")".to_string()));
+        }
+        multiply_assignments(ast.statement(), &mut edits);
+        let rewrite = if edits.is_empty() {
+            None
+        } else {
+            // close before opening at the same offset
+            edits.sort_by_key(|(span, text)| {
+                (span.begin(), !text.starts_with(')'))
+            });
+            let mut rewritten = String::with_capacity(source.len());
+            let mut insertions = Vec::with_capacity(edits.len());
+            let mut last = 0;
+            for (span, text) in edits {
+                rewritten.push_str(&source[last..span.begin().get() as usize]);
+                insertions.push((
+                    rewritten.len() as u32,
+                    text.len() as u32,
+                    span.end().get() - span.begin().get(),
+                ));
+                rewritten.push_str(&text);
+                last = span.end().get() as usize;
+            }
+            rewritten.push_str(&source[last..]);
+            ast = parse(rewritten)?;
+            Some(Rc::new(Rewrite {
+                original: CodeMap::new(
+                    "expression".to_string(),
+                    source.to_string(),
+                ),
+                insertions,
+            }))
+        };
+
+        ast.replace_binary_operators(&HashMap::from([(
+            "*".to_string(),
+            "__limit_mul".to_string(),
+        )]));
+        Ok(Self { ast, rewrite })
+    }
+}
+
+/// Converts an evaluation error, mapping its span to the original source.
+fn eval_diagnostic(
+    rewrite: Option<&Rewrite>,
+    error: starlark::Error,
+) -> super::StarlarkDiagnostic {
+    let Some(rewrite) = rewrite else {
+        return super::StarlarkDiagnostic::new(error);
+    };
+    let span = error.span().map(|span| {
+        let resolved = rewrite.original.resolve_span(Span::new(
+            rewrite.original_pos(span.span.begin()),
+            rewrite.original_pos(span.span.end()),
+        ));
+        super::SourceSpan {
+            line: resolved.begin.line,
+            column: resolved.begin.column,
+            end_line: resolved.end.line,
+            end_column: resolved.end.column,
+        }
+    });
+    super::StarlarkDiagnostic {
+        message: error.without_diagnostic().to_string(),
+        span,
+    }
+}
+
+/// Collects the spans of the iterables of the comprehensions in `expr`.
+fn comprehension_iterables(expr: &AstExpr, overs: &mut Vec<Span>) {
+    if let ExprP::ListComprehension(_, for_, clauses)
+    | ExprP::DictComprehension(_, for_, clauses) = &expr.node
+    {
+        overs.push(for_.over.span);
+        for clause in clauses {
+            if let ClauseP::For(for_) = clause {
+                overs.push(for_.over.span);
+            }
+        }
+    }
+    expr.visit_expr(|expr| comprehension_iterables(expr, overs));
+}
+
+/// Collects the edits that turn every `*=` in `stmt` into a call that
+/// charges the repetition before doing it, keeping the right-hand side in
+/// place.
+fn multiply_assignments(stmt: &AstStmt, edits: &mut Vec<(Span, String)>) {
+    if let StmtP::AssignModify(lhs, AssignOp::Multiply, rhs) = &stmt.node {
+        let end = Span::new(rhs.span.end(), rhs.span.end());
+        match &lhs.node {
+            AssignTargetP::Identifier(ident) => {
+                edits.push((
+                    Span::new(lhs.span.begin(), rhs.span.begin()),
+                    format!("{0} = __limit_mul({0}, (", ident.node.ident),
+                ));
+                edits.push((end, "))".to_string()));
+            }
+            AssignTargetP::Index(index) => {
+                let (collection, index) = &**index;
+                edits.push((
+                    Span::new(lhs.span.begin(), collection.span.begin()),
+                    "__limit_mul_at((".to_string(),
+                ));
+                edits.push((
+                    Span::new(collection.span.end(), index.span.begin()),
+                    "), (".to_string(),
+                ));
+                edits.push((
+                    Span::new(index.span.end(), rhs.span.begin()),
+                    "), (".to_string(),
+                ));
+                edits.push((end, "))".to_string()));
+            }
+            AssignTargetP::Dot(object, attribute) => {
+                edits.push((
+                    Span::new(lhs.span.begin(), object.span.begin()),
+                    "__limit_mul_attr((".to_string(),
+                ));
+                edits.push((
+                    Span::new(object.span.end(), rhs.span.begin()),
+                    format!("), \"{}\", (", attribute.node),
+                ));
+                edits.push((end, "))".to_string()));
+            }
+            // not a valid target of an augmented assignment
+            AssignTargetP::Tuple(_) => {}
+        }
+    }
+    stmt.visit_stmt(|stmt| multiply_assignments(stmt, edits));
+}
+
 /// Evaluate a Starlark expression with the given parameters.
+///
+/// Uses the process-wide [`starlark_limits`](super::starlark_limits).
 pub fn starlark_eval(
     code: &str,
     params: &super::Params,
 ) -> Result<Value, ExpressionError> {
+    starlark_eval_with_limits(code, params, &super::starlark_limits())
+}
+
+/// Evaluate a Starlark expression with the given parameters and limits.
+pub fn starlark_eval_with_limits(
+    code: &str,
+    params: &super::Params,
+    limits: &StarlarkLimits,
+) -> Result<Value, ExpressionError> {
+    // Outlives the module, which the evaluator's borrows are tied to
+    let state = Rc::new(LimitState::new(*limits));
+
     // Create module and inject variables directly (no JSON intermediate)
     let module = Module::new();
     {
@@ -241,6 +698,15 @@ pub fn starlark_eval(
                             m.to_starlark_value(heap)
                         }),
                 );
+                module.set(
+                    "tasks",
+                    owned
+                        .tasks
+                        .as_deref()
+                        .map_or(SValue::new_none(), |t| {
+                            t.to_starlark_value(heap)
+                        }),
+                );
             }
             super::Params::Ref(r) => {
                 module
@@ -259,25 +725,84 @@ pub fn starlark_eval(
                         m.to_starlark_value(heap)
                     }),
                 );
+                module.set(
+                    "tasks",
+                    r.tasks.map_or(SValue::new_none(), |t| {
+                        t.to_starlark_value(heap)
+                    }),
+                );
             }
         }
     }
 
-    // Parse the expression directly
-    let ast =
-        AstModule::parse("expression", code.to_string(), &Dialect::Extended)
-            .map_err(|e| ExpressionError::StarlarkParseError(e.to_string()))?;
+    // Parse the expression, or reuse the cached AST
+    let StarlarkProgram { ast, rewrite } = super::starlark_ast(code)?;
 
     // Evaluate - eval_module returns the value of the last statement
     let mut eval = Evaluator::new(&module);
-    let result = eval
-        .eval_module(ast, &STARLARK_GLOBALS)
-        .map_err(|e| ExpressionError::StarlarkEvalError(e.to_string()))?;
+    eval.extra = Some(&*state);
+    eval.set_max_callstack_size(limits.max_callstack_size)
+        .map_err(|e| {
+            ExpressionError::StarlarkEvalError(super::StarlarkDiagnostic {
+                message: e.to_string(),
+                span: None,
+            })
+        })?;
+    let guard: Box<dyn BeforeStmtFuncDyn<'_, '_>> =
+        Box::new(LimitGuard(state.clone()));
+    eval.before_stmt_for_dap(guard.into());
+    let result = eval.eval_module(ast, &STARLARK_GLOBALS).map_err(|e| {
+        match state.exceeded.get() {
+            Some(limit) => ExpressionError::StarlarkLimitExceeded(limit),
+            None => ExpressionError::StarlarkEvalError(eval_diagnostic(
+                rewrite.as_deref(),
+                e,
+            )),
+        }
+    })?;
+    drop(eval);
+
+    // Comprehensions allocate without executing statements
+    if module.heap().allocated_bytes() > limits.max_heap_bytes {
+        return Err(ExpressionError::StarlarkLimitExceeded(
+            StarlarkLimit::HeapBytes(limits.max_heap_bytes),
+        ));
+    }
 
     // Convert result to JSON
-    result
+    let value = result
         .to_json_value()
-        .map_err(|e| ExpressionError::StarlarkConversionError(e.to_string()))
+        .map_err(|e| ExpressionError::StarlarkConversionError(e.to_string()))?;
+    if serde_json::to_vec(&value).map_or(0, |v| v.len())
+        > limits.max_output_bytes
+    {
+        return Err(ExpressionError::StarlarkLimitExceeded(
+            StarlarkLimit::OutputBytes(limits.max_output_bytes),
+        ));
+    }
+    Ok(value)
+}
+
+/// Returns the names referenced by a Starlark expression that are neither
+/// builtins nor one of `input`, `output`, `map` and `tasks`.
+pub(crate) fn starlark_undefined_names(
+    code: &str,
+) -> Result<Vec<String>, ExpressionError> {
+    use starlark::analysis::AstModuleLint;
+    let ast = super::starlark_ast(code)?.ast;
+    let mut globals: std::collections::HashSet<String> = STARLARK_GLOBALS
+        .names()
+        .map(|name| name.as_str().to_string())
+        .collect();
+    for name in ["input", "output", "map", "tasks"] {
+        globals.insert(name.to_string());
+    }
+    Ok(ast
+        .lint(Some(&globals))
+        .into_iter()
+        .filter(|lint| lint.short_name == "using-undefined")
+        .map(|lint| lint.original)
+        .collect())
 }
 
 /// Convert JSON value to Starlark value.
@@ -314,8 +839,8 @@ fn json_to_starlark<'v>(heap: &'v Heap, json: &Value) -> SValue<'v> {
 mod tests {
     use super::*;
     use crate::functions::expression::{
-        FunctionOutput, Input, Params, ParamsOwned, TaskOutputOwned,
-        VectorCompletionOutput,
+        Expression, FunctionOutput, Input, Params, ParamsOwned,
+        TaskOutputOwned, VectorCompletionOutput,
     };
     use indexmap::IndexMap;
     use rust_decimal::dec;
@@ -329,6 +854,7 @@ mod tests {
             input,
             output: None,
             map: None,
+            tasks: None,
         })
     }
 
@@ -340,6 +866,7 @@ mod tests {
             input,
             output: Some(output),
             map: None,
+            tasks: None,
         })
     }
 
@@ -351,6 +878,7 @@ mod tests {
             input,
             output: None,
             map: Some(map),
+            tasks: None,
         })
     }
 
@@ -363,6 +891,7 @@ mod tests {
             input,
             output: Some(output),
             map: Some(map),
+            tasks: None,
         })
     }
 
@@ -621,6 +1150,173 @@ mod tests {
         assert!(matches!(result, Err(ExpressionError::StarlarkEvalError(_))));
     }
 
+    #[test]
+    fn test_step_limit() {
+        let params = make_params(empty_input());
+        let limits = StarlarkLimits {
+            max_steps: 100,
+            ..StarlarkLimits::default()
+        };
+        let code = "def f():\n    t = 0\n    for i in range(1000):\n        t += i\n    return t\nf()";
+        let result = starlark_eval_with_limits(code, &params, &limits);
+        assert!(matches!(
+            result,
+            Err(ExpressionError::StarlarkLimitExceeded(
+                StarlarkLimit::Steps(100)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_heap_limit() {
+        let params = make_params(empty_input());
+        let limits = StarlarkLimits {
+            max_heap_bytes: 1024,
+            ..StarlarkLimits::default()
+        };
+        let result = starlark_eval_with_limits(
+            "[str(i) for i in range(10000)]",
+            &params,
+            &limits,
+        );
+        assert!(matches!(
+            result,
+            Err(ExpressionError::StarlarkLimitExceeded(
+                StarlarkLimit::HeapBytes(1024)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_output_limit() {
+        let params = make_params(empty_input());
+        let limits = StarlarkLimits {
+            max_output_bytes: 16,
+            ..StarlarkLimits::default()
+        };
+        let result = starlark_eval_with_limits("\"x\" * 100", &params, &limits);
+        assert!(matches!(
+            result,
+            Err(ExpressionError::StarlarkLimitExceeded(
+                StarlarkLimit::OutputBytes(16)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_single_expression_limits() {
+        let input = Input::Array((0..1000).map(Input::Integer).collect());
+        let params = make_params(input);
+        let limits = StarlarkLimits::default();
+        for code in [
+            "max(range(2000000000))",
+            "len([1 for a in input for b in input for c in input])",
+            "{a: 1 for a in input for b in input for c in input}",
+            "'x' * 2000000000",
+            "len(input * 1000000)",
+        ] {
+            let started = std::time::Instant::now();
+            let result = starlark_eval_with_limits(code, &params, &limits);
+            assert!(
+                matches!(
+                    result,
+                    Err(ExpressionError::StarlarkLimitExceeded(_))
+                ),
+                "{}: {:?}",
+                code,
+                result,
+            );
+            // limits are checked while the expression runs, not after
+            assert!(
+                started.elapsed() < limits.timeout * 5,
+                "{}: {:?}",
+                code,
+                started.elapsed(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_multiply_assignment_limits() {
+        let params = make_params(empty_input());
+        let limits = StarlarkLimits {
+            max_heap_bytes: 1024 * 1024,
+            ..StarlarkLimits::default()
+        };
+        for code in [
+            "s = 'x'\ns *= 200000000",
+            "l = [1]\nl *= 100000000",
+            "l = [[1]]\nl[0] *= 100000000",
+            "def f():\n    s = 'x'\n    s *= 200000000\nf()",
+        ] {
+            let started = std::time::Instant::now();
+            let result = starlark_eval_with_limits(code, &params, &limits);
+            assert!(
+                matches!(
+                    result,
+                    Err(ExpressionError::StarlarkLimitExceeded(_))
+                ),
+                "{}: {:?}",
+                code,
+                result,
+            );
+            assert!(
+                started.elapsed() < limits.timeout,
+                "{}: {:?}",
+                code,
+                started.elapsed(),
+            );
+        }
+        assert_eq!(
+            starlark_eval("s = 'ab'\ns *= 3\ns", &params).unwrap(),
+            serde_json::json!("ababab"),
+        );
+        assert_eq!(
+            starlark_eval("l = [[1], 2]\nl[0] *= 2\nl[1] *= 3\nl", &params)
+                .unwrap(),
+            serde_json::json!([[1, 1], 6]),
+        );
+        match starlark_eval("l = [1]\nl[3] *= 2", &params) {
+            Err(ExpressionError::StarlarkEvalError(diagnostic)) => {
+                let span = diagnostic.span.unwrap();
+                assert_eq!(span.line, 1);
+            }
+            other => panic!("expected eval error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_rewritten_error_span() {
+        let params = make_params(empty_input());
+        let result = starlark_eval("[x for x in [1, 2]] + undefined", &params);
+        match result {
+            Err(ExpressionError::StarlarkEvalError(diagnostic)) => {
+                let span = diagnostic.span.unwrap();
+                assert_eq!((span.column, span.end_column), (22, 31));
+            }
+            other => panic!("expected eval error, got {:?}", other),
+        }
+        assert_eq!(
+            starlark_eval("[x * 2 for x in range(3)]", &params).unwrap(),
+            serde_json::json!([0, 2, 4]),
+        );
+        assert!(matches!(
+            starlark_eval("__limit_iter([])", &params),
+            Err(ExpressionError::StarlarkParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_within_limits() {
+        let params = make_params(empty_input());
+        let limits = StarlarkLimits {
+            max_steps: 100,
+            ..StarlarkLimits::default()
+        };
+        let result = starlark_eval_with_limits("1 + 2", &params, &limits);
+        assert_eq!(result.unwrap(), Value::Number(3.into()));
+    }
+
     // ==================== TESTS USING MAP ====================
 
     #[test]
@@ -710,6 +1406,26 @@ mod tests {
         assert_eq!(result, Value::Number(3.into()));
     }
 
+    #[test]
+    fn test_output_categorical() {
+        let input = empty_input();
+        let output = TaskOutputOwned::Function(FunctionOutput::Categorical(
+            [("spam".to_string(), dec!(0.25)), ("ham".to_string(), dec!(0.75))]
+                .into_iter()
+                .collect(),
+        ));
+        let params = make_params_with_output(input, output);
+
+        // labelled outputs are tagged with their type, as in JSON
+        let result =
+            starlark_eval("output['categorical']['ham']", &params).unwrap();
+        assert_eq!(result.as_f64().unwrap(), 0.75);
+        let result = Expression::JMESPath("output.categorical.ham".to_string())
+            .evaluate(&params)
+            .unwrap();
+        assert_eq!(result.as_f64().unwrap(), 0.75);
+    }
+
     #[test]
     fn test_output_vector_completion_scores() {
         let input = empty_input();
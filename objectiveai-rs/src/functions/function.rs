@@ -2,10 +2,9 @@
 //!
 //! # Output Computation
 //!
-//! Functions do **not** have a top-level output expression. Instead, each task has its
-//! own `output` expression that transforms its raw result into a [`FunctionOutput`].
-//! The function's final output is computed as a **weighted average** of all task outputs
-//! using profile weights.
+//! Each task has its own `output` expression that transforms its raw result into a
+//! [`FunctionOutput`]. By default, the function's final output is computed as a
+//! **weighted average** of all task outputs using profile weights.
 //!
 //! - If a function has only 1 task, that task's output becomes the function's output directly
 //! - If a function has multiple tasks, each task's output is weighted and averaged
@@ -13,6 +12,22 @@
 //! Each task's `output` expression must return a valid `FunctionOutput` for the function's type:
 //! - **Scalar functions**: each task must return `Scalar(value)` where value is in [0, 1]
 //! - **Vector functions**: each task must return `Vector(values)` where values sum to ~1
+//! - **Multi-label functions**: each task must return a score in [0, 1] for each of the
+//!   function's `labels`, either keyed by label or as a vector in label order
+//! - **Categorical functions**: each task must return scores that sum to ~1 over the
+//!   function's `categories`, either keyed by category or as a vector in category order
+//!
+//! Multi-label and categorical outputs are weighted and averaged per label.
+//!
+//! Chat completion tasks have no `output` expression and are excluded from the
+//! weighted average.
+//!
+//! A function may instead define a top-level `output` expression to combine its task
+//! outputs non-linearly (a minimum, a product, thresholds, a logistic combination).
+//! It receives `input` and `tasks`, where `tasks[i]` is `{"output": <FunctionOutput>,
+//! "weight": <profile weight>}` for every task that produced a valid output and `null`
+//! for tasks that were skipped, failed or are chat completion tasks. Its result must
+//! be a valid `FunctionOutput` for the function's type, just like a task's `output`.
 //!
 //! [`FunctionOutput`]: super::expression::FunctionOutput
 
@@ -23,7 +38,8 @@ use serde::{Deserialize, Serialize};
 /// Functions are composable scoring pipelines that transform structured input
 /// into scores. Each task has an `output` expression that transforms its raw result
 /// into a `FunctionOutput`. The function's final output is the weighted average of
-/// all task outputs using profile weights.
+/// all task outputs using profile weights, unless the function defines its own
+/// `output` expression.
 ///
 /// Use [`compile_tasks`](Self::compile_tasks) to preview how task expressions resolve
 /// for given inputs.
@@ -83,18 +99,18 @@ impl Function {
         super::expression::ExpressionError,
     > {
         let input_maps_expr = match self {
-            Function::Remote(RemoteFunction::Scalar { input_maps, .. }) => {
-                input_maps
-            }
-            Function::Remote(RemoteFunction::Vector { input_maps, .. }) => {
-                input_maps
-            }
-            Function::Inline(InlineFunction::Scalar { input_maps, .. }) => {
-                input_maps
-            }
-            Function::Inline(InlineFunction::Vector { input_maps, .. }) => {
-                input_maps
-            }
+            Function::Remote(
+                RemoteFunction::Scalar { input_maps, .. }
+                | RemoteFunction::Vector { input_maps, .. }
+                | RemoteFunction::MultiLabel { input_maps, .. }
+                | RemoteFunction::Categorical { input_maps, .. },
+            ) => input_maps,
+            Function::Inline(
+                InlineFunction::Scalar { input_maps, .. }
+                | InlineFunction::Vector { input_maps, .. }
+                | InlineFunction::MultiLabel { input_maps, .. }
+                | InlineFunction::Categorical { input_maps, .. },
+            ) => input_maps,
         };
         match input_maps_expr {
             Some(input_maps_expr) => {
@@ -104,6 +120,7 @@ impl Function {
                         input,
                         output: None,
                         map: None,
+                        tasks: None,
                     },
                 );
                 // compile input_maps
@@ -126,35 +143,199 @@ impl Function {
     /// - `None` if the task was skipped
     /// - `Some(CompiledTask::One(...))` for non-mapped tasks
     /// - `Some(CompiledTask::Many(...))` for mapped tasks
+    ///
+    /// Expressions that reference the output of other tasks
+    /// (`tasks[i].output`) see `null`; use
+    /// [`compile_tasks_with_results`](Self::compile_tasks_with_results) and
+    /// [`compile_dependent_task`](Self::compile_dependent_task) to compile
+    /// them once those tasks have run.
     pub fn compile_tasks(
         self,
         input: &super::expression::Input,
     ) -> Result<
         Vec<Option<super::CompiledTask>>,
         super::expression::ExpressionError,
+    > {
+        self.compile_tasks_where(input, None, |_, _| true)
+    }
+
+    /// Compiles only the chat completion tasks, which run before all other
+    /// tasks of the function.
+    ///
+    /// Returns one element per task definition. Tasks that are not chat
+    /// completions are `None`.
+    pub fn compile_chat_completion_tasks(
+        self,
+        input: &super::expression::Input,
+    ) -> Result<
+        Vec<Option<super::CompiledTask>>,
+        super::expression::ExpressionError,
+    > {
+        self.compile_tasks_where(input, None, |_, task| {
+            task.is_chat_completion()
+        })
+    }
+
+    /// Compiles the tasks that run after the chat completion tasks, exposing
+    /// the chat completion results to their expressions as `tasks`.
+    ///
+    /// `results` has one element per task definition. Returns one element
+    /// per task definition. Chat completion tasks and dependent tasks are
+    /// `None`.
+    pub fn compile_tasks_with_results(
+        self,
+        input: &super::expression::Input,
+        results: &[Option<super::expression::TaskResult>],
+    ) -> Result<
+        Vec<Option<super::CompiledTask>>,
+        super::expression::ExpressionError,
+    > {
+        let dependent = (0..self.tasks().len())
+            .map(|i| self.is_dependent_task(i))
+            .collect::<Vec<_>>();
+        self.compile_tasks_where(input, Some(results), |i, task| {
+            !task.is_chat_completion() && !dependent[i]
+        })
+    }
+
+    /// Compiles the dependent task at `index` once its dependencies have run.
+    ///
+    /// `results` has one element per task definition. Only the results of
+    /// the task's dependencies and of chat completion tasks are exposed to
+    /// its expressions. Returns `None` if the task was skipped.
+    pub fn compile_dependent_task(
+        self,
+        index: usize,
+        input: &super::expression::Input,
+        results: &[Option<super::expression::TaskResult>],
+    ) -> Result<Option<super::CompiledTask>, super::expression::ExpressionError>
+    {
+        let dependencies = self.tasks()[index].dependencies();
+        let results = self
+            .tasks()
+            .iter()
+            .zip(results)
+            .enumerate()
+            .map(|(i, (task, result))| {
+                if task.is_chat_completion()
+                    || dependencies.contains(&(i as u64))
+                {
+                    result.clone()
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        Ok(self
+            .compile_tasks_where(input, Some(&results), |i, _| i == index)?
+            .into_iter()
+            .nth(index)
+            .flatten())
+    }
+
+    /// Returns `true` if the task at `index` depends on a task other than a
+    /// chat completion task, so it can only be compiled once that task has
+    /// run.
+    pub fn is_dependent_task(&self, index: usize) -> bool {
+        let tasks = self.tasks();
+        tasks[index].dependencies().iter().any(|&dependency| {
+            tasks
+                .get(dependency as usize)
+                .is_none_or(|task| !task.is_chat_completion())
+        })
+    }
+
+    /// Validates the `dependencies` of every task.
+    ///
+    /// Dependencies must refer to earlier tasks, and dependent tasks cannot
+    /// be mapped.
+    pub fn validate_dependencies(&self) -> Result<(), String> {
+        for (i, task) in self.tasks().iter().enumerate() {
+            if let Some(dependency) = task
+                .dependencies()
+                .iter()
+                .find(|&&dependency| dependency as usize >= i)
+            {
+                return Err(format!(
+                    "tasks[{}] depends on tasks[{}], which is not an earlier task",
+                    i, dependency
+                ));
+            }
+            if task.input_map().is_some() && self.is_dependent_task(i) {
+                return Err(format!(
+                    "tasks[{}] is mapped and cannot have dependencies",
+                    i
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates the `labels` of a multi-label function, or the
+    /// `categories` of a categorical one.
+    ///
+    /// They must be non-empty and unique.
+    pub fn validate_labels(&self) -> Result<(), String> {
+        let Some(labels) = self.labels() else {
+            return Ok(());
+        };
+        let field = match self {
+            Function::Remote(RemoteFunction::Categorical { .. })
+            | Function::Inline(InlineFunction::Categorical { .. }) => {
+                "categories"
+            }
+            _ => "labels",
+        };
+        if labels.is_empty() {
+            return Err(format!("{} must not be empty", field));
+        }
+        for (i, label) in labels.iter().enumerate() {
+            if labels[..i].contains(label) {
+                return Err(format!("{} contain \"{}\" twice", field, label));
+            }
+        }
+        Ok(())
+    }
+
+    fn compile_tasks_where(
+        self,
+        input: &super::expression::Input,
+        results: Option<&[Option<super::expression::TaskResult>]>,
+        filter: impl Fn(usize, &super::TaskExpression) -> bool,
+    ) -> Result<
+        Vec<Option<super::CompiledTask>>,
+        super::expression::ExpressionError,
     > {
         // extract input_maps expression and task expressions
         let (input_maps_expr, task_exprs) = match self {
-            Function::Remote(RemoteFunction::Scalar {
-                input_maps,
-                tasks,
-                ..
-            }) => (input_maps, tasks),
-            Function::Remote(RemoteFunction::Vector {
-                input_maps,
-                tasks,
-                ..
-            }) => (input_maps, tasks),
-            Function::Inline(InlineFunction::Scalar {
-                input_maps,
-                tasks,
-                ..
-            }) => (input_maps, tasks),
-            Function::Inline(InlineFunction::Vector {
-                input_maps,
-                tasks,
-                ..
-            }) => (input_maps, tasks),
+            Function::Remote(
+                RemoteFunction::Scalar {
+                    input_maps, tasks, ..
+                }
+                | RemoteFunction::Vector {
+                    input_maps, tasks, ..
+                }
+                | RemoteFunction::MultiLabel {
+                    input_maps, tasks, ..
+                }
+                | RemoteFunction::Categorical {
+                    input_maps, tasks, ..
+                },
+            ) => (input_maps, tasks),
+            Function::Inline(
+                InlineFunction::Scalar {
+                    input_maps, tasks, ..
+                }
+                | InlineFunction::Vector {
+                    input_maps, tasks, ..
+                }
+                | InlineFunction::MultiLabel {
+                    input_maps, tasks, ..
+                }
+                | InlineFunction::Categorical {
+                    input_maps, tasks, ..
+                },
+            ) => (input_maps, tasks),
         };
 
         // prepare params for compiling expressions
@@ -163,21 +344,31 @@ impl Function {
                 input,
                 output: None,
                 map: None,
+                tasks: results,
             });
 
         // compile input_maps
         let input_maps = if let Some(input_maps_expr) = input_maps_expr {
-            Some(input_maps_expr.compile(&params)?)
+            Some(input_maps_expr.compile(&params).map_err(|e| {
+                e.with_field(super::expression::ExpressionField::InputMaps)
+            })?)
         } else {
             None
         };
 
         // compile tasks
         let mut tasks = Vec::with_capacity(task_exprs.len());
-        for mut task_expr in task_exprs {
+        for (task_index, mut task_expr) in task_exprs.into_iter().enumerate() {
+            if !filter(task_index, &task_expr) {
+                tasks.push(None);
+                continue;
+            }
             tasks.push(
                 if let Some(skip_expr) = task_expr.take_skip()
-                    && skip_expr.compile_one::<bool>(&params)?
+                    && skip_expr.compile_one::<bool>(&params).map_err(|e| {
+                        e.with_field(super::expression::ExpressionField::Skip)
+                            .with_task(task_index, None)
+                    })?
                 {
                     // None if task is skipped
                     None
@@ -189,7 +380,7 @@ impl Function {
                     {
                         // compile task for each map input
                         let mut map_tasks = Vec::with_capacity(input_map.len());
-                        for input in input_map {
+                        for (map_index, input) in input_map.iter().enumerate() {
                             // set map input
                             match &mut params {
                                 super::expression::Params::Ref(params_ref) => {
@@ -198,7 +389,13 @@ impl Function {
                                 _ => unreachable!(),
                             }
                             // compile task with map input
-                            map_tasks.push(task_expr.clone().compile(&params)?);
+                            map_tasks.push(
+                                task_expr.clone().compile(&params).map_err(
+                                    |e| {
+                                        e.with_task(task_index, Some(map_index))
+                                    },
+                                )?,
+                            );
                             // reset map input
                             match &mut params {
                                 super::expression::Params::Ref(params_ref) => {
@@ -214,7 +411,11 @@ impl Function {
                     }
                 } else {
                     // compile single task
-                    Some(super::CompiledTask::One(task_expr.compile(&params)?))
+                    Some(super::CompiledTask::One(
+                        task_expr
+                            .compile(&params)
+                            .map_err(|e| e.with_task(task_index, None))?,
+                    ))
                 },
             );
         }
@@ -223,103 +424,68 @@ impl Function {
         Ok(tasks)
     }
 
-    // /// Computes the final output given input and task outputs.
-    // ///
-    // /// Evaluates the function's output expression using the provided input data
-    // /// and task results. Also validates that the output meets constraints:
-    // /// - Scalar functions: output must be in [0, 1]
-    // /// - Vector functions: output must sum to approximately 1
-    // pub fn compile_output(
-    //     self,
-    //     input: &super::expression::Input,
-    //     task_outputs: &[Option<super::expression::TaskOutput>],
-    // ) -> Result<
-    //     super::expression::CompiledFunctionOutput,
-    //     super::expression::ExpressionError,
-    // > {
-    //     #[derive(Clone, Copy)]
-    //     enum FunctionType {
-    //         Scalar,
-    //         Vector,
-    //     }
-
-    //     // prepare params for compiling output_length expression
-    //     let mut params =
-    //         super::expression::Params::Ref(super::expression::ParamsRef {
-    //             input,
-    //             output: None,
-    //             map: None,
-    //         });
-
-    //     // extract output expression and output_length
-    //     let (function_type, output_expr, output_length) = match self {
-    //         Function::Remote(RemoteFunction::Scalar { output, .. }) => {
-    //             (FunctionType::Scalar, output, None)
-    //         }
-    //         Function::Remote(RemoteFunction::Vector {
-    //             output,
-    //             output_length,
-    //             ..
-    //         }) => (
-    //             FunctionType::Vector,
-    //             output,
-    //             Some(output_length.compile_one(&params)?),
-    //         ),
-    //         Function::Inline(InlineFunction::Scalar { output, .. }) => {
-    //             (FunctionType::Scalar, output, None)
-    //         }
-    //         Function::Inline(InlineFunction::Vector { output, .. }) => {
-    //             (FunctionType::Vector, output, None)
-    //         }
-    //     };
-
-    //     // prepare params for compiling output expression
-    //     match &mut params {
-    //         super::expression::Params::Ref(params_ref) => {
-    //             params_ref.tasks = task_outputs;
-    //         }
-    //         _ => unreachable!(),
-    //     }
-
-    //     // compile output
-    //     let output = output_expr
-    //         .compile_one::<super::expression::FunctionOutput>(&params)?;
-
-    //     // validate output
-    //     let valid = match (function_type, &output, output_length) {
-    //         (
-    //             FunctionType::Scalar,
-    //             &super::expression::FunctionOutput::Scalar(scalar),
-    //             _,
-    //         ) => {
-    //             scalar >= rust_decimal::Decimal::ZERO
-    //                 && scalar <= rust_decimal::Decimal::ONE
-    //         }
-    //         (
-    //             FunctionType::Vector,
-    //             super::expression::FunctionOutput::Vector(vector),
-    //             Some(length),
-    //         ) => {
-    //             let sum = vector.iter().sum::<rust_decimal::Decimal>();
-    //             vector.len() == length as usize
-    //                 && sum >= rust_decimal::dec!(0.99)
-    //                 && sum <= rust_decimal::dec!(1.01)
-    //         }
-    //         (
-    //             FunctionType::Vector,
-    //             super::expression::FunctionOutput::Vector(vector),
-    //             None,
-    //         ) => {
-    //             let sum = vector.iter().sum::<rust_decimal::Decimal>();
-    //             sum >= rust_decimal::dec!(0.99)
-    //                 && sum <= rust_decimal::dec!(1.01)
-    //         }
-    //         _ => false,
-    //     };
-
-    //     // compiled output
-    //     Ok(super::expression::CompiledFunctionOutput { output, valid })
-    // }
+    /// Computes the final output given input and task outputs.
+    ///
+    /// Evaluates the function's `output` expression with `tasks[i]` set to the
+    /// output and profile weight of each task (see the module docs). The result
+    /// is not validated against the function's type.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The function input
+    /// * `tasks` - The transformed output and weight of each task, `None` for
+    ///   tasks without a valid output
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(FunctionOutput))` - The compiled output
+    /// - `Ok(None)` - If the function has no `output` expression, in which case
+    ///   the output is the weighted average of the task outputs
+    /// - `Err(ExpressionError)` - If the expression fails to compile
+    pub fn compile_output(
+        self,
+        input: &super::expression::Input,
+        tasks: &[Option<super::expression::TaskResult>],
+    ) -> Result<
+        Option<super::expression::FunctionOutput>,
+        super::expression::ExpressionError,
+    > {
+        let output_expr = match self {
+            Function::Remote(
+                RemoteFunction::Scalar { output, .. }
+                | RemoteFunction::Vector { output, .. }
+                | RemoteFunction::MultiLabel { output, .. }
+                | RemoteFunction::Categorical { output, .. },
+            ) => output,
+            Function::Inline(
+                InlineFunction::Scalar { output, .. }
+                | InlineFunction::Vector { output, .. }
+                | InlineFunction::MultiLabel { output, .. }
+                | InlineFunction::Categorical { output, .. },
+            ) => output,
+        };
+        match output_expr {
+            Some(output_expr) => {
+                // prepare params for compiling output expression
+                let params = super::expression::Params::Ref(
+                    super::expression::ParamsRef {
+                        input,
+                        output: None,
+                        map: None,
+                        tasks: Some(tasks),
+                    },
+                );
+                // compile output
+                let output = output_expr
+                    .compile_one::<super::expression::FunctionOutput>(&params)
+                    .map_err(|e| {
+                        e.with_field(super::expression::ExpressionField::Output)
+                    })?;
+                Ok(Some(output))
+            }
+            None => Ok(None),
+        }
+    }
 
     /// Computes the expected output length for a vector function.
     ///
@@ -341,12 +507,10 @@ impl Function {
         input: &super::expression::Input,
     ) -> Result<Option<u64>, super::expression::ExpressionError> {
         let output_length_expr = match self {
-            Function::Remote(RemoteFunction::Scalar { .. }) => None,
             Function::Remote(RemoteFunction::Vector {
                 output_length, ..
             }) => Some(output_length),
-            Function::Inline(InlineFunction::Scalar { .. }) => None,
-            Function::Inline(InlineFunction::Vector { .. }) => None,
+            _ => None,
         };
         match output_length_expr {
             Some(output_length_expr) => {
@@ -356,10 +520,16 @@ impl Function {
                         input,
                         output: None,
                         map: None,
+                        tasks: None,
                     },
                 );
                 // compile output_length
-                let output_length = output_length_expr.compile_one(&params)?;
+                let output_length =
+                    output_length_expr.compile_one(&params).map_err(|e| {
+                        e.with_field(
+                            super::expression::ExpressionField::OutputLength,
+                        )
+                    })?;
                 Ok(Some(output_length))
             }
             None => Ok(None),
@@ -389,14 +559,13 @@ impl Function {
         super::expression::ExpressionError,
     > {
         let input_split_expr = match self {
-            Function::Remote(RemoteFunction::Scalar { .. }) => None,
             Function::Remote(RemoteFunction::Vector {
                 input_split, ..
             }) => Some(input_split),
-            Function::Inline(InlineFunction::Scalar { .. }) => None,
             Function::Inline(InlineFunction::Vector {
                 input_split, ..
             }) => input_split,
+            _ => None,
         };
         match input_split_expr {
             Some(input_split_expr) => {
@@ -406,10 +575,16 @@ impl Function {
                         input,
                         output: None,
                         map: None,
+                        tasks: None,
                     },
                 );
                 // compile input_split
-                let input_split = input_split_expr.compile_one(&params)?;
+                let input_split =
+                    input_split_expr.compile_one(&params).map_err(|e| {
+                        e.with_field(
+                            super::expression::ExpressionField::InputSplit,
+                        )
+                    })?;
                 Ok(Some(input_split))
             }
             None => Ok(None),
@@ -439,14 +614,13 @@ impl Function {
         super::expression::ExpressionError,
     > {
         let input_merge_expr = match self {
-            Function::Remote(RemoteFunction::Scalar { .. }) => None,
             Function::Remote(RemoteFunction::Vector {
                 input_merge, ..
             }) => Some(input_merge),
-            Function::Inline(InlineFunction::Scalar { .. }) => None,
             Function::Inline(InlineFunction::Vector {
                 input_merge, ..
             }) => input_merge,
+            _ => None,
         };
         match input_merge_expr {
             Some(input_merge_expr) => {
@@ -456,10 +630,16 @@ impl Function {
                         input,
                         output: None,
                         map: None,
+                        tasks: None,
                     },
                 );
                 // compile input_merge
-                let input_merge = input_merge_expr.compile_one(&params)?;
+                let input_merge =
+                    input_merge_expr.compile_one(&params).map_err(|e| {
+                        e.with_field(
+                            super::expression::ExpressionField::InputMerge,
+                        )
+                    })?;
                 Ok(Some(input_merge))
             }
             None => Ok(None),
@@ -543,6 +723,35 @@ impl Function {
             Function::Inline(inline_function) => inline_function.input_merge(),
         }
     }
+
+    /// Returns the function's output expression, if defined.
+    pub fn output(&self) -> Option<&super::expression::Expression> {
+        match self {
+            Function::Remote(remote_function) => remote_function.output(),
+            Function::Inline(inline_function) => inline_function.output(),
+        }
+    }
+
+    /// Returns the function's reasoning summary prompt, if defined.
+    pub fn reasoning_summary(&self) -> Option<&super::ReasoningSummaryPrompt> {
+        match self {
+            Function::Remote(remote_function) => {
+                remote_function.reasoning_summary()
+            }
+            Function::Inline(inline_function) => {
+                inline_function.reasoning_summary()
+            }
+        }
+    }
+
+    /// Returns the function's labels (multi-label functions) or categories
+    /// (categorical functions).
+    pub fn labels(&self) -> Option<&[String]> {
+        match self {
+            Function::Remote(remote_function) => remote_function.labels(),
+            Function::Inline(inline_function) => inline_function.labels(),
+        }
+    }
 }
 
 /// A GitHub-hosted function with full metadata.
@@ -573,6 +782,15 @@ pub enum RemoteFunction {
         /// Each instance is compiled with `map` set to that element's value.
         /// Receives: `input`, `map` (if mapped).
         tasks: Vec<super::TaskExpression>,
+        /// Expression combining the task outputs into the function's output.
+        /// Receives: `input`, `tasks` (the output and profile weight of each task).
+        /// If omitted, the output is the weighted average of the task outputs.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<super::expression::Expression>,
+        /// How the reasoning summaries of the function's executions are
+        /// prompted. If omitted, a default prompt is used.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reasoning_summary: Option<super::ReasoningSummaryPrompt>,
     },
     /// Produces a vector of scores that sums to 1.
     #[serde(rename = "vector.function")]
@@ -594,6 +812,15 @@ pub enum RemoteFunction {
         /// Each instance is compiled with `map` set to that element's value.
         /// Receives: `input`, `map` (if mapped).
         tasks: Vec<super::TaskExpression>,
+        /// Expression combining the task outputs into the function's output.
+        /// Receives: `input`, `tasks` (the output and profile weight of each task).
+        /// If omitted, the output is the weighted average of the task outputs.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<super::expression::Expression>,
+        /// How the reasoning summaries of the function's executions are
+        /// prompted. If omitted, a default prompt is used.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reasoning_summary: Option<super::ReasoningSummaryPrompt>,
         /// Expression computing the expected output vector length for task outputs.
         /// Receives: `input`.
         output_length: super::expression::WithExpression<u64>,
@@ -609,46 +836,124 @@ pub enum RemoteFunction {
         input_merge:
             super::expression::WithExpression<super::expression::Input>,
     },
+    /// Produces an independent score in [0, 1] for each of its labels.
+    #[serde(rename = "multi_label.function")]
+    MultiLabel {
+        /// Human-readable description of what the function does.
+        description: String,
+        /// Version history and changes for this function.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        changelog: Option<String>,
+        /// JSON Schema defining the expected input structure.
+        input_schema: super::expression::InputSchema,
+        /// Expressions that transform input into a 2D array for mapped tasks.
+        /// Each sub-array can be referenced by tasks via their `map` index.
+        /// Receives: `input`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        input_maps: Option<super::expression::InputMaps>,
+        /// The list of tasks to execute. Tasks with a `map` index are expanded
+        /// into multiple instances, one per element in the referenced sub-array.
+        /// Each instance is compiled with `map` set to that element's value.
+        /// Receives: `input`, `map` (if mapped).
+        tasks: Vec<super::TaskExpression>,
+        /// Expression combining the task outputs into the function's output.
+        /// Receives: `input`, `tasks` (the output and profile weight of each task).
+        /// If omitted, the output is the weighted average of the task outputs.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<super::expression::Expression>,
+        /// How the reasoning summaries of the function's executions are
+        /// prompted. If omitted, a default prompt is used.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reasoning_summary: Option<super::ReasoningSummaryPrompt>,
+        /// The labels scored by the function, in output order.
+        labels: Vec<String>,
+    },
+    /// Produces scores over its categories that sum to 1.
+    #[serde(rename = "categorical.function")]
+    Categorical {
+        /// Human-readable description of what the function does.
+        description: String,
+        /// Version history and changes for this function.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        changelog: Option<String>,
+        /// JSON Schema defining the expected input structure.
+        input_schema: super::expression::InputSchema,
+        /// Expressions that transform input into a 2D array for mapped tasks.
+        /// Each sub-array can be referenced by tasks via their `map` index.
+        /// Receives: `input`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        input_maps: Option<super::expression::InputMaps>,
+        /// The list of tasks to execute. Tasks with a `map` index are expanded
+        /// into multiple instances, one per element in the referenced sub-array.
+        /// Each instance is compiled with `map` set to that element's value.
+        /// Receives: `input`, `map` (if mapped).
+        tasks: Vec<super::TaskExpression>,
+        /// Expression combining the task outputs into the function's output.
+        /// Receives: `input`, `tasks` (the output and profile weight of each task).
+        /// If omitted, the output is the weighted average of the task outputs.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<super::expression::Expression>,
+        /// How the reasoning summaries of the function's executions are
+        /// prompted. If omitted, a default prompt is used.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reasoning_summary: Option<super::ReasoningSummaryPrompt>,
+        /// The categories the function chooses between, in output order.
+        categories: Vec<String>,
+    },
 }
 
 impl RemoteFunction {
     /// Returns the function's description.
     pub fn description(&self) -> &str {
         match self {
-            RemoteFunction::Scalar { description, .. } => description,
-            RemoteFunction::Vector { description, .. } => description,
+            RemoteFunction::Scalar { description, .. }
+            | RemoteFunction::Vector { description, .. }
+            | RemoteFunction::MultiLabel { description, .. }
+            | RemoteFunction::Categorical { description, .. } => description,
         }
     }
 
     /// Returns the function's changelog, if present.
     pub fn changelog(&self) -> Option<&str> {
         match self {
-            RemoteFunction::Scalar { changelog, .. } => changelog.as_deref(),
-            RemoteFunction::Vector { changelog, .. } => changelog.as_deref(),
+            RemoteFunction::Scalar { changelog, .. }
+            | RemoteFunction::Vector { changelog, .. }
+            | RemoteFunction::MultiLabel { changelog, .. }
+            | RemoteFunction::Categorical { changelog, .. } => {
+                changelog.as_deref()
+            }
         }
     }
 
     /// Returns the function's input schema.
     pub fn input_schema(&self) -> &super::expression::InputSchema {
         match self {
-            RemoteFunction::Scalar { input_schema, .. } => input_schema,
-            RemoteFunction::Vector { input_schema, .. } => input_schema,
+            RemoteFunction::Scalar { input_schema, .. }
+            | RemoteFunction::Vector { input_schema, .. }
+            | RemoteFunction::MultiLabel { input_schema, .. }
+            | RemoteFunction::Categorical { input_schema, .. } => input_schema,
         }
     }
 
     /// Returns the function's input maps, if defined.
     pub fn input_maps(&self) -> Option<&super::expression::InputMaps> {
         match self {
-            RemoteFunction::Scalar { input_maps, .. } => input_maps.as_ref(),
-            RemoteFunction::Vector { input_maps, .. } => input_maps.as_ref(),
+            RemoteFunction::Scalar { input_maps, .. }
+            | RemoteFunction::Vector { input_maps, .. }
+            | RemoteFunction::MultiLabel { input_maps, .. }
+            | RemoteFunction::Categorical { input_maps, .. } => {
+                input_maps.as_ref()
+            }
         }
     }
 
     /// Returns the function's tasks.
     pub fn tasks(&self) -> &[super::TaskExpression] {
         match self {
-            RemoteFunction::Scalar { tasks, .. } => tasks,
-            RemoteFunction::Vector { tasks, .. } => tasks,
+            RemoteFunction::Scalar { tasks, .. }
+            | RemoteFunction::Vector { tasks, .. }
+            | RemoteFunction::MultiLabel { tasks, .. }
+            | RemoteFunction::Categorical { tasks, .. } => tasks,
         }
     }
 
@@ -657,8 +962,8 @@ impl RemoteFunction {
         &self,
     ) -> Option<&super::expression::WithExpression<u64>> {
         match self {
-            RemoteFunction::Scalar { .. } => None,
             RemoteFunction::Vector { output_length, .. } => Some(output_length),
+            _ => None,
         }
     }
 
@@ -668,8 +973,8 @@ impl RemoteFunction {
     ) -> Option<&super::expression::WithExpression<Vec<super::expression::Input>>>
     {
         match self {
-            RemoteFunction::Scalar { .. } => None,
             RemoteFunction::Vector { input_split, .. } => Some(input_split),
+            _ => None,
         }
     }
 
@@ -679,8 +984,46 @@ impl RemoteFunction {
     ) -> Option<&super::expression::WithExpression<super::expression::Input>>
     {
         match self {
-            RemoteFunction::Scalar { .. } => None,
             RemoteFunction::Vector { input_merge, .. } => Some(input_merge),
+            _ => None,
+        }
+    }
+
+    /// Returns the function's output expression, if defined.
+    pub fn output(&self) -> Option<&super::expression::Expression> {
+        match self {
+            RemoteFunction::Scalar { output, .. }
+            | RemoteFunction::Vector { output, .. }
+            | RemoteFunction::MultiLabel { output, .. }
+            | RemoteFunction::Categorical { output, .. } => output.as_ref(),
+        }
+    }
+
+    /// Returns the function's reasoning summary prompt, if defined.
+    pub fn reasoning_summary(&self) -> Option<&super::ReasoningSummaryPrompt> {
+        match self {
+            RemoteFunction::Scalar {
+                reasoning_summary, ..
+            }
+            | RemoteFunction::Vector {
+                reasoning_summary, ..
+            }
+            | RemoteFunction::MultiLabel {
+                reasoning_summary, ..
+            }
+            | RemoteFunction::Categorical {
+                reasoning_summary, ..
+            } => reasoning_summary.as_ref(),
+        }
+    }
+
+    /// Returns the function's labels (multi-label functions) or categories
+    /// (categorical functions).
+    pub fn labels(&self) -> Option<&[String]> {
+        match self {
+            RemoteFunction::MultiLabel { labels, .. } => Some(labels),
+            RemoteFunction::Categorical { categories, .. } => Some(categories),
+            _ => None,
         }
     }
 }
@@ -706,6 +1049,15 @@ pub enum InlineFunction {
         /// Each instance is compiled with `map` set to that element's value.
         /// Receives: `input`, `map` (if mapped).
         tasks: Vec<super::TaskExpression>,
+        /// Expression combining the task outputs into the function's output.
+        /// Receives: `input`, `tasks` (the output and profile weight of each task).
+        /// If omitted, the output is the weighted average of the task outputs.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<super::expression::Expression>,
+        /// How the reasoning summaries of the function's executions are
+        /// prompted. If omitted, a default prompt is used.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reasoning_summary: Option<super::ReasoningSummaryPrompt>,
     },
     /// Produces a vector of scores that sums to 1.
     #[serde(rename = "vector.function")]
@@ -720,6 +1072,15 @@ pub enum InlineFunction {
         /// Each instance is compiled with `map` set to that element's value.
         /// Receives: `input`, `map` (if mapped).
         tasks: Vec<super::TaskExpression>,
+        /// Expression combining the task outputs into the function's output.
+        /// Receives: `input`, `tasks` (the output and profile weight of each task).
+        /// If omitted, the output is the weighted average of the task outputs.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<super::expression::Expression>,
+        /// How the reasoning summaries of the function's executions are
+        /// prompted. If omitted, a default prompt is used.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reasoning_summary: Option<super::ReasoningSummaryPrompt>,
         /// Expression transforming input into an input array of the output_length
         /// When the Function is executed with any input from the array,
         /// The output_length should be 1.
@@ -735,22 +1096,78 @@ pub enum InlineFunction {
         input_merge:
             Option<super::expression::WithExpression<super::expression::Input>>,
     },
+    /// Produces an independent score in [0, 1] for each of its labels.
+    #[serde(rename = "multi_label.function")]
+    MultiLabel {
+        /// Expressions that transform input into a 2D array for mapped tasks.
+        /// Each sub-array can be referenced by tasks via their `map` index.
+        /// Receives: `input`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        input_maps: Option<super::expression::InputMaps>,
+        /// The list of tasks to execute. Tasks with a `map` index are expanded
+        /// into multiple instances, one per element in the referenced sub-array.
+        /// Each instance is compiled with `map` set to that element's value.
+        /// Receives: `input`, `map` (if mapped).
+        tasks: Vec<super::TaskExpression>,
+        /// Expression combining the task outputs into the function's output.
+        /// Receives: `input`, `tasks` (the output and profile weight of each task).
+        /// If omitted, the output is the weighted average of the task outputs.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<super::expression::Expression>,
+        /// How the reasoning summaries of the function's executions are
+        /// prompted. If omitted, a default prompt is used.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reasoning_summary: Option<super::ReasoningSummaryPrompt>,
+        /// The labels scored by the function, in output order.
+        labels: Vec<String>,
+    },
+    /// Produces scores over its categories that sum to 1.
+    #[serde(rename = "categorical.function")]
+    Categorical {
+        /// Expressions that transform input into a 2D array for mapped tasks.
+        /// Each sub-array can be referenced by tasks via their `map` index.
+        /// Receives: `input`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        input_maps: Option<super::expression::InputMaps>,
+        /// The list of tasks to execute. Tasks with a `map` index are expanded
+        /// into multiple instances, one per element in the referenced sub-array.
+        /// Each instance is compiled with `map` set to that element's value.
+        /// Receives: `input`, `map` (if mapped).
+        tasks: Vec<super::TaskExpression>,
+        /// Expression combining the task outputs into the function's output.
+        /// Receives: `input`, `tasks` (the output and profile weight of each task).
+        /// If omitted, the output is the weighted average of the task outputs.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<super::expression::Expression>,
+        /// How the reasoning summaries of the function's executions are
+        /// prompted. If omitted, a default prompt is used.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reasoning_summary: Option<super::ReasoningSummaryPrompt>,
+        /// The categories the function chooses between, in output order.
+        categories: Vec<String>,
+    },
 }
 
 impl InlineFunction {
     /// Returns the function's input maps, if defined.
     pub fn input_maps(&self) -> Option<&super::expression::InputMaps> {
         match self {
-            InlineFunction::Scalar { input_maps, .. } => input_maps.as_ref(),
-            InlineFunction::Vector { input_maps, .. } => input_maps.as_ref(),
+            InlineFunction::Scalar { input_maps, .. }
+            | InlineFunction::Vector { input_maps, .. }
+            | InlineFunction::MultiLabel { input_maps, .. }
+            | InlineFunction::Categorical { input_maps, .. } => {
+                input_maps.as_ref()
+            }
         }
     }
 
     /// Returns the function's tasks.
     pub fn tasks(&self) -> &[super::TaskExpression] {
         match self {
-            InlineFunction::Scalar { tasks, .. } => tasks,
-            InlineFunction::Vector { tasks, .. } => tasks,
+            InlineFunction::Scalar { tasks, .. }
+            | InlineFunction::Vector { tasks, .. }
+            | InlineFunction::MultiLabel { tasks, .. }
+            | InlineFunction::Categorical { tasks, .. } => tasks,
         }
     }
 
@@ -760,8 +1177,8 @@ impl InlineFunction {
     ) -> Option<&super::expression::WithExpression<Vec<super::expression::Input>>>
     {
         match self {
-            InlineFunction::Scalar { .. } => None,
             InlineFunction::Vector { input_split, .. } => input_split.as_ref(),
+            _ => None,
         }
     }
 
@@ -771,8 +1188,422 @@ impl InlineFunction {
     ) -> Option<&super::expression::WithExpression<super::expression::Input>>
     {
         match self {
-            InlineFunction::Scalar { .. } => None,
             InlineFunction::Vector { input_merge, .. } => input_merge.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the function's output expression, if defined.
+    pub fn output(&self) -> Option<&super::expression::Expression> {
+        match self {
+            InlineFunction::Scalar { output, .. }
+            | InlineFunction::Vector { output, .. }
+            | InlineFunction::MultiLabel { output, .. }
+            | InlineFunction::Categorical { output, .. } => output.as_ref(),
         }
     }
+
+    /// Returns the function's reasoning summary prompt, if defined.
+    pub fn reasoning_summary(&self) -> Option<&super::ReasoningSummaryPrompt> {
+        match self {
+            InlineFunction::Scalar {
+                reasoning_summary, ..
+            }
+            | InlineFunction::Vector {
+                reasoning_summary, ..
+            }
+            | InlineFunction::MultiLabel {
+                reasoning_summary, ..
+            }
+            | InlineFunction::Categorical {
+                reasoning_summary, ..
+            } => reasoning_summary.as_ref(),
+        }
+    }
+
+    /// Returns the function's labels (multi-label functions) or categories
+    /// (categorical functions).
+    pub fn labels(&self) -> Option<&[String]> {
+        match self {
+            InlineFunction::MultiLabel { labels, .. } => Some(labels),
+            InlineFunction::Categorical { categories, .. } => Some(categories),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::expression::{
+        ExpressionDialect, ExpressionError, ExpressionField, Input,
+        TaskOutputOwned, TaskResult,
+    };
+    use crate::functions::{CompiledTask, Task};
+
+    fn function(tasks: serde_json::Value) -> Function {
+        serde_json::from_value(serde_json::json!({
+            "type": "scalar.function",
+            "description": "test",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "items": {
+                        "type": "array",
+                        "items": { "type": "integer" }
+                    }
+                }
+            },
+            "input_maps": { "$jmespath": "input.items" },
+            "tasks": tasks
+        }))
+        .unwrap()
+    }
+
+    fn input() -> Input {
+        serde_json::from_value(serde_json::json!({ "items": [1, 0, 2] }))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_compile_tasks_error_location_mapped() {
+        let function = function(serde_json::json!([
+            {
+                "type": "vector.completion",
+                "messages": [{ "role": "user", "content": "hi" }],
+                "responses": ["a", "b"],
+                "output": { "$jmespath": "output.scores[0]" }
+            },
+            {
+                "type": "vector.completion",
+                "map": 0,
+                "messages": [{
+                    "role": "user",
+                    "content": { "$starlark": "str(10 // map)" }
+                }],
+                "responses": ["a", "b"],
+                "output": { "$jmespath": "output[0].scores[0]" }
+            }
+        ]));
+        let err = function.compile_tasks(&input()).unwrap_err();
+        let location = err.location().unwrap();
+        assert_eq!(location.task_index, Some(1));
+        assert_eq!(location.field, Some(ExpressionField::Messages));
+        assert_eq!(location.map_index, Some(1));
+        assert_eq!(err.dialect(), Some(ExpressionDialect::Starlark));
+        assert!(matches!(err.inner(), ExpressionError::StarlarkEvalError(_)));
+        assert!(err.span().is_some());
+    }
+
+    #[test]
+    fn test_compile_tasks_error_location_skip() {
+        let function = function(serde_json::json!([
+            {
+                "type": "vector.completion",
+                "skip": { "$jmespath": "input.items" },
+                "messages": [{ "role": "user", "content": "hi" }],
+                "responses": ["a", "b"],
+                "output": { "$jmespath": "output.scores[0]" }
+            }
+        ]));
+        let err = function.compile_tasks(&input()).unwrap_err();
+        let location = err.location().unwrap();
+        assert_eq!(location.task_index, Some(0));
+        assert_eq!(location.field, Some(ExpressionField::Skip));
+        assert_eq!(location.map_index, None);
+        assert_eq!(location.dialect, Some(ExpressionDialect::JMESPath));
+        assert_eq!(
+            err.to_string().split(':').next(),
+            Some("tasks[0].skip [jmespath]")
+        );
+    }
+
+    fn chat_then_vector() -> Function {
+        function(serde_json::json!([
+            {
+                "type": "chat.completion",
+                "messages": [{ "role": "user", "content": "classify" }],
+                "response_format": { "type": "json_object" }
+            },
+            {
+                "type": "vector.completion",
+                "skip": { "$jmespath": "tasks[0].output.relevant == `false`" },
+                "messages": [{
+                    "role": "user",
+                    "content": { "$starlark": "tasks[0]['output']['topic']" }
+                }],
+                "responses": ["a", "b"],
+                "output": { "$jmespath": "output.scores[0]" }
+            }
+        ]))
+    }
+
+    fn chat_result(output: serde_json::Value) -> Vec<Option<TaskResult>> {
+        vec![
+            Some(TaskResult {
+                output: TaskOutputOwned::ChatCompletion(output),
+                weight: None,
+            }),
+            None,
+        ]
+    }
+
+    #[test]
+    fn test_compile_chat_completion_tasks() {
+        let tasks = chat_then_vector()
+            .compile_chat_completion_tasks(&input())
+            .unwrap();
+        assert_eq!(tasks.len(), 2);
+        let Some(CompiledTask::One(Task::ChatCompletion(task))) = &tasks[0]
+        else {
+            panic!("expected chat completion task");
+        };
+        assert!(task.is_json());
+        assert_eq!(
+            task.output(r#"{"topic":"rust"}"#.to_string()),
+            serde_json::json!({ "topic": "rust" })
+        );
+        assert!(tasks[1].is_none());
+    }
+
+    #[test]
+    fn test_compile_tasks_with_results() {
+        let results = chat_result(serde_json::json!({
+            "relevant": true,
+            "topic": "rust"
+        }));
+        let tasks = chat_then_vector()
+            .compile_tasks_with_results(&input(), &results)
+            .unwrap();
+        assert!(tasks[0].is_none());
+        let Some(CompiledTask::One(Task::VectorCompletion(task))) = &tasks[1]
+        else {
+            panic!("expected vector completion task");
+        };
+        assert_eq!(
+            serde_json::to_value(&task.messages[0]).unwrap()["content"],
+            serde_json::json!("rust")
+        );
+
+        // the dependent task is skipped on the chat completion's output
+        let results = chat_result(serde_json::json!({
+            "relevant": false,
+            "topic": "rust"
+        }));
+        let tasks = chat_then_vector()
+            .compile_tasks_with_results(&input(), &results)
+            .unwrap();
+        assert!(tasks.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_compile_dependent_task() {
+        let function = function(serde_json::json!([
+            {
+                "type": "vector.completion",
+                "messages": [{ "role": "user", "content": "filter" }],
+                "responses": ["a", "b"],
+                "output": { "$jmespath": "output.scores[0]" }
+            },
+            {
+                "type": "vector.completion",
+                "messages": [{ "role": "user", "content": "other" }],
+                "responses": ["a", "b"],
+                "output": { "$jmespath": "output.scores[0]" }
+            },
+            {
+                "type": "vector.completion",
+                "dependencies": [0],
+                "skip": {
+                    "$starlark": "tasks[0]['output']['scores'][0] < 0.5 or tasks[1] != None"
+                },
+                "messages": [{ "role": "user", "content": "judge" }],
+                "responses": ["a", "b"],
+                "output": { "$jmespath": "output.scores[0]" }
+            }
+        ]));
+        assert!(!function.is_dependent_task(1));
+        assert!(function.is_dependent_task(2));
+        function.validate_dependencies().unwrap();
+
+        // dependent tasks are not compiled with the other tasks
+        let tasks = function
+            .clone()
+            .compile_tasks_with_results(&input(), &[None, None, None])
+            .unwrap();
+        assert!(tasks[0].is_some() && tasks[1].is_some() && tasks[2].is_none());
+
+        // only the results of dependencies are exposed
+        let result = |score: &str| {
+            Some(TaskResult {
+                output: TaskOutputOwned::VectorCompletion(
+                    serde_json::from_value(serde_json::json!({
+                        "votes": [],
+                        "scores": [score, "0"],
+                        "weights": []
+                    }))
+                    .unwrap(),
+                ),
+                weight: None,
+            })
+        };
+        let results = vec![result("0.9"), result("0.9"), None];
+        assert!(
+            function
+                .clone()
+                .compile_dependent_task(2, &input(), &results)
+                .unwrap()
+                .is_some()
+        );
+        let results = vec![result("0.1"), result("0.9"), None];
+        assert!(
+            function
+                .compile_dependent_task(2, &input(), &results)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_validate_dependencies() {
+        let function = function(serde_json::json!([
+            {
+                "type": "vector.completion",
+                "dependencies": [0],
+                "messages": [{ "role": "user", "content": "hi" }],
+                "responses": ["a", "b"],
+                "output": { "$jmespath": "output.scores[0]" }
+            }
+        ]));
+        assert!(function.validate_dependencies().is_err());
+    }
+
+    #[test]
+    fn test_compile_output() {
+        use crate::functions::expression::FunctionOutput;
+        use rust_decimal::dec;
+
+        let task = |output: rust_decimal::Decimal, weight| {
+            Some(TaskResult {
+                output: TaskOutputOwned::Function(FunctionOutput::Scalar(
+                    output,
+                )),
+                weight: Some(weight),
+            })
+        };
+        let tasks =
+            vec![task(dec!(0.2), dec!(1)), None, task(dec!(0.8), dec!(3))];
+
+        // no output expression
+        assert!(
+            function(serde_json::json!([]))
+                .compile_output(&input(), &tasks)
+                .unwrap()
+                .is_none()
+        );
+
+        // threshold on the heaviest task
+        let mut value =
+            serde_json::to_value(function(serde_json::json!([]))).unwrap();
+        value["output"] = serde_json::json!({
+            "$starlark": "1.0 if max([t for t in tasks if t != None], key=lambda t: t['weight'])['output'] > 0.5 else 0.0"
+        });
+        let function: Function = serde_json::from_value(value).unwrap();
+        match function.clone().compile_output(&input(), &tasks).unwrap() {
+            Some(FunctionOutput::Scalar(output)) => {
+                assert_eq!(output, dec!(1))
+            }
+            other => panic!("expected scalar output, got {:?}", other),
+        }
+
+        // errors are located at the output
+        let err = function.compile_output(&input(), &[]).unwrap_err();
+        let location = err.location().unwrap();
+        assert_eq!(location.field, Some(ExpressionField::Output));
+    }
+
+    #[test]
+    fn test_labelled_function() {
+        use crate::functions::expression::FunctionOutput;
+        use rust_decimal::dec;
+
+        let mut value =
+            serde_json::to_value(function(serde_json::json!([]))).unwrap();
+        value["type"] = serde_json::json!("categorical.function");
+        value["categories"] = serde_json::json!(["spam", "ham"]);
+        let function: Function = serde_json::from_value(value).unwrap();
+        let labels = function.labels().unwrap();
+        assert_eq!(labels, ["spam", "ham"]);
+
+        // vectors are scored in label order
+        let scores = FunctionOutput::Vector(vec![dec!(0.3), dec!(0.7)])
+            .into_labelled(labels)
+            .unwrap();
+        assert_eq!(scores["ham"], dec!(0.7));
+
+        // labelled outputs must have exactly the function's labels
+        let output: FunctionOutput =
+            serde_json::from_value(serde_json::json!({ "spam": 1.0 })).unwrap();
+        assert!(output.into_labelled(labels).is_err());
+    }
+
+    #[test]
+    fn test_validate_labels() {
+        let mut value =
+            serde_json::to_value(function(serde_json::json!([]))).unwrap();
+        value["type"] = serde_json::json!("categorical.function");
+        value["categories"] = serde_json::json!(["spam", "ham"]);
+        let function: Function = serde_json::from_value(value.clone()).unwrap();
+        function.validate_labels().unwrap();
+
+        value["categories"] = serde_json::json!([]);
+        let function: Function = serde_json::from_value(value.clone()).unwrap();
+        assert!(function.validate_labels().is_err());
+
+        value["categories"] = serde_json::json!(["spam", "ham", "spam"]);
+        let function: Function = serde_json::from_value(value).unwrap();
+        assert!(function.validate_labels().is_err());
+    }
+
+    #[test]
+    fn test_labelled_output_round_trip() {
+        use crate::functions::expression::FunctionOutput;
+        use rust_decimal::dec;
+
+        let scores = [
+            ("spam".to_string(), dec!(0.25)),
+            ("ham".to_string(), dec!(0.75)),
+        ]
+        .into_iter()
+        .collect::<indexmap::IndexMap<_, _>>();
+        for output in [
+            FunctionOutput::MultiLabel(scores.clone()),
+            FunctionOutput::Categorical(scores.clone()),
+        ] {
+            let json = serde_json::to_string(&output).unwrap();
+            let round_trip: FunctionOutput =
+                serde_json::from_str(&json).unwrap();
+            match (output, round_trip) {
+                (
+                    FunctionOutput::MultiLabel(a),
+                    FunctionOutput::MultiLabel(b),
+                )
+                | (
+                    FunctionOutput::Categorical(a),
+                    FunctionOutput::Categorical(b),
+                ) => assert_eq!(a, b),
+                (output, round_trip) => {
+                    panic!("expected {:?}, got {:?}", output, round_trip)
+                }
+            }
+        }
+
+        // scalars and vectors are untagged
+        let output: FunctionOutput =
+            serde_json::from_value(serde_json::json!([0.5, 0.5])).unwrap();
+        assert!(matches!(output, FunctionOutput::Vector(_)));
+        let output: FunctionOutput =
+            serde_json::from_value(serde_json::json!(0.5)).unwrap();
+        assert!(matches!(output, FunctionOutput::Scalar(_)));
+    }
 }
@@ -187,3 +187,26 @@ pub async fn get_function_profile_pair_usage(
         .send_unary(reqwest::Method::GET, &path, None::<String>)
         .await
 }
+
+/// Checks that a Profile fits the task structure of a Function.
+///
+/// # Arguments
+///
+/// * `client` - The HTTP client to use
+/// * `request` - The Function and Profile, remote or inline
+///
+/// # Returns
+///
+/// Whether they are compatible, with every mismatch found.
+pub async fn check_compatibility(
+    client: &HttpClient,
+    request: &super::CheckCompatibilityRequest,
+) -> Result<super::CheckCompatibilityResponse, HttpError> {
+    client
+        .send_unary(
+            reqwest::Method::POST,
+            "functions/compatibility",
+            Some(request),
+        )
+        .await
+}
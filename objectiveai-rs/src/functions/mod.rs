@@ -8,8 +8,9 @@
 //! A Function consists of:
 //! - **Input schema** - Defines expected input structure
 //! - **Input maps** - Optional expressions to transform input into arrays for mapped tasks
-//! - **Tasks** - A list of operations (Vector Completions or nested Functions)
+//! - **Tasks** - A list of operations (Vector Completions, Chat Completions or nested Functions)
 //! - **Output** - Expression that combines task results into final score(s)
+//! - **Reasoning summary** - Optional [`ReasoningSummaryPrompt`] for summaries of executions
 //!
 //! # Function Types
 //!
@@ -23,6 +24,7 @@
 //! - [`ScalarFunctionTask`] - Calls a scalar function
 //! - [`VectorFunctionTask`] - Calls a vector function
 //! - [`VectorCompletionTask`] - Runs a vector completion
+//! - [`ChatCompletionTask`] - Runs a chat completion whose output feeds later tasks
 //!
 //! # Client-Side Compilation
 //!
@@ -31,6 +33,8 @@
 //!
 //! - [`Function::compile_tasks`] - Resolves task expressions to show final tasks for a given input
 //! - [`Function::compile_output`] - Computes the final output given input and task outputs
+//! - [`RemoteFunction::type_check`] - Checks expressions against the input schema without executing
+//! - [`Function::check_compatibility`] - Checks that a Profile fits a Function's task structure
 //!
 //! # Submodules
 //!
@@ -38,17 +42,23 @@
 //! - [`expression`] - Expression evaluation engine (JMESPath and Starlark)
 //! - [`profiles`] - Profile management and computation
 
+mod compatibility;
 pub mod executions;
 pub mod expression;
 mod function;
 mod profile;
 pub mod profiles;
+mod reasoning_summary;
 pub mod response;
 mod task;
+mod type_check;
 
+pub use compatibility::*;
 pub use function::*;
 pub use profile::*;
+pub use reasoning_summary::*;
 pub use task::*;
+pub use type_check::*;
 
 #[cfg(feature = "http")]
 mod http;
@@ -4,7 +4,7 @@
 //! a Function. Profiles are typically trained on example data to optimize
 //! scoring behavior.
 
-use crate::vector;
+use crate::{chat, vector};
 use serde::{Deserialize, Serialize};
 
 /// A Profile definition, either remote (GitHub-hosted) or inline.
@@ -84,6 +84,18 @@ pub enum TaskProfile {
         /// - A vector of objects with `weight` and optional `invert` fields.
         profile: vector::completions::request::Profile,
     },
+    /// Configuration for a chat completion task.
+    ///
+    /// Chat completion tasks do not contribute to the Function's output, so
+    /// their weight in the parent Profile is ignored.
+    ChatCompletion {
+        /// The model that runs the chat completion.
+        model: chat::completions::request::Model,
+        /// Fallback models tried in order if the primary is rate-limited or
+        /// errors.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        models: Option<Vec<chat::completions::request::Model>>,
+    },
 }
 
 impl TaskProfile {
@@ -100,6 +112,7 @@ impl TaskProfile {
                 .iter()
                 .all(TaskProfile::validate_commit_required),
             TaskProfile::VectorCompletion { .. } => true,
+            TaskProfile::ChatCompletion { .. } => true,
         }
     }
 }
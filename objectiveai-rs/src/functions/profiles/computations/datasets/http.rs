@@ -0,0 +1,21 @@
+use crate::{HttpClient, HttpError};
+
+pub async fn upload_dataset(
+    client: &HttpClient,
+    params: super::request::DatasetCreateParams,
+    content: impl Into<reqwest::Body>,
+) -> Result<super::response::Dataset, HttpError> {
+    let content_type = match params.format {
+        super::request::DatasetFormat::Jsonl => "application/x-ndjson",
+        super::request::DatasetFormat::Csv => "text/csv",
+    };
+    client
+        .send_unary_content(
+            reqwest::Method::POST,
+            "functions/profiles/compute/datasets",
+            Some(params),
+            content_type,
+            content,
+        )
+        .await
+}
@@ -0,0 +1,17 @@
+//! Uploaded datasets for Profile computations.
+//!
+//! Large datasets can be uploaded once as JSONL or CSV and then referenced
+//! by ID from a Profile computation request, instead of being embedded
+//! inline in every request body.
+
+mod parse;
+pub mod request;
+pub mod response;
+
+pub use parse::*;
+
+#[cfg(feature = "http")]
+mod http;
+
+#[cfg(feature = "http")]
+pub use http::*;
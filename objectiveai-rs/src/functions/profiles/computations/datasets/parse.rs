@@ -0,0 +1,714 @@
+//! Parsing of uploaded JSONL and CSV datasets.
+
+use super::request::{DatasetCreateParams, DatasetFormat, TargetType};
+use crate::functions::{
+    expression::Input,
+    profiles::computations::request::{Dataset, DatasetItem, Target},
+};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// An error for a single dataset item.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RowError {
+    /// Index of the dataset item, excluding CSV headers and blank lines.
+    pub index: usize,
+    /// Why the item is invalid.
+    pub message: String,
+}
+
+/// Errors that can occur when parsing or validating a dataset.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum DatasetError {
+    /// The CSV header or column mapping is invalid.
+    #[error("invalid header: {0}")]
+    Header(String),
+    /// One or more items are invalid.
+    #[error("{} invalid dataset items", .0.len())]
+    Rows(Vec<RowError>),
+    /// The content is not valid UTF-8.
+    #[error("dataset is not valid UTF-8")]
+    Encoding,
+    /// One or more item inputs do not match the Function's input schema.
+    #[error("{} dataset inputs do not match the input schema", .0.len())]
+    Inputs(Vec<RowError>),
+}
+
+/// Parses a dataset in the format given by `params`.
+///
+/// Every item is parsed, so that all invalid items are reported at once
+/// rather than only the first.
+pub fn parse_dataset(
+    params: &DatasetCreateParams,
+    content: &str,
+) -> Result<Dataset, DatasetError> {
+    let mut parser = DatasetParser::new(params)?;
+    parser.push(content.as_bytes());
+    parser.finish()
+}
+
+/// Parses a dataset with one JSON [`DatasetItem`] per line.
+///
+/// Blank lines are skipped.
+pub fn parse_jsonl(content: &str) -> Result<Dataset, DatasetError> {
+    parse_dataset(
+        &DatasetCreateParams {
+            format: DatasetFormat::Jsonl,
+            target_type: None,
+            target_column: None,
+            input_columns: None,
+            fowner: None,
+            frepository: None,
+            fcommit: None,
+        },
+        content,
+    )
+}
+
+/// Parses a CSV dataset with a header row.
+///
+/// Each input cell is parsed as JSON if it is valid JSON, and is otherwise
+/// taken as a string. Empty cells are omitted from the input.
+pub fn parse_csv(
+    params: &DatasetCreateParams,
+    content: &str,
+) -> Result<Dataset, DatasetError> {
+    parse_dataset(
+        &DatasetCreateParams {
+            format: DatasetFormat::Csv,
+            ..params.clone()
+        },
+        content,
+    )
+}
+
+/// Parses a dataset incrementally, as its content arrives.
+///
+/// Content may be pushed in chunks split anywhere, even within a UTF-8
+/// character. Only parsed items are kept, so an upload never has to be
+/// buffered whole. Errors are reported by [`finish`](Self::finish).
+#[derive(Debug)]
+pub struct DatasetParser {
+    format: Format,
+    // trailing bytes of an incomplete UTF-8 character
+    pending: Vec<u8>,
+    // whether any content was decoded yet, to strip a leading BOM
+    started: bool,
+    dataset: Dataset,
+    errors: Vec<RowError>,
+    // an error that ends parsing, after which content is ignored
+    fatal: Option<DatasetError>,
+}
+
+#[derive(Debug)]
+enum Format {
+    Jsonl { line: String, index: usize },
+    Csv(CsvParser),
+}
+
+impl DatasetParser {
+    /// Creates a parser for the format given by `params`.
+    pub fn new(params: &DatasetCreateParams) -> Result<Self, DatasetError> {
+        let format = match params.format {
+            DatasetFormat::Jsonl => Format::Jsonl {
+                line: String::new(),
+                index: 0,
+            },
+            DatasetFormat::Csv => Format::Csv(CsvParser::new(params)?),
+        };
+        Ok(Self {
+            format,
+            pending: Vec::new(),
+            started: false,
+            dataset: Vec::new(),
+            errors: Vec::new(),
+            fatal: None,
+        })
+    }
+
+    /// Returns whether parsing already failed, so later content is ignored.
+    pub fn failed(&self) -> bool {
+        self.fatal.is_some()
+    }
+
+    /// Parses the next chunk of content.
+    pub fn push(&mut self, bytes: &[u8]) {
+        if self.fatal.is_some() {
+            return;
+        }
+        self.pending.extend_from_slice(bytes);
+        let pending = std::mem::take(&mut self.pending);
+        let valid = match std::str::from_utf8(&pending) {
+            Ok(text) => text.len(),
+            // an incomplete character, completed by the next chunk
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => {
+                self.fatal = Some(DatasetError::Encoding);
+                return;
+            }
+        };
+        let (text, rest) = pending.split_at(valid);
+        self.push_str(std::str::from_utf8(text).unwrap_or_default());
+        self.pending = rest.to_vec();
+    }
+
+    fn push_str(&mut self, mut text: &str) {
+        if !self.started && !text.is_empty() {
+            self.started = true;
+            text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        }
+        let Self {
+            format,
+            dataset,
+            errors,
+            fatal,
+            ..
+        } = self;
+        match format {
+            Format::Jsonl { line, index } => {
+                let mut rest = text;
+                while let Some(end) = rest.find('\n') {
+                    line.push_str(&rest[..end]);
+                    rest = &rest[end + 1..];
+                    jsonl_line(&std::mem::take(line), index, dataset, errors);
+                }
+                line.push_str(rest);
+            }
+            Format::Csv(csv) => {
+                for c in text.chars() {
+                    let result = match csv.reader.push(c) {
+                        Ok(Some(record)) => csv.record(record, dataset, errors),
+                        Ok(None) => Ok(()),
+                        Err(e) => Err(csv_syntax_error(e)),
+                    };
+                    if let Err(e) = result {
+                        *fatal = Some(e);
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Ends the content, returning the dataset or every error found.
+    pub fn finish(mut self) -> Result<Dataset, DatasetError> {
+        if self.fatal.is_none() && !self.pending.is_empty() {
+            self.fatal = Some(DatasetError::Encoding);
+        }
+        if self.fatal.is_none() {
+            let result = match &mut self.format {
+                Format::Jsonl { line, index } => {
+                    jsonl_line(
+                        line,
+                        index,
+                        &mut self.dataset,
+                        &mut self.errors,
+                    );
+                    Ok(())
+                }
+                Format::Csv(csv) => {
+                    csv.finish(&mut self.dataset, &mut self.errors)
+                }
+            };
+            self.fatal = result.err();
+        }
+        if let Some(e) = self.fatal {
+            Err(e)
+        } else if self.errors.is_empty() {
+            Ok(self.dataset)
+        } else {
+            Err(DatasetError::Rows(self.errors))
+        }
+    }
+}
+
+/// Parses one JSONL line, skipping blank lines.
+fn jsonl_line(
+    line: &str,
+    index: &mut usize,
+    dataset: &mut Dataset,
+    errors: &mut Vec<RowError>,
+) {
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    if line.trim().is_empty() {
+        return;
+    }
+    match serde_json::from_str::<DatasetItem>(line) {
+        Ok(item) => dataset.push(item),
+        Err(e) => errors.push(RowError {
+            index: *index,
+            message: e.to_string(),
+        }),
+    }
+    *index += 1;
+}
+
+/// Reports a CSV syntax error in the header or in a single item.
+fn csv_syntax_error((record, message): (usize, String)) -> DatasetError {
+    match record {
+        0 => DatasetError::Header(message),
+        record => DatasetError::Rows(vec![RowError {
+            index: record - 1,
+            message,
+        }]),
+    }
+}
+
+/// Converts CSV records into dataset items.
+#[derive(Debug)]
+struct CsvParser {
+    target_type: TargetType,
+    target_column: String,
+    input_columns: Option<String>,
+    reader: CsvReader,
+    // resolved from the header row
+    columns: Option<CsvColumns>,
+    // index of the next item
+    index: usize,
+}
+
+#[derive(Debug)]
+struct CsvColumns {
+    len: usize,
+    target: usize,
+    inputs: Vec<(usize, String)>,
+}
+
+impl CsvParser {
+    fn new(params: &DatasetCreateParams) -> Result<Self, DatasetError> {
+        let target_type = params.target_type.ok_or_else(|| {
+            DatasetError::Header("target_type is required for csv".to_string())
+        })?;
+        Ok(Self {
+            target_type,
+            target_column: params
+                .target_column
+                .clone()
+                .unwrap_or_else(|| "target".to_string()),
+            input_columns: params.input_columns.clone(),
+            reader: CsvReader::default(),
+            columns: None,
+            index: 0,
+        })
+    }
+
+    /// Resolves the target and input columns from the header row.
+    fn header(&self, header: &[String]) -> Result<CsvColumns, DatasetError> {
+        let column = |name: &str| {
+            header.iter().position(|h| h.trim() == name).ok_or_else(|| {
+                DatasetError::Header(format!("missing column \"{}\"", name))
+            })
+        };
+        let target = column(&self.target_column)?;
+        let inputs = match &self.input_columns {
+            Some(input_columns) => input_columns
+                .split(',')
+                .map(|mapping| {
+                    let (name, field) = match mapping.split_once(':') {
+                        Some((name, field)) => (name.trim(), field.trim()),
+                        None => (mapping.trim(), mapping.trim()),
+                    };
+                    Ok((column(name)?, field.to_string()))
+                })
+                .collect::<Result<Vec<_>, DatasetError>>()?,
+            None => header
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != target)
+                .map(|(i, name)| (i, name.trim().to_string()))
+                .collect(),
+        };
+        let mut fields =
+            inputs.iter().map(|(_, field)| field).collect::<Vec<_>>();
+        fields.sort_unstable();
+        if let Some(window) = fields.windows(2).find(|w| w[0] == w[1]) {
+            return Err(DatasetError::Header(format!(
+                "duplicate input field \"{}\"",
+                window[0]
+            )));
+        }
+        Ok(CsvColumns {
+            len: header.len(),
+            target,
+            inputs,
+        })
+    }
+
+    /// Handles a complete record, the first being the header.
+    fn record(
+        &mut self,
+        record: Vec<String>,
+        dataset: &mut Dataset,
+        errors: &mut Vec<RowError>,
+    ) -> Result<(), DatasetError> {
+        let Some(columns) = &self.columns else {
+            self.columns = Some(self.header(&record)?);
+            return Ok(());
+        };
+        let index = self.index;
+        self.index += 1;
+        if record.len() != columns.len {
+            errors.push(RowError {
+                index,
+                message: format!(
+                    "expected {} columns, found {}",
+                    columns.len,
+                    record.len()
+                ),
+            });
+            return Ok(());
+        }
+        let input = columns
+            .inputs
+            .iter()
+            .filter(|(i, _)| !record[*i].is_empty())
+            .map(|(i, field)| (field.clone(), csv_input(&record[*i])))
+            .collect::<IndexMap<_, _>>();
+        match csv_target(self.target_type, record[columns.target].trim()) {
+            Ok(target) => dataset.push(DatasetItem {
+                input: Input::Object(input),
+                target,
+            }),
+            Err(message) => errors.push(RowError { index, message }),
+        }
+        Ok(())
+    }
+
+    fn finish(
+        &mut self,
+        dataset: &mut Dataset,
+        errors: &mut Vec<RowError>,
+    ) -> Result<(), DatasetError> {
+        if let Some(record) = self.reader.finish().map_err(csv_syntax_error)? {
+            self.record(record, dataset, errors)?;
+        }
+        if self.columns.is_none() {
+            return Err(DatasetError::Header("missing header row".to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn csv_input(cell: &str) -> Input {
+    serde_json::from_str(cell)
+        .unwrap_or_else(|_| Input::String(cell.to_string()))
+}
+
+fn csv_target(target_type: TargetType, cell: &str) -> Result<Target, String> {
+    match target_type {
+        TargetType::Scalar => cell
+            .parse()
+            .or_else(|_| rust_decimal::Decimal::from_scientific(cell))
+            .map(|value| Target::Scalar { value })
+            .map_err(|_| format!("invalid scalar target \"{}\"", cell)),
+        TargetType::Vector => serde_json::from_str(cell)
+            .map(|value| Target::Vector { value })
+            .map_err(|_| format!("invalid vector target \"{}\"", cell)),
+        TargetType::VectorWinner => cell
+            .parse()
+            .map(|value| Target::VectorWinner { value })
+            .map_err(|_| format!("invalid vector winner target \"{}\"", cell)),
+        TargetType::MultiLabel => serde_json::from_str(cell)
+            .map(|value| Target::MultiLabel { value })
+            .map_err(|_| format!("invalid multi-label target \"{}\"", cell)),
+        TargetType::Categorical if !cell.is_empty() => {
+            Ok(Target::Categorical {
+                value: cell.to_string(),
+            })
+        }
+        TargetType::Categorical => Err("empty categorical target".to_string()),
+    }
+}
+
+/// Splits RFC 4180 CSV content into records of unquoted fields, one
+/// character at a time.
+///
+/// Blank lines are skipped. On a syntax error, returns the index of the
+/// offending record, counting the header as record 0.
+#[derive(Debug, Default)]
+struct CsvReader {
+    record: Vec<String>,
+    field: String,
+    quoted: bool,
+    // a quote within a quoted field, either closing it or escaping a quote
+    quote: bool,
+    // the last record ended with a carriage return, so a line feed is skipped
+    cr: bool,
+    // complete records so far
+    records: usize,
+}
+
+impl CsvReader {
+    /// Reads a character, returning the record it completes, if any.
+    fn push(
+        &mut self,
+        c: char,
+    ) -> Result<Option<Vec<String>>, (usize, String)> {
+        let cr = std::mem::take(&mut self.cr);
+        if self.quote {
+            self.quote = false;
+            match c {
+                '"' => {
+                    self.field.push('"');
+                    return Ok(None);
+                }
+                ',' | '\n' | '\r' => self.quoted = false,
+                _ => {
+                    return Err((
+                        self.records,
+                        "unexpected character after closing quote".to_string(),
+                    ));
+                }
+            }
+        } else if self.quoted {
+            match c {
+                '"' => self.quote = true,
+                c => self.field.push(c),
+            }
+            return Ok(None);
+        }
+        match c {
+            '"' if self.field.is_empty() => self.quoted = true,
+            '"' => {
+                return Err((
+                    self.records,
+                    "unexpected quote in unquoted field".to_string(),
+                ));
+            }
+            ',' => self.record.push(std::mem::take(&mut self.field)),
+            '\n' if cr => {}
+            '\n' | '\r' => {
+                self.cr = c == '\r';
+                return Ok(self.end_record());
+            }
+            c => self.field.push(c),
+        }
+        Ok(None)
+    }
+
+    /// Ends the content, returning its last record, if any.
+    fn finish(&mut self) -> Result<Option<Vec<String>>, (usize, String)> {
+        if std::mem::take(&mut self.quote) {
+            self.quoted = false;
+        }
+        if self.quoted {
+            return Err((
+                self.records,
+                "unterminated quoted field".to_string(),
+            ));
+        }
+        Ok(self.end_record())
+    }
+
+    /// Ends the current record, skipping it if the line was blank.
+    fn end_record(&mut self) -> Option<Vec<String>> {
+        self.record.push(std::mem::take(&mut self.field));
+        if self.record.len() > 1 || !self.record[0].is_empty() {
+            self.records += 1;
+            Some(std::mem::take(&mut self.record))
+        } else {
+            self.record.clear();
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    fn csv_params(target_type: TargetType) -> DatasetCreateParams {
+        DatasetCreateParams {
+            format: DatasetFormat::Csv,
+            target_type: Some(target_type),
+            target_column: None,
+            input_columns: None,
+            fowner: None,
+            frepository: None,
+            fcommit: None,
+        }
+    }
+
+    fn csv_records(content: &str) -> Result<Vec<Vec<String>>, (usize, String)> {
+        let mut reader = CsvReader::default();
+        let mut records = Vec::new();
+        for c in content.chars() {
+            records.extend(reader.push(c)?);
+        }
+        records.extend(reader.finish()?);
+        Ok(records)
+    }
+
+    #[test]
+    fn test_csv_records() {
+        let records =
+            csv_records("a,b\r\n\"x, \"\"y\"\"\",\"line\nbreak\"\n\n1,\n")
+                .unwrap();
+        assert_eq!(
+            records,
+            vec![
+                vec!["a".to_string(), "b".to_string()],
+                vec!["x, \"y\"".to_string(), "line\nbreak".to_string()],
+                vec!["1".to_string(), String::new()],
+            ]
+        );
+        assert_eq!(csv_records("a\n\"b").unwrap_err().0, 1);
+        assert_eq!(csv_records("a\nb\"c").unwrap_err().0, 1);
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let dataset = parse_csv(
+            &csv_params(TargetType::Scalar),
+            "text,count,target\nhello,3,0.5\n\"[1,2]\",,1e-1\n",
+        )
+        .unwrap();
+        assert_eq!(dataset.len(), 2);
+        let Input::Object(input) = &dataset[0].input else {
+            panic!("expected object input");
+        };
+        assert!(
+            matches!(input.get("text"), Some(Input::String(s)) if s == "hello")
+        );
+        assert!(matches!(input.get("count"), Some(Input::Integer(3))));
+        let Input::Object(input) = &dataset[1].input else {
+            panic!("expected object input");
+        };
+        assert!(matches!(input.get("text"), Some(Input::Array(_))));
+        assert!(input.get("count").is_none());
+        assert!(matches!(
+            dataset[1].target,
+            Target::Scalar { value } if value == dec!(0.1)
+        ));
+    }
+
+    #[test]
+    fn test_parse_csv_columns() {
+        let params = DatasetCreateParams {
+            target_column: Some("label".to_string()),
+            input_columns: Some("a:first, b".to_string()),
+            ..csv_params(TargetType::Vector)
+        };
+        let dataset =
+            parse_csv(&params, "a,b,c,label\n1,2,3,\"[0.5,0.5]\"\n").unwrap();
+        let Input::Object(input) = &dataset[0].input else {
+            panic!("expected object input");
+        };
+        assert_eq!(input.keys().collect::<Vec<_>>(), vec!["first", "b"]);
+        assert!(
+            matches!(&dataset[0].target, Target::Vector { value } if value.len() == 2)
+        );
+
+        let params = DatasetCreateParams {
+            input_columns: Some("a:x,b:x".to_string()),
+            ..params
+        };
+        assert!(matches!(
+            parse_csv(&params, "a,b,label\n1,2,[1]\n"),
+            Err(DatasetError::Header(_))
+        ));
+        assert!(matches!(
+            parse_csv(&csv_params(TargetType::Scalar), "a,b\n1,2\n"),
+            Err(DatasetError::Header(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_csv_label_targets() {
+        let dataset = parse_csv(
+            &csv_params(TargetType::MultiLabel),
+            "a,target\n1,\"[\"\"x\"\", \"\"y\"\"]\"\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            &dataset[0].target,
+            Target::MultiLabel { value } if value.len() == 2
+        ));
+        let dataset = parse_csv(
+            &csv_params(TargetType::Categorical),
+            "a,target\n1,spam\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            &dataset[0].target,
+            Target::Categorical { value } if value == "spam"
+        ));
+    }
+
+    #[test]
+    fn test_row_errors() {
+        let error = parse_csv(
+            &csv_params(TargetType::VectorWinner),
+            "a,target\n1,0\n2,x\n3\n4,1\n",
+        )
+        .unwrap_err();
+        let DatasetError::Rows(rows) = error else {
+            panic!("expected row errors");
+        };
+        assert_eq!(
+            rows.iter().map(|row| row.index).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        let error = parse_jsonl(
+            "{\"input\":1,\"target\":{\"type\":\"scalar\",\"value\":1}}\n\n\
+             {\"input\":2}\n",
+        )
+        .unwrap_err();
+        let DatasetError::Rows(rows) = error else {
+            panic!("expected row errors");
+        };
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].index, 1);
+    }
+
+    #[test]
+    fn test_parser_chunks() {
+        let csv =
+            "\u{feff}text,target\r\ncafé,1\n\"naïve\r\n\"\"🙂\"\"\",0.5\n";
+        let jsonl = "\u{feff}{\"input\":\"é\",\"target\":{\"type\":\"scalar\",\"value\":1}}\r\n\n\
+                     {\"input\":\"🙂\",\"target\":{\"type\":\"scalar\",\"value\":0}}";
+        for (params, content) in [
+            (csv_params(TargetType::Scalar), csv),
+            (
+                DatasetCreateParams {
+                    format: DatasetFormat::Jsonl,
+                    ..csv_params(TargetType::Scalar)
+                },
+                jsonl,
+            ),
+        ] {
+            let whole = parse_dataset(&params, content).unwrap();
+            assert_eq!(whole.len(), 2);
+            // split into single bytes, including within characters
+            let mut parser = DatasetParser::new(&params).unwrap();
+            for byte in content.as_bytes() {
+                parser.push(std::slice::from_ref(byte));
+            }
+            let chunked = parser.finish().unwrap();
+            assert_eq!(
+                serde_json::to_value(&chunked).unwrap(),
+                serde_json::to_value(&whole).unwrap()
+            );
+        }
+
+        let dataset = parse_csv(&csv_params(TargetType::Scalar), csv).unwrap();
+        let Input::Object(input) = &dataset[1].input else {
+            panic!("expected object input");
+        };
+        assert!(
+            matches!(input.get("text"), Some(Input::String(s)) if s == "naïve\r\n\"🙂\"")
+        );
+
+        let mut parser =
+            DatasetParser::new(&csv_params(TargetType::Scalar)).unwrap();
+        parser.push(b"text,target\n\xff,1\n");
+        assert!(parser.failed());
+        assert_eq!(parser.finish().unwrap_err(), DatasetError::Encoding);
+        let mut parser =
+            DatasetParser::new(&csv_params(TargetType::Scalar)).unwrap();
+        parser.push("text,target\n1,é".as_bytes().split_last().unwrap().1);
+        assert_eq!(parser.finish().unwrap_err(), DatasetError::Encoding);
+    }
+}
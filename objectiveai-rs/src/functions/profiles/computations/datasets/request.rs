@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatasetFormat {
+    // one JSON `DatasetItem` per line
+    Jsonl,
+    // a header row, then one item per row
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetType {
+    // a decimal cell
+    Scalar,
+    // a JSON array of decimals
+    Vector,
+    // an integer index
+    VectorWinner,
+    // a JSON array of the labels that apply
+    MultiLabel,
+    // a category name
+    Categorical,
+}
+
+// sent as query parameters alongside the raw dataset body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetCreateParams {
+    pub format: DatasetFormat,
+    // csv only, the type of the target column
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_type: Option<TargetType>,
+    // csv only, the column holding the target, defaults to "target"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_column: Option<String>,
+    // csv only, comma-separated `column` or `column:field` input mappings
+    // defaults to every column other than the target, named after it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_columns: Option<String>,
+    // optional Function whose input schema every item is validated against
+    // at upload, reporting each invalid item
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fowner: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frepository: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fcommit: Option<String>,
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dataset {
+    // referenced by `dataset_id` in Profile computation requests
+    pub id: String,
+    pub created: u64,
+    // number of items in the dataset
+    pub len: usize,
+}
@@ -2,8 +2,11 @@
 //!
 //! Profile computations train a Profile by running a Function against a
 //! dataset of example inputs with expected outputs, optimizing the weights
-//! to minimize loss.
+//! to minimize loss. An optional validation split reports how well the
+//! trained Profile generalizes to items it was not fitted on. Large datasets
+//! can be uploaded ahead of time and referenced by ID.
 
+pub mod datasets;
 pub mod request;
 pub mod response;
 mod retry_token;
@@ -24,7 +24,23 @@ pub struct FunctionRemoteRequestBody {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_retries: Option<u64>,
     pub n: u64,
+    #[serde(default)]
     pub dataset: Vec<super::DatasetItem>,
+    // if present, an uploaded dataset used instead of `dataset`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dataset_id: Option<String>,
+    // if present, holds out part of the dataset to validate the profile
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation: Option<super::Validation>,
+    // loss functions to minimize
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loss: Option<super::Loss>,
+    // penalties on ensemble LLM weights
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub regularization: Option<super::Regularization>,
+    // cost budget or penalty on the expected cost per execution
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<super::Cost>,
     pub ensemble: vector::completions::request::Ensemble,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provider: Option<chat::completions::request::Provider>,
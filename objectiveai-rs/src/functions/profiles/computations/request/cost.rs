@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+// trades accuracy for the expected cost of each execution, dropping LLMs
+// whose votes are not worth their cost
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Cost {
+    // maximum expected cost per execution
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<rust_decimal::Decimal>,
+    // added to the objective per unit of expected cost per execution
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub penalty: Option<rust_decimal::Decimal>,
+}
@@ -15,4 +15,6 @@ pub enum Target {
     Scalar { value: rust_decimal::Decimal }, // desired scalar output
     Vector { value: Vec<rust_decimal::Decimal> }, // desired vector output
     VectorWinner { value: usize }, // desired winning index in vector completion
+    MultiLabel { value: Vec<String> }, // labels that apply, others are 0
+    Categorical { value: String }, // desired category
 }
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+// loss functions by target type, omitted targets use their default
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Loss {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scalar: Option<ScalarLoss>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector: Option<VectorLoss>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector_winner: Option<VectorWinnerLoss>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multi_label: Option<MultiLabelLoss>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub categorical: Option<CategoricalLoss>,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ScalarLoss {
+    #[default]
+    SquaredError,
+    AbsoluteError,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorLoss {
+    #[default]
+    SquaredError,
+    CrossEntropy,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorWinnerLoss {
+    #[default]
+    CrossEntropy,
+    // margin of 1 between the winner and the highest other score
+    Hinge,
+    // mean margin of 1 between the winner and each other score
+    Ranking,
+}
+
+// averaged over labels
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum MultiLabelLoss {
+    #[default]
+    BinaryCrossEntropy,
+    SquaredError,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum CategoricalLoss {
+    #[default]
+    CrossEntropy,
+    // margin of 1 between the category and the highest other score
+    Hinge,
+}
+
+// penalties on ensemble LLM weights, scaled so that the largest weight of
+// each task is 1, pushing unhelpful LLMs to zero
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Regularization {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1: Option<rust_decimal::Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l2: Option<rust_decimal::Decimal>,
+}
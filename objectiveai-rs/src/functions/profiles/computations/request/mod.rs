@@ -1,9 +1,15 @@
 mod body;
+mod cost;
 mod dataset;
+mod loss;
 mod path;
 mod request;
+mod validation;
 
 pub use body::*;
+pub use cost::*;
 pub use dataset::*;
+pub use loss::*;
 pub use path::*;
 pub use request::*;
+pub use validation::*;
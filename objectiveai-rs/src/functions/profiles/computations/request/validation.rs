@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Validation {
+    // validate against an explicit dataset, fitting on the whole `dataset`
+    Holdout { dataset: Vec<super::DatasetItem> },
+    // hold out this fraction of `dataset`, in (0, 1)
+    Fraction { fraction: rust_decimal::Decimal },
+    // split `dataset` into `k` folds, validating against each in turn
+    KFold { k: usize },
+}
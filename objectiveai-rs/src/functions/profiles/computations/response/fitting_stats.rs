@@ -3,6 +3,18 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
 pub struct FittingStats {
     pub loss: rust_decimal::Decimal,
+    // L1 penalty of the fitted weights
+    #[serde(default)]
+    pub l1: rust_decimal::Decimal,
+    // L2 penalty of the fitted weights
+    #[serde(default)]
+    pub l2: rust_decimal::Decimal,
+    // expected cost per execution of the fitted profile
+    #[serde(default)]
+    pub cost: rust_decimal::Decimal,
+    // loss plus penalties, which fitting minimizes
+    #[serde(default)]
+    pub objective: rust_decimal::Decimal,
     pub executions: usize,
     pub starts: usize,
     pub rounds: usize,
@@ -1,5 +1,7 @@
 mod fitting_stats;
 pub mod streaming;
 pub mod unary;
+mod validation;
 
 pub use fitting_stats::*;
+pub use validation::*;
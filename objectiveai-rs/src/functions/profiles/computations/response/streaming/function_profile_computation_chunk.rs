@@ -15,6 +15,8 @@ pub struct FunctionProfileComputationChunk {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fitting_stats: Option<response::FittingStats>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation: Option<response::ValidationReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub retry_token: Option<String>,
     pub created: u64,
     pub function: Option<String>,
@@ -37,6 +39,7 @@ impl FunctionProfileComputationChunk {
             executions_errors,
             profile,
             fitting_stats,
+            validation,
             retry_token,
             usage,
             ..
@@ -52,6 +55,9 @@ impl FunctionProfileComputationChunk {
         if let Some(fitting_stats) = fitting_stats {
             self.fitting_stats = Some(fitting_stats.clone());
         }
+        if let Some(validation) = validation {
+            self.validation = Some(validation.clone());
+        }
         if let Some(retry_token) = retry_token {
             self.retry_token = Some(retry_token.clone());
         }
@@ -11,6 +11,8 @@ pub struct FunctionProfileComputation {
     pub executions_errors: bool,
     pub profile: functions::InlineProfile,
     pub fitting_stats: response::FittingStats,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation: Option<response::ValidationReport>,
     pub retry_token: Option<String>,
     pub created: u64,
     pub function: Option<String>,
@@ -34,6 +36,7 @@ impl From<response::streaming::FunctionProfileComputationChunk>
             executions_errors,
             profile,
             fitting_stats,
+            validation,
             retry_token,
             created,
             function,
@@ -56,6 +59,7 @@ impl From<response::streaming::FunctionProfileComputationChunk>
             }),
             fitting_stats: fitting_stats
                 .unwrap_or(response::FittingStats::default()),
+            validation,
             retry_token,
             created,
             function,
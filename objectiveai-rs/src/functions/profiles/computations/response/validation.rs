@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub folds: Vec<ValidationFold>,
+    // mean of the folds' validation metrics
+    pub validation: ValidationMetrics,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationFold {
+    pub fold: usize,
+    pub train_items: usize,
+    pub validation_items: usize,
+    pub train: ValidationMetrics,
+    pub validation: ValidationMetrics,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+pub struct ValidationMetrics {
+    pub loss: rust_decimal::Decimal,
+    // fraction of `VectorWinner` and `Categorical` targets ranked first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accuracy: Option<rust_decimal::Decimal>,
+    // expected calibration error over `Scalar` and `MultiLabel` targets,
+    // each label counting as a prediction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calibration_error: Option<rust_decimal::Decimal>,
+}
@@ -0,0 +1,110 @@
+//! Prompts of the reasoning summaries of Function executions.
+
+use crate::chat;
+use serde::{Deserialize, Serialize};
+
+/// How the reasoning summary of a Function execution is prompted.
+///
+/// The template is rendered into a single user message, with these
+/// placeholders replaced:
+/// - `{{description}}` - the Function's description
+/// - `{{input}}` - the input, including any media
+/// - `{{output}}` - the Function's output
+/// - `{{assertions}}` - the responses voted for, with their confidence
+///
+/// With a schema, the summary is generated as JSON conforming to it, and is
+/// also returned parsed, as the summary's `data`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReasoningSummaryPrompt {
+    /// The prompt template. If omitted, a default prompt is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// JSON schema of structured summaries, such as a rationale for each
+    /// criterion or quotes of key evidence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<chat::completions::request::JsonSchema>,
+}
+
+/// A segment of a reasoning summary prompt template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReasoningSummarySegment<'a> {
+    /// Literal text.
+    Text(&'a str),
+    /// The `{{description}}` placeholder.
+    Description,
+    /// The `{{input}}` placeholder.
+    Input,
+    /// The `{{output}}` placeholder.
+    Output,
+    /// The `{{assertions}}` placeholder.
+    Assertions,
+}
+
+impl ReasoningSummaryPrompt {
+    /// Splits a template into literal text and placeholders.
+    ///
+    /// Unknown placeholders are kept as literal text.
+    pub fn segments(template: &str) -> Vec<ReasoningSummarySegment<'_>> {
+        let mut segments = Vec::new();
+        let mut text_start = 0;
+        let mut rest_start = 0;
+        while let Some(open) = template[rest_start..].find("{{") {
+            let open = rest_start + open;
+            let Some(close) = template[open..].find("}}") else {
+                break;
+            };
+            let close = open + close;
+            let placeholder = match template[open + 2..close].trim() {
+                "description" => ReasoningSummarySegment::Description,
+                "input" => ReasoningSummarySegment::Input,
+                "output" => ReasoningSummarySegment::Output,
+                "assertions" => ReasoningSummarySegment::Assertions,
+                _ => {
+                    rest_start = open + 2;
+                    continue;
+                }
+            };
+            if open > text_start {
+                segments.push(ReasoningSummarySegment::Text(
+                    &template[text_start..open],
+                ));
+            }
+            segments.push(placeholder);
+            text_start = close + 2;
+            rest_start = text_start;
+        }
+        if text_start < template.len() {
+            segments
+                .push(ReasoningSummarySegment::Text(&template[text_start..]));
+        }
+        segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments() {
+        use ReasoningSummarySegment::*;
+        assert_eq!(
+            ReasoningSummaryPrompt::segments(
+                "Explain {{ output }} for {{input}}:\n{{assertions}}{{other}}"
+            ),
+            vec![
+                Text("Explain "),
+                Output,
+                Text(" for "),
+                Input,
+                Text(":\n"),
+                Assertions,
+                Text("{{other}}"),
+            ],
+        );
+        assert_eq!(
+            ReasoningSummaryPrompt::segments("{{description}} {{"),
+            vec![Description, Text(" {{")],
+        );
+    }
+}
@@ -1,8 +1,29 @@
 //! Task types for Function definitions.
 //!
 //! Tasks are the building blocks of Functions. Each task either calls another
-//! Function or runs a Vector Completion. Tasks can be conditionally skipped
-//! or mapped over arrays of inputs.
+//! Function, runs a Vector Completion or runs a Chat Completion. Tasks can be
+//! conditionally skipped or mapped over arrays of inputs.
+//!
+//! # Chat Completion Tasks
+//!
+//! Chat completion tasks run before all other tasks of their Function. They
+//! do not produce a score; instead, their generated text (or parsed JSON, if
+//! a JSON response format is requested) is exposed to the expressions of the
+//! remaining tasks as `tasks[i].output`, where `i` is the chat completion
+//! task's index. This makes a Function a two-stage pipeline: extract or
+//! generate with a chat completion, then score with the other tasks.
+//!
+//! # Task Dependencies
+//!
+//! A task may declare `dependencies` on earlier tasks of its Function. Its
+//! `skip` and task field expressions then receive the outputs of those tasks
+//! as `tasks[i].output`, and the task only runs once they have finished. A
+//! skipped or failed dependency has a `null` entry. Dependencies on chat
+//! completion tasks are always satisfied, since those run first. This allows
+//! gating, e.g. only running an expensive judge if a cheap filter passes.
+//!
+//! The `output` of a dependency is its raw output, one of the variants
+//! listed below.
 //!
 //! # Output Expressions
 //!
@@ -23,7 +44,8 @@
 //!
 //! The function's final output is computed as a **weighted average** of all task outputs
 //! using profile weights. If a function has only one task, that task's output becomes
-//! the function's output directly (with weight 1.0).
+//! the function's output directly (with weight 1.0). A function with its own `output`
+//! expression combines the task outputs itself instead.
 
 use crate::chat;
 use serde::{Deserialize, Serialize};
@@ -42,6 +64,8 @@ pub enum TaskExpression {
     VectorFunction(VectorFunctionTaskExpression),
     #[serde(rename = "vector.completion")]
     VectorCompletion(VectorCompletionTaskExpression),
+    #[serde(rename = "chat.completion")]
+    ChatCompletion(ChatCompletionTaskExpression),
 }
 
 impl TaskExpression {
@@ -51,6 +75,7 @@ impl TaskExpression {
             TaskExpression::ScalarFunction(task) => task.skip.take(),
             TaskExpression::VectorFunction(task) => task.skip.take(),
             TaskExpression::VectorCompletion(task) => task.skip.take(),
+            TaskExpression::ChatCompletion(task) => task.skip.take(),
         }
     }
 
@@ -60,7 +85,30 @@ impl TaskExpression {
             TaskExpression::ScalarFunction(task) => task.map,
             TaskExpression::VectorFunction(task) => task.map,
             TaskExpression::VectorCompletion(task) => task.map,
+            TaskExpression::ChatCompletion(_) => None,
+        }
+    }
+
+    /// Returns the indices of the tasks this task depends on.
+    pub fn dependencies(&self) -> &[u64] {
+        match self {
+            TaskExpression::ScalarFunction(task) => {
+                task.dependencies.as_deref()
+            }
+            TaskExpression::VectorFunction(task) => {
+                task.dependencies.as_deref()
+            }
+            TaskExpression::VectorCompletion(task) => {
+                task.dependencies.as_deref()
+            }
+            TaskExpression::ChatCompletion(_) => None,
         }
+        .unwrap_or_default()
+    }
+
+    /// Returns `true` if this is a chat completion task.
+    pub fn is_chat_completion(&self) -> bool {
+        matches!(self, TaskExpression::ChatCompletion(_))
     }
 
     /// Compiles the expression into a concrete [`Task`].
@@ -78,6 +126,9 @@ impl TaskExpression {
             TaskExpression::VectorCompletion(task) => {
                 task.compile(params).map(Task::VectorCompletion)
             }
+            TaskExpression::ChatCompletion(task) => {
+                task.compile(params).map(Task::ChatCompletion)
+            }
         }
     }
 }
@@ -98,27 +149,35 @@ pub enum Task {
     /// Runs a vector completion.
     #[serde(rename = "vector.completion")]
     VectorCompletion(VectorCompletionTask),
+    /// Runs a chat completion whose output is exposed to later tasks.
+    #[serde(rename = "chat.completion")]
+    ChatCompletion(ChatCompletionTask),
 }
 
 impl Task {
+    /// Compiles the task's `output` expression against its raw output.
+    ///
+    /// Returns `None` for chat completion tasks, which have no `output`
+    /// expression and do not contribute to the Function's output.
     pub fn compile_output(
         &self,
         input: &super::expression::Input,
         raw_output: super::expression::TaskOutput,
     ) -> Result<
-        super::expression::FunctionOutput,
+        Option<super::expression::FunctionOutput>,
         super::expression::ExpressionError,
     > {
         match self {
             Task::ScalarFunction(task) => {
-                task.compile_output(input, raw_output)
+                task.compile_output(input, raw_output).map(Some)
             }
             Task::VectorFunction(task) => {
-                task.compile_output(input, raw_output)
+                task.compile_output(input, raw_output).map(Some)
             }
             Task::VectorCompletion(task) => {
-                task.compile_output(input, raw_output)
+                task.compile_output(input, raw_output).map(Some)
             }
+            Task::ChatCompletion(_) => Ok(None),
         }
     }
 }
@@ -133,7 +192,8 @@ pub struct ScalarFunctionTaskExpression {
     /// Git commit SHA for the function version.
     pub commit: String,
 
-    /// If this expression evaluates to true, skip the task. Receives: `input`.
+    /// If this expression evaluates to true, skip the task. Receives:
+    /// `input`, `tasks`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub skip: Option<super::expression::Expression>,
 
@@ -142,8 +202,14 @@ pub struct ScalarFunctionTaskExpression {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub map: Option<u64>,
 
+    /// Indices of earlier tasks whose outputs this task reads as
+    /// `tasks[i].output`. Mapped tasks may only depend on chat completion
+    /// tasks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<Vec<u64>>,
+
     /// Expression for the input to pass to the function.
-    /// Receives: `input`, `map` (if mapped).
+    /// Receives: `input`, `map` (if mapped), `tasks`.
     pub input:
         super::expression::WithExpression<super::expression::InputExpression>,
 
@@ -179,7 +245,13 @@ impl ScalarFunctionTaskExpression {
         self,
         params: &super::expression::Params,
     ) -> Result<ScalarFunctionTask, super::expression::ExpressionError> {
-        let input = self.input.compile_one(params)?.compile(params)?;
+        let input = self
+            .input
+            .compile_one(params)
+            .and_then(|input| input.compile(params))
+            .map_err(|e| {
+                e.with_field(super::expression::ExpressionField::Input)
+            })?;
         Ok(ScalarFunctionTask {
             owner: self.owner,
             repository: self.repository,
@@ -228,8 +300,12 @@ impl ScalarFunctionTask {
                 input,
                 output: Some(raw_output),
                 map: None,
+                tasks: None,
             });
-        let compiled_output = self.output.compile_one(&params)?;
+        let compiled_output =
+            self.output.compile_one(&params).map_err(|e| {
+                e.with_field(super::expression::ExpressionField::Output)
+            })?;
         Ok(compiled_output)
     }
 }
@@ -244,7 +320,8 @@ pub struct VectorFunctionTaskExpression {
     /// Git commit SHA for the function version.
     pub commit: String,
 
-    /// If this expression evaluates to true, skip the task. Receives: `input`.
+    /// If this expression evaluates to true, skip the task. Receives:
+    /// `input`, `tasks`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub skip: Option<super::expression::Expression>,
 
@@ -253,8 +330,14 @@ pub struct VectorFunctionTaskExpression {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub map: Option<u64>,
 
+    /// Indices of earlier tasks whose outputs this task reads as
+    /// `tasks[i].output`. Mapped tasks may only depend on chat completion
+    /// tasks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<Vec<u64>>,
+
     /// Expression for the input to pass to the function.
-    /// Receives: `input`, `map` (if mapped).
+    /// Receives: `input`, `map` (if mapped), `tasks`.
     pub input:
         super::expression::WithExpression<super::expression::InputExpression>,
 
@@ -290,7 +373,13 @@ impl VectorFunctionTaskExpression {
         self,
         params: &super::expression::Params,
     ) -> Result<VectorFunctionTask, super::expression::ExpressionError> {
-        let input = self.input.compile_one(params)?.compile(params)?;
+        let input = self
+            .input
+            .compile_one(params)
+            .and_then(|input| input.compile(params))
+            .map_err(|e| {
+                e.with_field(super::expression::ExpressionField::Input)
+            })?;
         Ok(VectorFunctionTask {
             owner: self.owner,
             repository: self.repository,
@@ -339,8 +428,12 @@ impl VectorFunctionTask {
                 input,
                 output: Some(raw_output),
                 map: None,
+                tasks: None,
             });
-        let compiled_output = self.output.compile_one(&params)?;
+        let compiled_output =
+            self.output.compile_one(&params).map_err(|e| {
+                e.with_field(super::expression::ExpressionField::Output)
+            })?;
         Ok(compiled_output)
     }
 }
@@ -348,7 +441,8 @@ impl VectorFunctionTask {
 /// Expression for a task that runs a vector completion (pre-compilation).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VectorCompletionTaskExpression {
-    /// If this expression evaluates to true, skip the task. Receives: `input`.
+    /// If this expression evaluates to true, skip the task. Receives:
+    /// `input`, `tasks`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub skip: Option<super::expression::Expression>,
 
@@ -357,8 +451,14 @@ pub struct VectorCompletionTaskExpression {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub map: Option<u64>,
 
+    /// Indices of earlier tasks whose outputs this task reads as
+    /// `tasks[i].output`. Mapped tasks may only depend on chat completion
+    /// tasks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<Vec<u64>>,
+
     /// Expression for the conversation messages (the prompt).
-    /// Receives: `input`, `map` (if mapped).
+    /// Receives: `input`, `map` (if mapped), `tasks`.
     pub messages: super::expression::WithExpression<
         Vec<
             super::expression::WithExpression<
@@ -367,7 +467,7 @@ pub struct VectorCompletionTaskExpression {
         >,
     >,
     /// Expression for tools available to the completion (read-only context).
-    /// Receives: `input`, `map` (if mapped).
+    /// Receives: `input`, `map` (if mapped), `tasks`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<
         super::expression::WithExpression<
@@ -381,7 +481,7 @@ pub struct VectorCompletionTaskExpression {
         >,
     >,
     /// Expression for the possible responses the LLMs can vote for.
-    /// Receives: `input`, `map` (if mapped).
+    /// Receives: `input`, `map` (if mapped), `tasks`.
     pub responses: super::expression::WithExpression<
         Vec<
             super::expression::WithExpression<
@@ -420,26 +520,38 @@ impl VectorCompletionTaskExpression {
         params: &super::expression::Params,
     ) -> Result<VectorCompletionTask, super::expression::ExpressionError> {
         // compile messages
-        let messages = self.messages.compile_one(params)?;
+        let messages_err = |e: super::expression::ExpressionError| {
+            e.with_field(super::expression::ExpressionField::Messages)
+        };
+        let messages =
+            self.messages.compile_one(params).map_err(messages_err)?;
         let mut compiled_messages = Vec::with_capacity(messages.len());
         for message in messages {
-            match message.compile_one_or_many(params)? {
+            match message.compile_one_or_many(params).map_err(messages_err)? {
                 super::expression::OneOrMany::One(one_message) => {
-                    compiled_messages.push(one_message.compile(params)?);
+                    compiled_messages.push(
+                        one_message.compile(params).map_err(messages_err)?,
+                    );
                 }
                 super::expression::OneOrMany::Many(many_messages) => {
                     for message in many_messages {
-                        compiled_messages.push(message.compile(params)?);
+                        compiled_messages.push(
+                            message.compile(params).map_err(messages_err)?,
+                        );
                     }
                 }
             }
         }
 
         // compile tools
+        let tools_err = |e: super::expression::ExpressionError| {
+            e.with_field(super::expression::ExpressionField::Tools)
+        };
         let tools = self
             .tools
             .map(|tools| tools.compile_one(params))
-            .transpose()?
+            .transpose()
+            .map_err(tools_err)?
             .flatten()
             .map(|tools| {
                 let mut compiled_tools = Vec::with_capacity(tools.len());
@@ -457,19 +569,31 @@ impl VectorCompletionTaskExpression {
                 }
                 Ok::<_, super::expression::ExpressionError>(compiled_tools)
             })
-            .transpose()?;
+            .transpose()
+            .map_err(tools_err)?;
 
         // compile responses
-        let responses = self.responses.compile_one(params)?;
+        let responses_err = |e: super::expression::ExpressionError| {
+            e.with_field(super::expression::ExpressionField::Responses)
+        };
+        let responses =
+            self.responses.compile_one(params).map_err(responses_err)?;
         let mut compiled_responses = Vec::with_capacity(responses.len());
         for response in responses {
-            match response.compile_one_or_many(params)? {
+            match response
+                .compile_one_or_many(params)
+                .map_err(responses_err)?
+            {
                 super::expression::OneOrMany::One(one_response) => {
-                    compiled_responses.push(one_response.compile(params)?);
+                    compiled_responses.push(
+                        one_response.compile(params).map_err(responses_err)?,
+                    );
                 }
                 super::expression::OneOrMany::Many(many_responses) => {
                     for response in many_responses {
-                        compiled_responses.push(response.compile(params)?);
+                        compiled_responses.push(
+                            response.compile(params).map_err(responses_err)?,
+                        );
                     }
                 }
             }
@@ -522,12 +646,117 @@ impl VectorCompletionTask {
                 input,
                 output: Some(raw_output),
                 map: None,
+                tasks: None,
             });
-        let compiled_output = self.output.compile_one(&params)?;
+        let compiled_output =
+            self.output.compile_one(&params).map_err(|e| {
+                e.with_field(super::expression::ExpressionField::Output)
+            })?;
         Ok(compiled_output)
     }
 }
 
+/// Expression for a task that runs a chat completion (pre-compilation).
+///
+/// The model is supplied by the Profile. The task's output is exposed to the
+/// expressions of the Function's other tasks as `tasks[i].output`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatCompletionTaskExpression {
+    /// If this expression evaluates to true, skip the task. Receives: `input`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip: Option<super::expression::Expression>,
+
+    /// Expression for the conversation messages (the prompt).
+    /// Receives: `input`.
+    pub messages: super::expression::WithExpression<
+        Vec<
+            super::expression::WithExpression<
+                chat::completions::request::MessageExpression,
+            >,
+        >,
+    >,
+
+    /// The response format of the completion. With a `json_schema` or
+    /// `json_object` format, the generated content is parsed as JSON before
+    /// it is exposed to other tasks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<chat::completions::request::ResponseFormat>,
+}
+
+impl ChatCompletionTaskExpression {
+    /// Compiles the expression into a concrete [`ChatCompletionTask`].
+    pub fn compile(
+        self,
+        params: &super::expression::Params,
+    ) -> Result<ChatCompletionTask, super::expression::ExpressionError> {
+        // compile messages
+        let messages_err = |e: super::expression::ExpressionError| {
+            e.with_field(super::expression::ExpressionField::Messages)
+        };
+        let messages =
+            self.messages.compile_one(params).map_err(messages_err)?;
+        let mut compiled_messages = Vec::with_capacity(messages.len());
+        for message in messages {
+            match message.compile_one_or_many(params).map_err(messages_err)? {
+                super::expression::OneOrMany::One(one_message) => {
+                    compiled_messages.push(
+                        one_message.compile(params).map_err(messages_err)?,
+                    );
+                }
+                super::expression::OneOrMany::Many(many_messages) => {
+                    for message in many_messages {
+                        compiled_messages.push(
+                            message.compile(params).map_err(messages_err)?,
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(ChatCompletionTask {
+            messages: compiled_messages,
+            response_format: self.response_format,
+        })
+    }
+}
+
+/// A compiled chat completion task ready for execution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatCompletionTask {
+    /// The resolved conversation messages.
+    pub messages: Vec<chat::completions::request::Message>,
+    /// The response format of the completion.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<chat::completions::request::ResponseFormat>,
+}
+
+impl ChatCompletionTask {
+    /// Returns `true` if the generated content should be parsed as JSON.
+    pub fn is_json(&self) -> bool {
+        matches!(
+            self.response_format,
+            Some(chat::completions::request::ResponseFormat::JsonObject)
+                | Some(
+                    chat::completions::request::ResponseFormat::JsonSchema { .. }
+                )
+        )
+    }
+
+    /// Converts generated content into the output exposed to other tasks.
+    ///
+    /// JSON response formats are parsed; content that fails to parse is
+    /// exposed as a string.
+    pub fn output(&self, content: String) -> serde_json::Value {
+        if self.is_json()
+            && let Ok(value) = serde_json::from_str(&content)
+        {
+            value
+        } else {
+            serde_json::Value::String(content)
+        }
+    }
+}
+
 /// The result of compiling a task expression.
 ///
 /// Tasks without a `map` field compile to a single task. Tasks with a `map`
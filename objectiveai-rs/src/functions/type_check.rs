@@ -0,0 +1,1148 @@
+//! Static type-checking of Function expressions against the input schema.
+//!
+//! [`RemoteFunction::type_check`] reports problems that would otherwise only
+//! surface when the Function is executed. It runs two passes:
+//!
+//! - **Static pass** - every JMESPath and Starlark expression in the Function
+//!   is parsed. JMESPath field paths rooted at `input` are resolved against
+//!   `input_schema`, and calls to unknown functions are reported for both
+//!   dialects.
+//! - **Witness pass** - representative inputs ("witnesses") are generated
+//!   from `input_schema`, once with every property present and once with
+//!   only the required properties. Each witness is compiled the way an
+//!   execution would compile it: `input_maps`, then each task's `skip`,
+//!   `map`, task fields and `output` (against a synthetic task output), then
+//!   `output_length`, `input_split` and `input_merge` for vector Functions.
+//!   Tasks see the synthetic outputs of their `dependencies` as
+//!   `tasks[i].output`. The Function's own `output`, if any, is compiled
+//!   against the resulting task outputs. Evaluation errors and results of the
+//!   wrong type are reported.
+//!
+//! Task `dependencies` that do not refer to an earlier task, mapped tasks
+//! with dependencies, and empty or duplicate `labels` / `categories` are
+//! reported as well.
+
+use serde::{Deserialize, Serialize};
+
+/// A problem found while type-checking a Function.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeCheckIssue {
+    /// Location of the offending expression, e.g. `tasks[1].output`.
+    pub path: String,
+    /// The category of the problem.
+    pub kind: TypeCheckIssueKind,
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+impl std::fmt::Display for TypeCheckIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// The category of a [`TypeCheckIssue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeCheckIssueKind {
+    /// The expression could not be parsed.
+    InvalidSyntax,
+    /// The expression references a field that `input_schema` does not define.
+    MissingField,
+    /// The expression calls a function or references a name that does not exist.
+    UnknownFunction,
+    /// The expression returns a value of the wrong type.
+    WrongType,
+    /// The expression failed to evaluate against a witness input.
+    EvaluationError,
+}
+
+/// Maximum number of `anyOf` / `enum` choices explored when generating
+/// witness inputs.
+const MAX_WITNESS_CHOICES: usize = 4;
+
+impl super::RemoteFunction {
+    /// Type-checks every expression in the function against its `input_schema`.
+    ///
+    /// Returns an empty vector if no problems were found. See the
+    /// [module documentation](self) for the checks performed.
+    pub fn type_check(&self) -> Vec<TypeCheckIssue> {
+        let mut issues = Issues::default();
+
+        // static pass
+        match serde_json::to_value(self) {
+            Ok(value) => {
+                let mut path = String::new();
+                check_static(
+                    &value,
+                    &mut path,
+                    self.input_schema(),
+                    &mut issues,
+                );
+            }
+            Err(e) => issues.push(
+                String::new(),
+                TypeCheckIssueKind::InvalidSyntax,
+                e.to_string(),
+            ),
+        }
+
+        // dependencies
+        for (i, task) in self.tasks().iter().enumerate() {
+            let path = format!("tasks[{}].dependencies", i);
+            for &dependency in task.dependencies() {
+                if dependency as usize >= i {
+                    issues.push(
+                        path.clone(),
+                        TypeCheckIssueKind::WrongType,
+                        format!("tasks[{}] is not an earlier task", dependency),
+                    );
+                }
+            }
+            let dependent = task.dependencies().iter().any(|&dependency| {
+                self.tasks()
+                    .get(dependency as usize)
+                    .is_none_or(|task| !task.is_chat_completion())
+            });
+            if task.input_map().is_some() && dependent {
+                issues.push(
+                    path,
+                    TypeCheckIssueKind::WrongType,
+                    "mapped tasks cannot have dependencies",
+                );
+            }
+        }
+
+        // labels or categories
+        if let Err(message) =
+            super::Function::Remote(self.clone()).validate_labels()
+        {
+            let path = match self {
+                super::RemoteFunction::Categorical { .. } => "categories",
+                _ => "labels",
+            };
+            issues.push(path, TypeCheckIssueKind::WrongType, message);
+        }
+
+        // witness pass
+        for witness in witnesses(self.input_schema()) {
+            check_witness(self, &witness, &mut issues);
+        }
+
+        issues.0
+    }
+}
+
+/// Deduplicated list of issues.
+#[derive(Default)]
+struct Issues(Vec<TypeCheckIssue>);
+
+impl Issues {
+    fn push(
+        &mut self,
+        path: impl Into<String>,
+        kind: TypeCheckIssueKind,
+        message: impl Into<String>,
+    ) {
+        let issue = TypeCheckIssue {
+            path: path.into(),
+            kind,
+            message: message.into(),
+        };
+        if !self.0.contains(&issue) {
+            self.0.push(issue);
+        }
+    }
+
+    fn push_expression_error(
+        &mut self,
+        path: impl Into<String>,
+        error: super::expression::ExpressionError,
+    ) {
+        use super::expression::ExpressionError;
+        let kind = match error.inner() {
+            ExpressionError::StarlarkParseError(_) => {
+                TypeCheckIssueKind::InvalidSyntax
+            }
+            ExpressionError::DeserializationError(_)
+            | ExpressionError::ExpectedOneValueFoundMany => {
+                TypeCheckIssueKind::WrongType
+            }
+            _ => TypeCheckIssueKind::EvaluationError,
+        };
+        self.push(path, kind, error.to_string());
+    }
+}
+
+// ==================== STATIC PASS ====================
+
+/// Walks the serialized function, checking every `{"$jmespath": ...}` and
+/// `{"$starlark": ...}` object found.
+fn check_static(
+    value: &serde_json::Value,
+    path: &mut String,
+    input_schema: &super::expression::InputSchema,
+    issues: &mut Issues,
+) {
+    match value {
+        serde_json::Value::Object(map) => {
+            if map.len() == 1
+                && let Some((key, serde_json::Value::String(source))) =
+                    map.iter().next()
+            {
+                match key.as_str() {
+                    "$jmespath" => {
+                        return check_jmespath(
+                            source,
+                            path,
+                            input_schema,
+                            issues,
+                        );
+                    }
+                    "$starlark" => {
+                        return check_starlark(source, path, issues);
+                    }
+                    _ => {}
+                }
+            }
+            for (key, value) in map {
+                // the schema itself contains no expressions
+                if path.is_empty() && key == "input_schema" {
+                    continue;
+                }
+                let len = path.len();
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+                check_static(value, path, input_schema, issues);
+                path.truncate(len);
+            }
+        }
+        serde_json::Value::Array(array) => {
+            for (i, value) in array.iter().enumerate() {
+                let len = path.len();
+                path.push_str(&format!("[{}]", i));
+                check_static(value, path, input_schema, issues);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_starlark(source: &str, path: &str, issues: &mut Issues) {
+    match super::expression::starlark_undefined_names(source) {
+        Ok(names) => {
+            for name in names {
+                issues.push(
+                    path,
+                    TypeCheckIssueKind::UnknownFunction,
+                    format!("`{}` is not defined", name),
+                );
+            }
+        }
+        Err(e) => issues.push_expression_error(path, e),
+    }
+}
+
+fn check_jmespath(
+    source: &str,
+    path: &str,
+    input_schema: &super::expression::InputSchema,
+    issues: &mut Issues,
+) {
+    match super::expression::jmespath_expression(source) {
+        Ok(expr) => {
+            check_jmespath_ast(expr.as_ast(), true, path, input_schema, issues)
+        }
+        Err(e) => {
+            issues.push(path, TypeCheckIssueKind::InvalidSyntax, e.to_string())
+        }
+    }
+}
+
+/// A segment of a JMESPath field path.
+enum Segment<'a> {
+    Field(&'a str),
+    Index,
+}
+
+/// Resolves a pure chain of fields and indices, e.g. `input.items[0].name`.
+fn jmespath_chain<'a>(
+    ast: &'a jmespath::ast::Ast,
+    segments: &mut Vec<Segment<'a>>,
+) -> bool {
+    use jmespath::ast::Ast;
+    match ast {
+        Ast::Field { name, .. } => {
+            segments.push(Segment::Field(name));
+            true
+        }
+        Ast::Index { .. } => {
+            segments.push(Segment::Index);
+            true
+        }
+        Ast::Subexpr { lhs, rhs, .. } => {
+            jmespath_chain(lhs, segments) && jmespath_chain(rhs, segments)
+        }
+        _ => false,
+    }
+}
+
+/// Checks a JMESPath AST node. `root` is true if the node is evaluated
+/// against the root params object rather than a projected element.
+fn check_jmespath_ast(
+    ast: &jmespath::ast::Ast,
+    root: bool,
+    path: &str,
+    input_schema: &super::expression::InputSchema,
+    issues: &mut Issues,
+) {
+    use jmespath::ast::Ast;
+    match ast {
+        Ast::Field { .. } | Ast::Index { .. } | Ast::Subexpr { .. } => {
+            let mut segments = Vec::new();
+            if jmespath_chain(ast, &mut segments) {
+                if root && let Some(Segment::Field(first)) = segments.first() {
+                    check_jmespath_chain(
+                        first,
+                        &segments[1..],
+                        path,
+                        input_schema,
+                        issues,
+                    );
+                }
+            } else if let Ast::Subexpr { lhs, rhs, .. } = ast {
+                check_jmespath_ast(lhs, root, path, input_schema, issues);
+                check_jmespath_ast(rhs, false, path, input_schema, issues);
+            }
+        }
+        Ast::Function { name, args, .. } => {
+            if super::expression::JMESPATH_RUNTIME
+                .get_function(name)
+                .is_none()
+            {
+                issues.push(
+                    path,
+                    TypeCheckIssueKind::UnknownFunction,
+                    format!("unknown function `{}`", name),
+                );
+            }
+            for arg in args {
+                check_jmespath_ast(arg, root, path, input_schema, issues);
+            }
+        }
+        Ast::Projection { lhs, rhs, .. } => {
+            check_jmespath_ast(lhs, root, path, input_schema, issues);
+            check_jmespath_ast(rhs, false, path, input_schema, issues);
+        }
+        Ast::Condition {
+            predicate, then, ..
+        } => {
+            check_jmespath_ast(predicate, false, path, input_schema, issues);
+            check_jmespath_ast(then, false, path, input_schema, issues);
+        }
+        Ast::Comparison { lhs, rhs, .. }
+        | Ast::And { lhs, rhs, .. }
+        | Ast::Or { lhs, rhs, .. } => {
+            check_jmespath_ast(lhs, root, path, input_schema, issues);
+            check_jmespath_ast(rhs, root, path, input_schema, issues);
+        }
+        Ast::Not { node, .. }
+        | Ast::Flatten { node, .. }
+        | Ast::ObjectValues { node, .. } => {
+            check_jmespath_ast(node, root, path, input_schema, issues);
+        }
+        Ast::Expref { ast, .. } => {
+            check_jmespath_ast(ast, false, path, input_schema, issues);
+        }
+        Ast::MultiList { elements, .. } => {
+            for element in elements {
+                check_jmespath_ast(element, root, path, input_schema, issues);
+            }
+        }
+        Ast::MultiHash { elements, .. } => {
+            for element in elements {
+                check_jmespath_ast(
+                    &element.value,
+                    root,
+                    path,
+                    input_schema,
+                    issues,
+                );
+            }
+        }
+        Ast::Identity { .. } | Ast::Literal { .. } | Ast::Slice { .. } => {}
+    }
+}
+
+/// Checks a field chain evaluated against the root params object.
+fn check_jmespath_chain(
+    first: &str,
+    rest: &[Segment],
+    path: &str,
+    input_schema: &super::expression::InputSchema,
+    issues: &mut Issues,
+) {
+    match first {
+        "input" => {
+            let mut field_path = String::from("input");
+            let mut schemas = vec![input_schema];
+            for segment in rest {
+                schemas = schemas
+                    .into_iter()
+                    .flat_map(|schema| child_schemas(schema, segment))
+                    .collect();
+                match segment {
+                    Segment::Field(name) => {
+                        field_path.push('.');
+                        field_path.push_str(name);
+                    }
+                    Segment::Index => field_path.push_str("[]"),
+                }
+                if schemas.is_empty() {
+                    issues.push(
+                        path,
+                        TypeCheckIssueKind::MissingField,
+                        format!(
+                            "`{}` is not defined by input_schema",
+                            field_path
+                        ),
+                    );
+                    return;
+                }
+            }
+        }
+        // shapes of `output`, `map` and `tasks` depend on the task
+        "output" | "map" | "tasks" => {}
+        other => issues.push(
+            path,
+            TypeCheckIssueKind::MissingField,
+            format!(
+                "`{}` is not defined; expressions receive `input`, `output`, `map` and `tasks`",
+                other
+            ),
+        ),
+    }
+}
+
+/// Returns the schemas reachable from `schema` through `segment`.
+fn child_schemas<'a>(
+    schema: &'a super::expression::InputSchema,
+    segment: &Segment,
+) -> Vec<&'a super::expression::InputSchema> {
+    use super::expression::InputSchema;
+    match (schema, segment) {
+        (InputSchema::AnyOf(any_of), _) => any_of
+            .any_of
+            .iter()
+            .flat_map(|schema| child_schemas(schema, segment))
+            .collect(),
+        (InputSchema::Object(object), Segment::Field(name)) => {
+            object.properties.get(*name).into_iter().collect()
+        }
+        (InputSchema::Array(array), Segment::Index) => vec![&array.items],
+        _ => Vec::new(),
+    }
+}
+
+// ==================== WITNESS PASS ====================
+
+/// Generates representative inputs for `schema`.
+fn witnesses(
+    schema: &super::expression::InputSchema,
+) -> Vec<super::expression::Input> {
+    let mut witnesses = Vec::new();
+    for choice in 0..MAX_WITNESS_CHOICES.min(max_choices(schema)) {
+        for required_only in [false, true] {
+            let witness = witness(schema, required_only, choice);
+            if !witnesses.iter().any(|w| inputs_eq(w, &witness)) {
+                witnesses.push(witness);
+            }
+        }
+    }
+    witnesses
+}
+
+fn inputs_eq(
+    a: &super::expression::Input,
+    b: &super::expression::Input,
+) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Returns the largest number of `anyOf` or `enum` choices in `schema`.
+fn max_choices(schema: &super::expression::InputSchema) -> usize {
+    use super::expression::InputSchema;
+    match schema {
+        InputSchema::Object(object) => object
+            .properties
+            .values()
+            .map(max_choices)
+            .max()
+            .unwrap_or(1),
+        InputSchema::Array(array) => max_choices(&array.items),
+        InputSchema::String(string) => {
+            string.r#enum.as_ref().map_or(1, |e| e.len().max(1))
+        }
+        InputSchema::AnyOf(any_of) => any_of
+            .any_of
+            .iter()
+            .map(max_choices)
+            .max()
+            .unwrap_or(1)
+            .max(any_of.any_of.len()),
+        _ => 1,
+    }
+}
+
+/// Generates a single witness input. `choice` selects which `anyOf`
+/// variant and `enum` value is used.
+fn witness(
+    schema: &super::expression::InputSchema,
+    required_only: bool,
+    choice: usize,
+) -> super::expression::Input {
+    use super::expression::{Input, InputSchema};
+    use crate::chat::completions::request::{
+        File, ImageUrl, InputAudio, RichContentPart, VideoUrl,
+    };
+    match schema {
+        InputSchema::Object(object) => {
+            let required = object.required.as_deref().unwrap_or(&[]);
+            Input::Object(
+                object
+                    .properties
+                    .iter()
+                    .filter(|(key, _)| !required_only || required.contains(key))
+                    .map(|(key, schema)| {
+                        (key.clone(), witness(schema, required_only, choice))
+                    })
+                    .collect(),
+            )
+        }
+        InputSchema::Array(array) => {
+            let len = array
+                .min_items
+                .unwrap_or(0)
+                .max(1)
+                .min(array.max_items.unwrap_or(u64::MAX));
+            Input::Array(
+                (0..len)
+                    .map(|_| witness(&array.items, required_only, choice))
+                    .collect(),
+            )
+        }
+        InputSchema::String(string) => Input::String(
+            string
+                .r#enum
+                .as_ref()
+                .filter(|e| !e.is_empty())
+                .map(|e| e[choice % e.len()].clone())
+                .unwrap_or_default(),
+        ),
+        InputSchema::Integer(integer) => {
+            Input::Integer(integer.minimum.or(integer.maximum).unwrap_or(0))
+        }
+        InputSchema::Number(number) => {
+            Input::Number(number.minimum.or(number.maximum).unwrap_or(0.0))
+        }
+        InputSchema::Boolean(_) => Input::Boolean(false),
+        InputSchema::Image(_) => {
+            Input::RichContentPart(RichContentPart::ImageUrl {
+                image_url: ImageUrl {
+                    url: "https://example.com/image.png".to_string(),
+                    detail: None,
+                },
+            })
+        }
+        InputSchema::Audio(_) => {
+            Input::RichContentPart(RichContentPart::InputAudio {
+                input_audio: InputAudio {
+                    data: String::new(),
+                    format: "wav".to_string(),
+                },
+            })
+        }
+        InputSchema::Video(_) => {
+            Input::RichContentPart(RichContentPart::VideoUrl {
+                video_url: VideoUrl {
+                    url: "https://example.com/video.mp4".to_string(),
+                },
+            })
+        }
+        InputSchema::File(_) => Input::RichContentPart(RichContentPart::File {
+            file: File {
+                file_data: None,
+                file_id: None,
+                filename: Some("file.txt".to_string()),
+                file_url: Some("https://example.com/file.txt".to_string()),
+            },
+        }),
+        InputSchema::AnyOf(any_of) => match any_of.any_of.len() {
+            0 => Input::Object(Default::default()),
+            len => witness(&any_of.any_of[choice % len], required_only, choice),
+        },
+    }
+}
+
+/// Compiles `function` against a witness input the way an execution would.
+fn check_witness(
+    function: &super::RemoteFunction,
+    input: &super::expression::Input,
+    issues: &mut Issues,
+) {
+    use super::expression::{
+        FunctionOutput, Params, ParamsRef, TaskOutput, TaskOutputOwned,
+        TaskResult, VectorCompletionOutput,
+    };
+    use rust_decimal::Decimal;
+
+    let params = Params::Ref(ParamsRef {
+        input,
+        output: None,
+        map: None,
+        tasks: None,
+    });
+
+    // compile output_length, input_split and input_merge
+    let output_length = match function {
+        super::RemoteFunction::Scalar { .. }
+        | super::RemoteFunction::MultiLabel { .. }
+        | super::RemoteFunction::Categorical { .. } => None,
+        super::RemoteFunction::Vector {
+            output_length,
+            input_split,
+            input_merge,
+            input_schema,
+            ..
+        } => {
+            let output_length = match output_length.clone().compile_one(&params)
+            {
+                Ok(output_length) => Some(output_length),
+                Err(e) => {
+                    issues.push_expression_error("output_length", e);
+                    None
+                }
+            };
+            match input_split.clone().compile_one(&params) {
+                Ok(split) => {
+                    if let Some(output_length) = output_length
+                        && split.len() as u64 != output_length
+                    {
+                        issues.push(
+                            "input_split",
+                            TypeCheckIssueKind::WrongType,
+                            format!(
+                                "expected {} inputs, found {}",
+                                output_length,
+                                split.len()
+                            ),
+                        );
+                    }
+                    let split = super::expression::Input::Array(split);
+                    let merge_params = Params::Ref(ParamsRef {
+                        input: &split,
+                        output: None,
+                        map: None,
+                        tasks: None,
+                    });
+                    match input_merge.clone().compile_one(&merge_params) {
+                        Ok(merged) if !input_schema.validate_input(&merged) => {
+                            issues.push(
+                                "input_merge",
+                                TypeCheckIssueKind::WrongType,
+                                "merged input does not match input_schema",
+                            )
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            issues.push_expression_error("input_merge", e)
+                        }
+                    }
+                }
+                Err(e) => issues.push_expression_error("input_split", e),
+            }
+            output_length
+        }
+    };
+
+    // compile input_maps
+    let input_maps = match function.input_maps() {
+        Some(input_maps) => match input_maps.clone().compile(&params) {
+            Ok(input_maps) => Some(input_maps),
+            Err(e) => {
+                issues.push_expression_error("input_maps", e);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    // chat completion tasks run first, synthesize their results
+    let mut results: Vec<Option<TaskResult>> = function
+        .tasks()
+        .iter()
+        .map(|task_expr| {
+            task_expr.is_chat_completion().then(|| TaskResult {
+                output: TaskOutputOwned::ChatCompletion(
+                    serde_json::Value::String(String::new()),
+                ),
+                weight: None,
+            })
+        })
+        .collect();
+
+    // transformed task outputs, exposed to the function's output
+    let mut outputs: Vec<Option<TaskResult>> =
+        vec![None; function.tasks().len()];
+
+    for (i, task_expr) in function.tasks().iter().enumerate() {
+        let mut task_expr = task_expr.clone();
+
+        // expose chat completion and dependency results
+        let dependencies = task_expr.dependencies();
+        let visible_results = function
+            .tasks()
+            .iter()
+            .zip(&results)
+            .enumerate()
+            .map(|(j, (task, result))| {
+                if task.is_chat_completion()
+                    || dependencies.contains(&(j as u64))
+                {
+                    result.clone()
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        let params = Params::Ref(ParamsRef {
+            input,
+            output: None,
+            map: None,
+            tasks: Some(&visible_results),
+        });
+
+        // compile skip
+        if let Some(skip_expr) = task_expr.take_skip() {
+            match skip_expr.compile_one::<bool>(&params) {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) => {
+                    issues
+                        .push_expression_error(format!("tasks[{}].skip", i), e);
+                    continue;
+                }
+            }
+        }
+
+        // compile task fields
+        let tasks = if let Some(map) = task_expr.input_map() {
+            let Some(input_map) = input_maps
+                .as_ref()
+                .and_then(|input_maps| input_maps.get(map as usize))
+            else {
+                issues.push(
+                    format!("tasks[{}].map", i),
+                    TypeCheckIssueKind::WrongType,
+                    format!("input_maps has no index {}", map),
+                );
+                continue;
+            };
+            let mut tasks = Vec::with_capacity(input_map.len());
+            for map_input in input_map {
+                let params = Params::Ref(ParamsRef {
+                    input,
+                    output: None,
+                    map: Some(map_input),
+                    tasks: Some(&visible_results),
+                });
+                match task_expr.clone().compile(&params) {
+                    Ok(task) => tasks.push(task),
+                    Err(e) => {
+                        issues
+                            .push_expression_error(format!("tasks[{}]", i), e);
+                        break;
+                    }
+                }
+            }
+            if tasks.len() != input_map.len() {
+                continue;
+            }
+            tasks
+        } else {
+            match task_expr.clone().compile(&params) {
+                Ok(task) => vec![task],
+                Err(e) => {
+                    issues.push_expression_error(format!("tasks[{}]", i), e);
+                    continue;
+                }
+            }
+        };
+        let Some(first) = tasks.first() else {
+            continue;
+        };
+        if let super::Task::ChatCompletion(_) = first {
+            continue;
+        }
+
+        // synthesize the raw task output
+        let uniform = |len: usize| match len {
+            0 => Vec::new(),
+            len => vec![Decimal::ONE / Decimal::from(len); len],
+        };
+        let function_output = |task: &super::Task| match task {
+            super::Task::ScalarFunction(_) => {
+                FunctionOutput::Scalar(rust_decimal::dec!(0.5))
+            }
+            _ => FunctionOutput::Vector(uniform(
+                output_length.unwrap_or(1).max(1) as usize,
+            )),
+        };
+        let vector_completion_output = |task: &super::Task| match task {
+            super::Task::VectorCompletion(task)
+                if !task.responses.is_empty() =>
+            {
+                VectorCompletionOutput::default_from_request_responses_len(
+                    task.responses.len(),
+                )
+            }
+            _ => VectorCompletionOutput {
+                votes: Vec::new(),
+                scores: Vec::new(),
+                weights: Vec::new(),
+            },
+        };
+        let raw_output = match (task_expr.input_map().is_some(), first) {
+            (false, super::Task::VectorCompletion(_)) => {
+                TaskOutputOwned::VectorCompletion(vector_completion_output(
+                    first,
+                ))
+            }
+            (false, _) => TaskOutputOwned::Function(function_output(first)),
+            (true, super::Task::VectorCompletion(_)) => {
+                TaskOutputOwned::MapVectorCompletion(
+                    tasks.iter().map(vector_completion_output).collect(),
+                )
+            }
+            (true, _) => TaskOutputOwned::MapFunction(
+                tasks.iter().map(function_output).collect(),
+            ),
+        };
+
+        results[i] = Some(TaskResult {
+            output: raw_output.clone(),
+            weight: None,
+        });
+
+        // compile output
+        let path = format!("tasks[{}].output", i);
+        match first.compile_output(input, TaskOutput::Owned(raw_output)) {
+            Ok(None) => {}
+            Ok(Some(output)) => {
+                if check_function_output(
+                    function,
+                    output_length,
+                    path,
+                    &output,
+                    issues,
+                ) {
+                    outputs[i] = Some(TaskResult {
+                        output: TaskOutputOwned::Function(output),
+                        weight: Some(Decimal::ONE),
+                    });
+                }
+            }
+            Err(e) => issues.push_expression_error(path, e),
+        }
+    }
+
+    // compile the function's output against the synthesized task outputs
+    if let Some(output_expr) = function.output() {
+        let params = Params::Ref(ParamsRef {
+            input,
+            output: None,
+            map: None,
+            tasks: Some(&outputs),
+        });
+        match output_expr.compile_one::<FunctionOutput>(&params) {
+            Ok(output) => {
+                check_function_output(
+                    function,
+                    output_length,
+                    "output".to_string(),
+                    &output,
+                    issues,
+                );
+            }
+            Err(e) => issues.push_expression_error("output", e),
+        }
+    }
+}
+
+/// Reports `output` at `path` if it is not a valid output for `function`.
+///
+/// Returns whether the output is valid.
+fn check_function_output(
+    function: &super::RemoteFunction,
+    output_length: Option<u64>,
+    path: String,
+    output: &super::expression::FunctionOutput,
+    issues: &mut Issues,
+) -> bool {
+    use super::expression::FunctionOutput;
+    match (function, output) {
+        (super::RemoteFunction::Scalar { .. }, FunctionOutput::Scalar(_)) => {
+            true
+        }
+        (
+            super::RemoteFunction::Vector { .. },
+            FunctionOutput::Vector(vector),
+        ) => {
+            if let Some(output_length) = output_length
+                && vector.len() as u64 != output_length
+            {
+                issues.push(
+                    path,
+                    TypeCheckIssueKind::WrongType,
+                    format!(
+                        "expected a vector of length {}, found {}",
+                        output_length,
+                        vector.len()
+                    ),
+                );
+                false
+            } else {
+                true
+            }
+        }
+        (
+            super::RemoteFunction::MultiLabel { labels, .. }
+            | super::RemoteFunction::Categorical {
+                categories: labels, ..
+            },
+            output,
+        ) => {
+            if output.clone().into_labelled(labels).is_ok() {
+                true
+            } else {
+                issues.push(
+                    path,
+                    TypeCheckIssueKind::WrongType,
+                    format!(
+                        "expected a score for each of the labels [{}]",
+                        labels.join(", ")
+                    ),
+                );
+                false
+            }
+        }
+        _ => {
+            issues.push(
+                path,
+                TypeCheckIssueKind::WrongType,
+                match function {
+                    super::RemoteFunction::Scalar { .. } => {
+                        "expected a scalar output for a scalar function"
+                    }
+                    _ => "expected a vector output for a vector function",
+                },
+            );
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::RemoteFunction;
+
+    fn function(value: serde_json::Value) -> RemoteFunction {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn scalar_function(tasks: serde_json::Value) -> RemoteFunction {
+        function(serde_json::json!({
+            "type": "scalar.function",
+            "description": "test",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "text": { "type": "string" },
+                    "note": { "type": "string" }
+                },
+                "required": ["text"]
+            },
+            "tasks": tasks
+        }))
+    }
+
+    fn vector_completion_task(output: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "type": "vector.completion",
+            "messages": [{
+                "role": "user",
+                "content": { "$jmespath": "input.text" }
+            }],
+            "responses": ["yes", "no"],
+            "output": output
+        })
+    }
+
+    #[test]
+    fn test_valid_function() {
+        let function =
+            scalar_function(serde_json::json!([vector_completion_task(
+                serde_json::json!({ "$jmespath": "output.scores[0]" })
+            )]));
+        assert_eq!(function.type_check(), Vec::new());
+    }
+
+    #[test]
+    fn test_missing_field() {
+        let function = scalar_function(serde_json::json!([
+            vector_completion_task(
+                serde_json::json!({ "$jmespath": "output.scores[0]" })
+            ),
+            {
+                "type": "vector.completion",
+                "skip": { "$jmespath": "input.missing" },
+                "messages": [{ "role": "user", "content": "hi" }],
+                "responses": ["yes", "no"],
+                "output": { "$jmespath": "output.scores[0]" }
+            }
+        ]));
+        let issues = function.type_check();
+        assert!(issues.iter().any(|issue| {
+            issue.path == "tasks[1].skip"
+                && issue.kind == TypeCheckIssueKind::MissingField
+        }));
+    }
+
+    #[test]
+    fn test_wrong_output_type() {
+        let function =
+            scalar_function(serde_json::json!([vector_completion_task(
+                serde_json::json!({ "$jmespath": "output.scores" })
+            )]));
+        let issues = function.type_check();
+        assert!(issues.iter().any(|issue| {
+            issue.path == "tasks[0].output"
+                && issue.kind == TypeCheckIssueKind::WrongType
+        }));
+    }
+
+    #[test]
+    fn test_unknown_function() {
+        let function = scalar_function(serde_json::json!([
+            vector_completion_task(
+                serde_json::json!({ "$starlark": "mean(output['scores'])" })
+            ),
+            vector_completion_task(
+                serde_json::json!({ "$jmespath": "mean(output.scores)" })
+            )
+        ]));
+        let issues = function.type_check();
+        for path in ["tasks[0].output", "tasks[1].output"] {
+            assert!(issues.iter().any(|issue| {
+                issue.path == path
+                    && issue.kind == TypeCheckIssueKind::UnknownFunction
+            }));
+        }
+    }
+
+    #[test]
+    fn test_optional_field_missing_from_witness() {
+        let function = scalar_function(serde_json::json!([
+            vector_completion_task(serde_json::json!({
+                "$starlark": "output['scores'][0] if len(input['note']) >= 0 else 0.0"
+            }))
+        ]));
+        let issues = function.type_check();
+        assert!(issues.iter().any(|issue| {
+            issue.path == "tasks[0].output"
+                && issue.kind == TypeCheckIssueKind::EvaluationError
+        }));
+    }
+
+    #[test]
+    fn test_chat_completion_task_output() {
+        let function = scalar_function(serde_json::json!([
+            {
+                "type": "chat.completion",
+                "messages": [{
+                    "role": "user",
+                    "content": { "$jmespath": "input.text" }
+                }]
+            },
+            {
+                "type": "vector.completion",
+                "messages": [{
+                    "role": "user",
+                    "content": { "$jmespath": "tasks[0].output" }
+                }],
+                "responses": ["yes", "no"],
+                "output": { "$jmespath": "output.scores[0]" }
+            }
+        ]));
+        assert_eq!(function.type_check(), Vec::new());
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let mut dependent = vector_completion_task(
+            serde_json::json!({ "$jmespath": "output.scores[0]" }),
+        );
+        dependent["dependencies"] = serde_json::json!([0]);
+        dependent["skip"] = serde_json::json!({ "$jmespath": "tasks[0].output.scores[0] < `0.5`" });
+        let mut invalid = vector_completion_task(
+            serde_json::json!({ "$jmespath": "output.scores[0]" }),
+        );
+        invalid["dependencies"] = serde_json::json!([2]);
+        let function = scalar_function(serde_json::json!([
+            vector_completion_task(
+                serde_json::json!({ "$jmespath": "output.scores[0]" })
+            ),
+            dependent,
+            invalid
+        ]));
+        let issues = function.type_check();
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(issues[0].path, "tasks[2].dependencies");
+        assert_eq!(issues[0].kind, TypeCheckIssueKind::WrongType);
+    }
+
+    #[test]
+    fn test_function_output() {
+        let with_output = |output: serde_json::Value| {
+            let mut value =
+                serde_json::to_value(scalar_function(serde_json::json!([
+                    vector_completion_task(
+                        serde_json::json!({ "$jmespath": "output.scores[0]" })
+                    ),
+                    vector_completion_task(
+                        serde_json::json!({ "$jmespath": "output.scores[1]" })
+                    )
+                ])))
+                .unwrap();
+            value["output"] = output;
+            function(value)
+        };
+
+        let function = with_output(serde_json::json!({
+            "$starlark": "min([t['output'] for t in tasks if t != None and t['weight'] > 0])"
+        }));
+        assert_eq!(function.type_check(), Vec::new());
+
+        let function = with_output(serde_json::json!({
+            "$starlark": "[t['output'] for t in tasks]"
+        }));
+        let issues = function.type_check();
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(issues[0].path, "output");
+        assert_eq!(issues[0].kind, TypeCheckIssueKind::WrongType);
+    }
+}
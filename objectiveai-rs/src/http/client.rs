@@ -124,14 +124,64 @@ impl HttpClient {
         method: reqwest::Method,
         path: impl AsRef<str>,
         body: Option<impl serde::Serialize>,
+    ) -> Result<T, super::HttpError> {
+        self.unary_response(
+            self.request(method, path.as_ref(), body)
+                .build()
+                .map_err(super::HttpError::RequestError)?,
+        )
+        .await
+    }
+
+    /// Sends a unary API call with a raw request body and deserializes the
+    /// response.
+    ///
+    /// Used for uploads, where the body is not JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - HTTP method (typically POST)
+    /// * `path` - API endpoint path (will be appended to `api_base`)
+    /// * `query` - Optional query parameters to serialize into the URL
+    /// * `content_type` - Value for the `Content-Type` header
+    /// * `content` - The raw request body
+    ///
+    /// # Errors
+    ///
+    /// Returns [`super::HttpError`] if the request fails, returns a non-success status,
+    /// or the response cannot be deserialized.
+    pub async fn send_unary_content<
+        T: serde::de::DeserializeOwned + Send + 'static,
+    >(
+        &self,
+        method: reqwest::Method,
+        path: impl AsRef<str>,
+        query: Option<impl serde::Serialize>,
+        content_type: &str,
+        content: impl Into<reqwest::Body>,
+    ) -> Result<T, super::HttpError> {
+        let mut request = self.request(method, path.as_ref(), None::<()>);
+        if let Some(query) = query {
+            request = request.query(&query);
+        }
+        self.unary_response(
+            request
+                .header("content-type", content_type)
+                .body(content)
+                .build()
+                .map_err(super::HttpError::RequestError)?,
+        )
+        .await
+    }
+
+    /// Executes a unary request and deserializes the response.
+    async fn unary_response<T: serde::de::DeserializeOwned + Send + 'static>(
+        &self,
+        request: reqwest::Request,
     ) -> Result<T, super::HttpError> {
         let response = self
             .http_client
-            .execute(
-                self.request(method, path.as_ref(), body)
-                    .build()
-                    .map_err(super::HttpError::RequestError)?,
-            )
+            .execute(request)
             .await
             .map_err(super::HttpError::HttpError)?;
         let code = response.status();
@@ -0,0 +1,80 @@
+//! HTTP functions for jobs.
+
+use crate::{HttpClient, HttpError};
+use futures::Stream;
+
+/// Submits a job, returning as soon as it has started.
+pub async fn create_job(
+    client: &HttpClient,
+    params: super::request::JobCreateParams,
+) -> Result<super::response::Job, HttpError> {
+    client
+        .send_unary(reqwest::Method::POST, "jobs", Some(params))
+        .await
+}
+
+/// Gets the status of a job.
+pub async fn get_job(
+    client: &HttpClient,
+    id: &str,
+) -> Result<super::response::Job, HttpError> {
+    client
+        .send_unary(
+            reqwest::Method::GET,
+            format!("jobs/{}", id),
+            None::<String>,
+        )
+        .await
+}
+
+/// Attaches to a job's chunks, starting at `offset`.
+///
+/// Replays every recorded chunk from `offset`, then streams new chunks as
+/// they are recorded until the job finishes.
+pub async fn get_job_events(
+    client: &HttpClient,
+    id: &str,
+    offset: usize,
+) -> Result<
+    impl Stream<Item = Result<super::response::JobEvent, HttpError>>
+    + Send
+    + 'static
+    + use<>,
+    HttpError,
+> {
+    client
+        .send_streaming(
+            reqwest::Method::GET,
+            format!("jobs/{}/events?offset={}", id, offset),
+            None::<String>,
+        )
+        .await
+}
+
+/// Cancels a running job.
+pub async fn cancel_job(
+    client: &HttpClient,
+    id: &str,
+) -> Result<super::response::Job, HttpError> {
+    client
+        .send_unary(
+            reqwest::Method::POST,
+            format!("jobs/{}/cancel", id),
+            None::<String>,
+        )
+        .await
+}
+
+/// Gets the final result of a completed job.
+pub async fn get_job_result(
+    client: &HttpClient,
+    id: &str,
+) -> Result<super::response::JobResult, HttpError> {
+    client
+        .send_unary(
+            reqwest::Method::GET,
+            format!("jobs/{}/result", id),
+            None::<String>,
+        )
+        .await
+}
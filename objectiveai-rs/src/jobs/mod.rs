@@ -0,0 +1,15 @@
+//! Durable asynchronous job request and response types.
+//!
+//! A job runs a Function execution or Profile computation in the
+//! background. Once submitted, a job can be polled for its status,
+//! re-attached to from any chunk offset, cancelled, and its final result
+//! fetched after it completes.
+
+pub mod request;
+pub mod response;
+
+#[cfg(feature = "http")]
+mod http;
+
+#[cfg(feature = "http")]
+pub use http::*;
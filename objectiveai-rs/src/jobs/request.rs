@@ -0,0 +1,59 @@
+//! Request types for jobs.
+
+use crate::functions;
+use serde::{Deserialize, Serialize};
+
+/// Parameters for submitting a job.
+///
+/// The `stream` option of the wrapped request is ignored, as every job is
+/// recorded chunk by chunk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum JobCreateParams {
+    /// A Function execution.
+    #[serde(rename = "function.execution")]
+    FunctionExecution {
+        /// The execution request.
+        request: functions::executions::request::Request,
+    },
+    /// A Profile computation.
+    #[serde(rename = "function.profile.computation")]
+    FunctionProfileComputation {
+        /// The computation request.
+        request: functions::profiles::computations::request::Request,
+    },
+}
+
+impl JobCreateParams {
+    /// Returns the type of job these parameters submit.
+    pub fn job_type(&self) -> super::response::JobType {
+        match self {
+            JobCreateParams::FunctionExecution { .. } => {
+                super::response::JobType::FunctionExecution
+            }
+            JobCreateParams::FunctionProfileComputation { .. } => {
+                super::response::JobType::FunctionProfileComputation
+            }
+        }
+    }
+
+    /// Sets the retry token of the wrapped request.
+    pub fn set_retry_token(&mut self, retry_token: Option<String>) {
+        match self {
+            JobCreateParams::FunctionExecution { request } => {
+                request.base_mut().retry_token = retry_token;
+            }
+            JobCreateParams::FunctionProfileComputation { request } => {
+                request.base_mut().retry_token = retry_token;
+            }
+        }
+    }
+}
+
+/// Query parameters for re-attaching to a job's events.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobEventsParams {
+    /// Number of chunks to skip. Defaults to 0, replaying every chunk.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+}
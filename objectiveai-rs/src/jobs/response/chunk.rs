@@ -0,0 +1,40 @@
+//! Job events.
+
+use crate::functions;
+use serde::{Deserialize, Serialize};
+
+/// A chunk recorded by a job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum JobChunk {
+    /// A chunk of a Function execution.
+    FunctionExecution(
+        functions::executions::response::streaming::FunctionExecutionChunk,
+    ),
+    /// A chunk of a Profile computation.
+    FunctionProfileComputation(
+        functions::profiles::computations::response::streaming::FunctionProfileComputationChunk,
+    ),
+}
+
+impl JobChunk {
+    /// Returns the retry token carried by this chunk, if any.
+    pub fn retry_token(&self) -> Option<&str> {
+        match self {
+            JobChunk::FunctionExecution(chunk) => chunk.retry_token.as_deref(),
+            JobChunk::FunctionProfileComputation(chunk) => {
+                chunk.retry_token.as_deref()
+            }
+        }
+    }
+}
+
+/// A chunk streamed while attached to a job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobEvent {
+    /// Offset of the chunk within the job. Re-attaching at `offset + 1`
+    /// continues after this chunk.
+    pub offset: usize,
+    /// The recorded chunk.
+    pub chunk: JobChunk,
+}
@@ -0,0 +1,71 @@
+//! Job status.
+
+use crate::error;
+use serde::{Deserialize, Serialize};
+
+/// The kind of work a job runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobType {
+    /// A Function execution.
+    #[serde(rename = "function.execution")]
+    FunctionExecution,
+    /// A Profile computation.
+    #[serde(rename = "function.profile.computation")]
+    FunctionProfileComputation,
+}
+
+/// The lifecycle state of a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// The job is running, or will be resumed when the server restarts.
+    Running,
+    /// The job finished and its result is available.
+    Completed,
+    /// The job failed before producing a result.
+    Failed,
+    /// The job was cancelled.
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Returns true if the job will not produce any more chunks.
+    pub fn is_finished(self) -> bool {
+        !matches!(self, JobStatus::Running)
+    }
+}
+
+/// The status of a job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    /// Unique identifier for this job.
+    pub id: String,
+    /// The kind of work this job runs.
+    #[serde(rename = "type")]
+    pub r#type: JobType,
+    /// The lifecycle state of this job.
+    pub status: JobStatus,
+    /// Number of chunks recorded so far.
+    pub chunks: usize,
+    /// Number of times the job has been started. Greater than 1 if the job
+    /// was resumed after a server restart.
+    pub attempts: u64,
+    /// The latest retry token produced by the job, used to resume it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_token: Option<String>,
+    /// Why the job failed, if it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<error::ResponseError>,
+    /// Unix timestamp when the job was submitted.
+    pub created: u64,
+    /// Unix timestamp when the job last changed state.
+    pub updated: u64,
+    /// Object type identifier.
+    pub object: Object,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Object {
+    #[serde(rename = "job")]
+    Job,
+}
@@ -0,0 +1,9 @@
+//! Response types for jobs.
+
+mod chunk;
+mod job;
+mod result;
+
+pub use chunk::*;
+pub use job::*;
+pub use result::*;
@@ -0,0 +1,49 @@
+//! Job results.
+
+use crate::functions;
+use serde::{Deserialize, Serialize};
+
+/// The final result of a completed job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum JobResult {
+    /// The result of a Function execution.
+    FunctionExecution(
+        functions::executions::response::unary::FunctionExecution,
+    ),
+    /// The result of a Profile computation.
+    FunctionProfileComputation(
+        functions::profiles::computations::response::unary::FunctionProfileComputation,
+    ),
+}
+
+impl JobResult {
+    /// Aggregates the chunks of a single attempt into its final result.
+    ///
+    /// Returns None if there are no chunks.
+    pub fn from_chunks<'a>(
+        chunks: impl IntoIterator<Item = &'a super::JobChunk>,
+    ) -> Option<Self> {
+        let mut chunks = chunks.into_iter();
+        match chunks.next()?.clone() {
+            super::JobChunk::FunctionExecution(mut aggregate) => {
+                for chunk in chunks {
+                    if let super::JobChunk::FunctionExecution(chunk) = chunk {
+                        aggregate.push(chunk);
+                    }
+                }
+                Some(JobResult::FunctionExecution(aggregate.into()))
+            }
+            super::JobChunk::FunctionProfileComputation(mut aggregate) => {
+                for chunk in chunks {
+                    if let super::JobChunk::FunctionProfileComputation(chunk) =
+                        chunk
+                    {
+                        aggregate.push(chunk);
+                    }
+                }
+                Some(JobResult::FunctionProfileComputation(aggregate.into()))
+            }
+        }
+    }
+}
@@ -24,6 +24,7 @@
 //! - [`ensemble_llm`] - Ensemble LLM configurations
 //! - [`error`] - Error types
 //! - [`functions`] - Function definitions, execution, and client-side compilation
+//! - [`jobs`] - Durable asynchronous jobs
 //! - [`prefixed_uuid`] - UUID utilities
 //! - [`vector`] - Vector completion APIs
 //!
@@ -37,6 +38,7 @@ pub mod ensemble;
 pub mod ensemble_llm;
 pub mod error;
 pub mod functions;
+pub mod jobs;
 pub mod prefixed_uuid;
 pub mod vector;
 
@@ -49,6 +49,11 @@ pub struct VectorCompletionCreateParams {
     pub tools: Option<Vec<chat::completions::request::Tool>>,
     /// The possible responses the LLMs can vote for.
     pub responses: Vec<chat::completions::request::RichContent>,
+    /// If present, the maximum total cost of the request, including every
+    /// upstream call made for it. Once reached, the remaining calls are
+    /// cancelled and the request ends with the votes that completed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_cost: Option<rust_decimal::Decimal>,
 
     // --- Retry configuration ---
 